@@ -0,0 +1,110 @@
+// Evelyn: Your personal assistant, project manager and calendar
+// Copyright (C) 2017 Gregory Jensen
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+
+use core::agile::sprint;
+use core::agile::task as agile_task;
+use core::calendar;
+use core::date_time_service;
+use core::error_messages::{EvelynBaseError, EvelynCoreError};
+use core::simple_task;
+use core::todo_list;
+use core::user;
+use data::agile::task as task_data;
+use model;
+use model::agenda as agenda_model;
+use processing::ProcessorData;
+use std::sync::Arc;
+
+// Sprints are the ones running now whichever day is asked for, as that is
+// what the agile tasks are being worked on in.
+pub fn lookup_my_day(
+    model: agenda_model::LookupAgendaRequestModel,
+    session_token_model: model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Result<agenda_model::LookupAgendaResponseModel, EvelynCoreError> {
+    let ds = processor_data.data_store.clone();
+    let user_id = session_token_model.user_id.clone();
+    let zone = user::lookup_time_zone(&ds, &user_id);
+
+    let date = match model.date {
+        Some(ref date) if !date.is_empty() => {
+            match date_time_service::parse_date(date) {
+                Some(date) => date,
+                None => return Err(EvelynCoreError::InvalidAgendaDate(EvelynBaseError::NothingElse)),
+            }
+        },
+        _ => date_time_service::timestamp_to_local(date_time_service::get_timestamp(), &zone).date(),
+    };
+
+    let day_begin = date_time_service::start_of_day(&date, &zone);
+    let next_day_begin = match date.succ_opt() {
+        Some(next_day) => date_time_service::start_of_day(&next_day, &zone),
+        None => return Err(EvelynCoreError::InvalidAgendaDate(EvelynBaseError::NothingElse)),
+    };
+
+    let events = match calendar::lookup_occurrences(&ds, &user_id, day_begin, next_day_begin - 1, &zone) {
+        Ok(events) => events,
+        Err(e) => return Err(e),
+    };
+
+    let simple_tasks = match simple_task::lookup_due_before(&ds, &user_id, next_day_begin, &zone) {
+        Ok(simple_tasks) => simple_tasks,
+        Err(e) => return Err(e),
+    };
+
+    let todo_lists = match todo_list::lookup_incomplete_todo_lists(&ds, &user_id) {
+        Ok(todo_lists) => todo_lists,
+        Err(e) => return Err(e),
+    };
+
+    let sprints = match sprint::lookup_active(session_token_model, processor_data.clone()) {
+        Ok(result) => result.sprints,
+        Err(e) => return Err(e),
+    };
+
+    let mut agile_tasks = Vec::new();
+    for sprint in sprints {
+        let task_ids = match sprint::lookup_task_ids(&ds, &sprint.project_id, &sprint.sprint_id) {
+            Ok(task_ids) => task_ids,
+            Err(e) => return Err(e),
+        };
+
+        match task_data::lookup_assigned_in(&ds, &user_id, &task_ids) {
+            Ok(tasks) => {
+                for task in tasks {
+                    agile_tasks.push(agenda_model::AgendaAgileTaskExternalModel {
+                        sprint_id: sprint.sprint_id.clone(),
+                        sprint_title: sprint.title.clone(),
+                        task: agile_task::to_external_model(&ds, task, &zone),
+                    });
+                }
+            },
+            Err(e) => return Err(EvelynCoreError::FailedToLookupAssignedAgileTasks(e)),
+        }
+    }
+
+    Ok(agenda_model::LookupAgendaResponseModel {
+        agenda: Some(agenda_model::AgendaExternalModel {
+            date: date_time_service::date_to_string(&date),
+            events: events,
+            simple_tasks: simple_tasks,
+            agile_tasks: agile_tasks,
+            todo_lists: todo_lists,
+        }),
+        error: None,
+    })
+}
@@ -0,0 +1,132 @@
+// Evelyn: Your personal assistant, project manager and calendar
+// Copyright (C) 2017 Gregory Jensen
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use core::agile::project;
+use core::date_time_service as dts;
+use core::error_messages::{EvelynBaseError, EvelynCoreError, EvelynDatabaseError};
+use core::user;
+use data::agile::audit as audit_data;
+use model;
+use model::agile::audit as audit_model;
+use mongodb::Client;
+use processing::ProcessorData;
+use std::sync::Arc;
+use uuid::Uuid;
+
+pub fn change(
+    field: &str,
+    before: Option<String>,
+    after: Option<String>,
+) -> Option<audit_model::FieldChangeModel> {
+    if before == after {
+        return None;
+    }
+
+    Some(audit_model::FieldChangeModel {
+        field: field.to_owned(),
+        before: before,
+        after: after,
+    })
+}
+
+// The audit log must not get in the way of the change it describes, failures
+// to write it are logged rather than returned.
+pub fn record(
+    ds: &Client,
+    project_id: &String,
+    entity_type: audit_model::AuditEntityTypeModel,
+    entity_id: &String,
+    action: audit_model::AuditActionModel,
+    user_id: &String,
+    changes: Vec<Option<audit_model::FieldChangeModel>>,
+) {
+    let entry = audit_model::AuditEntryModel {
+        audit_entry_id: format!("{}", Uuid::new_v4()),
+        project_id: project_id.to_owned(),
+        entity_type: entity_type,
+        entity_id: entity_id.to_owned(),
+        action: action,
+        user_id: user_id.to_owned(),
+        date: dts::get_timestamp(),
+        changes: changes.into_iter().filter_map(|x| x).collect(),
+    };
+
+    if let Some(e) = audit_data::insert_entry(ds, &entry) {
+        error!("Failed to record audit entry for {:?} [{}]: {}", entry.entity_type, entry.entity_id, e);
+    }
+}
+
+// The history shows every change made to the project, so only its
+// contributors may read it.
+fn check_project_access(
+    project_id: &String,
+    session_token_model: &model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Option<EvelynCoreError> {
+    let project_session_token_model = model::SessionTokenModel {
+        user_id: session_token_model.user_id.clone(),
+        server_session_token: session_token_model.server_session_token.clone(),
+    };
+
+    match project::lookup_contributing_to(project_session_token_model, processor_data) {
+        Ok(result) if result.projects.iter().any(|x| &x.project_id == project_id) => None,
+        Ok(_) => Some(EvelynCoreError::FailedToLookupAgileHistory(
+            EvelynDatabaseError::AgileProjectNotFound(EvelynBaseError::NothingElse),
+        )),
+        Err(e) => Some(e),
+    }
+}
+
+pub fn lookup_history(
+    entity_type: audit_model::AuditEntityTypeModel,
+    request_model: audit_model::LookupHistoryRequestModel,
+    session_token_model: model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Result<audit_model::LookupHistoryResponseModel, EvelynCoreError> {
+    if let Some(e) = check_project_access(&request_model.project_id, &session_token_model, processor_data.clone()) {
+        return Err(e);
+    }
+
+    let ds = processor_data.data_store.clone();
+    let zone = user::lookup_time_zone(&ds, &session_token_model.user_id);
+
+    match audit_data::lookup_entries(&ds, &request_model.project_id, &entity_type, &request_model.entity_id) {
+        Ok(entries) => Ok(audit_model::LookupHistoryResponseModel {
+            history: entries.into_iter().map(|x| {
+                audit_model::AuditEntryExternalModel {
+                    action: match x.action {
+                        audit_model::AuditActionModel::Create => audit_model::AuditActionExternalModel::Create,
+                        audit_model::AuditActionModel::Update => audit_model::AuditActionExternalModel::Update,
+                        audit_model::AuditActionModel::Link => audit_model::AuditActionExternalModel::Link,
+                        audit_model::AuditActionModel::Assign => audit_model::AuditActionExternalModel::Assign,
+                        audit_model::AuditActionModel::AddContributor => audit_model::AuditActionExternalModel::AddContributor,
+                    },
+                    user_id: x.user_id,
+                    date: dts::timestamp_to_string(x.date, &zone),
+                    changes: x.changes.into_iter().map(|c| {
+                        audit_model::FieldChangeExternalModel {
+                            field: c.field,
+                            before: c.before,
+                            after: c.after,
+                        }
+                    }).collect(),
+                }
+            }).collect(),
+            error: None,
+        }),
+        Err(e) => Err(EvelynCoreError::FailedToLookupAgileHistory(e)),
+    }
+}
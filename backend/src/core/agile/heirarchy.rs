@@ -21,6 +21,11 @@ use model::agile::heirarchy as heirarchy_model;
 use processing::ProcessorData;
 use std::sync::Arc;
 use chrono::prelude::*;
+use core::agile::audit;
+use core::webhook;
+use model::agile::audit as audit_model;
+use model::webhook::WebhookEventTypeModel;
+use mongodb::Client;
 
 fn check_link(
     link_from: heirarchy_model::LinkFromTypeNameExternalModel, 
@@ -42,6 +47,30 @@ fn check_link(
     }
 }
 
+fn record_link(
+    ds: &Client,
+    link_model: &heirarchy_model::LinkModel,
+) {
+    let (from_type, from_type_name) = match link_model.link_from_type_name {
+        heirarchy_model::LinkFromTypeNameModel::Sprint => (audit_model::AuditEntityTypeModel::Sprint, "Sprint"),
+        heirarchy_model::LinkFromTypeNameModel::Story => (audit_model::AuditEntityTypeModel::Story, "Story"),
+        heirarchy_model::LinkFromTypeNameModel::Task => (audit_model::AuditEntityTypeModel::Task, "Task"),
+    };
+    let (to_type, to_type_name) = match link_model.link_to_type_name {
+        heirarchy_model::LinkToTypeNameModel::Story => (audit_model::AuditEntityTypeModel::Story, "Story"),
+        heirarchy_model::LinkToTypeNameModel::Task => (audit_model::AuditEntityTypeModel::Task, "Task"),
+    };
+
+    audit::record(ds, &link_model.project_id, from_type, &link_model.link_from_id,
+                  audit_model::AuditActionModel::Link, &link_model.created_by_user_id, vec![
+        audit::change("linkedTo", None, Some(format!("{}:{}", to_type_name, link_model.link_to_id))),
+    ]);
+    audit::record(ds, &link_model.project_id, to_type, &link_model.link_to_id,
+                  audit_model::AuditActionModel::Link, &link_model.created_by_user_id, vec![
+        audit::change("linkedFrom", None, Some(format!("{}:{}", from_type_name, link_model.link_from_id))),
+    ]);
+}
+
 pub fn make_link(
     request_model: heirarchy_model::MakeLinkRequestModel,
     session_token_model: model::SessionTokenModel,
@@ -66,9 +95,21 @@ pub fn make_link(
                     match heirarchy_data::insert_link(&ds, &link_model) {
                         None => {
                             match heirarchy_data::remove_by_db_ids(&ds, links_to_id) {
-                                None => Ok(heirarchy_model::MakeLinkResponseModel {
-                                    error: None,
-                                }),
+                                None => {
+                                    record_link(&ds, &link_model);
+                                    webhook::dispatch(&ds, &link_model.project_id, WebhookEventTypeModel::LinkMade, &link_model.link_from_id,
+                                                      &format!("{:?} linked to {:?}", link_model.link_from_type_name, link_model.link_to_type_name),
+                                                      Some(&link_model.created_by_user_id), vec![
+                                        ("LinkFromTypeName", format!("{:?}", link_model.link_from_type_name)),
+                                        ("LinkFromId", link_model.link_from_id.to_owned()),
+                                        ("LinkToTypeName", format!("{:?}", link_model.link_to_type_name)),
+                                        ("LinkToId", link_model.link_to_id.to_owned()),
+                                    ]);
+
+                                    Ok(heirarchy_model::MakeLinkResponseModel {
+                                        error: None,
+                                    })
+                                },
                                 Some(e) => Err(EvelynCoreError::FailedToRemoveAgileHeirarchyLink(e))
                             }
                         },
@@ -31,6 +31,8 @@ use processing::ProcessorData;
 use std::sync::Arc;
 use uuid::Uuid;
 use chrono::prelude::*;
+use core::agile::audit;
+use model::agile::audit as audit_model;
 
 pub fn create(
     request_model: project_model::CreateProjectRequestModel,
@@ -41,7 +43,7 @@ pub fn create(
 
     let project_model = project_model::ProjectModel {
         project_id: project_id,
-        created_by_user_id: session_token_model.user_id,
+        created_by_user_id: session_token_model.user_id.to_owned(),
         date_created: format!("{}", Utc::now()),
         name: request_model.name,
         short_name: request_model.short_name,
@@ -53,16 +55,26 @@ pub fn create(
     let ds = processor_data.data_store.clone();
 
     match project_data::insert_project(&ds, &project_model) {
-        None => Ok(project_model::CreateProjectResponseModel {
-            project_id: Some(project_model.project_id),
-            error: None,
-        }),
+        None => {
+            audit::record(&ds, &project_model.project_id, audit_model::AuditEntityTypeModel::Project, &project_model.project_id,
+                          audit_model::AuditActionModel::Create, &session_token_model.user_id, vec![
+                audit::change("name", None, Some(project_model.name.to_owned())),
+                audit::change("shortName", None, Some(project_model.short_name.to_owned())),
+                audit::change("description", None, Some(project_model.description.to_owned())),
+            ]);
+
+            Ok(project_model::CreateProjectResponseModel {
+                project_id: Some(project_model.project_id),
+                error: None,
+            })
+        },
         Some(e) => Err(EvelynCoreError::FailedToCreateAgileProject(e)),
     }
 }
 
 pub fn add_user_contributor(
     request_model: project_model::AddUserContributorRequestModel,
+    session_token_model: model::SessionTokenModel,
     processor_data: Arc<ProcessorData>,
 ) -> Option<EvelynCoreError> {
     let user_contributor_model = project_model::AddUserContributorModel {
@@ -74,14 +86,25 @@ pub fn add_user_contributor(
 
     let ds = processor_data.data_store.clone();
 
+    let project_id = user_contributor_model.project_id.to_owned();
+    let user_id = user_contributor_model.user_contributor.user_id.to_owned();
+
     match project_data::add_user_contributor(&ds, user_contributor_model) {
-        None => None,
+        None => {
+            audit::record(&ds, &project_id, audit_model::AuditEntityTypeModel::Project, &project_id,
+                          audit_model::AuditActionModel::AddContributor, &session_token_model.user_id, vec![
+                audit::change("userContributors", None, Some(user_id)),
+            ]);
+
+            None
+        },
         Some(e) => Some(EvelynCoreError::FailedToAddUserContributorToAgileProject(e)),
     }
 }
 
 pub fn add_user_group_contributor(
     request_model: project_model::AddUserGroupContributorRequestModel,
+    session_token_model: model::SessionTokenModel,
     processor_data: Arc<ProcessorData>,
 ) -> Option<EvelynCoreError> {
     let group_contributor_model = project_model::AddUserGroupContributorModel {
@@ -93,8 +116,18 @@ pub fn add_user_group_contributor(
 
     let ds = processor_data.data_store.clone();
 
+    let project_id = group_contributor_model.project_id.to_owned();
+    let user_group_id = group_contributor_model.user_group_contributor.user_group_id.to_owned();
+
     match project_data::add_user_group_contributor(&ds, group_contributor_model) {
-        None => None,
+        None => {
+            audit::record(&ds, &project_id, audit_model::AuditEntityTypeModel::Project, &project_id,
+                          audit_model::AuditActionModel::AddContributor, &session_token_model.user_id, vec![
+                audit::change("userGroupContributors", None, Some(user_group_id)),
+            ]);
+
+            None
+        },
         Some(e) => Some(EvelynCoreError::FailedToAddUserGroupContributorToAgileProject(e)),
     }
 }
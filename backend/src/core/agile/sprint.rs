@@ -15,14 +15,20 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use core::error_messages::EvelynCoreError;
+use data::agile::heirarchy as heirarchy_data;
 use data::agile::sprint as sprint_data;
 use model;
+use model::agile::heirarchy as heirarchy_model;
 use model::agile::sprint as sprint_model;
+use mongodb::Client;
 use core::agile::project;
 use processing::ProcessorData;
 use std::sync::Arc;
 use uuid::Uuid;
 use core::date_time_service as dts;
+use core::agile::audit;
+use core::user;
+use model::agile::audit as audit_model;
 
 pub fn create(
     request_model: sprint_model::CreateSprintRequestModel,
@@ -31,23 +37,43 @@ pub fn create(
 ) -> Result<sprint_model::CreateSprintResponseModel, EvelynCoreError> {
     let sprint_id = format!("{}", Uuid::new_v4());
 
+    let ds = processor_data.data_store.clone();
+    let zone = user::lookup_time_zone(&ds, &session_token_model.user_id);
+
+    let start_date = match dts::parse_timestamp(&request_model.start_date, &zone) {
+        Ok(x) => x,
+        Err(e) => return Err(e),
+    };
+    let end_date = match dts::parse_timestamp(&request_model.end_date, &zone) {
+        Ok(x) => x,
+        Err(e) => return Err(e),
+    };
+
     let sprint_model = sprint_model::SprintModel {
         sprint_id: sprint_id,
-        created_by_user_id: session_token_model.user_id,
+        created_by_user_id: session_token_model.user_id.to_owned(),
         date_created: dts::get_timestamp(),
         project_id: request_model.project_id,
         title: request_model.title,
-        start_date: dts::string_to_timestamp(request_model.start_date),
-        end_date: dts::string_to_timestamp(request_model.end_date),
+        start_date: start_date,
+        end_date: end_date,
+        start_announced: false,
     };
 
-    let ds = processor_data.data_store.clone();
-
     match sprint_data::insert_sprint(&ds, &sprint_model) {
-        None => Ok(sprint_model::CreateSprintResponseModel {
-            sprint_id: Some(sprint_model.sprint_id),
-            error: None,
-        }),
+        None => {
+            audit::record(&ds, &sprint_model.project_id, audit_model::AuditEntityTypeModel::Sprint, &sprint_model.sprint_id,
+                          audit_model::AuditActionModel::Create, &session_token_model.user_id, vec![
+                audit::change("title", None, Some(sprint_model.title.to_owned())),
+                audit::change("startDate", None, Some(dts::timestamp_to_string(sprint_model.start_date, &dts::UTC))),
+                audit::change("endDate", None, Some(dts::timestamp_to_string(sprint_model.end_date, &dts::UTC))),
+            ]);
+
+            Ok(sprint_model::CreateSprintResponseModel {
+                sprint_id: Some(sprint_model.sprint_id),
+                error: None,
+            })
+        },
         Some(e) => Err(EvelynCoreError::FailedToCreateAgileSprint(e)),
     }
 }
@@ -57,6 +83,7 @@ pub fn lookup_active(
     processor_data: Arc<ProcessorData>,
 ) -> Result<sprint_model::LookupActiveSprintsResponseModel, EvelynCoreError> {
     let ds = processor_data.data_store.clone();
+    let zone = user::lookup_time_zone(&ds, &session_token_model.user_id);
 
     match project::lookup_contributing_to(session_token_model, processor_data.clone()) {
         Ok(result) => {
@@ -71,8 +98,8 @@ pub fn lookup_active(
                             sprint_id: x.sprint_id,
                             project_id: x.project_id,
                             title: x.title,
-                            start_date: dts::timestamp_to_string(x.start_date),
-                            end_date: dts::timestamp_to_string(x.end_date),
+                            start_date: dts::timestamp_to_string(x.start_date, &zone),
+                            end_date: dts::timestamp_to_string(x.end_date, &zone),
                         }
                     }).collect(),
                     error: None,
@@ -86,9 +113,11 @@ pub fn lookup_active(
 
 pub fn lookup_backlog(
     request_model: sprint_model::LookupBacklogRequestModel,
+    session_token_model: model::SessionTokenModel,
     processor_data: Arc<ProcessorData>,
 ) -> Result<sprint_model::LookupBacklogResponseModel, EvelynCoreError> {
     let ds = processor_data.data_store.clone();
+    let zone = user::lookup_time_zone(&ds, &session_token_model.user_id);
 
     match sprint_data::lookup_backlog(&ds, &request_model.project_id) {
         Ok(result) => Ok(sprint_model::LookupBacklogResponseModel {
@@ -97,8 +126,8 @@ pub fn lookup_backlog(
                     sprint_id: sprint.sprint_id,
                     project_id: sprint.project_id,
                     title: sprint.title,
-                    start_date: dts::timestamp_to_string(sprint.start_date),
-                    end_date: dts::timestamp_to_string(sprint.end_date),
+                    start_date: dts::timestamp_to_string(sprint.start_date, &zone),
+                    end_date: dts::timestamp_to_string(sprint.end_date, &zone),
                 }
             }).collect(),
             error: None,
@@ -106,3 +135,45 @@ pub fn lookup_backlog(
         Err(e) => Err(EvelynCoreError::FailedToLookupBacklogAgileSprints(e)),
     } 
 }
+
+// The tasks linked to a sprint, either directly or through its stories.
+pub fn lookup_task_ids(
+    ds: &Client,
+    project_id: &String,
+    sprint_id: &String,
+) -> Result<Vec<String>, EvelynCoreError> {
+    let links = match heirarchy_data::lookup_links(ds, project_id, &heirarchy_model::LinkFromTypeNameModel::Sprint, sprint_id) {
+        Ok(links) => links,
+        Err(e) => return Err(EvelynCoreError::FailedToLookupAgileHeirarchyLinks(e)),
+    };
+
+    let mut task_ids = Vec::new();
+    for link in links {
+        match link.link_to_type_name {
+            heirarchy_model::LinkToTypeNameModel::Task => task_ids.push(link.link_to_id),
+            heirarchy_model::LinkToTypeNameModel::Story => {
+                let story_links = match heirarchy_data::lookup_links(ds, project_id, &heirarchy_model::LinkFromTypeNameModel::Story,
+                                                                     &link.link_to_id) {
+                    Ok(story_links) => story_links,
+                    Err(e) => return Err(EvelynCoreError::FailedToLookupAgileHeirarchyLinks(e)),
+                };
+
+                for story_link in story_links {
+                    if let heirarchy_model::LinkToTypeNameModel::Task = story_link.link_to_type_name {
+                        task_ids.push(story_link.link_to_id);
+                    }
+                }
+            },
+        }
+    }
+
+    Ok(task_ids)
+}
+
+pub fn history(
+    request_model: audit_model::LookupHistoryRequestModel,
+    session_token_model: model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Result<audit_model::LookupHistoryResponseModel, EvelynCoreError> {
+    audit::lookup_history(audit_model::AuditEntityTypeModel::Sprint, request_model, session_token_model, processor_data)
+}
@@ -26,6 +26,10 @@ use processing::ProcessorData;
 use std::sync::Arc;
 use uuid::Uuid;
 use chrono::prelude::*;
+use core::agile::audit;
+use core::webhook;
+use model::agile::audit as audit_model;
+use model::webhook::WebhookEventTypeModel;
 
 fn lookup_linked_tasks(project_id: &String, story_id: &String, processor_data: Arc<ProcessorData>) -> Vec<story_model::TaskExternalModel> {
     let links = heirarchy::lookup_links(heirarchy_model::LookupLinksRequestModel {
@@ -77,7 +81,7 @@ pub fn create(
 
     let story_model = story_model::StoryModel {
         story_id: story_id,
-        created_by_user_id: session_token_model.user_id,
+        created_by_user_id: session_token_model.user_id.to_owned(),
         date_created: format!("{}", Utc::now()),
         project_id: request_model.project_id,
         title: request_model.title,
@@ -90,10 +94,22 @@ pub fn create(
     let ds = processor_data.data_store.clone();
 
     match story_data::insert_story(&ds, &story_model) {
-        None => Ok(story_model::CreateStoryResponseModel {
-            story_id: Some(story_model.story_id),
-            error: None,
-        }),
+        None => {
+            audit::record(&ds, &story_model.project_id, audit_model::AuditEntityTypeModel::Story, &story_model.story_id,
+                          audit_model::AuditActionModel::Create, &session_token_model.user_id, vec![
+                audit::change("title", None, Some(story_model.title.to_owned())),
+                audit::change("description", None, Some(story_model.description.to_owned())),
+            ]);
+            webhook::dispatch(&ds, &story_model.project_id, WebhookEventTypeModel::StoryCreated, &story_model.story_id,
+                              &story_model.title, Some(&session_token_model.user_id), vec![
+                ("Description", story_model.description.to_owned()),
+            ]);
+
+            Ok(story_model::CreateStoryResponseModel {
+                story_id: Some(story_model.story_id),
+                error: None,
+            })
+        },
         Some(e) => Err(EvelynCoreError::FailedToCreateAgileStory(e)),
     }
 }
@@ -161,3 +177,11 @@ pub fn lookup_backlog(
         Err(e) => Err(EvelynCoreError::FailedToLookupAgileHeirarchyLinksToType(e)),
     }
 }
+
+pub fn history(
+    request_model: audit_model::LookupHistoryRequestModel,
+    session_token_model: model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Result<audit_model::LookupHistoryResponseModel, EvelynCoreError> {
+    audit::lookup_history(audit_model::AuditEntityTypeModel::Story, request_model, session_token_model, processor_data)
+}
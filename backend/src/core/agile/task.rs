@@ -14,17 +14,40 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use core::error_messages::{EvelynCoreError, EvelynBaseError};
+use chrono_tz::Tz;
+use core::error_messages::{EvelynCoreError, EvelynBaseError, EvelynDatabaseError};
 use data::agile::task as task_data;
 use model;
 use model::agile::task as task_model;
+use model::notification::NotificationKindModel;
 use data::agile::heirarchy as heirarchy_data;
+use data::agile::sprint as sprint_data;
 use model::agile::heirarchy as heirarchy_model;
+use data;
 use data::user as user_data;
+use mongodb::Client;
 use processing::ProcessorData;
 use std::sync::Arc;
 use uuid::Uuid;
 use core::date_time_service as dts;
+use core::agile::audit;
+use core::agile::project;
+use core::agile::sprint;
+use core::conversion;
+use core::user;
+use core::webhook;
+use model::agile::audit as audit_model;
+use model::webhook::WebhookEventTypeModel;
+
+// Webhook payloads use the same casing as the rest of the API.
+fn webhook_detail_name(field: &str) -> &'static str {
+    match field {
+        "title" => "Title",
+        "description" => "Description",
+        "originalEstimate" => "OriginalEstimate",
+        _ => "Other",
+    }
+}
 
 pub fn create(
     request_model: task_model::CreateTaskRequestModel,
@@ -50,75 +73,117 @@ pub fn create(
             None => "0m".to_owned(),
         },
         assignment: None,
+        origin: None,
+        version: data::version::INITIAL_VERSION,
     };
 
     let ds = processor_data.data_store.clone();
 
-    match task_data::insert_task(&ds, &task_model) {
-        None => Ok(task_model::CreateTaskResponseModel {
-            task_id: Some(task_model.task_id),
-            error: None,
-        }),
+    match add_task(&ds, &task_model) {
+        None => {
+            Ok(task_model::CreateTaskResponseModel {
+                task_id: Some(task_model.task_id),
+                error: None,
+            })
+        },
         Some(e) => Err(EvelynCoreError::FailedToCreateAgileTask(e)),
     }
 }
 
+// Inserts a new task and lets the project know it was created.
+pub fn add_task(
+    ds: &Client,
+    task_model: &task_model::TaskModel,
+) -> Option<EvelynDatabaseError> {
+    if let Some(e) = task_data::insert_task(ds, task_model) {
+        return Some(e);
+    }
+
+    audit::record(ds, &task_model.project_id, audit_model::AuditEntityTypeModel::Task, &task_model.task_id,
+                  audit_model::AuditActionModel::Create, &task_model.created_by_user_id, vec![
+        audit::change("title", None, Some(task_model.title.to_owned())),
+        audit::change("description", None, Some(task_model.description.to_owned())),
+        audit::change("originalEstimate", None, Some(task_model.original_estimate.to_owned())),
+    ]);
+    webhook::dispatch(ds, &task_model.project_id, WebhookEventTypeModel::TaskCreated, &task_model.task_id,
+                      &task_model.title, Some(&task_model.created_by_user_id), vec![
+        ("Description", task_model.description.to_owned()),
+        ("OriginalEstimate", task_model.original_estimate.to_owned()),
+    ]);
+
+    None
+}
+
+// Looks up the names of the users who last changed and were assigned the
+// task. Those who can't be found are left out.
+pub fn to_external_model(
+    ds: &Client,
+    result: task_model::TaskModel,
+    zone: &Tz,
+) -> task_model::TaskExternalModel {
+    let modified_by_user = user_data::find_user_by_id(ds, &result.modified_by_user_id);
+
+    task_model::TaskExternalModel {
+        task_id: result.task_id,
+        project_id: result.project_id,
+        title: result.title,
+        description: result.description,
+        original_estimate: result.original_estimate,
+        origin: result.origin.map(conversion::to_link_external_model),
+        version: result.version,
+        date_modified: dts::timestamp_to_string(result.date_modified, zone),
+        modified_by_user: match modified_by_user {
+            Ok(Some(e)) => {
+                Some(task_model::UserExternalModel {
+                    user_name: e.user_name,
+                    user_id: e.user_id,
+                })
+            },
+            _ => {
+                None
+            },
+        },
+        assignment: match result.assignment {
+            None => None,
+            Some(a) => {
+                let assigned_to_user = user_data::find_user_by_id(ds, &a.assigned_to_user_id);
+                let assigned_by_user = user_data::find_user_by_id(ds, &a.assigned_by_user_id);
+
+                match (assigned_to_user, assigned_by_user) {
+                    (Ok(Some(a)), Ok(Some(b))) => {
+                        Some(task_model::AssignmentExternalModel {
+                            assigned_to_user: task_model::UserExternalModel {
+                                user_name: a.user_name,
+                                user_id: a.user_id,
+                            },
+                            assigned_by_user: task_model::UserExternalModel {
+                                user_name: b.user_name,
+                                user_id: b.user_id,   
+                            },
+                        })
+                    },
+                    _ => {
+                        None
+                    },
+                }
+            }
+        }
+    }
+}
+
 pub fn lookup(
     request_model: task_model::LookupTaskRequestModel,
+    session_token_model: model::SessionTokenModel,
     processor_data: Arc<ProcessorData>,
 ) -> Result<task_model::LookupTaskResponseModel, EvelynCoreError> {
     let ds = processor_data.data_store.clone();
+    let zone = user::lookup_time_zone(&ds, &session_token_model.user_id);
 
     match task_data::find_task_by_id(&ds, &request_model.project_id, &request_model.task_id) {
         Ok(result) => {
             if let Some(result) = result {
-                let modified_by_user = user_data::find_user_by_id(&ds, &result.modified_by_user_id);
-
                 Ok(task_model::LookupTaskResponseModel {
-                    task: Some(task_model::TaskExternalModel {
-                        task_id: result.task_id,
-                        project_id: result.project_id,
-                        title: result.title,
-                        description: result.description,
-                        original_estimate: result.original_estimate,
-                        date_modified: dts::timestamp_to_string(result.date_modified),
-                        modified_by_user: match modified_by_user {
-                            Ok(Some(e)) => {
-                                Some(task_model::UserExternalModel {
-                                    user_name: e.user_name,
-                                    user_id: e.user_id,
-                                })
-                            },
-                            _ => {
-                                None
-                            },
-                        },
-                        assignment: match result.assignment {
-                            None => None,
-                            Some(a) => {
-                                let assigned_to_user = user_data::find_user_by_id(&ds, &a.assigned_to_user_id);
-                                let assigned_by_user = user_data::find_user_by_id(&ds, &a.assigned_by_user_id);
-
-                                match (assigned_to_user, assigned_by_user) {
-                                    (Ok(Some(a)), Ok(Some(b))) => {
-                                        Some(task_model::AssignmentExternalModel {
-                                            assigned_to_user: task_model::UserExternalModel {
-                                                user_name: a.user_name,
-                                                user_id: a.user_id,
-                                            },
-                                            assigned_by_user: task_model::UserExternalModel {
-                                                user_name: b.user_name,
-                                                user_id: b.user_id,   
-                                            },
-                                        })
-                                    },
-                                    _ => {
-                                        None
-                                    },
-                                }
-                            }
-                        }
-                    }),
+                    task: Some(to_external_model(&ds, result, &zone)),
                     error: None,
                 })
             }
@@ -189,6 +254,91 @@ pub fn lookup_backlog(
     }
 }
 
+// Statuses are worked out from the links to each task, as a task is only
+// in a sprint through those.
+pub fn lookup_assigned(
+    request_model: task_model::LookupAssignedTasksRequestModel,
+    session_token_model: model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Result<task_model::LookupAssignedTasksResponseModel, EvelynCoreError> {
+    let ds = processor_data.data_store.clone();
+    let user_id = session_token_model.user_id.clone();
+    let zone = user::lookup_time_zone(&ds, &user_id);
+
+    let ref project_filter = request_model.project_id;
+    let project_ids: Vec<String> = match project::lookup_contributing_to(session_token_model, processor_data.clone()) {
+        Ok(result) => result.projects.into_iter().map(|x| x.project_id).filter(|x| {
+            project_filter.as_ref().map_or(true, |project_id| project_id == x)
+        }).collect(),
+        Err(e) => return Err(e),
+    };
+
+    let mut linked_task_ids = Vec::new();
+    for project_id in project_ids.iter() {
+        match heirarchy_data::lookup_links_to_type(&ds, project_id, &heirarchy_model::LinkToTypeNameModel::Task) {
+            Ok(links) => linked_task_ids.extend(links.into_iter().map(|link| link.link_to_id)),
+            Err(e) => return Err(EvelynCoreError::FailedToLookupAgileHeirarchyLinksToType(e)),
+        }
+    }
+
+    let active_sprints = match sprint_data::find_active(&ds, &project_ids) {
+        Ok(sprints) => sprints,
+        Err(e) => return Err(EvelynCoreError::FailedToLookupActiveAgileSprints(e)),
+    };
+
+    let mut in_sprint_task_ids = Vec::new();
+    for active_sprint in active_sprints {
+        match sprint::lookup_task_ids(&ds, &active_sprint.project_id, &active_sprint.sprint_id) {
+            Ok(task_ids) => in_sprint_task_ids.extend(task_ids),
+            Err(e) => return Err(e),
+        }
+    }
+
+    let (task_ids_in, task_ids_not_in) = match request_model.status {
+        None => (None, Vec::new()),
+        Some(task_model::TaskStatusExternalModel::Backlog) => (None, linked_task_ids.clone()),
+        Some(task_model::TaskStatusExternalModel::Planned) => (Some(linked_task_ids.clone()), in_sprint_task_ids.clone()),
+        Some(task_model::TaskStatusExternalModel::InSprint) => (Some(in_sprint_task_ids.clone()), Vec::new()),
+    };
+
+    let lookup_model = task_model::AssignedTaskLookupModel {
+        user_id: user_id,
+        project_ids: project_ids,
+        task_ids_in: task_ids_in,
+        task_ids_not_in: task_ids_not_in,
+        limit: request_model.limit,
+        cursor: request_model.cursor,
+    };
+
+    match task_data::lookup_assigned(&ds, &lookup_model) {
+        Ok((tasks, more)) => {
+            let next_cursor = if more { tasks.last().map(|x| x.task_id.clone()) } else { None };
+
+            Ok(task_model::LookupAssignedTasksResponseModel {
+                tasks: tasks.into_iter().map(|task| {
+                    let status = if in_sprint_task_ids.contains(&task.task_id) {
+                        task_model::TaskStatusExternalModel::InSprint
+                    }
+                    else if linked_task_ids.contains(&task.task_id) {
+                        task_model::TaskStatusExternalModel::Planned
+                    }
+                    else {
+                        task_model::TaskStatusExternalModel::Backlog
+                    };
+
+                    task_model::AssignedTaskExternalModel {
+                        status: status,
+                        task: to_external_model(&ds, task, &zone),
+                    }
+                }).collect(),
+                next_cursor: next_cursor,
+                error: None,
+            })
+        },
+        Err(e) => Err(EvelynCoreError::FailedToLookupAssignedAgileTasks(e)),
+    }
+}
+
 pub fn update(
     request_model: task_model::UpdateTaskRequestModel,
     session_token_model: model::SessionTokenModel,
@@ -208,12 +358,77 @@ pub fn update(
                 assigned_by_user_id: session_token_model.user_id.to_owned(),
             }),
         },
+        expected_version: request_model.expected_version,
     };
 
+    let new_title = update_model.title.to_owned();
+    let new_description = update_model.description.to_owned();
+    let new_original_estimate = update_model.original_estimate.to_owned();
+    let new_assigned_to_user_id = update_model.assignment.as_ref().map(|a| a.assigned_to_user_id.to_owned());
+
     let ds = processor_data.data_store.clone();
 
     match task_data::update(&ds, update_model) {
-        None => None,
-        Some(e) => Some(EvelynCoreError::FailedToUpdateAgileTask(e)),
+        Ok(before) => {
+            let user_id = &session_token_model.user_id;
+            let title = new_title.clone().unwrap_or_else(|| before.title.clone());
+
+            // Only fields which were part of the update are compared.
+            let changes = vec![
+                if new_title.is_some() { audit::change("title", Some(before.title), new_title) } else { None },
+                if new_description.is_some() { audit::change("description", Some(before.description), new_description) } else { None },
+                if new_original_estimate.is_some() { audit::change("originalEstimate", Some(before.original_estimate), new_original_estimate) } else { None },
+            ];
+            if changes.iter().any(|x| x.is_some()) {
+                let details = changes.iter().filter_map(|x| x.as_ref()).filter_map(|x| {
+                    x.after.as_ref().map(|after| (webhook_detail_name(&x.field), after.to_owned()))
+                }).collect();
+                webhook::dispatch(&ds, &before.project_id, WebhookEventTypeModel::TaskUpdated, &before.task_id,
+                                  &title, Some(user_id), details);
+
+                audit::record(&ds, &before.project_id, audit_model::AuditEntityTypeModel::Task, &before.task_id,
+                              audit_model::AuditActionModel::Update, user_id, changes);
+            }
+
+            if let Some(ref assigned_to_user_id) = new_assigned_to_user_id {
+                let before_assigned_to_user_id = before.assignment.as_ref().map(|a| &a.assigned_to_user_id);
+
+                // Users aren't told about tasks they assigned to themselves.
+                if before_assigned_to_user_id != Some(assigned_to_user_id) && assigned_to_user_id != user_id {
+                    processor_data.notifier.notify(&ds, assigned_to_user_id, NotificationKindModel::TaskAssigned,
+                                                   format!("Task assigned: {}", title),
+                                                   format!("You have been assigned the task \"{}\".", title));
+                }
+            }
+
+            if new_assigned_to_user_id.is_some() {
+                let before_assigned_to_user_id = before.assignment.map(|a| a.assigned_to_user_id);
+
+                if let Some(assignment_change) = audit::change("assignedToUserId", before_assigned_to_user_id, new_assigned_to_user_id) {
+                    if let Some(ref assigned_to_user_id) = assignment_change.after {
+                        webhook::dispatch(&ds, &before.project_id, WebhookEventTypeModel::TaskAssigned, &before.task_id,
+                                          &title, Some(user_id), vec![
+                            ("AssignedToUserId", assigned_to_user_id.to_owned()),
+                        ]);
+                    }
+
+                    audit::record(&ds, &before.project_id, audit_model::AuditEntityTypeModel::Task, &before.task_id,
+                                  audit_model::AuditActionModel::Assign, user_id, vec![Some(assignment_change)]);
+                }
+            }
+
+            None
+        },
+        Err(EvelynDatabaseError::AgileTaskNotFound(e)) => Some(EvelynCoreError::AgileTaskNotFound(e)),
+        Err(EvelynDatabaseError::AgileTaskVersionConflict(e)) => Some(EvelynCoreError::AgileTaskVersionConflict(e)),
+        Err(e) => Some(EvelynCoreError::FailedToUpdateAgileTask(e)),
     }
 }
+
+pub fn history(
+    request_model: audit_model::LookupHistoryRequestModel,
+    session_token_model: model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Result<audit_model::LookupHistoryResponseModel, EvelynCoreError> {
+    audit::lookup_history(audit_model::AuditEntityTypeModel::Task, request_model, session_token_model, processor_data)
+}
@@ -14,31 +14,1196 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use core::error_messages::EvelynCoreError;
+use chrono_tz::Tz;
+use core::date_time_service as dts;
+use core::agile::project;
+use core::error_messages::{EvelynBaseError, EvelynCoreError, EvelynDatabaseError};
+use core::icalendar;
+use core::icalendar::{ICalendarEvent, ICalendarWriter};
+use core::recurrence;
+use core::reminder;
+use core::recurrence::RecurrenceRule;
+use core::simple_task;
+use core::user;
+use data;
 use data::calendar as calendar_data;
+use data::version;
 use model;
 use model::calendar as calendar_model;
+use model::reminder::ReminderSourceModel;
+use mongodb::Client;
 use processing::ProcessorData;
+use std::cmp;
 use std::sync::Arc;
 use uuid::Uuid;
 
+const SECONDS_PER_YEAR: i64 = 366 * 24 * 60 * 60;
+
+// How far ahead to look for the next occurrence of a recurring event.
+const MAX_OCCURRENCE_SEARCH_YEARS: i32 = 10;
+
+fn parse_time(time: &String, zone: &Tz) -> Result<i64, EvelynCoreError> {
+    match dts::parse_timestamp(time, zone) {
+        Ok(timestamp) => Ok(timestamp),
+        Err(_) => Err(EvelynCoreError::InvalidCalendarEventTime(EvelynBaseError::NothingElse)),
+    }
+}
+
+fn parse_optional_time(time: &Option<String>, zone: &Tz) -> Result<Option<i64>, EvelynCoreError> {
+    match *time {
+        Some(ref time) => parse_time(time, zone).map(Some),
+        None => Ok(None),
+    }
+}
+
+fn parse_time_range(
+    begin: &String,
+    end: &String,
+    zone: &Tz,
+) -> Result<(i64, i64), EvelynCoreError> {
+    match (parse_time(begin, zone), parse_time(end, zone)) {
+        (Ok(begin), Ok(end)) if begin <= end => Ok((begin, end)),
+        _ => Err(EvelynCoreError::InvalidCalendarEventTime(EvelynBaseError::NothingElse)),
+    }
+}
+
+// The zone times in a request are read in, the one given with the request or
+// else the user's own. Returns the zone's name along with it so that it can
+// be stored on the event.
+fn request_time_zone(
+    ds: &Client,
+    user_id: &String,
+    time_zone: Option<String>,
+) -> Result<(Option<String>, Tz), EvelynCoreError> {
+    let time_zone = time_zone
+        .or_else(|| user::lookup_preferred_time_zone(ds, user_id))
+        .and_then(|x| if x.is_empty() { None } else { Some(x) });
+
+    match dts::parse_optional_time_zone(&time_zone) {
+        Ok(zone) => Ok((time_zone, zone)),
+        Err(e) => Err(e),
+    }
+}
+
+fn event_time_zone(event: &calendar_model::CalendarEventModel) -> Tz {
+    dts::parse_optional_time_zone(&event.time_zone).unwrap_or(dts::UTC)
+}
+
+pub fn legacy_string_to_timestamp(time: &str) -> Option<i64> {
+    dts::parse_timestamp(time, &dts::UTC).ok()
+}
+
+fn parse_recurrence(recurrence: &Option<String>) -> Result<Option<RecurrenceRule>, EvelynCoreError> {
+    match *recurrence {
+        Some(ref recurrence) => recurrence::parse(recurrence).map(Some),
+        None => Ok(None),
+    }
+}
+
+// The end of the last occurrence, including any occurrences which have been
+// moved later.
+fn calculate_series_end(
+    rule: &RecurrenceRule,
+    event_begin: i64,
+    event_end: i64,
+    overrides: &Vec<calendar_model::OccurrenceOverrideModel>,
+    zone: &Tz,
+) -> Option<i64> {
+    recurrence::series_end(rule, event_begin, event_end - event_begin, zone)
+        .map(|series_end| overrides.iter().fold(series_end, |series_end, x| cmp::max(series_end, x.event_end)))
+}
+
+fn to_attendee_response_model(response: calendar_model::AttendeeResponseExternalModel) -> calendar_model::AttendeeResponseModel {
+    match response {
+        calendar_model::AttendeeResponseExternalModel::NeedsAction => calendar_model::AttendeeResponseModel::NeedsAction,
+        calendar_model::AttendeeResponseExternalModel::Accepted => calendar_model::AttendeeResponseModel::Accepted,
+        calendar_model::AttendeeResponseExternalModel::Declined => calendar_model::AttendeeResponseModel::Declined,
+        calendar_model::AttendeeResponseExternalModel::Tentative => calendar_model::AttendeeResponseModel::Tentative,
+    }
+}
+
+fn to_attendee_external_model(attendee: &calendar_model::AttendeeModel) -> calendar_model::AttendeeExternalModel {
+    calendar_model::AttendeeExternalModel {
+        user_id: attendee.user_id.clone(),
+        user_group_id: attendee.user_group_id.clone(),
+        response: match attendee.response {
+            calendar_model::AttendeeResponseModel::NeedsAction => calendar_model::AttendeeResponseExternalModel::NeedsAction,
+            calendar_model::AttendeeResponseModel::Accepted => calendar_model::AttendeeResponseExternalModel::Accepted,
+            calendar_model::AttendeeResponseModel::Declined => calendar_model::AttendeeResponseExternalModel::Declined,
+            calendar_model::AttendeeResponseModel::Tentative => calendar_model::AttendeeResponseExternalModel::Tentative,
+        },
+    }
+}
+
+fn to_external_model(
+    event: &calendar_model::CalendarEventModel,
+    occurrence_begin: Option<i64>,
+    event_begin: i64,
+    event_end: i64,
+    title: String,
+    zone: &Tz,
+) -> calendar_model::CalendarEventExternalModel {
+    calendar_model::CalendarEventExternalModel {
+        event_id: event.event_id.clone(),
+        organizer_user_id: event.user_id.clone(),
+        event_begin: dts::timestamp_to_string(event_begin, zone),
+        event_end: dts::timestamp_to_string(event_end, zone),
+        title: title,
+        recurrence: event.recurrence.clone(),
+        time_zone: event.time_zone.clone(),
+        occurrence_begin: occurrence_begin.map(|x| dts::timestamp_to_string(x, zone)),
+        attendees: event.attendees.iter().map(to_attendee_external_model).collect(),
+        version: event.version,
+    }
+}
+
+// The occurrences of the event which overlap the range, as the time the
+// occurrence would have begun (None for events which don't recur), its begin
+// and end times and its title. Occurrences are worked out in the event's zone.
+fn occurrences_in_range(
+    event: &calendar_model::CalendarEventModel,
+    range_begin: i64,
+    range_end: i64,
+) -> Vec<(Option<i64>, i64, i64, String)> {
+    let rule = match parse_recurrence(&event.recurrence) {
+        Ok(Some(rule)) => rule,
+        Ok(None) => return vec![(None, event.event_begin, event.event_end, event.title.clone())],
+        Err(_) => {
+            warn!("Skipping calendar event with an invalid recurrence rule [{}]", event.event_id);
+            return Vec::new();
+        },
+    };
+
+    let duration = event.event_end - event.event_begin;
+    let mut occurrences = Vec::new();
+
+    for occurrence_begin in recurrence::occurrences_between(&rule, event.event_begin, duration, range_begin, range_end, &event_time_zone(event)) {
+        let changed = event.exception_dates.contains(&occurrence_begin) ||
+            event.overrides.iter().any(|x| x.occurrence_begin == occurrence_begin);
+
+        if !changed {
+            occurrences.push((Some(occurrence_begin), occurrence_begin, occurrence_begin + duration, event.title.clone()));
+        }
+    }
+
+    // Moved occurrences are checked separately because they may have been
+    // moved into the range from outside it.
+    for x in &event.overrides {
+        if x.event_begin <= range_end && x.event_end >= range_begin && !event.exception_dates.contains(&x.occurrence_begin) {
+            let title = x.title.clone().unwrap_or(event.title.clone());
+            occurrences.push((Some(x.occurrence_begin), x.event_begin, x.event_end, title));
+        }
+    }
+
+    occurrences
+}
+
+// The first occurrence of the event beginning at or after the given time.
+// Recurring events are searched a year at a time until the series ends.
+pub fn next_occurrence_begin(
+    event: &calendar_model::CalendarEventModel,
+    after: i64,
+) -> Option<i64> {
+    let mut range_begin = after;
+
+    for _ in 0..MAX_OCCURRENCE_SEARCH_YEARS {
+        let range_end = range_begin + SECONDS_PER_YEAR;
+
+        let next = occurrences_in_range(event, range_begin, range_end).into_iter()
+            .map(|x| x.1)
+            .filter(|x| *x >= after)
+            .min();
+
+        if next.is_some() || event.recurrence.is_none() || event.series_end.map_or(false, |x| x < range_end) {
+            return next;
+        }
+
+        range_begin = range_end;
+    }
+
+    None
+}
+
+// Adds the occurrences of the event which overlap the range, paired with
+// their begin and end times for sorting. Occurrences are rendered in the
+// caller's zone.
+fn expand_event(
+    event: calendar_model::CalendarEventModel,
+    range_begin: i64,
+    range_end: i64,
+    zone: &Tz,
+    occurrences: &mut Vec<(i64, i64, calendar_model::CalendarEventExternalModel)>,
+) {
+    for (occurrence_begin, event_begin, event_end, title) in occurrences_in_range(&event, range_begin, range_end) {
+        let external_model = to_external_model(&event, occurrence_begin, event_begin, event_end, title, zone);
+        occurrences.push((event_begin, event_end, external_model));
+    }
+}
+
 pub fn calendar_add_event(
     model: calendar_model::CalendarAddEventRequestModel,
     session_token_model: model::SessionTokenModel,
     processor_data: Arc<ProcessorData>,
 ) -> Option<EvelynCoreError> {
+    let ds = processor_data.data_store.clone();
+
+    let (time_zone, zone) = match request_time_zone(&ds, &session_token_model.user_id, model.time_zone) {
+        Ok(time_zone) => time_zone,
+        Err(e) => return Some(e),
+    };
+
+    let (event_begin, event_end) = match parse_time_range(&model.event_begin, &model.event_end, &zone) {
+        Ok(range) => range,
+        Err(e) => return Some(e),
+    };
+
+    let series_end = match parse_recurrence(&model.recurrence) {
+        Ok(rule) => rule.and_then(|rule| calculate_series_end(&rule, event_begin, event_end, &Vec::new(), &zone)),
+        Err(e) => return Some(e),
+    };
+
     let calendar_event_model = calendar_model::CalendarEventModel {
         user_id: session_token_model.user_id,
         event_id: format!("{}", Uuid::new_v4()),
-        event_begin: model.event_begin,
-        event_end: model.event_end,
+        event_begin: event_begin,
+        event_end: event_end,
         title: model.title,
+        uid: None,
+        recurrence: model.recurrence,
+        time_zone: time_zone,
+        series_end: series_end,
+        exception_dates: Vec::new(),
+        overrides: Vec::new(),
+        attendees: Vec::new(),
+        version: version::INITIAL_VERSION,
+        deleted: None,
     };
 
-    let ds = processor_data.data_store.clone();
-
     match calendar_data::insert_calendar_event(&ds, &calendar_event_model) {
         Some(e) => Some(EvelynCoreError::FailedToAddCalendarEvent(e)),
         None => None,
     }
 }
+
+pub fn calendar_lookup(
+    model: calendar_model::CalendarLookupRequestModel,
+    session_token_model: model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Result<calendar_model::CalendarLookupResponseModel, EvelynCoreError> {
+    let ds = processor_data.data_store.clone();
+    let zone = user::lookup_time_zone(&ds, &session_token_model.user_id);
+
+    let (range_begin, range_end) = match parse_time_range(&model.time_range_begin, &model.time_range_end, &zone) {
+        Ok(range) => range,
+        Err(e) => return Err(e),
+    };
+
+    match lookup_occurrences(&ds, &session_token_model.user_id, range_begin, range_end, &zone) {
+        Ok(events) => {
+            Ok(calendar_model::CalendarLookupResponseModel {
+                events: events,
+                error: None,
+            })
+        },
+        Err(e) => Err(e),
+    }
+}
+
+// The occurrences in the range of the events the user organises or has been
+// invited to, in the order they begin.
+pub fn lookup_occurrences(
+    ds: &Client,
+    user_id: &String,
+    range_begin: i64,
+    range_end: i64,
+    zone: &Tz,
+) -> Result<Vec<calendar_model::CalendarEventExternalModel>, EvelynCoreError> {
+    match calendar_data::lookup_events_attended_by(ds, &vec![user_id.clone()], range_begin, range_end) {
+        Ok(events) => {
+            let mut occurrences = Vec::new();
+            for event in events {
+                expand_event(event, range_begin, range_end, zone, &mut occurrences);
+            }
+
+            occurrences.sort_by(|a, b| (a.0, a.1).cmp(&(b.0, b.1)));
+
+            Ok(occurrences.into_iter().map(|x| x.2).collect())
+        },
+        Err(e) => Err(EvelynCoreError::FailedToLookupCalendarEvents(e)),
+    }
+}
+
+pub fn calendar_update_event(
+    model: calendar_model::CalendarUpdateEventRequestModel,
+    session_token_model: model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Option<EvelynCoreError> {
+    let ds = processor_data.data_store.clone();
+
+    let zone = match request_time_zone(&ds, &session_token_model.user_id, model.new_time_zone.clone()) {
+        Ok((_, zone)) => zone,
+        Err(e) => return Some(e),
+    };
+
+    let new_event_begin = match parse_optional_time(&model.new_event_begin, &zone) {
+        Ok(time) => time,
+        Err(e) => return Some(e),
+    };
+    let new_event_end = match parse_optional_time(&model.new_event_end, &zone) {
+        Ok(time) => time,
+        Err(e) => return Some(e),
+    };
+
+    let mut expected_version = model.expected_version;
+
+    // The new schedule is worked out against the stored event and the update
+    // is then made against the version which was checked, so that a
+    // concurrent change can't leave the event inverted.
+    let schedule = if new_event_begin.is_some() || new_event_end.is_some() || model.new_recurrence.is_some() || model.clear_recurrence || model.new_time_zone.is_some() {
+        let event = match calendar_data::lookup_event(&ds, &session_token_model.user_id, &model.event_id) {
+            Ok(event) => event,
+            Err(EvelynDatabaseError::CalendarEventNotFound(e)) => return Some(EvelynCoreError::CalendarEventNotFound(e)),
+            Err(e) => return Some(EvelynCoreError::FailedToUpdateCalendarEvent(e)),
+        };
+
+        if expected_version.is_none() {
+            expected_version = Some(event.version);
+        }
+
+        let event_begin = new_event_begin.unwrap_or(event.event_begin);
+        let event_end = new_event_end.unwrap_or(event.event_end);
+        if event_end < event_begin {
+            return Some(EvelynCoreError::InvalidCalendarEventTime(EvelynBaseError::NothingElse));
+        }
+
+        let recurrence = if model.clear_recurrence {
+            None
+        } else if model.new_recurrence.is_some() {
+            model.new_recurrence
+        } else {
+            event.recurrence.clone()
+        };
+
+        let rule = match parse_recurrence(&recurrence) {
+            Ok(rule) => rule,
+            Err(e) => return Some(e),
+        };
+
+        // An empty zone clears it.
+        let time_zone = match model.new_time_zone {
+            Some(ref time_zone) if time_zone.is_empty() => None,
+            Some(time_zone) => Some(time_zone),
+            None => event.time_zone.clone(),
+        };
+        let event_zone = dts::parse_optional_time_zone(&time_zone).unwrap_or(dts::UTC);
+
+        let clear_exceptions = event_begin != event.event_begin || recurrence != event.recurrence || time_zone != event.time_zone;
+        let overrides = if clear_exceptions {
+            Vec::new()
+        } else {
+            event.overrides
+        };
+
+        Some(calendar_model::CalendarEventScheduleModel {
+            event_begin: event_begin,
+            event_end: event_end,
+            recurrence: recurrence,
+            series_end: rule.and_then(|rule| calculate_series_end(&rule, event_begin, event_end, &overrides, &event_zone)),
+            time_zone: time_zone,
+            clear_exceptions: clear_exceptions,
+        })
+    } else {
+        None
+    };
+
+    let event_id = model.event_id.clone();
+    let schedule_changed = schedule.is_some();
+
+    let update_model = calendar_model::CalendarEventUpdateModel {
+        user_id: session_token_model.user_id,
+        event_id: model.event_id,
+        title: model.new_title,
+        schedule: schedule,
+        expected_version: expected_version,
+    };
+
+    match calendar_data::update_event(&ds, update_model) {
+        None => {
+            if schedule_changed {
+                reminder::source_changed(&ds, ReminderSourceModel::CalendarEvent, &event_id);
+            }
+            None
+        },
+        Some(EvelynDatabaseError::CalendarEventNotFound(e)) => Some(EvelynCoreError::CalendarEventNotFound(e)),
+        Some(EvelynDatabaseError::CalendarEventVersionConflict(e)) => Some(EvelynCoreError::CalendarEventVersionConflict(e)),
+        Some(e) => Some(EvelynCoreError::FailedToUpdateCalendarEvent(e)),
+    }
+}
+
+pub fn calendar_remove_event(
+    model: calendar_model::CalendarRemoveEventRequestModel,
+    session_token_model: model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Option<EvelynCoreError> {
+    let ds = processor_data.data_store.clone();
+
+    let deletion = model::trash::DeletionModel {
+        deleted_by_user_id: session_token_model.user_id.clone(),
+        date_deleted: dts::get_timestamp(),
+    };
+
+    match calendar_data::remove_event(&ds, &session_token_model.user_id, &model.event_id, &deletion) {
+        None => None,
+        Some(EvelynDatabaseError::CalendarEventNotFound(e)) => Some(EvelynCoreError::CalendarEventNotFound(e)),
+        Some(e) => Some(EvelynCoreError::FailedToRemoveCalendarEvent(e)),
+    }
+}
+
+// Loads a recurring event and checks that it has an occurrence beginning at
+// the given time which hasn't been cancelled.
+fn lookup_occurrence(
+    ds: &Client,
+    user_id: &String,
+    event_id: &String,
+    occurrence_begin: &String,
+    zone: &Tz,
+    lookup_error: fn(EvelynDatabaseError) -> EvelynCoreError,
+) -> Result<(calendar_model::CalendarEventModel, RecurrenceRule, i64), EvelynCoreError> {
+    let occurrence_begin = match parse_time(occurrence_begin, zone) {
+        Ok(occurrence_begin) => occurrence_begin,
+        Err(e) => return Err(e),
+    };
+
+    let event = match calendar_data::lookup_event(ds, user_id, event_id) {
+        Ok(event) => event,
+        Err(EvelynDatabaseError::CalendarEventNotFound(e)) => return Err(EvelynCoreError::CalendarEventNotFound(e)),
+        Err(e) => return Err(lookup_error(e)),
+    };
+
+    let rule = match parse_recurrence(&event.recurrence) {
+        Ok(Some(rule)) => rule,
+        Ok(None) => return Err(EvelynCoreError::CalendarEventOccurrenceNotFound(EvelynBaseError::NothingElse)),
+        Err(e) => return Err(e),
+    };
+
+    if event.exception_dates.contains(&occurrence_begin) || !recurrence::is_occurrence(&rule, event.event_begin, occurrence_begin, &event_time_zone(&event)) {
+        return Err(EvelynCoreError::CalendarEventOccurrenceNotFound(EvelynBaseError::NothingElse));
+    }
+
+    Ok((event, rule, occurrence_begin))
+}
+
+fn update_occurrences(
+    ds: &Client,
+    update_model: calendar_model::CalendarEventOccurrencesUpdateModel,
+    update_error: fn(EvelynDatabaseError) -> EvelynCoreError,
+) -> Option<EvelynCoreError> {
+    let event_id = update_model.event_id.clone();
+
+    match calendar_data::update_occurrences(ds, update_model) {
+        None => {
+            reminder::source_changed(ds, ReminderSourceModel::CalendarEvent, &event_id);
+            None
+        },
+        Some(EvelynDatabaseError::CalendarEventNotFound(e)) => Some(EvelynCoreError::CalendarEventNotFound(e)),
+        Some(EvelynDatabaseError::CalendarEventVersionConflict(e)) => Some(EvelynCoreError::CalendarEventVersionConflict(e)),
+        Some(e) => Some(update_error(e)),
+    }
+}
+
+pub fn calendar_update_occurrence(
+    model: calendar_model::CalendarUpdateOccurrenceRequestModel,
+    session_token_model: model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Option<EvelynCoreError> {
+    let ds = processor_data.data_store.clone();
+    let zone = user::lookup_time_zone(&ds, &session_token_model.user_id);
+
+    let new_event_begin = match parse_optional_time(&model.new_event_begin, &zone) {
+        Ok(time) => time,
+        Err(e) => return Some(e),
+    };
+    let new_event_end = match parse_optional_time(&model.new_event_end, &zone) {
+        Ok(time) => time,
+        Err(e) => return Some(e),
+    };
+
+    let (mut event, rule, occurrence_begin) = match lookup_occurrence(&ds, &session_token_model.user_id, &model.event_id, &model.occurrence_begin, &zone, EvelynCoreError::FailedToUpdateCalendarEventOccurrence) {
+        Ok(occurrence) => occurrence,
+        Err(e) => return Some(e),
+    };
+
+    let existing = event.overrides.iter().position(|x| x.occurrence_begin == occurrence_begin);
+    let (title, event_begin, event_end) = match existing {
+        Some(index) => {
+            let ref current = event.overrides[index];
+            (current.title.clone(), current.event_begin, current.event_end)
+        },
+        None => (None, occurrence_begin, occurrence_begin + event.event_end - event.event_begin),
+    };
+
+    let occurrence_override = calendar_model::OccurrenceOverrideModel {
+        occurrence_begin: occurrence_begin,
+        title: model.new_title.or(title),
+        event_begin: new_event_begin.unwrap_or(event_begin),
+        event_end: new_event_end.unwrap_or(event_end),
+    };
+
+    if occurrence_override.event_end < occurrence_override.event_begin {
+        return Some(EvelynCoreError::InvalidCalendarEventTime(EvelynBaseError::NothingElse));
+    }
+
+    match existing {
+        Some(index) => event.overrides[index] = occurrence_override,
+        None => event.overrides.push(occurrence_override),
+    }
+
+    let update_model = calendar_model::CalendarEventOccurrencesUpdateModel {
+        series_end: calculate_series_end(&rule, event.event_begin, event.event_end, &event.overrides, &event_time_zone(&event)),
+        user_id: session_token_model.user_id,
+        event_id: model.event_id,
+        exception_dates: event.exception_dates,
+        overrides: event.overrides,
+        expected_version: model.expected_version.unwrap_or(event.version),
+    };
+
+    update_occurrences(&ds, update_model, EvelynCoreError::FailedToUpdateCalendarEventOccurrence)
+}
+
+pub fn calendar_cancel_occurrence(
+    model: calendar_model::CalendarCancelOccurrenceRequestModel,
+    session_token_model: model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Option<EvelynCoreError> {
+    let ds = processor_data.data_store.clone();
+    let zone = user::lookup_time_zone(&ds, &session_token_model.user_id);
+
+    let (mut event, rule, occurrence_begin) = match lookup_occurrence(&ds, &session_token_model.user_id, &model.event_id, &model.occurrence_begin, &zone, EvelynCoreError::FailedToCancelCalendarEventOccurrence) {
+        Ok(occurrence) => occurrence,
+        Err(e) => return Some(e),
+    };
+
+    event.overrides.retain(|x| x.occurrence_begin != occurrence_begin);
+    event.exception_dates.push(occurrence_begin);
+
+    let update_model = calendar_model::CalendarEventOccurrencesUpdateModel {
+        series_end: calculate_series_end(&rule, event.event_begin, event.event_end, &event.overrides, &event_time_zone(&event)),
+        user_id: session_token_model.user_id,
+        event_id: model.event_id,
+        exception_dates: event.exception_dates,
+        overrides: event.overrides,
+        expected_version: model.expected_version.unwrap_or(event.version),
+    };
+
+    update_occurrences(&ds, update_model, EvelynCoreError::FailedToCancelCalendarEventOccurrence)
+}
+
+fn invitee_not_found() -> EvelynCoreError {
+    EvelynCoreError::CalendarInviteeNotFound(EvelynBaseError::NothingElse)
+}
+
+// Adds users and the members of user groups to the event's attendees. Only
+// the organizer can invite, and only to groups they can see. People who are
+// already invited keep their response.
+pub fn calendar_invite(
+    model: calendar_model::CalendarInviteRequestModel,
+    session_token_model: model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Option<EvelynCoreError> {
+    let ds = processor_data.data_store.clone();
+    let ref organizer_user_id = session_token_model.user_id;
+
+    let event = match calendar_data::lookup_event(&ds, organizer_user_id, &model.event_id) {
+        Ok(event) => event,
+        Err(EvelynDatabaseError::CalendarEventNotFound(e)) => return Some(EvelynCoreError::CalendarEventNotFound(e)),
+        Err(e) => return Some(EvelynCoreError::FailedToInviteToCalendarEvent(e)),
+    };
+
+    let mut invitees: Vec<(String, Option<String>)> = model.user_ids.into_iter().map(|x| (x, None)).collect();
+
+    for user_group_id in model.user_group_ids {
+        match data::user_group::lookup_user_group(&ds, organizer_user_id, &user_group_id) {
+            Ok(user_group) => {
+                for member in user_group.members {
+                    invitees.push((member.user_id, Some(user_group_id.clone())));
+                }
+            },
+            Err(EvelynDatabaseError::UserGroupNotFound(_)) => return Some(invitee_not_found()),
+            Err(e) => return Some(EvelynCoreError::FailedToInviteToCalendarEvent(e)),
+        }
+    }
+
+    let mut attendees: Vec<calendar_model::AttendeeModel> = Vec::new();
+
+    for (user_id, user_group_id) in invitees {
+        if &user_id == organizer_user_id ||
+            event.attendees.iter().chain(attendees.iter()).any(|x| x.user_id == user_id) {
+            continue;
+        }
+
+        match data::user::find_user_by_id(&ds, &user_id) {
+            Ok(Some(_)) => {},
+            Ok(None) => return Some(invitee_not_found()),
+            Err(e) => return Some(EvelynCoreError::FailedToInviteToCalendarEvent(e)),
+        }
+
+        attendees.push(calendar_model::AttendeeModel {
+            user_id: user_id,
+            user_group_id: user_group_id,
+            response: calendar_model::AttendeeResponseModel::NeedsAction,
+        });
+    }
+
+    let update_model = calendar_model::CalendarEventAttendeesUpdateModel {
+        user_id: organizer_user_id.clone(),
+        event_id: model.event_id,
+        attendees: attendees,
+        expected_version: model.expected_version.unwrap_or(event.version),
+    };
+
+    match calendar_data::add_attendees(&ds, update_model) {
+        None => None,
+        Some(EvelynDatabaseError::CalendarEventNotFound(e)) => Some(EvelynCoreError::CalendarEventNotFound(e)),
+        Some(EvelynDatabaseError::CalendarEventVersionConflict(e)) => Some(EvelynCoreError::CalendarEventVersionConflict(e)),
+        Some(e) => Some(EvelynCoreError::FailedToInviteToCalendarEvent(e)),
+    }
+}
+
+pub fn calendar_respond(
+    model: calendar_model::CalendarRespondRequestModel,
+    session_token_model: model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Option<EvelynCoreError> {
+    let ds = processor_data.data_store.clone();
+    let response = to_attendee_response_model(model.response);
+
+    match calendar_data::respond(&ds, &session_token_model.user_id, &model.event_id, &response) {
+        None => None,
+        Some(EvelynDatabaseError::NotInvitedToCalendarEvent(e)) => Some(EvelynCoreError::NotInvitedToCalendarEvent(e)),
+        Some(e) => Some(EvelynCoreError::FailedToRespondToCalendarEvent(e)),
+    }
+}
+
+// Whether the event keeps the user busy, attendees who have declined are
+// free.
+fn is_busy(event: &calendar_model::CalendarEventModel, user_id: &String) -> bool {
+    &event.user_id == user_id ||
+        event.attendees.iter().any(|x| &x.user_id == user_id && x.response != calendar_model::AttendeeResponseModel::Declined)
+}
+
+// Sorts the periods and joins together any which overlap or touch.
+fn merge_busy_periods(mut periods: Vec<(i64, i64)>) -> Vec<(i64, i64)> {
+    periods.sort();
+
+    let mut merged: Vec<(i64, i64)> = Vec::new();
+    for (begin, end) in periods {
+        if let Some(last) = merged.last_mut() {
+            if begin <= last.1 {
+                last.1 = cmp::max(last.1, end);
+                continue;
+            }
+        }
+
+        merged.push((begin, end));
+    }
+
+    merged
+}
+
+// The user groups and agile projects a user is part of.
+fn lookup_circles(ds: &Client, user_id: &String) -> Result<(Vec<String>, Vec<String>), EvelynDatabaseError> {
+    let user_groups: Vec<model::user_group::UserGroupsExternalModel> = match data::user_group::lookup_user_groups(user_id, ds) {
+        Ok(user_groups) => user_groups.into_iter().map(|x| model::user_group::UserGroupsExternalModel {
+            user_group_id: x.user_group_id,
+            name: x.name,
+            description: x.description,
+        }).collect(),
+        Err(e) => return Err(e),
+    };
+    let user_group_ids = user_groups.iter().map(|x| x.user_group_id.clone()).collect();
+
+    match data::agile::project::lookup_contributing_to(ds, user_id, user_groups) {
+        Ok(projects) => Ok((user_group_ids, projects.into_iter().map(|x| x.project_id).collect())),
+        Err(e) => Err(e),
+    }
+}
+
+// Free busy times are only shown for users who share a user group or an
+// agile project with the caller, or who the caller has invited to an event.
+fn check_free_busy_access(ds: &Client, caller_user_id: &String, user_ids: &Vec<String>) -> Option<EvelynCoreError> {
+    let (caller_user_group_ids, caller_project_ids) = match lookup_circles(ds, caller_user_id) {
+        Ok(circles) => circles,
+        Err(e) => return Some(EvelynCoreError::FailedToLookupFreeBusy(e)),
+    };
+
+    for user_id in user_ids {
+        if user_id == caller_user_id {
+            continue;
+        }
+
+        match calendar_data::has_invited(ds, caller_user_id, user_id) {
+            Ok(true) => continue,
+            Ok(false) => {},
+            Err(e) => return Some(EvelynCoreError::FailedToLookupFreeBusy(e)),
+        }
+
+        let (user_group_ids, project_ids) = match lookup_circles(ds, user_id) {
+            Ok(circles) => circles,
+            Err(e) => return Some(EvelynCoreError::FailedToLookupFreeBusy(e)),
+        };
+
+        if !user_group_ids.iter().any(|x| caller_user_group_ids.contains(x)) &&
+            !project_ids.iter().any(|x| caller_project_ids.contains(x)) {
+            return Some(invitee_not_found());
+        }
+    }
+
+    None
+}
+
+// The times in the range each user is busy, without saying what they are
+// doing, to help find a time to meet.
+pub fn calendar_free_busy(
+    model: calendar_model::CalendarFreeBusyRequestModel,
+    session_token_model: model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Result<calendar_model::CalendarFreeBusyResponseModel, EvelynCoreError> {
+    let ds = processor_data.data_store.clone();
+    let zone = user::lookup_time_zone(&ds, &session_token_model.user_id);
+
+    let (range_begin, range_end) = match parse_time_range(&model.time_range_begin, &model.time_range_end, &zone) {
+        Ok(range) => range,
+        Err(e) => return Err(e),
+    };
+
+    if let Some(e) = check_free_busy_access(&ds, &session_token_model.user_id, &model.user_ids) {
+        return Err(e);
+    }
+
+    let events = match calendar_data::lookup_events_attended_by(&ds, &model.user_ids, range_begin, range_end) {
+        Ok(events) => events,
+        Err(e) => return Err(EvelynCoreError::FailedToLookupFreeBusy(e)),
+    };
+
+    let occurrences: Vec<(&calendar_model::CalendarEventModel, Vec<(Option<i64>, i64, i64, String)>)> = events.iter()
+        .map(|x| (x, occurrences_in_range(x, range_begin, range_end)))
+        .collect();
+
+    let users = model.user_ids.iter().map(|user_id| {
+        let periods = occurrences.iter()
+            .filter(|&&(event, _)| is_busy(event, user_id))
+            .flat_map(|&(_, ref event_occurrences)| event_occurrences.iter().map(|x| (x.1, x.2)))
+            .collect();
+
+        calendar_model::FreeBusyExternalModel {
+            user_id: user_id.clone(),
+            busy: merge_busy_periods(periods).into_iter().map(|(begin, end)| calendar_model::BusyPeriodExternalModel {
+                begin: dts::timestamp_to_string(begin, &zone),
+                end: dts::timestamp_to_string(end, &zone),
+            }).collect(),
+        }
+    }).collect();
+
+    Ok(calendar_model::CalendarFreeBusyResponseModel {
+        users: users,
+        error: None,
+    })
+}
+
+const EVELYN_UID_SUFFIX: &'static str = "@evelyn";
+const SIMPLE_TASK_UID_SUFFIX: &'static str = "@evelyn-simpletask";
+const SPRINT_UID_SUFFIX: &'static str = "@evelyn-sprint";
+
+// Events created in Evelyn are given a uid based on their id when exported,
+// so that importing the export again updates them rather than making copies.
+fn event_uid(event: &calendar_model::CalendarEventModel) -> String {
+    match event.uid {
+        Some(ref uid) => uid.clone(),
+        None => format!("{}{}", event.event_id, EVELYN_UID_SUFFIX),
+    }
+}
+
+fn write_event_time(
+    writer: &mut ICalendarWriter,
+    name: &str,
+    timestamps: &[i64],
+    time_zone: &Option<String>,
+) {
+    match *time_zone {
+        Some(ref time_zone) => writer.zoned_date_time_property(name, timestamps, time_zone),
+        None => {
+            let values: Vec<String> = timestamps.iter().map(|x| icalendar::format_date_time(*x)).collect();
+            writer.property(name, &values.join(","));
+        },
+    }
+}
+
+fn write_event(
+    writer: &mut ICalendarWriter,
+    event: &calendar_model::CalendarEventModel,
+    timestamp: i64,
+) {
+    let uid = event_uid(event);
+
+    writer.begin("VEVENT");
+    writer.text_property("UID", &uid);
+    writer.date_time_property("DTSTAMP", timestamp);
+    write_event_time(writer, "DTSTART", &[event.event_begin], &event.time_zone);
+    write_event_time(writer, "DTEND", &[event.event_end], &event.time_zone);
+    writer.text_property("SUMMARY", &event.title);
+    if let Some(ref recurrence) = event.recurrence {
+        writer.property("RRULE", recurrence);
+        if !event.exception_dates.is_empty() {
+            write_event_time(writer, "EXDATE", &event.exception_dates, &event.time_zone);
+        }
+    }
+    writer.end("VEVENT");
+
+    for x in &event.overrides {
+        writer.begin("VEVENT");
+        writer.text_property("UID", &uid);
+        writer.date_time_property("DTSTAMP", timestamp);
+        write_event_time(writer, "RECURRENCE-ID", &[x.occurrence_begin], &event.time_zone);
+        write_event_time(writer, "DTSTART", &[x.event_begin], &event.time_zone);
+        write_event_time(writer, "DTEND", &[x.event_end], &event.time_zone);
+        writer.text_property("SUMMARY", x.title.as_ref().unwrap_or(&event.title));
+        writer.end("VEVENT");
+    }
+}
+
+fn write_simple_task(
+    writer: &mut ICalendarWriter,
+    task: &model::simple_task::SimpleTaskModel,
+    timestamp: i64,
+) {
+    writer.begin("VTODO");
+    writer.text_property("UID", &format!("{}{}", task.task_id, SIMPLE_TASK_UID_SUFFIX));
+    writer.date_time_property("DTSTAMP", timestamp);
+    writer.text_property("SUMMARY", &task.title);
+    if !task.description.is_empty() {
+        writer.text_property("DESCRIPTION", &task.description);
+    }
+    if let Some(due) = task.due_date {
+        match simple_task::due_day(task) {
+            Some(day) => writer.date_property("DUE", &day),
+            None => writer.date_time_property("DUE", due),
+        }
+    }
+    writer.property("STATUS", if task.completed { "COMPLETED" } else { "NEEDS-ACTION" });
+    writer.end("VTODO");
+}
+
+fn write_sprint(
+    writer: &mut ICalendarWriter,
+    sprint: &model::agile::sprint::SprintModel,
+    timestamp: i64,
+) {
+    writer.begin("VEVENT");
+    writer.text_property("UID", &format!("{}{}", sprint.sprint_id, SPRINT_UID_SUFFIX));
+    writer.date_time_property("DTSTAMP", timestamp);
+    writer.date_time_property("DTSTART", sprint.start_date);
+    writer.date_time_property("DTEND", sprint.end_date);
+    writer.text_property("SUMMARY", &sprint.title);
+    writer.property("CATEGORIES", "SPRINT");
+    writer.end("VEVENT");
+}
+
+pub fn event_to_icalendar(event: &calendar_model::CalendarEventModel) -> String {
+    let mut writer = ICalendarWriter::new();
+    write_event(&mut writer, event, dts::get_timestamp());
+    writer.finish()
+}
+
+pub fn calendar_export(
+    model: calendar_model::CalendarExportRequestModel,
+    session_token_model: model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Result<calendar_model::CalendarExportResponseModel, EvelynCoreError> {
+    let ds = processor_data.data_store.clone();
+    let timestamp = dts::get_timestamp();
+
+    let mut writer = ICalendarWriter::new();
+
+    match calendar_data::lookup_all_events(&ds, &session_token_model.user_id) {
+        Ok(events) => {
+            for event in &events {
+                write_event(&mut writer, event, timestamp);
+            }
+        },
+        Err(e) => return Err(EvelynCoreError::FailedToExportCalendar(e)),
+    }
+
+    if model.include_simple_tasks {
+        match data::simple_task::lookup_all_simple_tasks(&ds, &session_token_model.user_id) {
+            Ok(tasks) => {
+                for task in &tasks {
+                    write_simple_task(&mut writer, task, timestamp);
+                }
+            },
+            Err(e) => return Err(EvelynCoreError::FailedToExportCalendar(e)),
+        }
+    }
+
+    if model.include_sprints {
+        let project_session_token_model = model::SessionTokenModel {
+            user_id: session_token_model.user_id.clone(),
+            server_session_token: session_token_model.server_session_token.clone(),
+        };
+
+        let project_ids: Vec<String> = match project::lookup_contributing_to(project_session_token_model, processor_data.clone()) {
+            Ok(result) => result.projects.into_iter().map(|x| x.project_id).collect(),
+            Err(e) => return Err(e),
+        };
+
+        match data::agile::sprint::lookup_for_projects(&ds, &project_ids) {
+            Ok(sprints) => {
+                for sprint in &sprints {
+                    write_sprint(&mut writer, sprint, timestamp);
+                }
+            },
+            Err(e) => return Err(EvelynCoreError::FailedToExportCalendar(e)),
+        }
+    }
+
+    Ok(calendar_model::CalendarExportResponseModel {
+        calendar: Some(writer.finish()),
+        error: None,
+    })
+}
+
+fn find_imported_event(
+    ds: &Client,
+    user_id: &String,
+    uid: &String,
+) -> Result<Option<calendar_model::CalendarEventModel>, EvelynDatabaseError> {
+    match calendar_data::lookup_event_by_uid(ds, user_id, uid) {
+        Ok(None) if uid.ends_with(EVELYN_UID_SUFFIX) => {
+            let event_id = String::from(uid.trim_right_matches(EVELYN_UID_SUFFIX));
+
+            match calendar_data::lookup_event(ds, user_id, &event_id) {
+                Ok(event) => Ok(Some(event)),
+                Err(EvelynDatabaseError::CalendarEventNotFound(_)) => Ok(None),
+                Err(e) => Err(e),
+            }
+        },
+        result => result,
+    }
+}
+
+// Builds the event for a VEVENT along with its changed occurrences, None if
+// it uses recurrence features which Evelyn doesn't support.
+fn to_event_model(
+    user_id: &String,
+    event_id: String,
+    version: i64,
+    event: ICalendarEvent,
+    changed_occurrences: Vec<ICalendarEvent>,
+    attendees: Vec<calendar_model::AttendeeModel>,
+) -> Option<calendar_model::CalendarEventModel> {
+    let rule = match parse_recurrence(&event.recurrence) {
+        Ok(rule) => rule,
+        Err(_) => return None,
+    };
+
+    let zone = dts::parse_optional_time_zone(&event.time_zone).unwrap_or(dts::UTC);
+
+    let overrides: Vec<calendar_model::OccurrenceOverrideModel> = match rule {
+        Some(ref rule) => {
+            changed_occurrences.into_iter()
+                .filter_map(|x| x.recurrence_id.map(|occurrence_begin| (occurrence_begin, x)))
+                .filter(|&(occurrence_begin, _)| recurrence::is_occurrence(rule, event.begin, occurrence_begin, &zone))
+                .map(|(occurrence_begin, x)| calendar_model::OccurrenceOverrideModel {
+                    occurrence_begin: occurrence_begin,
+                    title: Some(x.summary),
+                    event_begin: x.begin,
+                    event_end: x.end,
+                })
+                .collect()
+        },
+        None => Vec::new(),
+    };
+
+    let series_end = rule.and_then(|rule| calculate_series_end(&rule, event.begin, event.end, &overrides, &zone));
+
+    Some(calendar_model::CalendarEventModel {
+        user_id: user_id.clone(),
+        event_id: event_id,
+        event_begin: event.begin,
+        event_end: event.end,
+        title: event.summary,
+        uid: Some(event.uid),
+        recurrence: event.recurrence,
+        time_zone: event.time_zone,
+        series_end: series_end,
+        exception_dates: event.exception_dates,
+        overrides: overrides,
+        attendees: attendees,
+        version: version,
+        deleted: None,
+    })
+}
+
+pub fn calendar_import(
+    model: calendar_model::CalendarImportRequestModel,
+    session_token_model: model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Result<calendar_model::CalendarImportResponseModel, EvelynCoreError> {
+    let (parsed_events, mut skipped) = match icalendar::parse_events(&model.calendar) {
+        Ok(parsed) => parsed,
+        Err(e) => return Err(e),
+    };
+
+    let (mut changed_occurrences, events): (Vec<ICalendarEvent>, Vec<ICalendarEvent>) = parsed_events.into_iter()
+        .partition(|x| x.recurrence_id.is_some());
+
+    let ds = processor_data.data_store.clone();
+    let ref user_id = session_token_model.user_id;
+
+    let mut created = 0;
+    let mut updated = 0;
+
+    for event in events {
+        let (event_changed_occurrences, others): (Vec<ICalendarEvent>, Vec<ICalendarEvent>) = changed_occurrences.into_iter()
+            .partition(|x| x.uid == event.uid);
+        changed_occurrences = others;
+
+        // Sprints are exported for reference, they can't be changed by an
+        // import.
+        if event.uid.ends_with(SPRINT_UID_SUFFIX) {
+            skipped += 1;
+            continue;
+        }
+
+        let existing = match find_imported_event(&ds, user_id, &event.uid) {
+            Ok(existing) => existing,
+            Err(e) => return Err(EvelynCoreError::FailedToImportCalendar(e)),
+        };
+        let is_update = existing.is_some();
+
+        // Attendees aren't imported, the ones already invited are kept.
+        let (event_id, version, attendees) = match existing {
+            Some(existing) => (existing.event_id, existing.version + 1, existing.attendees),
+            None => (format!("{}", Uuid::new_v4()), version::INITIAL_VERSION, Vec::new()),
+        };
+        let expected_version = version - 1;
+
+        let event_model = match to_event_model(user_id, event_id, version, event, event_changed_occurrences, attendees) {
+            Some(event_model) => event_model,
+            None => {
+                skipped += 1;
+                continue;
+            },
+        };
+
+        let error = if is_update {
+            calendar_data::replace_event(&ds, &event_model, expected_version)
+        } else {
+            calendar_data::insert_calendar_event(&ds, &event_model)
+        };
+
+        match error {
+            None if is_update => updated += 1,
+            None => created += 1,
+            Some(e) => return Err(EvelynCoreError::FailedToImportCalendar(e)),
+        }
+    }
+
+    // Changed occurrences of events which weren't in the calendar.
+    skipped += changed_occurrences.len() as i32;
+
+    Ok(calendar_model::CalendarImportResponseModel {
+        created: created,
+        updated: updated,
+        skipped: skipped,
+        error: None,
+    })
+}
+
+pub enum StoredVersionCondition {
+    Any,
+    Exists(i64),
+    DoesNotExist,
+}
+
+// Creates or replaces an event from a single event iCalendar document, as
+// used by CalDAV clients. Returns whether the event was created and its new
+// version.
+pub fn store_icalendar_event(
+    ds: &Client,
+    user_id: &String,
+    event_id: &String,
+    document: &str,
+    condition: StoredVersionCondition,
+) -> Result<(bool, i64), EvelynCoreError> {
+    let (parsed_events, _) = match icalendar::parse_events(document) {
+        Ok(parsed) => parsed,
+        Err(e) => return Err(e),
+    };
+
+    let (changed_occurrences, mut events): (Vec<ICalendarEvent>, Vec<ICalendarEvent>) = parsed_events.into_iter()
+        .partition(|x| x.recurrence_id.is_some());
+
+    if events.len() != 1 {
+        return Err(EvelynCoreError::InvalidICalendar(EvelynBaseError::NothingElse));
+    }
+    let event = events.remove(0);
+
+    let existing = match calendar_data::lookup_event(ds, user_id, event_id) {
+        Ok(existing) => Some(existing),
+        Err(EvelynDatabaseError::CalendarEventNotFound(_)) => None,
+        Err(e) => return Err(EvelynCoreError::FailedToImportCalendar(e)),
+    };
+
+    let condition_met = match (&condition, &existing) {
+        (&StoredVersionCondition::Any, _) => true,
+        (&StoredVersionCondition::Exists(expected_version), &Some(ref existing)) => existing.version == expected_version,
+        (&StoredVersionCondition::Exists(_), &None) => false,
+        (&StoredVersionCondition::DoesNotExist, existing) => existing.is_none(),
+    };
+
+    if !condition_met {
+        return Err(EvelynCoreError::CalendarEventVersionConflict(EvelynBaseError::NothingElse));
+    }
+
+    // The client picks the id of a new event, so it mustn't be one which is
+    // already used by another user's event or one in the trash. Events are
+    // looked up by id alone elsewhere.
+    if existing.is_none() {
+        match calendar_data::event_id_exists(ds, event_id) {
+            Ok(false) => {},
+            Ok(true) => return Err(EvelynCoreError::CalendarEventIdTaken(EvelynBaseError::NothingElse)),
+            Err(e) => return Err(EvelynCoreError::FailedToImportCalendar(e)),
+        }
+    }
+
+    let version = existing.as_ref().map_or(version::INITIAL_VERSION, |x| x.version + 1);
+    let attendees = existing.as_ref().map_or(Vec::new(), |x| x.attendees.clone());
+
+    let event_model = match to_event_model(user_id, event_id.clone(), version, event, changed_occurrences, attendees) {
+        Some(event_model) => event_model,
+        None => return Err(EvelynCoreError::InvalidRecurrenceRule(EvelynBaseError::NothingElse)),
+    };
+
+    let error = match existing {
+        Some(ref existing) => calendar_data::replace_event(ds, &event_model, existing.version),
+        None => calendar_data::insert_calendar_event(ds, &event_model),
+    };
+
+    match error {
+        None => Ok((existing.is_none(), version)),
+        Some(EvelynDatabaseError::CalendarEventVersionConflict(e)) => Err(EvelynCoreError::CalendarEventVersionConflict(e)),
+        Some(e) => Err(EvelynCoreError::FailedToImportCalendar(e)),
+    }
+}
+
+pub fn migrate_event_times(client: &Client) -> Option<EvelynCoreError> {
+    match calendar_data::migrate_string_times(client, legacy_string_to_timestamp) {
+        Ok(migrated) => {
+            if migrated > 0 {
+                info!("Converted the times of {} calendar event(s) to timestamps", migrated);
+            }
+            None
+        },
+        Err(e) => Some(EvelynCoreError::FailedToMigrateCalendarEvents(e)),
+    }
+}
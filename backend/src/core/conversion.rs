@@ -0,0 +1,242 @@
+// Evelyn: Your personal assistant, project manager and calendar
+// Copyright (C) 2017 Gregory Jensen
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+// There is no transaction across collections so a conversion is done in two
+// steps. The original is first marked with the id of the item it will become,
+// then that item is created. A conversion which was interrupted between the
+// steps is finished off when it is asked for again, so repeating a request
+// never creates a second copy. If the new item can't be created the mark is
+// taken off again so nothing is lost.
+
+use core::agile::project;
+use core::agile::task as agile_task;
+use core::date_time_service;
+use core::error_messages::{EvelynBaseError, EvelynCoreError, EvelynDatabaseError};
+use core::reminder;
+use core::simple_task;
+use core::todo_list;
+use data;
+use model;
+use model::agile::task as task_model;
+use model::conversion::{ConversionLinkExternalModel, ConversionLinkModel, ConvertedItemTypeExternalModel,
+                        ConvertedItemTypeModel};
+use processing::ProcessorData;
+use std::sync::Arc;
+use uuid::Uuid;
+
+pub fn to_link_external_model(link: ConversionLinkModel) -> ConversionLinkExternalModel {
+    ConversionLinkExternalModel {
+        item_type: match link.item_type {
+            ConvertedItemTypeModel::TodoListItem => ConvertedItemTypeExternalModel::TodoListItem,
+            ConvertedItemTypeModel::SimpleTask => ConvertedItemTypeExternalModel::SimpleTask,
+            ConvertedItemTypeModel::AgileTask => ConvertedItemTypeExternalModel::AgileTask,
+        },
+        item_id: link.item_id,
+        container_id: link.container_id,
+    }
+}
+
+fn check_project_access(
+    project_id: &String,
+    session_token_model: &model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Option<EvelynCoreError> {
+    let project_session_token_model = model::SessionTokenModel {
+        user_id: session_token_model.user_id.clone(),
+        server_session_token: session_token_model.server_session_token.clone(),
+    };
+
+    match project::lookup_contributing_to(project_session_token_model, processor_data) {
+        Ok(result) if result.projects.iter().any(|x| &x.project_id == project_id) => None,
+        Ok(_) => Some(EvelynCoreError::CannotPromoteToAgileProject(EvelynBaseError::NothingElse)),
+        Err(e) => Some(e),
+    }
+}
+
+pub fn promote_simple_task(
+    model: model::conversion::PromoteSimpleTaskRequestModel,
+    session_token_model: model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Result<model::conversion::PromoteSimpleTaskResponseModel, EvelynCoreError> {
+    let ds = processor_data.data_store.clone();
+    let ref user_id = session_token_model.user_id;
+
+    let task = match data::simple_task::lookup_simple_task(&ds, user_id, &model.task_id) {
+        Ok(task) => task,
+        Err(EvelynDatabaseError::SimpleTaskNotFound(e)) => return Err(EvelynCoreError::SimpleTaskNotFound(e)),
+        Err(e) => return Err(EvelynCoreError::FailedToPromoteSimpleTask(e)),
+    };
+
+    let link = match task.converted_to.clone() {
+        Some(link) => link,
+        None => {
+            if let Some(e) = check_project_access(&model.project_id, &session_token_model, processor_data.clone()) {
+                return Err(e);
+            }
+
+            let link = ConversionLinkModel {
+                item_type: ConvertedItemTypeModel::AgileTask,
+                item_id: format!("{}", Uuid::new_v4()),
+                container_id: Some(model.project_id.clone()),
+            };
+
+            match data::simple_task::claim_conversion(&ds, user_id, &task.task_id, task.version, &link,
+                                                      date_time_service::get_timestamp()) {
+                Ok(true) => link,
+                Ok(false) => return Err(EvelynCoreError::SimpleTaskVersionConflict(EvelynBaseError::NothingElse)),
+                Err(e) => return Err(EvelynCoreError::FailedToPromoteSimpleTask(e)),
+            }
+        },
+    };
+
+    let project_id = link.container_id.clone().unwrap_or(model.project_id);
+
+    match data::agile::task::find_task_by_id(&ds, &project_id, &link.item_id) {
+        Ok(Some(_)) => {},
+        Ok(None) => {
+            let agile_task_model = task_model::TaskModel {
+                task_id: link.item_id.clone(),
+                created_by_user_id: user_id.clone(),
+                date_created: date_time_service::get_timestamp(),
+                modified_by_user_id: user_id.clone(),
+                date_modified: date_time_service::get_timestamp(),
+                project_id: project_id.clone(),
+                title: task.title.clone(),
+                description: task.description.clone(),
+                original_estimate: model.original_estimate.unwrap_or_else(|| "0m".to_owned()),
+                assignment: None,
+                origin: Some(ConversionLinkModel {
+                    item_type: ConvertedItemTypeModel::SimpleTask,
+                    item_id: task.task_id.clone(),
+                    container_id: None,
+                }),
+                version: data::version::INITIAL_VERSION,
+            };
+
+            if let Some(e) = agile_task::add_task(&ds, &agile_task_model) {
+                if let Some(release_error) = data::simple_task::release_conversion(&ds, user_id, &task.task_id, &link.item_id) {
+                    error!("Failed to release promotion of simple task {}: {}", task.task_id, release_error);
+                }
+                return Err(EvelynCoreError::FailedToPromoteSimpleTask(e));
+            }
+        },
+        Err(e) => return Err(EvelynCoreError::FailedToPromoteSimpleTask(e)),
+    }
+
+    // Promoting completes the task, which may complete its parents too.
+    reminder::source_changed(&ds, model::reminder::ReminderSourceModel::SimpleTask, &task.task_id);
+    if let Some(e) = simple_task::complete_parents(&ds, user_id, task.parent_task_id.clone()) {
+        warn!("Failed to complete the parents of promoted simple task {}: {}", task.task_id, e);
+    }
+
+    Ok(model::conversion::PromoteSimpleTaskResponseModel {
+        agile_task_id: Some(link.item_id),
+        error: None,
+    })
+}
+
+pub fn convert_todo_list_item(
+    model: model::conversion::ConvertTodoListItemRequestModel,
+    session_token_model: model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Result<model::conversion::ConvertTodoListItemResponseModel, EvelynCoreError> {
+    let ds = processor_data.data_store.clone();
+    let ref user_id = session_token_model.user_id;
+
+    let todo_list = match todo_list::lookup_editable_todo_list(&ds, user_id, &model.todo_list_id) {
+        Ok(todo_list) => todo_list,
+        Err(EvelynDatabaseError::TodoListNotFound(e)) => return Err(EvelynCoreError::TodoListNotFound(e)),
+        Err(e) => return Err(EvelynCoreError::FailedToConvertTodoListItem(e)),
+    };
+
+    let item = match todo_list::find_item(&todo_list, model.item_id.as_ref(), model.item_index) {
+        Some(item) => item,
+        None => return Err(EvelynCoreError::TodoListNotFound(EvelynBaseError::NothingElse)),
+    };
+
+    // Checked before the item is claimed so a bad date doesn't leave it claimed.
+    let (due_date, due_date_only, time_zone) = match simple_task::normalise_due_date(
+        model.due_date.as_ref().map_or("", |x| x.as_str()), model.time_zone, user_id, &ds) {
+        Ok(x) => x,
+        Err(e) => return Err(e),
+    };
+
+    let link = match item.converted_to.clone() {
+        Some(link) => link,
+        None => {
+            let link = ConversionLinkModel {
+                item_type: ConvertedItemTypeModel::SimpleTask,
+                item_id: format!("{}", Uuid::new_v4()),
+                container_id: None,
+            };
+
+            match data::todo_list::claim_item_conversion(&ds, &model.todo_list_id, &item.item_id, &link) {
+                Ok(true) => link,
+                // Someone else converted or removed the item in the meantime.
+                Ok(false) => return Err(EvelynCoreError::TodoListVersionConflict(EvelynBaseError::NothingElse)),
+                Err(e) => return Err(EvelynCoreError::FailedToConvertTodoListItem(e)),
+            }
+        },
+    };
+
+    match data::simple_task::find_simple_task(&ds, user_id, &link.item_id) {
+        Ok(Some(_)) => {},
+        Ok(None) => {
+            let simple_task_model = model::simple_task::SimpleTaskModel {
+                user_id: user_id.clone(),
+                task_id: link.item_id.clone(),
+                title: item.text.clone(),
+                description: "".to_owned(),
+                due_date: due_date,
+                due_date_only: due_date_only,
+                time_zone: time_zone,
+                completed: item.is_done,
+                priority: model::simple_task::PRIORITY_NORMAL,
+                tags: Vec::new(),
+                recurrence: None,
+                series_id: None,
+                series_begin: None,
+                date_completed: if item.is_done { Some(date_time_service::get_timestamp()) } else { None },
+                checklist: Vec::new(),
+                parent_task_id: None,
+                complete_with_children: false,
+                origin: Some(ConversionLinkModel {
+                    item_type: ConvertedItemTypeModel::TodoListItem,
+                    item_id: item.item_id.clone(),
+                    container_id: Some(model.todo_list_id.clone()),
+                }),
+                converted_to: None,
+                next_task_id: None,
+                version: data::version::INITIAL_VERSION,
+                deleted: None,
+            };
+
+            if let Some(e) = data::simple_task::insert_simple_task(&ds, &simple_task_model) {
+                if let Some(release_error) = data::todo_list::release_item_conversion(&ds, &model.todo_list_id, &link.item_id) {
+                    error!("Failed to release conversion of todo list {} item {}: {}", model.todo_list_id,
+                           item.item_id, release_error);
+                }
+                return Err(EvelynCoreError::FailedToConvertTodoListItem(e));
+            }
+        },
+        Err(e) => return Err(EvelynCoreError::FailedToConvertTodoListItem(e)),
+    }
+
+    Ok(model::conversion::ConvertTodoListItemResponseModel {
+        task_id: Some(link.item_id),
+        error: None,
+    })
+}
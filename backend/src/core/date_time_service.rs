@@ -15,16 +15,110 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use chrono::prelude::*;
-use std::str::FromStr;
+use chrono::{Duration, LocalResult};
+use chrono_tz::Tz;
+use core::error_messages::{EvelynBaseError, EvelynCoreError};
+
+pub use chrono_tz::UTC;
+
+// Formats accepted for dates and times which don't carry an offset, these are
+// read in the time zone of the caller.
+const LOCAL_DATE_TIME_FORMATS: [&'static str; 4] = [
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M",
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%d %H:%M",
+];
+
+const LOCAL_DATE_FORMAT: &'static str = "%Y-%m-%d";
 
 pub fn get_timestamp() -> i64 {
     Utc::now().timestamp()
 }
 
-pub fn string_to_timestamp(timestamp: String) -> i64 {
-    DateTime::<Utc>::from_str(timestamp.as_ref()).unwrap().timestamp()
+pub fn parse_time_zone(name: &str) -> Result<Tz, EvelynCoreError> {
+    match name.parse::<Tz>() {
+        Ok(zone) => Ok(zone),
+        Err(_) => Err(EvelynCoreError::InvalidTimeZone(EvelynBaseError::NothingElse)),
+    }
+}
+
+// None and empty names mean no preference, which is UTC.
+pub fn parse_optional_time_zone(name: &Option<String>) -> Result<Tz, EvelynCoreError> {
+    match *name {
+        Some(ref name) if !name.is_empty() => parse_time_zone(name),
+        _ => Ok(UTC),
+    }
+}
+
+// Date times with an offset are taken as they are, anything without one is
+// read in the given zone. A date on its own is the start of that day.
+pub fn parse_timestamp(value: &str, zone: &Tz) -> Result<i64, EvelynCoreError> {
+    if let Ok(date_time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(date_time.timestamp());
+    }
+
+    for format in LOCAL_DATE_TIME_FORMATS.iter() {
+        if let Ok(date_time) = NaiveDateTime::parse_from_str(value, format) {
+            return local_to_timestamp(&date_time, zone);
+        }
+    }
+
+    match NaiveDate::parse_from_str(value, LOCAL_DATE_FORMAT) {
+        Ok(date) => local_to_timestamp(&date.and_hms(0, 0, 0), zone),
+        Err(_) => Err(EvelynCoreError::InvalidDateTime(EvelynBaseError::NothingElse)),
+    }
+}
+
+// Local times which happen twice when the clocks go back are read as the
+// first of the two. Local times which are skipped when the clocks go forward
+// don't exist so they are an error.
+pub fn local_to_timestamp(date_time: &NaiveDateTime, zone: &Tz) -> Result<i64, EvelynCoreError> {
+    match zone.from_local_datetime(date_time) {
+        LocalResult::Single(x) => Ok(x.timestamp()),
+        LocalResult::Ambiguous(earliest, _) => Ok(earliest.timestamp()),
+        LocalResult::None => Err(EvelynCoreError::InvalidDateTime(EvelynBaseError::NothingElse)),
+    }
+}
+
+// Used for generated times such as the occurrences of a recurring event, a
+// time which is skipped when the clocks go forward is moved forward by the
+// length of the gap as RFC 5545 describes.
+pub fn local_to_timestamp_lenient(date_time: &NaiveDateTime, zone: &Tz) -> i64 {
+    match local_to_timestamp(date_time, zone) {
+        Ok(x) => x,
+        Err(_) => {
+            let before = *date_time - Duration::days(1);
+            let offset = zone.offset_from_utc_datetime(&before).fix().local_minus_utc();
+            date_time.timestamp() - offset as i64
+        },
+    }
+}
+
+// A date on its own, for things which happen some time that day.
+pub fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value, LOCAL_DATE_FORMAT).ok()
+}
+
+pub fn date_to_string(date: &NaiveDate) -> String {
+    date.format(LOCAL_DATE_FORMAT).to_string()
+}
+
+// Midnight is skipped in some zones when the clocks go forward, the day then
+// starts at the end of the gap.
+pub fn start_of_day(date: &NaiveDate, zone: &Tz) -> i64 {
+    local_to_timestamp_lenient(&date.and_hms(0, 0, 0), zone)
+}
+
+pub fn timestamp_to_local(timestamp: i64, zone: &Tz) -> NaiveDateTime {
+    zone.timestamp(timestamp, 0).naive_local()
+}
+
+pub fn timestamp_to_string(timestamp: i64, zone: &Tz) -> String {
+    zone.timestamp(timestamp, 0).to_rfc3339()
 }
 
-pub fn timestamp_to_string(timestamp: i64) -> String {
-    DateTime::<Utc>::from_utc(NaiveDateTime::from_timestamp(timestamp, 0), Utc).to_rfc3339()
+// For mail headers.
+pub fn timestamp_to_rfc2822(timestamp: i64) -> String {
+    Utc.timestamp(timestamp, 0).to_rfc2822()
 }
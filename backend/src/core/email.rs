@@ -0,0 +1,220 @@
+// Evelyn: Your personal assistant, project manager and calendar
+// Copyright (C) 2017 Gregory Jensen
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+// Notifications are emailed by queueing them in an outbox which is sent from
+// by a scheduled job, so that mail isn't lost while the relay is unavailable.
+// Failed sends are retried with a growing delay until they are given up on.
+
+use core::date_time_service as dts;
+use core::error_messages::EvelynCoreError;
+use core::notification::NotificationChannel;
+use core::scheduler;
+use core::smtp;
+use data::email as email_data;
+use data::user as user_data;
+use model::email as email_model;
+use model::notification::{NotificationKindModel, NotificationModel};
+use model::user::UserModel;
+use mongodb::Client;
+use uuid::Uuid;
+
+struct EmailTemplate {
+    subject: &'static str,
+    text_body: &'static str,
+    html_body: &'static str,
+}
+
+const SUBJECT: &'static str = "[Evelyn] {{title}}";
+
+const TEXT_BODY: &'static str = "Hello {{user_name}},\r\n\r\n{{intro}}\r\n\r\n{{title}}\r\n{{body}}\r\n\r\n-- \r\nEvelyn\r\n";
+
+const HTML_BODY: &'static str = "<html><body>\
+<p>Hello {{user_name}},</p>\
+<p>{{intro}}</p>\
+<p><strong>{{title}}</strong><br>{{body}}</p>\
+<p>-- <br>Evelyn</p>\
+</body></html>";
+
+fn template_for(kind: &NotificationKindModel) -> (EmailTemplate, &'static str) {
+    let intro = match *kind {
+        NotificationKindModel::Reminder => "This is a reminder you asked for.",
+        NotificationKindModel::TaskAssigned => "A task has been assigned to you.",
+        NotificationKindModel::AddedToUserGroup => "You have been added to a user group.",
+        NotificationKindModel::RemovedFromUserGroup => "You have been removed from a user group.",
+    };
+
+    (EmailTemplate {
+        subject: SUBJECT,
+        text_body: TEXT_BODY,
+        html_body: HTML_BODY,
+    }, intro)
+}
+
+fn escape_html(value: &str) -> String {
+    let mut escaped = String::new();
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            '\n' => escaped.push_str("<br>"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn render(template: &str, values: &Vec<(&'static str, String)>) -> String {
+    let mut rendered = String::from(template);
+    for &(ref name, ref value) in values {
+        rendered = rendered.replace(&format!("{{{{{}}}}}", name), value);
+    }
+    rendered
+}
+
+fn wants_email(user: &UserModel, kind: &NotificationKindModel) -> bool {
+    if user.email_address.is_empty() {
+        return false;
+    }
+
+    match user.email_notifications {
+        Some(ref kinds) => kinds.contains(kind),
+        None => true,
+    }
+}
+
+// Queues an email for each notification the user has asked to be emailed.
+pub struct EmailChannel;
+
+impl NotificationChannel for EmailChannel {
+    fn name(&self) -> &'static str {
+        "email"
+    }
+
+    fn deliver(
+        &self,
+        client: &Client,
+        notification: &NotificationModel,
+    ) -> Option<EvelynCoreError> {
+        let user = match user_data::find_user_by_id(client, &notification.user_id) {
+            Ok(Some(user)) => user,
+            Ok(None) => return None,
+            Err(e) => return Some(EvelynCoreError::FailedToQueueEmail(e)),
+        };
+
+        if !wants_email(&user, &notification.kind) {
+            return None;
+        }
+
+        let (template, intro) = template_for(&notification.kind);
+
+        let text_values = vec![
+            ("user_name", user.user_name.clone()),
+            ("intro", String::from(intro)),
+            ("title", notification.title.clone()),
+            ("body", notification.body.clone()),
+        ];
+        let html_values: Vec<(&'static str, String)> = text_values.iter().map(|&(name, ref value)| (name, escape_html(value))).collect();
+
+        let now = dts::get_timestamp();
+
+        let email = email_model::EmailModel {
+            email_id: format!("{}", Uuid::new_v4()),
+            user_id: user.user_id,
+            notification_id: notification.notification_id.clone(),
+            to_address: user.email_address,
+            subject: render(template.subject, &text_values),
+            text_body: render(template.text_body, &text_values),
+            html_body: render(template.html_body, &html_values),
+            status: email_model::EmailStatusModel::Pending,
+            attempts: 0,
+            next_attempt_at: now,
+            last_error: None,
+            date_created: now,
+            date_sent: None,
+        };
+
+        match email_data::insert_email(client, &email) {
+            None => None,
+            Some(e) => Some(EvelynCoreError::FailedToQueueEmail(e)),
+        }
+    }
+}
+
+// Sends the emails in the outbox which are due. A send which fails is left
+// in the outbox for a later run, unless it has failed too many times.
+pub fn send_outbox(
+    client: &Client,
+    config: &smtp::SmtpConfig,
+    from: &String,
+    max_attempts: i32,
+) -> Option<EvelynCoreError> {
+    let now = dts::get_timestamp();
+
+    let emails = match email_data::lookup_pending(client, now) {
+        Ok(emails) => emails,
+        Err(e) => return Some(EvelynCoreError::FailedToSendEmails(e)),
+    };
+
+    for email in emails {
+        match email_data::claim(client, &email.email_id, email.attempts) {
+            Ok(true) => {},
+            Ok(false) => continue,
+            Err(e) => return Some(EvelynCoreError::FailedToSendEmails(e)),
+        }
+
+        let attempts = email.attempts + 1;
+
+        let message = smtp::EmailMessage {
+            from: from.clone(),
+            to: email.to_address,
+            subject: email.subject,
+            text_body: email.text_body,
+            html_body: email.html_body,
+            message_id: format!("{}@evelyn", email.email_id),
+            date: dts::timestamp_to_rfc2822(email.date_created),
+        };
+
+        let error = match smtp::send(config, &message) {
+            Ok(()) => email_data::mark_sent(client, &email.email_id, dts::get_timestamp()),
+            Err(e) => {
+                // Retrying won't make a bad address any better.
+                let give_up = match e {
+                    smtp::SmtpError::InvalidAddress(_) => true,
+                    _ => attempts >= max_attempts,
+                };
+
+                let (status, next_attempt_at) = if give_up {
+                    error!("Giving up on email [{}] after {} attempt(s): {}", email.email_id, attempts, e);
+                    (email_model::EmailStatusModel::Failed, now)
+                } else {
+                    warn!("Failed to send email [{}], will retry: {}", email.email_id, e);
+                    (email_model::EmailStatusModel::Pending, now + scheduler::retry_delay(attempts))
+                };
+
+                email_data::record_failure(client, &email.email_id, &status, next_attempt_at, &format!("{}", e))
+            },
+        };
+
+        if let Some(e) = error {
+            return Some(EvelynCoreError::FailedToSendEmails(e));
+        }
+    }
+
+    None
+}
@@ -88,10 +88,16 @@ pub enum EvelynServiceError {
 
     CouldNotDecodeTheRequestPayload(serde_json::Error),
     ForeignSessionToken(EvelynBaseError),
+    InvalidDateTime(EvelynCoreError),
+    InvalidTimeZone(EvelynCoreError),
 
     // Server Admin
     FailedToPurge(EvelynCoreError),
     InvalidPurgeTargetType(EvelynBaseError),
+    DestructivePurgeDisabled(EvelynBaseError),
+    DeleteUserData(EvelynCoreError),
+    DeleteAgileProject(EvelynCoreError),
+    NotPermittedToDeleteAgileProject(EvelynCoreError),
 
     // User
     CreateUser(EvelynCoreError),
@@ -99,6 +105,9 @@ pub enum EvelynServiceError {
     LogonUser(EvelynCoreError),
     FailedToLogonUser(EvelynCoreError),
     SearchForUsers(EvelynCoreError),
+    LookupUserProfile(EvelynCoreError),
+    UpdateUserProfile(EvelynCoreError),
+    InvalidEmailAddress(EvelynCoreError),
 
     // User group
     CreateUserGroup(EvelynCoreError),
@@ -113,6 +122,14 @@ pub enum EvelynServiceError {
     FailedToUpdateSimpleTask(EvelynCoreError),
     FailedToLookupSimpleTask(EvelynCoreError),
     FailedToRemoveSimpleTask(EvelynCoreError),
+    SimpleTaskVersionConflict(EvelynCoreError),
+    SimpleTaskNotFound(EvelynCoreError),
+    InvalidSimpleTaskCursor(EvelynCoreError),
+    RecurringSimpleTaskWithoutDueDate(EvelynCoreError),
+    InvalidParentSimpleTask(EvelynCoreError),
+    FailedToUpdateSimpleTaskChecklist(EvelynCoreError),
+    FailedToPromoteSimpleTask(EvelynCoreError),
+    CannotPromoteToAgileProject(EvelynCoreError),
 
     // Todo List
     CreateTodoList(EvelynCoreError),
@@ -120,6 +137,21 @@ pub enum EvelynServiceError {
     LookupTodoLists(EvelynCoreError),
     LookupTodoList(EvelynCoreError),
     UpdateTodoListItem(EvelynCoreError),
+    TodoListVersionConflict(EvelynCoreError),
+    TodoListNotFound(EvelynCoreError),
+    ConvertTodoListItem(EvelynCoreError),
+    UpdateTodoList(EvelynCoreError),
+    RemoveTodoList(EvelynCoreError),
+    RemoveTodoListItem(EvelynCoreError),
+    MoveTodoListItem(EvelynCoreError),
+    InvalidTodoListShare(EvelynCoreError),
+    UpdateTodoListShares(EvelynCoreError),
+    CreateTodoListTemplate(EvelynCoreError),
+    LookupTodoListTemplates(EvelynCoreError),
+    InstantiateTodoListTemplate(EvelynCoreError),
+    RemoveTodoListTemplate(EvelynCoreError),
+    TodoListTemplateNotFound(EvelynCoreError),
+    InvalidTodoListTemplateUserGroup(EvelynCoreError),
 
     // Agile: Project
     CreateAgileProject(EvelynCoreError),
@@ -134,6 +166,9 @@ pub enum EvelynServiceError {
     LookupAgileTask(EvelynCoreError),
     LookupBacklogAgileTask(EvelynCoreError),
     UpdateAgileTask(EvelynCoreError),
+    AgileTaskVersionConflict(EvelynCoreError),
+    AgileTaskNotFound(EvelynCoreError),
+    LookupAssignedAgileTasks(EvelynCoreError),
 
     // Agile: Story
     CreateAgileStory(EvelynCoreError),
@@ -148,8 +183,59 @@ pub enum EvelynServiceError {
     // Agile: Heirarchy
     MakeAgileHeirarchyLink(EvelynCoreError),
 
+    // Agile: Audit
+    LookupAgileHistory(EvelynCoreError),
+
     // Calendar
     AddCalendarEvent(EvelynCoreError),
+    LookupCalendarEvents(EvelynCoreError),
+    InvalidCalendarEventTime(EvelynCoreError),
+    UpdateCalendarEvent(EvelynCoreError),
+    RemoveCalendarEvent(EvelynCoreError),
+    CalendarEventNotFound(EvelynCoreError),
+    CalendarEventVersionConflict(EvelynCoreError),
+    InvalidRecurrenceRule(EvelynCoreError),
+    CalendarEventOccurrenceNotFound(EvelynCoreError),
+    UpdateCalendarEventOccurrence(EvelynCoreError),
+    CancelCalendarEventOccurrence(EvelynCoreError),
+    ExportCalendar(EvelynCoreError),
+    ImportCalendar(EvelynCoreError),
+    InvalidICalendar(EvelynCoreError),
+    InviteToCalendarEvent(EvelynCoreError),
+    RespondToCalendarEvent(EvelynCoreError),
+    LookupFreeBusy(EvelynCoreError),
+    NotInvitedToCalendarEvent(EvelynCoreError),
+    CalendarInviteeNotFound(EvelynCoreError),
+
+    // Trash
+    LookupTrash(EvelynCoreError),
+    RestoreFromTrash(EvelynCoreError),
+    TrashItemNotFound(EvelynCoreError),
+    InvalidTrashItemType(EvelynCoreError),
+
+    // Reminder
+    AddReminder(EvelynCoreError),
+    LookupReminders(EvelynCoreError),
+    RemoveReminder(EvelynCoreError),
+    ReminderNotFound(EvelynCoreError),
+    ReminderSourceNotFound(EvelynCoreError),
+    InvalidReminder(EvelynCoreError),
+
+    // Notification
+    LookupNotifications(EvelynCoreError),
+    MarkNotificationsRead(EvelynCoreError),
+
+    // Webhook
+    AddWebhook(EvelynCoreError),
+    LookupWebhooks(EvelynCoreError),
+    RemoveWebhook(EvelynCoreError),
+    WebhookNotFound(EvelynCoreError),
+    InvalidWebhook(EvelynCoreError),
+    LookupWebhookDeliveries(EvelynCoreError),
+
+    // Agenda
+    LookupAgenda(EvelynCoreError),
+    InvalidAgendaDate(EvelynCoreError),
 }
 
 EvelynErrorDisplay!{
@@ -166,6 +252,13 @@ EvelynErrorDisplay!{
     // Server admin.
     {FailedToPurge, "100103", "Failed to purge"},
     {InvalidPurgeTargetType, "100104", "Invalid purge target type"},
+    {DestructivePurgeDisabled, "100105", "Purging data is disabled on this server"},
+    {DeleteUserData, "100106", "Failed to delete user data"},
+    {DeleteAgileProject, "100107", "Failed to delete agile project"},
+    {NotPermittedToDeleteAgileProject, "100108", "Only the creator of a project can delete it"},
+
+    {InvalidDateTime, "100109", "Dates must be RFC 3339 date times, or dates and times without an offset which are read in the user's time zone"},
+    {InvalidTimeZone, "100110", "Time zones must be IANA time zone names such as Europe/London"},
 
     // User
     {CreateUser, "100201", "Failed to create user"},
@@ -173,6 +266,9 @@ EvelynErrorDisplay!{
     {LogonUser, "100203", "Invalid logon"},
     {FailedToLogonUser, "100204", "Failed to logon user"},
     {SearchForUsers, "100205", "Failed to search for users"},
+    {LookupUserProfile, "100206", "Failed to lookup user profile"},
+    {UpdateUserProfile, "100207", "Failed to update user profile"},
+    {InvalidEmailAddress, "100208", "Email addresses must not contain line breaks or angle brackets"},
 
     // User group
     {CreateUserGroup, "100601", "Failed to create user group"},
@@ -187,6 +283,14 @@ EvelynErrorDisplay!{
     {FailedToUpdateSimpleTask, "100302", "Failed to update simple task"},
     {FailedToLookupSimpleTask, "100303", "Failed to lookup simple task"},
     {FailedToRemoveSimpleTask, "100304", "Failed to remove simple task"},
+    {SimpleTaskVersionConflict, "100305", "Simple task has been changed since it was looked up"},
+    {SimpleTaskNotFound, "100306", "Simple task not found"},
+    {InvalidSimpleTaskCursor, "100307", "The cursor does not refer to a page of simple tasks"},
+    {RecurringSimpleTaskWithoutDueDate, "100308", "A recurring simple task needs a due date"},
+    {InvalidParentSimpleTask, "100309", "The parent task does not exist or is a child of this task"},
+    {FailedToUpdateSimpleTaskChecklist, "100310", "Failed to update simple task checklist item"},
+    {FailedToPromoteSimpleTask, "100311", "Failed to promote simple task to an agile task"},
+    {CannotPromoteToAgileProject, "100312", "Simple tasks can only be promoted into projects you contribute to"},
 
     // Todo List
     {CreateTodoList, "100401", "Failed to create todo list"},
@@ -194,6 +298,21 @@ EvelynErrorDisplay!{
     {LookupTodoLists, "100403", "Failed to lookup todo lists"},
     {LookupTodoList, "100404", "Failed to lookup todo list"},
     {UpdateTodoListItem, "100405", "Failed to update todo list item"},
+    {TodoListVersionConflict, "100406", "Todo list has been changed since it was looked up"},
+    {TodoListNotFound, "100407", "Todo list or todo list item not found"},
+    {ConvertTodoListItem, "100408", "Failed to convert todo list item to a simple task"},
+    {UpdateTodoList, "100409", "Failed to update todo list"},
+    {RemoveTodoList, "100410", "Failed to remove todo list"},
+    {RemoveTodoListItem, "100411", "Failed to remove todo list item"},
+    {MoveTodoListItem, "100412", "Failed to move todo list item"},
+    {InvalidTodoListShare, "100413", "Todo lists are shared with one other user, or one of your user groups, at a time"},
+    {UpdateTodoListShares, "100414", "Failed to update who the todo list is shared with"},
+    {CreateTodoListTemplate, "100415", "Failed to create todo list template"},
+    {LookupTodoListTemplates, "100416", "Failed to lookup todo list templates"},
+    {InstantiateTodoListTemplate, "100417", "Failed to create todo list from template"},
+    {RemoveTodoListTemplate, "100418", "Failed to remove todo list template"},
+    {TodoListTemplateNotFound, "100419", "Todo list template not found"},
+    {InvalidTodoListTemplateUserGroup, "100420", "Todo list templates can only be shared with one of your user groups"},
 
     // Agile: Project
     {CreateAgileProject, "1006001", "Failed to create agile project"},
@@ -208,6 +327,9 @@ EvelynErrorDisplay!{
     {LookupAgileTask, "1006102", "Failed to lookup agile task"},
     {LookupBacklogAgileTask, "1006104", "Failed to lookup backlog agile task"},
     {UpdateAgileTask, "1006103", "Failed to update agile task"},
+    {AgileTaskVersionConflict, "1006105", "Agile task has been changed since it was looked up"},
+    {AgileTaskNotFound, "1006106", "Agile task not found"},
+    {LookupAssignedAgileTasks, "1006107", "Failed to lookup assigned agile tasks"},
 
     // Agile: Story
     {CreateAgileStory, "1006401", "Failed to create agile story"},
@@ -222,8 +344,59 @@ EvelynErrorDisplay!{
     // Agile: Heirarchy
     {MakeAgileHeirarchyLink, "1006301", "Failed to make agile heirarchy link"},
 
+    // Agile: Audit
+    {LookupAgileHistory, "1006501", "Failed to lookup agile history"},
+
     // Calendar
-    {AddCalendarEvent, "100501", "Failed to add calendar event"}
+    {AddCalendarEvent, "100501", "Failed to add calendar event"},
+    {LookupCalendarEvents, "100502", "Failed to lookup calendar events"},
+    {InvalidCalendarEventTime, "100503", "Times must be valid dates and the end must not be before the beginning"},
+    {UpdateCalendarEvent, "100504", "Failed to update calendar event"},
+    {RemoveCalendarEvent, "100505", "Failed to remove calendar event"},
+    {CalendarEventNotFound, "100506", "Calendar event not found"},
+    {CalendarEventVersionConflict, "100507", "Calendar event has been changed since it was looked up"},
+    {InvalidRecurrenceRule, "100508", "The recurrence rule is not valid or uses an unsupported feature"},
+    {CalendarEventOccurrenceNotFound, "100509", "The event does not have an occurrence at that time"},
+    {UpdateCalendarEventOccurrence, "100510", "Failed to update calendar event occurrence"},
+    {CancelCalendarEventOccurrence, "100511", "Failed to cancel calendar event occurrence"},
+    {ExportCalendar, "100512", "Failed to export calendar"},
+    {ImportCalendar, "100513", "Failed to import calendar"},
+    {InvalidICalendar, "100514", "The calendar is not a valid iCalendar document"},
+    {InviteToCalendarEvent, "100515", "Failed to invite attendees to calendar event"},
+    {RespondToCalendarEvent, "100516", "Failed to respond to calendar event invitation"},
+    {LookupFreeBusy, "100517", "Failed to lookup free busy times"},
+    {NotInvitedToCalendarEvent, "100518", "You have not been invited to that calendar event"},
+    {CalendarInviteeNotFound, "100519", "A user or user group to invite was not found"},
+
+    // Trash
+    {LookupTrash, "100701", "Failed to lookup trash"},
+    {RestoreFromTrash, "100702", "Failed to restore item from trash"},
+    {TrashItemNotFound, "100703", "Item not found in trash or it can no longer be restored"},
+    {InvalidTrashItemType, "100704", "Items of that type cannot be restored"},
+
+    // Reminder
+    {AddReminder, "100801", "Failed to add reminder"},
+    {LookupReminders, "100802", "Failed to lookup reminders"},
+    {RemoveReminder, "100803", "Failed to remove reminder"},
+    {ReminderNotFound, "100804", "Reminder not found"},
+    {ReminderSourceNotFound, "100805", "The item to be reminded about was not found"},
+    {InvalidReminder, "100806", "Reminders must be a whole number of minutes, zero or more, before the item"},
+
+    // Notification
+    {LookupNotifications, "100901", "Failed to lookup notifications"},
+    {MarkNotificationsRead, "100902", "Failed to mark notifications as read"},
+
+    // Webhook
+    {AddWebhook, "101001", "Failed to add webhook"},
+    {LookupWebhooks, "101002", "Failed to lookup webhooks"},
+    {RemoveWebhook, "101003", "Failed to remove webhook"},
+    {WebhookNotFound, "101004", "Webhook not found"},
+    {InvalidWebhook, "101005", "Webhooks need an http or https URL, a secret, at least one event type and a project you contribute to"},
+    {LookupWebhookDeliveries, "101006", "Failed to lookup webhook deliveries"},
+
+    // Agenda
+    {LookupAgenda, "101101", "Failed to lookup agenda"},
+    {InvalidAgendaDate, "101102", "Agenda dates must be given as YYYY-MM-DD"}
 }
 
 #[derive(Debug)]
@@ -231,6 +404,9 @@ pub enum EvelynCoreError {
     // Server Admin
     FailedToPurgeDatabase(EvelynDatabaseError),
     FailedToPurgeDatabaseArea(EvelynDatabaseError),
+    FailedToDeleteUserData(EvelynDatabaseError),
+    FailedToDeleteAgileProject(EvelynDatabaseError),
+    NotPermittedToDeleteAgileProject(EvelynBaseError),
 
     // User
     WillNotCreateUserBecauseUserAlreadyExists(EvelynBaseError),
@@ -239,6 +415,13 @@ pub enum EvelynCoreError {
     InvalidLogon(EvelynBaseError),
     FailedToLogonUser(EvelynDatabaseError),
     FailedToSearchForUsers(EvelynDatabaseError),
+    FailedToLookupUserProfile(EvelynDatabaseError),
+    FailedToUpdateUserProfile(EvelynDatabaseError),
+    InvalidEmailAddress(EvelynBaseError),
+
+    // Dates and times
+    InvalidDateTime(EvelynBaseError),
+    InvalidTimeZone(EvelynBaseError),
 
     // User group
     FailedToCreateUserGroup(EvelynDatabaseError),
@@ -253,6 +436,15 @@ pub enum EvelynCoreError {
     FailedToUpdateSimpleTask(EvelynDatabaseError),
     FailedToLookupSimpleTask(EvelynDatabaseError),
     FailedToRemoveSimpleTask(EvelynDatabaseError),
+    SimpleTaskVersionConflict(EvelynBaseError),
+    SimpleTaskNotFound(EvelynBaseError),
+    InvalidSimpleTaskCursor(EvelynBaseError),
+    RecurringSimpleTaskWithoutDueDate(EvelynBaseError),
+    InvalidParentSimpleTask(EvelynBaseError),
+    FailedToUpdateSimpleTaskChecklist(EvelynDatabaseError),
+    FailedToPromoteSimpleTask(EvelynDatabaseError),
+    CannotPromoteToAgileProject(EvelynBaseError),
+    FailedToMigrateSimpleTasks(EvelynDatabaseError),
 
     // Todo List
     FailedToCreateTodoList(EvelynDatabaseError),
@@ -260,6 +452,22 @@ pub enum EvelynCoreError {
     FailedToLookupTodoLists(EvelynDatabaseError),
     FailedToLookupTodoList(EvelynDatabaseError),
     FailedToUpdateTodoListItem(EvelynDatabaseError),
+    TodoListVersionConflict(EvelynBaseError),
+    TodoListNotFound(EvelynBaseError),
+    FailedToConvertTodoListItem(EvelynDatabaseError),
+    FailedToUpdateTodoList(EvelynDatabaseError),
+    FailedToRemoveTodoList(EvelynDatabaseError),
+    FailedToRemoveTodoListItem(EvelynDatabaseError),
+    FailedToMoveTodoListItem(EvelynDatabaseError),
+    FailedToMigrateTodoLists(EvelynDatabaseError),
+    InvalidTodoListShare(EvelynBaseError),
+    FailedToUpdateTodoListShares(EvelynDatabaseError),
+    FailedToCreateTodoListTemplate(EvelynDatabaseError),
+    FailedToLookupTodoListTemplates(EvelynDatabaseError),
+    FailedToInstantiateTodoListTemplate(EvelynDatabaseError),
+    FailedToRemoveTodoListTemplate(EvelynDatabaseError),
+    TodoListTemplateNotFound(EvelynBaseError),
+    InvalidTodoListTemplateUserGroup(EvelynBaseError),
 
     // Agile: Project
     FailedToCreateAgileProject(EvelynDatabaseError),
@@ -274,6 +482,8 @@ pub enum EvelynCoreError {
     FailedToLookupBacklogAgileTasks(EvelynDatabaseError),
     AgileTaskNotFound(EvelynBaseError),
     FailedToUpdateAgileTask(EvelynDatabaseError),
+    AgileTaskVersionConflict(EvelynBaseError),
+    FailedToLookupAssignedAgileTasks(EvelynDatabaseError),
 
     // Agile: Story
     FailedToCreateAgileStory(EvelynDatabaseError),
@@ -294,8 +504,69 @@ pub enum EvelynCoreError {
     FailedToLookupAgileHeirarchyLinks(EvelynDatabaseError),
     FailedToLookupAgileHeirarchyLinksToType(EvelynDatabaseError),
 
+    // Agile: Audit
+    FailedToLookupAgileHistory(EvelynDatabaseError),
+
     // Calendar
     FailedToAddCalendarEvent(EvelynDatabaseError),
+    FailedToLookupCalendarEvents(EvelynDatabaseError),
+    InvalidCalendarEventTime(EvelynBaseError),
+    FailedToMigrateCalendarEvents(EvelynDatabaseError),
+    FailedToUpdateCalendarEvent(EvelynDatabaseError),
+    FailedToRemoveCalendarEvent(EvelynDatabaseError),
+    CalendarEventNotFound(EvelynBaseError),
+    CalendarEventVersionConflict(EvelynBaseError),
+    InvalidRecurrenceRule(EvelynBaseError),
+    CalendarEventOccurrenceNotFound(EvelynBaseError),
+    FailedToUpdateCalendarEventOccurrence(EvelynDatabaseError),
+    FailedToCancelCalendarEventOccurrence(EvelynDatabaseError),
+    FailedToExportCalendar(EvelynDatabaseError),
+    FailedToImportCalendar(EvelynDatabaseError),
+    InvalidICalendar(EvelynBaseError),
+    FailedToInviteToCalendarEvent(EvelynDatabaseError),
+    FailedToRespondToCalendarEvent(EvelynDatabaseError),
+    FailedToLookupFreeBusy(EvelynDatabaseError),
+    NotInvitedToCalendarEvent(EvelynBaseError),
+    CalendarInviteeNotFound(EvelynBaseError),
+    CalendarEventIdTaken(EvelynBaseError),
+
+    // Trash
+    FailedToLookupTrash(EvelynDatabaseError),
+    FailedToRestoreFromTrash(EvelynDatabaseError),
+    TrashItemNotFound(EvelynBaseError),
+    InvalidTrashItemType(EvelynBaseError),
+    FailedToPurgeTrash(EvelynDatabaseError),
+
+    // Reminder
+    FailedToAddReminder(EvelynDatabaseError),
+    FailedToLookupReminders(EvelynDatabaseError),
+    FailedToRemoveReminder(EvelynDatabaseError),
+    ReminderNotFound(EvelynBaseError),
+    ReminderSourceNotFound(EvelynBaseError),
+    InvalidReminder(EvelynBaseError),
+    FailedToFireReminders(EvelynDatabaseError),
+
+    // Notification
+    FailedToDeliverNotification(EvelynDatabaseError),
+    FailedToLookupNotifications(EvelynDatabaseError),
+    FailedToMarkNotificationsRead(EvelynDatabaseError),
+
+    // Email
+    FailedToQueueEmail(EvelynDatabaseError),
+    FailedToSendEmails(EvelynDatabaseError),
+
+    // Webhook
+    FailedToAddWebhook(EvelynDatabaseError),
+    FailedToLookupWebhooks(EvelynDatabaseError),
+    FailedToRemoveWebhook(EvelynDatabaseError),
+    WebhookNotFound(EvelynBaseError),
+    InvalidWebhook(EvelynBaseError),
+    FailedToLookupWebhookDeliveries(EvelynDatabaseError),
+    FailedToSendWebhooks(EvelynDatabaseError),
+    FailedToAnnounceSprints(EvelynDatabaseError),
+
+    // Agenda
+    InvalidAgendaDate(EvelynBaseError),
 }
 
 EvelynErrorDisplay!{
@@ -304,6 +575,9 @@ EvelynErrorDisplay!{
     // Server Admin
     {FailedToPurgeDatabase, "Failed to purge database {}"},
     {FailedToPurgeDatabaseArea, "Failed to purge database area {}"},
+    {FailedToDeleteUserData, "Failed to delete user data: {}"},
+    {FailedToDeleteAgileProject, "Failed to delete agile project: {}"},
+    {NotPermittedToDeleteAgileProject, "Not permitted to delete agile project: {}"},
 
     // User
     {WillNotCreateUserBecauseUserAlreadyExists, "Will not create the requested user because that user already exists. {}"},
@@ -312,6 +586,13 @@ EvelynErrorDisplay!{
     {InvalidLogon, "Invalid logon {}"},
     {FailedToLogonUser, "Failed to logon user: {}"},
     {FailedToSearchForUsers, "Failed to search for users: {}"},
+    {FailedToLookupUserProfile, "Failed to lookup user profile: {}"},
+    {FailedToUpdateUserProfile, "Failed to update user profile: {}"},
+    {InvalidEmailAddress, "Invalid email address: {}"},
+
+    // Dates and times
+    {InvalidDateTime, "Invalid date time: {}"},
+    {InvalidTimeZone, "Invalid time zone: {}"},
 
     // User Group
     {FailedToCreateUserGroup, "Failed to create user group: {}"},
@@ -326,6 +607,15 @@ EvelynErrorDisplay!{
     {FailedToUpdateSimpleTask, "Failed to update task: {}"},
     {FailedToLookupSimpleTask, "Failed to lookup task: {}"},
     {FailedToRemoveSimpleTask, "Failed to remove task: {}"},
+    {SimpleTaskVersionConflict, "Task has been modified by another update: {}"},
+    {SimpleTaskNotFound, "Task not found: {}"},
+    {InvalidSimpleTaskCursor, "Invalid simple task cursor: {}"},
+    {RecurringSimpleTaskWithoutDueDate, "Recurring task without a due date: {}"},
+    {InvalidParentSimpleTask, "Invalid parent task: {}"},
+    {FailedToUpdateSimpleTaskChecklist, "Failed to update task checklist item: {}"},
+    {FailedToPromoteSimpleTask, "Failed to promote task: {}"},
+    {CannotPromoteToAgileProject, "Cannot promote task into project: {}"},
+    {FailedToMigrateSimpleTasks, "Failed to migrate simple tasks: {}"},
 
     // Todo List
     {FailedToCreateTodoList, "Failed to create todo list: {}"},
@@ -333,6 +623,22 @@ EvelynErrorDisplay!{
     {FailedToLookupTodoLists, "Failed to lookup todo lists: {}"},
     {FailedToLookupTodoList, "Failed to lookup todo list: {}"},
     {FailedToUpdateTodoListItem, "Failed to update todo list item: {}"},
+    {TodoListVersionConflict, "Todo list has been modified by another update: {}"},
+    {TodoListNotFound, "Todo list not found: {}"},
+    {FailedToConvertTodoListItem, "Failed to convert todo list item: {}"},
+    {FailedToUpdateTodoList, "Failed to update todo list: {}"},
+    {FailedToRemoveTodoList, "Failed to remove todo list: {}"},
+    {FailedToRemoveTodoListItem, "Failed to remove todo list item: {}"},
+    {FailedToMoveTodoListItem, "Failed to move todo list item: {}"},
+    {FailedToMigrateTodoLists, "Failed to migrate todo lists: {}"},
+    {InvalidTodoListShare, "Invalid todo list share: {}"},
+    {FailedToUpdateTodoListShares, "Failed to update todo list shares: {}"},
+    {FailedToCreateTodoListTemplate, "Failed to create todo list template: {}"},
+    {FailedToLookupTodoListTemplates, "Failed to lookup todo list templates: {}"},
+    {FailedToInstantiateTodoListTemplate, "Failed to create todo list from template: {}"},
+    {FailedToRemoveTodoListTemplate, "Failed to remove todo list template: {}"},
+    {TodoListTemplateNotFound, "Todo list template not found: {}"},
+    {InvalidTodoListTemplateUserGroup, "Invalid todo list template user group: {}"},
 
     // Agile: Project
     {FailedToCreateAgileProject, "Failed to create agile project: {}"},
@@ -347,6 +653,8 @@ EvelynErrorDisplay!{
     {FailedToLookupBacklogAgileTasks, "Failed to lookup backlog agile tasks: {}"},
     {AgileTaskNotFound, "Agile task not found: {}"},
     {FailedToUpdateAgileTask, "Failed to update agile task: {}"},
+    {AgileTaskVersionConflict, "Agile task has been modified by another update: {}"},
+    {FailedToLookupAssignedAgileTasks, "Failed to lookup assigned agile tasks: {}"},
 
     // Agile: Story
     {FailedToCreateAgileStory, "Failed to create agile story: {}"},
@@ -367,23 +675,87 @@ EvelynErrorDisplay!{
     {FailedToLookupAgileHeirarchyLinks, "Failed to lookup agile heirarchy links: {}"},
     {FailedToLookupAgileHeirarchyLinksToType, "Failed to lookup agile heirarchy links to type: {}"},
 
+    // Agile: Audit
+    {FailedToLookupAgileHistory, "Failed to lookup agile history: {}"},
+
     // Calendar
-    {FailedToAddCalendarEvent, "Failed to add calendar event: {}"}
+    {FailedToAddCalendarEvent, "Failed to add calendar event: {}"},
+    {FailedToLookupCalendarEvents, "Failed to lookup calendar events: {}"},
+    {InvalidCalendarEventTime, "Invalid calendar event time: {}"},
+    {FailedToMigrateCalendarEvents, "Failed to migrate calendar events: {}"},
+    {FailedToUpdateCalendarEvent, "Failed to update calendar event: {}"},
+    {FailedToRemoveCalendarEvent, "Failed to remove calendar event: {}"},
+    {CalendarEventNotFound, "Calendar event not found: {}"},
+    {CalendarEventVersionConflict, "Calendar event has been modified by another update: {}"},
+    {InvalidRecurrenceRule, "Invalid recurrence rule: {}"},
+    {CalendarEventOccurrenceNotFound, "Calendar event occurrence not found: {}"},
+    {FailedToUpdateCalendarEventOccurrence, "Failed to update calendar event occurrence: {}"},
+    {FailedToCancelCalendarEventOccurrence, "Failed to cancel calendar event occurrence: {}"},
+    {FailedToExportCalendar, "Failed to export calendar: {}"},
+    {FailedToImportCalendar, "Failed to import calendar: {}"},
+    {InvalidICalendar, "Invalid iCalendar document: {}"},
+    {FailedToInviteToCalendarEvent, "Failed to invite attendees to calendar event: {}"},
+    {FailedToRespondToCalendarEvent, "Failed to respond to calendar event invitation: {}"},
+    {FailedToLookupFreeBusy, "Failed to lookup free busy times: {}"},
+    {NotInvitedToCalendarEvent, "Not invited to calendar event: {}"},
+    {CalendarInviteeNotFound, "Calendar invitee not found: {}"},
+    {CalendarEventIdTaken, "Calendar event id is already in use: {}"},
+
+    // Trash
+    {FailedToLookupTrash, "Failed to lookup trash: {}"},
+    {FailedToRestoreFromTrash, "Failed to restore item from trash: {}"},
+    {TrashItemNotFound, "Item not found in trash: {}"},
+    {InvalidTrashItemType, "Invalid trash item type: {}"},
+    {FailedToPurgeTrash, "Failed to purge trash: {}"},
+
+    // Reminder
+    {FailedToAddReminder, "Failed to add reminder: {}"},
+    {FailedToLookupReminders, "Failed to lookup reminders: {}"},
+    {FailedToRemoveReminder, "Failed to remove reminder: {}"},
+    {ReminderNotFound, "Reminder not found: {}"},
+    {ReminderSourceNotFound, "Reminder source not found: {}"},
+    {InvalidReminder, "Invalid reminder: {}"},
+    {FailedToFireReminders, "Failed to fire reminders: {}"},
+
+    // Notification
+    {FailedToDeliverNotification, "Failed to deliver notification: {}"},
+    {FailedToLookupNotifications, "Failed to lookup notifications: {}"},
+    {FailedToMarkNotificationsRead, "Failed to mark notifications as read: {}"},
+
+    // Email
+    {FailedToQueueEmail, "Failed to queue email: {}"},
+    {FailedToSendEmails, "Failed to send emails: {}"},
+
+    // Webhook
+    {FailedToAddWebhook, "Failed to add webhook: {}"},
+    {FailedToLookupWebhooks, "Failed to lookup webhooks: {}"},
+    {FailedToRemoveWebhook, "Failed to remove webhook: {}"},
+    {WebhookNotFound, "Webhook not found: {}"},
+    {InvalidWebhook, "Invalid webhook: {}"},
+    {FailedToLookupWebhookDeliveries, "Failed to lookup webhook deliveries: {}"},
+    {FailedToSendWebhooks, "Failed to send webhooks: {}"},
+    {FailedToAnnounceSprints, "Failed to announce started sprints: {}"},
+    {InvalidAgendaDate, "Invalid agenda date: {}"}
 }
 
 #[derive(Debug)]
 pub enum EvelynDatabaseError {
     SerialisationFailed(EvelynBaseError),
     BSONEncodeFailed(bson::EncoderError),
-    BSONDecodeFailed(bson::DecoderError),
+    BSONDecodeFailed(EvelynDecodeError),
 
     // Server Admin
     PurgeDatabase(MongoDbError),
     PurgeCollection(MongoDbError),
+    UnknownPurgeTarget(EvelynBaseError),
+    CountAdminScope(MongoDbError),
+    ApplyAdminScope(MongoDbError),
 
     // User
     InsertUser(MongoDbError),
     LookupUser(MongoDbError),
+    UpdateUser(MongoDbError),
+    UserNotFound(EvelynBaseError),
     SearchForUsers(MongoDbError),
 
     // User group
@@ -400,6 +772,12 @@ pub enum EvelynDatabaseError {
     UpdateSimpleTask(MongoDbError),
     LookupSimpleTask(MongoDbError),
     RemoveSimpleTask(MongoDbError),
+    SimpleTaskNotFound(EvelynBaseError),
+    SimpleTaskVersionConflict(EvelynBaseError),
+    SimpleTaskCursorNotFound(EvelynBaseError),
+    UpdateSimpleTaskChecklist(MongoDbError),
+    ConvertSimpleTask(MongoDbError),
+    MigrateSimpleTasks(MongoDbError),
 
     // Todo List
     InsertTodoList(MongoDbError),
@@ -408,6 +786,17 @@ pub enum EvelynDatabaseError {
     TodoListNotFound(EvelynBaseError),
     LookupTodoList(MongoDbError),
     UpdateTodoListItem(MongoDbError),
+    TodoListVersionConflict(EvelynBaseError),
+    ConvertTodoListItem(MongoDbError),
+    UpdateTodoList(MongoDbError),
+    RemoveTodoList(MongoDbError),
+    RemoveTodoListItem(MongoDbError),
+    MigrateTodoLists(MongoDbError),
+    UpdateTodoListShares(MongoDbError),
+    InsertTodoListTemplate(MongoDbError),
+    LookupTodoListTemplates(MongoDbError),
+    TodoListTemplateNotFound(EvelynBaseError),
+    RemoveTodoListTemplate(MongoDbError),
 
     // Agile: Project
     InsertAgileProject(MongoDbError),
@@ -422,6 +811,9 @@ pub enum EvelynDatabaseError {
     LookupAgileTaskById(MongoDbError),
     LookupBacklogAgileTasks(MongoDbError),
     UpdateAgileTask(MongoDbError),
+    AgileTaskNotFound(EvelynBaseError),
+    AgileTaskVersionConflict(EvelynBaseError),
+    LookupAssignedAgileTasks(MongoDbError),
 
     // Agile: Story
     InsertAgileStory(MongoDbError),
@@ -432,6 +824,9 @@ pub enum EvelynDatabaseError {
     InsertAgileSprint(MongoDbError),
     LookupActiveAgileSprints(MongoDbError),
     LookupBacklogAgileSprints(MongoDbError),
+    LookupAgileSprints(MongoDbError),
+    AgileSprintNotFound(EvelynBaseError),
+    UpdateAgileSprint(MongoDbError),
 
     // Agile: Heirarchy
     InsertAgileHeirarchyLink(MongoDbError),
@@ -440,8 +835,56 @@ pub enum EvelynDatabaseError {
     LookupAgileHeirarchyLinks(MongoDbError),
     LookupAgileHeirarchyLinksToType(MongoDbError),
 
+    // Agile: Audit
+    InsertAgileAuditEntry(MongoDbError),
+    LookupAgileAuditEntries(MongoDbError),
+
     // Calendar
     InsertCalendarEvent(MongoDbError),
+    LookupCalendarEvents(MongoDbError),
+    MigrateCalendarEvents(MongoDbError),
+    LookupCalendarEvent(MongoDbError),
+    UpdateCalendarEvent(MongoDbError),
+    RemoveCalendarEvent(MongoDbError),
+    CalendarEventNotFound(EvelynBaseError),
+    CalendarEventVersionConflict(EvelynBaseError),
+    UpdateCalendarEventOccurrences(MongoDbError),
+    ReplaceCalendarEvent(MongoDbError),
+    UpdateCalendarEventAttendees(MongoDbError),
+    RespondToCalendarEvent(MongoDbError),
+    NotInvitedToCalendarEvent(EvelynBaseError),
+
+    // Trash
+    LookupTrash(MongoDbError),
+    RestoreFromTrash(MongoDbError),
+    TrashItemNotFound(EvelynBaseError),
+    PurgeTrash(MongoDbError),
+
+    // Reminder
+    InsertReminder(MongoDbError),
+    LookupReminders(MongoDbError),
+    UpdateReminder(MongoDbError),
+    RemoveReminder(MongoDbError),
+    ReminderNotFound(EvelynBaseError),
+
+    // Notification
+    InsertNotification(MongoDbError),
+    LookupNotifications(MongoDbError),
+    UpdateNotifications(MongoDbError),
+
+    // Email
+    InsertEmail(MongoDbError),
+    LookupEmails(MongoDbError),
+    UpdateEmail(MongoDbError),
+
+    // Webhook
+    InsertWebhook(MongoDbError),
+    LookupWebhooks(MongoDbError),
+    RemoveWebhook(MongoDbError),
+    WebhookNotFound(EvelynBaseError),
+    InsertWebhookDelivery(MongoDbError),
+    LookupWebhookDeliveries(MongoDbError),
+    UpdateWebhookDelivery(MongoDbError),
 }
 
 EvelynErrorDisplay!{
@@ -449,15 +892,20 @@ EvelynErrorDisplay!{
     // Processing
     {SerialisationFailed, "Failed to serialise data for storage. {}"},
     {BSONEncodeFailed, "Failed to serialise data for storage. {}"},
-    {BSONDecodeFailed, "Failed to deserialise data for storage. {}"},
+    {BSONDecodeFailed, "Failed to deserialise stored data. {}"},
 
     // Server Admin
     {PurgeDatabase, "Failed to purge database {}"},
     {PurgeCollection, "Failed to purge collection {}"},
+    {UnknownPurgeTarget, "Not a collection which can be purged {}"},
+    {CountAdminScope, "Failed to count data in scope: {}"},
+    {ApplyAdminScope, "Failed to apply change to data in scope: {}"},
 
     // User
     {InsertUser, "Failed to create record for new user: {}"},
     {LookupUser, "Failed to lookup user: {}"},
+    {UpdateUser, "Failed to update user: {}"},
+    {UserNotFound, "User not found: {}"},
     {SearchForUsers, "Failed to search for users: {}"},
 
     // User group
@@ -474,6 +922,12 @@ EvelynErrorDisplay!{
     {UpdateSimpleTask, "Failed to update simple task: {}"},
     {LookupSimpleTask, "Failed to lookup simple tasks: {}"},
     {RemoveSimpleTask, "Failed to remove simple task: {}"},
+    {SimpleTaskNotFound, "Simple task not found: {}"},
+    {SimpleTaskVersionConflict, "Simple task version does not match: {}"},
+    {SimpleTaskCursorNotFound, "Simple task cursor not found: {}"},
+    {UpdateSimpleTaskChecklist, "Failed to update simple task checklist item: {}"},
+    {ConvertSimpleTask, "Failed to record simple task conversion: {}"},
+    {MigrateSimpleTasks, "Failed to migrate simple tasks: {}"},
 
     // Todo List
     {InsertTodoList, "Failed to insert todo list: {}"},
@@ -482,6 +936,17 @@ EvelynErrorDisplay!{
     {TodoListNotFound, "Todo list not found {}"},
     {LookupTodoList, "Failed to lookup todo list:  {}"},
     {UpdateTodoListItem, "Failed to update todo list item:  {}"},
+    {TodoListVersionConflict, "Todo list version does not match: {}"},
+    {ConvertTodoListItem, "Failed to record todo list item conversion: {}"},
+    {UpdateTodoList, "Failed to update todo list: {}"},
+    {RemoveTodoList, "Failed to remove todo list: {}"},
+    {RemoveTodoListItem, "Failed to remove todo list item: {}"},
+    {MigrateTodoLists, "Failed to migrate todo lists: {}"},
+    {UpdateTodoListShares, "Failed to update todo list shares: {}"},
+    {InsertTodoListTemplate, "Failed to insert todo list template: {}"},
+    {LookupTodoListTemplates, "Failed to lookup todo list templates: {}"},
+    {TodoListTemplateNotFound, "Todo list template not found: {}"},
+    {RemoveTodoListTemplate, "Failed to remove todo list template: {}"},
 
     // Agile: Project
     {InsertAgileProject, "Failed to insert agile project: {}"},
@@ -496,6 +961,9 @@ EvelynErrorDisplay!{
     {LookupAgileTaskById, "Failed to lookup agile task by id: {}"},
     {LookupBacklogAgileTasks, "Failed to lookup backlog agile tasks: {}"},
     {UpdateAgileTask, "Failed to update agile task: {}"},
+    {AgileTaskNotFound, "Agile task not found: {}"},
+    {AgileTaskVersionConflict, "Agile task version does not match: {}"},
+    {LookupAssignedAgileTasks, "Failed to lookup assigned agile tasks: {}"},
 
     // Agile: Story
     {InsertAgileStory, "Failed to insert agile story: {}"},
@@ -506,6 +974,9 @@ EvelynErrorDisplay!{
     {InsertAgileSprint, "Failed to insert agile sprint: {}"},
     {LookupActiveAgileSprints, "Failed to lookup active agile sprints: {}"},
     {LookupBacklogAgileSprints, "Failed to lookup backlog agile sprints: {}"},
+    {LookupAgileSprints, "Failed to lookup agile sprints: {}"},
+    {AgileSprintNotFound, "Agile sprint not found: {}"},
+    {UpdateAgileSprint, "Failed to update agile sprint: {}"},
 
     // Agile: Heirarchy
     {InsertAgileHeirarchyLink, "Failed to insert agile heirarchy link: {}"},
@@ -514,8 +985,56 @@ EvelynErrorDisplay!{
     {LookupAgileHeirarchyLinks, "Failed to lookup agile heirarchy links: {}"},
     {LookupAgileHeirarchyLinksToType, "Failed to lookup agile heirarchy links to type: {}"},
 
+    // Agile: Audit
+    {InsertAgileAuditEntry, "Failed to insert agile audit entry: {}"},
+    {LookupAgileAuditEntries, "Failed to lookup agile audit entries: {}"},
+
     // Calendar
-    {InsertCalendarEvent, "Failed to insert calendar event: {}"}
+    {InsertCalendarEvent, "Failed to insert calendar event: {}"},
+    {LookupCalendarEvents, "Failed to lookup calendar events: {}"},
+    {MigrateCalendarEvents, "Failed to migrate calendar events: {}"},
+    {LookupCalendarEvent, "Failed to lookup calendar event: {}"},
+    {UpdateCalendarEvent, "Failed to update calendar event: {}"},
+    {RemoveCalendarEvent, "Failed to remove calendar event: {}"},
+    {CalendarEventNotFound, "Calendar event not found: {}"},
+    {CalendarEventVersionConflict, "Calendar event version does not match: {}"},
+    {UpdateCalendarEventOccurrences, "Failed to update calendar event occurrences: {}"},
+    {ReplaceCalendarEvent, "Failed to replace calendar event: {}"},
+    {UpdateCalendarEventAttendees, "Failed to update calendar event attendees: {}"},
+    {RespondToCalendarEvent, "Failed to respond to calendar event: {}"},
+    {NotInvitedToCalendarEvent, "Not invited to calendar event: {}"},
+
+    // Trash
+    {LookupTrash, "Failed to lookup deleted items: {}"},
+    {RestoreFromTrash, "Failed to restore deleted item: {}"},
+    {TrashItemNotFound, "Deleted item not found: {}"},
+    {PurgeTrash, "Failed to purge deleted items: {}"},
+
+    // Reminder
+    {InsertReminder, "Failed to insert reminder: {}"},
+    {LookupReminders, "Failed to lookup reminders: {}"},
+    {UpdateReminder, "Failed to update reminder: {}"},
+    {RemoveReminder, "Failed to remove reminder: {}"},
+    {ReminderNotFound, "Reminder not found: {}"},
+
+    // Notification
+    {InsertNotification, "Failed to insert notification: {}"},
+    {LookupNotifications, "Failed to lookup notifications: {}"},
+    {UpdateNotifications, "Failed to update notifications: {}"},
+
+    // Email
+    {InsertEmail, "Failed to insert email: {}"},
+    {LookupEmails, "Failed to lookup emails: {}"},
+    {UpdateEmail, "Failed to update email: {}"},
+
+    // Webhook
+    {InsertWebhook, "Failed to insert webhook: {}"},
+    {LookupWebhooks, "Failed to lookup webhooks: {}"},
+    {RemoveWebhook, "Failed to remove webhook: {}"},
+    {WebhookNotFound, "Webhook not found: {}"},
+    {InsertWebhookDelivery, "Failed to insert webhook delivery: {}"},
+    {LookupWebhookDeliveries, "Failed to lookup webhook deliveries: {}"},
+    {UpdateWebhookDelivery, "Failed to update webhook delivery: {}"}
 }
 
 // This error is a null enum that gets passed if there is no specific error to
@@ -529,3 +1048,27 @@ pub enum EvelynBaseError {
 EvelynErrorDisplay!(EvelynBaseError, {
     NothingElse
 });
+
+// Decode failures carry the id of the document which could not be read so
+// that bad data can be tracked down from the logs.
+#[derive(Debug)]
+pub struct EvelynDecodeError {
+    pub document_id: String,
+    pub error: bson::DecoderError,
+}
+
+impl fmt::Display for EvelynDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Document [{}]: {}", self.document_id, self.error)
+    }
+}
+
+impl error::Error for EvelynDecodeError {
+    fn description(&self) -> &str {
+        "Failed to decode document"
+    }
+
+    fn cause(&self) -> Option<&error::Error> {
+        Some(&self.error)
+    }
+}
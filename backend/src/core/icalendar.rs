@@ -0,0 +1,585 @@
+// Evelyn: Your personal assistant, project manager and calendar
+// Copyright (C) 2017 Gregory Jensen
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+
+// Reading and writing RFC 5545 iCalendar documents. Only what is needed to
+// move events in and out of Evelyn is supported, other components and
+// properties are skipped over.
+
+use chrono::Duration;
+use chrono::prelude::*;
+use chrono_tz::Tz;
+use core::date_time_service as dts;
+use core::error_messages::{EvelynBaseError, EvelynCoreError};
+use std::cmp;
+use std::mem;
+
+const PRODUCT_ID: &'static str = "-//Evelyn//Evelyn//EN";
+
+// Content lines longer than this are folded.
+const MAX_LINE_OCTETS: usize = 75;
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+// Zone definitions carry on this many years past the last time written in
+// them, so that recurring events are still read at the right local time.
+const TIME_ZONE_YEARS_AHEAD: i32 = 10;
+
+// The lines before the first component, where zone definitions are put.
+const CALENDAR_HEADER_LINES: usize = 3;
+
+struct UsedTimeZone {
+    name: String,
+    zone: Tz,
+    earliest: i64,
+    latest: i64,
+}
+
+pub struct ICalendarWriter {
+    lines: Vec<String>,
+    time_zones: Vec<UsedTimeZone>,
+}
+
+impl ICalendarWriter {
+    pub fn new() -> Self {
+        let mut writer = ICalendarWriter {
+            lines: Vec::new(),
+            time_zones: Vec::new(),
+        };
+
+        writer.begin("VCALENDAR");
+        writer.property("VERSION", "2.0");
+        writer.property("PRODID", PRODUCT_ID);
+        writer
+    }
+
+    pub fn begin(&mut self, component: &str) {
+        self.property("BEGIN", component);
+    }
+
+    pub fn end(&mut self, component: &str) {
+        self.property("END", component);
+    }
+
+    pub fn property(&mut self, name: &str, value: &str) {
+        let line = format!("{}:{}", name, value);
+        self.lines.push(fold_line(&line));
+    }
+
+    pub fn text_property(&mut self, name: &str, value: &str) {
+        self.property(name, &escape_text(value));
+    }
+
+    pub fn date_time_property(&mut self, name: &str, timestamp: i64) {
+        self.property(name, &format_date_time(timestamp));
+    }
+
+    pub fn date_property(&mut self, name: &str, date: &NaiveDate) {
+        self.property(&format!("{};VALUE=DATE", name), &date.format("%Y%m%d").to_string());
+    }
+
+    // Writes local times with a TZID so that other calendars repeat the event
+    // at the same local time across daylight saving changes. Times in an
+    // unknown zone are written in UTC instead. Each zone used is defined by a
+    // VTIMEZONE when the document is finished.
+    pub fn zoned_date_time_property(&mut self, name: &str, timestamps: &[i64], time_zone: &str) {
+        match dts::parse_time_zone(time_zone) {
+            Ok(zone) => {
+                let values: Vec<String> = timestamps.iter().map(|x| format_local_date_time(*x, &zone)).collect();
+                self.property(&format!("{};TZID={}", name, time_zone), &values.join(","));
+                self.use_time_zone(time_zone, zone, timestamps);
+            },
+            Err(_) => {
+                let values: Vec<String> = timestamps.iter().map(|x| format_date_time(*x)).collect();
+                self.property(name, &values.join(","));
+            },
+        }
+    }
+
+    fn use_time_zone(&mut self, name: &str, zone: Tz, timestamps: &[i64]) {
+        let earliest = match timestamps.iter().min() {
+            Some(earliest) => *earliest,
+            None => return,
+        };
+        let latest = *timestamps.iter().max().unwrap_or(&earliest);
+
+        if let Some(used) = self.time_zones.iter_mut().find(|x| x.name == name) {
+            used.earliest = cmp::min(used.earliest, earliest);
+            used.latest = cmp::max(used.latest, latest);
+            return;
+        }
+
+        self.time_zones.push(UsedTimeZone {
+            name: String::from(name),
+            zone: zone,
+            earliest: earliest,
+            latest: latest,
+        });
+    }
+
+    // The zone is described by the transitions it makes from the start of
+    // the year of the earliest time used. Transitions between the same pair
+    // of offsets are written as one observance with an RDATE for each repeat.
+    fn time_zone_component(&mut self, used: &UsedTimeZone) {
+        let first_year = NaiveDateTime::from_timestamp(used.earliest, 0).year();
+        let last_year = NaiveDateTime::from_timestamp(used.latest, 0).year() + TIME_ZONE_YEARS_AHEAD;
+        let begin = NaiveDate::from_ymd(first_year, 1, 1).and_hms(0, 0, 0).timestamp();
+        let end = NaiveDate::from_ymd(last_year, 1, 1).and_hms(0, 0, 0).timestamp();
+
+        let initial_offset = utc_offset(begin, &used.zone);
+        let mut onsets = vec![(begin, initial_offset, initial_offset)];
+        let mut offset = initial_offset;
+        for transition in find_transitions(&used.zone, begin, end) {
+            let next_offset = utc_offset(transition, &used.zone);
+            onsets.push((transition, offset, next_offset));
+            offset = next_offset;
+        }
+
+        let standard_offset = onsets.iter().map(|x| x.2).min().unwrap_or(initial_offset);
+
+        let mut observances: Vec<(i32, i32, Vec<String>)> = Vec::new();
+        for (onset, offset_from, offset_to) in onsets {
+            // Onsets are given in the local time before the change.
+            let local_onset = NaiveDateTime::from_timestamp(onset + offset_from as i64, 0).format("%Y%m%dT%H%M%S").to_string();
+
+            match observances.iter_mut().find(|x| x.0 == offset_from && x.1 == offset_to) {
+                Some(observance) => {
+                    observance.2.push(local_onset);
+                    continue;
+                },
+                None => {},
+            }
+            observances.push((offset_from, offset_to, vec![local_onset]));
+        }
+
+        self.begin("VTIMEZONE");
+        self.property("TZID", &used.name);
+        for (offset_from, offset_to, local_onsets) in observances {
+            let kind = if offset_to > standard_offset { "DAYLIGHT" } else { "STANDARD" };
+
+            self.begin(kind);
+            self.property("DTSTART", &local_onsets[0]);
+            if local_onsets.len() > 1 {
+                self.property("RDATE", &local_onsets[1..].join(","));
+            }
+            self.property("TZOFFSETFROM", &format_utc_offset(offset_from));
+            self.property("TZOFFSETTO", &format_utc_offset(offset_to));
+            self.end(kind);
+        }
+        self.end("VTIMEZONE");
+    }
+
+    pub fn finish(mut self) -> String {
+        // Zone definitions go before the events which use them.
+        let components = self.lines.split_off(CALENDAR_HEADER_LINES);
+        let time_zones = mem::replace(&mut self.time_zones, Vec::new());
+        for used in time_zones.iter() {
+            self.time_zone_component(used);
+        }
+        self.lines.extend(components);
+
+        self.end("VCALENDAR");
+
+        let mut document = self.lines.join("\r\n");
+        document.push_str("\r\n");
+        document
+    }
+}
+
+fn utc_offset(timestamp: i64, zone: &Tz) -> i32 {
+    zone.offset_from_utc_datetime(&NaiveDateTime::from_timestamp(timestamp, 0)).fix().local_minus_utc()
+}
+
+// The times in the range at which the zone's offset changes. Offsets are
+// compared a day apart and then narrowed down to the second, zones don't
+// change twice in a day.
+fn find_transitions(zone: &Tz, begin: i64, end: i64) -> Vec<i64> {
+    let mut transitions = Vec::new();
+    let mut at = begin;
+    let mut offset = utc_offset(at, zone);
+
+    while at < end {
+        let next = at + SECONDS_PER_DAY;
+        let next_offset = utc_offset(next, zone);
+
+        if next_offset != offset {
+            let (mut low, mut high) = (at, next);
+            while high - low > 1 {
+                let middle = low + (high - low) / 2;
+                if utc_offset(middle, zone) == offset {
+                    low = middle;
+                } else {
+                    high = middle;
+                }
+            }
+
+            transitions.push(high);
+            offset = next_offset;
+        }
+
+        at = next;
+    }
+
+    transitions
+}
+
+fn format_utc_offset(offset: i32) -> String {
+    let sign = if offset < 0 { '-' } else { '+' };
+    let minutes = offset.abs() / 60;
+    format!("{}{:02}{:02}", sign, minutes / 60, minutes % 60)
+}
+
+fn escape_text(value: &str) -> String {
+    value.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace('\n', "\\n")
+}
+
+fn unescape_text(value: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = value.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') | Some('N') => unescaped.push('\n'),
+                Some(escaped) => unescaped.push(escaped),
+                None => {},
+            }
+        } else {
+            unescaped.push(c);
+        }
+    }
+
+    unescaped
+}
+
+// Splits long lines without breaking up multi-byte characters, each
+// continuation line starts with a space.
+fn fold_line(line: &str) -> String {
+    let mut folded = String::new();
+    let mut line_octets = 0;
+
+    for c in line.chars() {
+        if line_octets + c.len_utf8() > MAX_LINE_OCTETS {
+            folded.push_str("\r\n ");
+            line_octets = 1;
+        }
+
+        folded.push(c);
+        line_octets += c.len_utf8();
+    }
+
+    folded
+}
+
+pub fn format_date_time(timestamp: i64) -> String {
+    NaiveDateTime::from_timestamp(timestamp, 0).format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn format_local_date_time(timestamp: i64, zone: &Tz) -> String {
+    dts::timestamp_to_local(timestamp, zone).format("%Y%m%dT%H%M%S").to_string()
+}
+
+fn parse_local_date_time(value: &str) -> Option<NaiveDateTime> {
+    if let Ok(date_time) = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S") {
+        return Some(date_time);
+    }
+
+    NaiveDate::parse_from_str(value, "%Y%m%d").ok().map(|date| date.and_hms(0, 0, 0))
+}
+
+// Times without a zone are read as UTC.
+pub fn parse_date_time(value: &str) -> Option<i64> {
+    parse_local_date_time(value.trim_right_matches('Z')).map(|x| x.timestamp())
+}
+
+// Times with a TZID are read in that zone, unless they are in UTC anyway.
+// Zones which aren't IANA names are read as UTC.
+fn parse_zoned_date_time(value: &str, time_zone: &Option<String>) -> Option<i64> {
+    let zone = time_zone.as_ref().and_then(|x| dts::parse_time_zone(x).ok());
+
+    match zone {
+        Some(ref zone) if !value.ends_with('Z') => parse_local_date_time(value).map(|x| dts::local_to_timestamp_lenient(&x, zone)),
+        _ => parse_date_time(value),
+    }
+}
+
+fn is_date_value(value: &str) -> bool {
+    !value.contains('T')
+}
+
+// An RFC 5545 duration as whole days, which are nominal and so follow
+// daylight saving changes, and seconds. Negative durations aren't allowed as
+// events can't end before they begin.
+pub fn parse_duration(value: &str) -> Option<(i64, i64)> {
+    let value = value.trim_left_matches('+');
+    if !value.starts_with('P') {
+        return None;
+    }
+
+    let mut days = 0;
+    let mut seconds = 0;
+    let mut number = String::new();
+    let mut in_time = false;
+    let mut any = false;
+
+    for c in value[1..].chars() {
+        if c.is_digit(10) {
+            number.push(c);
+            continue;
+        }
+
+        if c == 'T' && !in_time && number.is_empty() {
+            in_time = true;
+            continue;
+        }
+
+        let amount = match number.parse::<i64>() {
+            Ok(amount) => amount,
+            Err(_) => return None,
+        };
+        number.clear();
+        any = true;
+
+        match (c, in_time) {
+            ('W', false) => days += amount * 7,
+            ('D', false) => days += amount,
+            ('H', true) => seconds += amount * 60 * 60,
+            ('M', true) => seconds += amount * 60,
+            ('S', true) => seconds += amount,
+            _ => return None,
+        }
+    }
+
+    if any && number.is_empty() {
+        Some((days, seconds))
+    } else {
+        None
+    }
+}
+
+// Days are added to the local time in the start's zone, if it has one.
+fn add_duration(begin: i64, begin_value: &DateTimeValue, duration: (i64, i64)) -> i64 {
+    let (days, seconds) = duration;
+    let zone = begin_value.time_zone.as_ref().and_then(|x| dts::parse_time_zone(x).ok());
+
+    let after_days = match zone {
+        Some(ref zone) if !begin_value.value.ends_with('Z') => {
+            let local = dts::timestamp_to_local(begin, zone) + Duration::days(days);
+            dts::local_to_timestamp_lenient(&local, zone)
+        },
+        _ => begin + days * SECONDS_PER_DAY,
+    };
+
+    after_days + seconds
+}
+
+pub struct ICalendarEvent {
+    pub uid: String,
+    pub summary: String,
+    pub begin: i64,
+    pub end: i64,
+    pub recurrence: Option<String>,
+    pub exception_dates: Vec<i64>,
+    // Set when the event changes a single occurrence of another event with
+    // the same uid.
+    pub recurrence_id: Option<i64>,
+    // The zone the start was given in, if it is one Evelyn knows.
+    pub time_zone: Option<String>,
+}
+
+struct DateTimeValue {
+    value: String,
+    time_zone: Option<String>,
+}
+
+impl DateTimeValue {
+    fn timestamp(&self) -> Option<i64> {
+        parse_zoned_date_time(&self.value, &self.time_zone)
+    }
+}
+
+#[derive(Default)]
+struct EventProperties {
+    uid: Option<String>,
+    summary: Option<String>,
+    begin: Option<DateTimeValue>,
+    end: Option<DateTimeValue>,
+    duration: Option<String>,
+    recurrence: Option<String>,
+    exception_dates: Vec<DateTimeValue>,
+    recurrence_id: Option<DateTimeValue>,
+}
+
+impl EventProperties {
+    fn to_event(self) -> Option<ICalendarEvent> {
+        let uid = match self.uid {
+            Some(uid) => uid,
+            None => return None,
+        };
+
+        let begin_value = match self.begin {
+            Some(begin_value) => begin_value,
+            None => return None,
+        };
+
+        let begin = match begin_value.timestamp() {
+            Some(begin) => begin,
+            None => return None,
+        };
+
+        // Without an end or a duration an all day event lasts the day and any
+        // other event takes no time.
+        let end = match (self.end, self.duration) {
+            (Some(end_value), _) => end_value.timestamp(),
+            (None, Some(duration)) => parse_duration(&duration).map(|x| add_duration(begin, &begin_value, x)),
+            (None, None) if is_date_value(&begin_value.value) => Some(begin + SECONDS_PER_DAY),
+            (None, None) => Some(begin),
+        };
+
+        let end = match end {
+            Some(end) if end >= begin => end,
+            _ => return None,
+        };
+
+        let recurrence_id = match self.recurrence_id {
+            Some(recurrence_id) => {
+                match recurrence_id.timestamp() {
+                    Some(recurrence_id) => Some(recurrence_id),
+                    None => return None,
+                }
+            },
+            None => None,
+        };
+
+        Some(ICalendarEvent {
+            uid: uid,
+            summary: self.summary.unwrap_or(String::new()),
+            begin: begin,
+            end: end,
+            recurrence: self.recurrence,
+            exception_dates: self.exception_dates.iter().filter_map(|x| x.timestamp()).collect(),
+            recurrence_id: recurrence_id,
+            time_zone: begin_value.time_zone.and_then(|x| dts::parse_time_zone(&x).ok().map(|_| x)),
+        })
+    }
+}
+
+// Joins folded lines back together.
+fn unfold_lines(document: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+
+    for line in document.split('\n') {
+        let line = line.trim_right_matches('\r');
+
+        if line.starts_with(' ') || line.starts_with('\t') {
+            if let Some(last) = lines.last_mut() {
+                last.push_str(&line[1..]);
+                continue;
+            }
+        }
+
+        if !line.is_empty() {
+            lines.push(String::from(line));
+        }
+    }
+
+    lines
+}
+
+// Splits a content line into its name, TZID parameter and value, other
+// parameters are dropped. Parameter values may be quoted and contain colons.
+fn split_line(line: &str) -> Option<(String, Option<String>, String)> {
+    let mut in_quotes = false;
+
+    for (index, c) in line.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ':' if !in_quotes => {
+                let mut parts = line[..index].split(';');
+                let name = parts.next().unwrap_or("").to_uppercase();
+                let time_zone = parts
+                    .filter(|x| x.to_uppercase().starts_with("TZID="))
+                    .map(|x| String::from(x[5..].trim_matches('"')))
+                    .next();
+                return Some((name, time_zone, String::from(&line[index + 1..])));
+            },
+            _ => {},
+        }
+    }
+
+    None
+}
+
+// Returns the events in the document and the number of events which could
+// not be read.
+pub fn parse_events(document: &str) -> Result<(Vec<ICalendarEvent>, i32), EvelynCoreError> {
+    let lines = unfold_lines(document);
+
+    if lines.first().map_or(true, |x| x.to_uppercase() != "BEGIN:VCALENDAR") {
+        return Err(EvelynCoreError::InvalidICalendar(EvelynBaseError::NothingElse));
+    }
+
+    let mut events = Vec::new();
+    let mut skipped = 0;
+
+    let mut current: Option<EventProperties> = None;
+    // Components inside an event, such as alarms, are skipped.
+    let mut nested_depth = 0;
+
+    for line in lines {
+        let (name, time_zone, value) = match split_line(&line) {
+            Some(property) => property,
+            None => return Err(EvelynCoreError::InvalidICalendar(EvelynBaseError::NothingElse)),
+        };
+
+        match (name.as_str(), current.is_some()) {
+            ("BEGIN", false) if value.to_uppercase() == "VEVENT" => current = Some(EventProperties::default()),
+            ("BEGIN", true) => nested_depth += 1,
+            ("END", true) if nested_depth > 0 => nested_depth -= 1,
+            ("END", true) => {
+                match current.take().and_then(|x| x.to_event()) {
+                    Some(event) => events.push(event),
+                    None => skipped += 1,
+                }
+            },
+            (_, true) if nested_depth == 0 => {
+                if let Some(ref mut properties) = current {
+                    match name.as_str() {
+                        "UID" => properties.uid = Some(value),
+                        "SUMMARY" => properties.summary = Some(unescape_text(&value)),
+                        "DTSTART" => properties.begin = Some(DateTimeValue { value: value, time_zone: time_zone }),
+                        "DTEND" => properties.end = Some(DateTimeValue { value: value, time_zone: time_zone }),
+                        "DURATION" => properties.duration = Some(value),
+                        "RRULE" => properties.recurrence = Some(value),
+                        "EXDATE" => properties.exception_dates.extend(value.split(',').map(|x| DateTimeValue {
+                            value: String::from(x),
+                            time_zone: time_zone.clone(),
+                        })),
+                        "RECURRENCE-ID" => properties.recurrence_id = Some(DateTimeValue { value: value, time_zone: time_zone }),
+                        _ => {},
+                    }
+                }
+            },
+            _ => {},
+        }
+    }
+
+    Ok((events, skipped))
+}
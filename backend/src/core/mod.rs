@@ -21,6 +21,17 @@ pub mod simple_task;
 pub mod todo_list;
 pub mod error_messages;
 pub mod calendar;
+pub mod recurrence;
+pub mod icalendar;
 pub mod server_admin;
 pub mod agile;
+pub mod trash;
+pub mod scheduler;
+pub mod reminder;
+pub mod notification;
+pub mod smtp;
+pub mod email;
+pub mod webhook;
+pub mod conversion;
+pub mod agenda;
 pub mod date_time_service;
@@ -0,0 +1,165 @@
+// Evelyn: Your personal assistant, project manager and calendar
+// Copyright (C) 2017 Gregory Jensen
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+// Notifications are sent to users through each of the configured channels,
+// the in-app inbox always being one of them.
+
+use core::date_time_service as dts;
+use core::error_messages::EvelynCoreError;
+use core::user;
+use data::notification as notification_data;
+use model;
+use model::notification as notification_model;
+use mongodb::Client;
+use processing::ProcessorData;
+use std::sync::Arc;
+use uuid::Uuid;
+
+pub trait NotificationChannel: Send + Sync {
+    // Used when logging delivery failures.
+    fn name(&self) -> &'static str;
+
+    fn deliver(
+        &self,
+        client: &Client,
+        notification: &notification_model::NotificationModel,
+    ) -> Option<EvelynCoreError>;
+}
+
+// Keeps notifications so that they can be looked up through the API.
+pub struct InboxChannel;
+
+impl NotificationChannel for InboxChannel {
+    fn name(&self) -> &'static str {
+        "inbox"
+    }
+
+    fn deliver(
+        &self,
+        client: &Client,
+        notification: &notification_model::NotificationModel,
+    ) -> Option<EvelynCoreError> {
+        match notification_data::insert_notification(client, notification) {
+            None => None,
+            Some(e) => Some(EvelynCoreError::FailedToDeliverNotification(e)),
+        }
+    }
+}
+
+pub struct Notifier {
+    channels: Vec<Box<NotificationChannel>>,
+}
+
+impl Notifier {
+    pub fn new(channels: Vec<Box<NotificationChannel>>) -> Self {
+        Notifier {
+            channels: channels,
+        }
+    }
+
+    // A channel which fails is logged and doesn't stop delivery through the
+    // others.
+    pub fn notify(
+        &self,
+        client: &Client,
+        user_id: &String,
+        kind: notification_model::NotificationKindModel,
+        title: String,
+        body: String,
+    ) {
+        let notification = notification_model::NotificationModel {
+            notification_id: format!("{}", Uuid::new_v4()),
+            user_id: user_id.clone(),
+            kind: kind,
+            title: title,
+            body: body,
+            date_created: dts::get_timestamp(),
+            read: false,
+        };
+
+        for channel in &self.channels {
+            if let Some(e) = channel.deliver(client, &notification) {
+                error!("Failed to deliver notification [{}] through {}: {}", notification.notification_id, channel.name(), e);
+            }
+        }
+    }
+}
+
+pub fn all_kinds() -> Vec<notification_model::NotificationKindModel> {
+    vec![
+        notification_model::NotificationKindModel::Reminder,
+        notification_model::NotificationKindModel::TaskAssigned,
+        notification_model::NotificationKindModel::AddedToUserGroup,
+        notification_model::NotificationKindModel::RemovedFromUserGroup,
+    ]
+}
+
+pub fn to_kind_model(kind: notification_model::NotificationKindExternalModel) -> notification_model::NotificationKindModel {
+    match kind {
+        notification_model::NotificationKindExternalModel::Reminder => notification_model::NotificationKindModel::Reminder,
+        notification_model::NotificationKindExternalModel::TaskAssigned => notification_model::NotificationKindModel::TaskAssigned,
+        notification_model::NotificationKindExternalModel::AddedToUserGroup => notification_model::NotificationKindModel::AddedToUserGroup,
+        notification_model::NotificationKindExternalModel::RemovedFromUserGroup => notification_model::NotificationKindModel::RemovedFromUserGroup,
+    }
+}
+
+pub fn to_kind_external_model(kind: notification_model::NotificationKindModel) -> notification_model::NotificationKindExternalModel {
+    match kind {
+        notification_model::NotificationKindModel::Reminder => notification_model::NotificationKindExternalModel::Reminder,
+        notification_model::NotificationKindModel::TaskAssigned => notification_model::NotificationKindExternalModel::TaskAssigned,
+        notification_model::NotificationKindModel::AddedToUserGroup => notification_model::NotificationKindExternalModel::AddedToUserGroup,
+        notification_model::NotificationKindModel::RemovedFromUserGroup => notification_model::NotificationKindExternalModel::RemovedFromUserGroup,
+    }
+}
+
+pub fn lookup_notifications(
+    model: notification_model::LookupNotificationsRequestModel,
+    session_token_model: model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Result<notification_model::LookupNotificationsResponseModel, EvelynCoreError> {
+    let ds = processor_data.data_store.clone();
+    let zone = user::lookup_time_zone(&ds, &session_token_model.user_id);
+
+    match notification_data::lookup_notifications(&ds, &session_token_model.user_id, model.unread_only) {
+        Ok(notifications) => {
+            Ok(notification_model::LookupNotificationsResponseModel {
+                notifications: notifications.into_iter().map(|x| notification_model::NotificationExternalModel {
+                    notification_id: x.notification_id,
+                    kind: to_kind_external_model(x.kind),
+                    title: x.title,
+                    body: x.body,
+                    date_created: dts::timestamp_to_string(x.date_created, &zone),
+                    read: x.read,
+                }).collect(),
+                error: None,
+            })
+        },
+        Err(e) => Err(EvelynCoreError::FailedToLookupNotifications(e)),
+    }
+}
+
+pub fn mark_read(
+    model: notification_model::MarkNotificationsReadRequestModel,
+    session_token_model: model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Option<EvelynCoreError> {
+    let ds = processor_data.data_store.clone();
+
+    match notification_data::mark_read(&ds, &session_token_model.user_id, &model.notification_ids) {
+        None => None,
+        Some(e) => Some(EvelynCoreError::FailedToMarkNotificationsRead(e)),
+    }
+}
@@ -0,0 +1,445 @@
+// Evelyn: Your personal assistant, project manager and calendar
+// Copyright (C) 2017 Gregory Jensen
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+
+// Recurrence rules in the RFC 5545 RRULE format, for example
+// "FREQ=WEEKLY;INTERVAL=2;BYDAY=MO,WE;COUNT=10". Only the parts of the format
+// which Evelyn supports are accepted, anything else is rejected rather than
+// being silently ignored.
+
+use chrono::prelude::*;
+use chrono::Duration;
+use chrono_tz::Tz;
+use core::date_time_service as dts;
+use core::error_messages::{EvelynBaseError, EvelynCoreError};
+
+// Stops a rule which rarely or never produces an occurrence from looping
+// forever.
+const MAX_PERIODS: i64 = 100000;
+
+const MAX_INTERVAL: i64 = 1000;
+
+// Limits the number of occurrences a single expansion can return.
+pub const MAX_OCCURRENCES: usize = 1000;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct WeekdayRule {
+    // Only used by monthly rules, 1 is the first such weekday of the month
+    // and -1 the last.
+    pub ordinal: Option<i32>,
+    pub weekday: Weekday,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct RecurrenceRule {
+    pub frequency: Frequency,
+    pub interval: i64,
+    pub by_day: Vec<WeekdayRule>,
+    pub count: Option<i64>,
+    pub until: Option<i64>,
+}
+
+fn invalid_rule(reason: &str) -> EvelynCoreError {
+    debug!("Invalid recurrence rule: {}", reason);
+    EvelynCoreError::InvalidRecurrenceRule(EvelynBaseError::NothingElse)
+}
+
+fn parse_frequency(value: &str) -> Result<Frequency, EvelynCoreError> {
+    match value {
+        "DAILY" => Ok(Frequency::Daily),
+        "WEEKLY" => Ok(Frequency::Weekly),
+        "MONTHLY" => Ok(Frequency::Monthly),
+        "YEARLY" => Ok(Frequency::Yearly),
+        _ => Err(invalid_rule("unsupported frequency")),
+    }
+}
+
+fn parse_positive(value: &str) -> Result<i64, EvelynCoreError> {
+    match value.parse::<i64>() {
+        Ok(number) if number > 0 => Ok(number),
+        _ => Err(invalid_rule("expected a positive number")),
+    }
+}
+
+fn parse_weekday(value: &str) -> Result<Weekday, EvelynCoreError> {
+    match value {
+        "MO" => Ok(Weekday::Mon),
+        "TU" => Ok(Weekday::Tue),
+        "WE" => Ok(Weekday::Wed),
+        "TH" => Ok(Weekday::Thu),
+        "FR" => Ok(Weekday::Fri),
+        "SA" => Ok(Weekday::Sat),
+        "SU" => Ok(Weekday::Sun),
+        _ => Err(invalid_rule("unknown weekday")),
+    }
+}
+
+fn parse_by_day(value: &str) -> Result<Vec<WeekdayRule>, EvelynCoreError> {
+    let mut by_day = Vec::new();
+
+    for part in value.split(',') {
+        if part.len() < 2 {
+            return Err(invalid_rule("unknown weekday"));
+        }
+
+        let (ordinal, weekday) = part.split_at(part.len() - 2);
+        let ordinal = if ordinal.is_empty() {
+            None
+        } else {
+            match ordinal.parse::<i32>() {
+                Ok(ordinal) if ordinal != 0 && ordinal.abs() <= 5 => Some(ordinal),
+                _ => return Err(invalid_rule("weekday ordinals must be between -5 and 5")),
+            }
+        };
+
+        match parse_weekday(weekday) {
+            Ok(weekday) => by_day.push(WeekdayRule {
+                ordinal: ordinal,
+                weekday: weekday,
+            }),
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(by_day)
+}
+
+// UNTIL is either a UTC date time or a date, a date includes the whole day.
+fn parse_until(value: &str) -> Result<i64, EvelynCoreError> {
+    if let Ok(date_time) = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%SZ") {
+        return Ok(date_time.timestamp());
+    }
+
+    match NaiveDate::parse_from_str(value, "%Y%m%d") {
+        Ok(date) => Ok(date.and_hms(23, 59, 59).timestamp()),
+        Err(_) => Err(invalid_rule("UNTIL must be a UTC date time or a date")),
+    }
+}
+
+pub fn parse(rule: &str) -> Result<RecurrenceRule, EvelynCoreError> {
+    let rule = rule.trim();
+    let rule = if rule.starts_with("RRULE:") {
+        &rule[6..]
+    } else {
+        rule
+    };
+
+    let mut frequency = None;
+    let mut interval = 1;
+    let mut by_day = Vec::new();
+    let mut count = None;
+    let mut until = None;
+
+    for part in rule.split(';').filter(|x| !x.is_empty()) {
+        let mut key_value = part.splitn(2, '=');
+        let key = key_value.next().unwrap_or("");
+        let value = match key_value.next() {
+            Some(value) => value,
+            None => return Err(invalid_rule("expected KEY=VALUE")),
+        };
+
+        let parsed = match key {
+            "FREQ" => parse_frequency(value).map(|x| frequency = Some(x)),
+            "INTERVAL" => parse_positive(value).map(|x| interval = x),
+            "BYDAY" => parse_by_day(value).map(|x| by_day = x),
+            "COUNT" => parse_positive(value).map(|x| count = Some(x)),
+            "UNTIL" => parse_until(value).map(|x| until = Some(x)),
+            // Weeks always start on a Monday.
+            "WKST" if value == "MO" => Ok(()),
+            _ => Err(invalid_rule("unsupported rule part")),
+        };
+
+        if let Err(e) = parsed {
+            return Err(e);
+        }
+    }
+
+    let frequency = match frequency {
+        Some(frequency) => frequency,
+        None => return Err(invalid_rule("FREQ is required")),
+    };
+
+    if interval > MAX_INTERVAL {
+        return Err(invalid_rule("INTERVAL is too large"));
+    }
+
+    if count.is_some() && until.is_some() {
+        return Err(invalid_rule("COUNT and UNTIL can't both be used"));
+    }
+
+    if frequency == Frequency::Yearly && !by_day.is_empty() {
+        return Err(invalid_rule("BYDAY is not supported for yearly rules"));
+    }
+
+    if frequency != Frequency::Monthly && by_day.iter().any(|x| x.ordinal.is_some()) {
+        return Err(invalid_rule("weekday ordinals are only supported for monthly rules"));
+    }
+
+    Ok(RecurrenceRule {
+        frequency: frequency,
+        interval: interval,
+        by_day: by_day,
+        count: count,
+        until: until,
+    })
+}
+
+fn add_months(
+    year: i32,
+    month: u32,
+    months: i64,
+) -> Option<(i32, u32)> {
+    let total = year as i64 * 12 + (month as i64 - 1) + months;
+    let year = total / 12;
+
+    if year > i32::max_value() as i64 {
+        None
+    } else {
+        Some((year as i32, (total % 12) as u32 + 1))
+    }
+}
+
+fn days_in_month(
+    year: i32,
+    month: u32,
+) -> Vec<NaiveDate> {
+    (1..32).filter_map(|day| NaiveDate::from_ymd_opt(year, month, day)).collect()
+}
+
+fn matches_weekday(
+    by_day: &Vec<WeekdayRule>,
+    date: &NaiveDate,
+) -> bool {
+    by_day.is_empty() || by_day.iter().any(|x| x.weekday == date.weekday())
+}
+
+// The candidate dates in the period which is `offset` periods after the one
+// containing the series start. None once the dates can no longer be
+// represented.
+fn period_dates(
+    rule: &RecurrenceRule,
+    start: &NaiveDate,
+    offset: i64,
+) -> Option<Vec<NaiveDate>> {
+    let mut dates = match rule.frequency {
+        Frequency::Daily => {
+            let date = match start.checked_add_signed(Duration::days(offset)) {
+                Some(date) => date,
+                None => return None,
+            };
+
+            if matches_weekday(&rule.by_day, &date) {
+                vec![date]
+            } else {
+                Vec::new()
+            }
+        },
+        Frequency::Weekly => {
+            let week_offset = Duration::days(start.weekday().num_days_from_monday() as i64);
+            let week_start = match Duration::weeks(offset).checked_sub(&week_offset).and_then(|x| start.checked_add_signed(x)) {
+                Some(week_start) => week_start,
+                None => return None,
+            };
+
+            let mut weekdays: Vec<Weekday> = rule.by_day.iter().map(|x| x.weekday).collect();
+            if weekdays.is_empty() {
+                weekdays.push(start.weekday());
+            }
+
+            weekdays.iter()
+                .filter_map(|x| week_start.checked_add_signed(Duration::days(x.num_days_from_monday() as i64)))
+                .collect()
+        },
+        Frequency::Monthly => {
+            let (year, month) = match add_months(start.year(), start.month(), offset) {
+                Some(year_month) => year_month,
+                None => return None,
+            };
+
+            if rule.by_day.is_empty() {
+                // Months without the start's day of the month are skipped.
+                NaiveDate::from_ymd_opt(year, month, start.day()).into_iter().collect()
+            } else {
+                let month_days = days_in_month(year, month);
+
+                let mut dates = Vec::new();
+                for weekday_rule in &rule.by_day {
+                    let matching: Vec<NaiveDate> = month_days.iter()
+                        .filter(|x| x.weekday() == weekday_rule.weekday)
+                        .cloned()
+                        .collect();
+
+                    match weekday_rule.ordinal {
+                        None => dates.extend(matching),
+                        Some(ordinal) if ordinal > 0 => dates.extend(matching.get(ordinal as usize - 1).cloned()),
+                        Some(ordinal) => {
+                            let from_end = (-ordinal) as usize;
+                            if from_end <= matching.len() {
+                                dates.push(matching[matching.len() - from_end]);
+                            }
+                        },
+                    }
+                }
+                dates
+            }
+        },
+        Frequency::Yearly => {
+            if offset > i32::max_value() as i64 - start.year() as i64 {
+                return None;
+            }
+
+            // As with monthly rules, the 29th of February only occurs on leap
+            // years.
+            NaiveDate::from_ymd_opt(start.year() + offset as i32, start.month(), start.day()).into_iter().collect()
+        },
+    };
+
+    dates.sort();
+    dates.dedup();
+    Some(dates)
+}
+
+// Visits the start of each occurrence in order until the visitor returns
+// false or the rule runs out. A series start which doesn't match the rule is
+// not itself an occurrence. Dates are worked out in the given zone so that
+// occurrences keep their local time across daylight saving changes.
+fn each_occurrence<F>(
+    rule: &RecurrenceRule,
+    series_begin: i64,
+    zone: &Tz,
+    mut visit: F,
+) where
+    F: FnMut(i64) -> bool,
+{
+    let start = dts::timestamp_to_local(series_begin, zone);
+    let mut produced = 0;
+
+    for period in 0..MAX_PERIODS {
+        let dates = match period_dates(rule, &start.date(), period * rule.interval) {
+            Some(dates) => dates,
+            None => return,
+        };
+
+        for date in dates {
+            let occurrence = dts::local_to_timestamp_lenient(&date.and_time(start.time()), zone);
+            if occurrence < series_begin {
+                continue;
+            }
+
+            if rule.until.map_or(false, |until| occurrence > until) || rule.count.map_or(false, |count| produced >= count) {
+                return;
+            }
+
+            produced += 1;
+            if !visit(occurrence) {
+                return;
+            }
+        }
+    }
+}
+
+// The starts of the occurrences which overlap the range, including those
+// which touch either end of it.
+pub fn occurrences_between(
+    rule: &RecurrenceRule,
+    series_begin: i64,
+    duration: i64,
+    range_begin: i64,
+    range_end: i64,
+    zone: &Tz,
+) -> Vec<i64> {
+    let mut occurrences = Vec::new();
+
+    each_occurrence(rule, series_begin, zone, |occurrence| {
+        if occurrence > range_end {
+            return false;
+        }
+
+        if occurrence + duration >= range_begin {
+            occurrences.push(occurrence);
+        }
+
+        occurrences.len() < MAX_OCCURRENCES
+    });
+
+    occurrences
+}
+
+pub fn is_occurrence(
+    rule: &RecurrenceRule,
+    series_begin: i64,
+    timestamp: i64,
+    zone: &Tz,
+) -> bool {
+    let mut found = false;
+
+    each_occurrence(rule, series_begin, zone, |occurrence| {
+        found = occurrence == timestamp;
+        occurrence < timestamp
+    });
+
+    found
+}
+
+// The first occurrence which begins after the given time, None once the
+// series has ended.
+pub fn next_occurrence_after(
+    rule: &RecurrenceRule,
+    series_begin: i64,
+    after: i64,
+    zone: &Tz,
+) -> Option<i64> {
+    let mut next = None;
+
+    each_occurrence(rule, series_begin, zone, |occurrence| {
+        if occurrence > after {
+            next = Some(occurrence);
+            false
+        } else {
+            true
+        }
+    });
+
+    next
+}
+
+// The end of the last occurrence, or None if the series never ends.
+pub fn series_end(
+    rule: &RecurrenceRule,
+    series_begin: i64,
+    duration: i64,
+    zone: &Tz,
+) -> Option<i64> {
+    if rule.count.is_none() && rule.until.is_none() {
+        return None;
+    }
+
+    let mut last = series_begin;
+    each_occurrence(rule, series_begin, zone, |occurrence| {
+        last = occurrence;
+        true
+    });
+
+    Some(last + duration)
+}
@@ -0,0 +1,356 @@
+// Evelyn: Your personal assistant, project manager and calendar
+// Copyright (C) 2017 Gregory Jensen
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+// Reminders fire a set number of minutes before a calendar event, a simple
+// task's due date or the start or end of a sprint. The time is looked up from
+// the item each time the reminder fires so that changes to the item are
+// followed, and a recurring event is reminded about at each occurrence.
+
+use core::agile::project;
+use core::calendar;
+use core::date_time_service as dts;
+use core::error_messages::{EvelynBaseError, EvelynCoreError, EvelynDatabaseError};
+use core::notification::Notifier;
+use core::user;
+use data;
+use data::reminder as reminder_data;
+use model;
+use model::notification as notification_model;
+use model::reminder as reminder_model;
+use mongodb::Client;
+use processing::ProcessorData;
+use std::cmp;
+use std::sync::Arc;
+use uuid::Uuid;
+
+const SECONDS_PER_MINUTE: i64 = 60;
+
+fn to_source_model(source: reminder_model::ReminderSourceExternalModel) -> reminder_model::ReminderSourceModel {
+    match source {
+        reminder_model::ReminderSourceExternalModel::CalendarEvent => reminder_model::ReminderSourceModel::CalendarEvent,
+        reminder_model::ReminderSourceExternalModel::SimpleTask => reminder_model::ReminderSourceModel::SimpleTask,
+        reminder_model::ReminderSourceExternalModel::SprintStart => reminder_model::ReminderSourceModel::SprintStart,
+        reminder_model::ReminderSourceExternalModel::SprintEnd => reminder_model::ReminderSourceModel::SprintEnd,
+    }
+}
+
+fn to_source_external_model(source: reminder_model::ReminderSourceModel) -> reminder_model::ReminderSourceExternalModel {
+    match source {
+        reminder_model::ReminderSourceModel::CalendarEvent => reminder_model::ReminderSourceExternalModel::CalendarEvent,
+        reminder_model::ReminderSourceModel::SimpleTask => reminder_model::ReminderSourceExternalModel::SimpleTask,
+        reminder_model::ReminderSourceModel::SprintStart => reminder_model::ReminderSourceExternalModel::SprintStart,
+        reminder_model::ReminderSourceModel::SprintEnd => reminder_model::ReminderSourceExternalModel::SprintEnd,
+    }
+}
+
+fn is_source_not_found(e: &EvelynDatabaseError) -> bool {
+    match *e {
+        EvelynDatabaseError::CalendarEventNotFound(_) |
+        EvelynDatabaseError::SimpleTaskNotFound(_) |
+        EvelynDatabaseError::AgileSprintNotFound(_) => true,
+        _ => false,
+    }
+}
+
+fn time_if_after(time: i64, after: i64) -> Option<i64> {
+    if time >= after {
+        Some(time)
+    } else {
+        None
+    }
+}
+
+// The next time at or after the given one that the item begins or is due,
+// along with its title. None if it won't again, such as once a task is
+// completed.
+fn next_source_time(
+    client: &Client,
+    user_id: &String,
+    source: &reminder_model::ReminderSourceModel,
+    source_id: &String,
+    after: i64,
+) -> Result<Option<(i64, String)>, EvelynDatabaseError> {
+    match *source {
+        reminder_model::ReminderSourceModel::CalendarEvent => {
+            data::calendar::lookup_attended_event(client, user_id, source_id)
+                .map(|event| calendar::next_occurrence_begin(&event, after).map(|x| (x, event.title)))
+        },
+        reminder_model::ReminderSourceModel::SimpleTask => {
+            data::simple_task::lookup_simple_task(client, user_id, source_id).map(|task| {
+                if task.completed {
+                    None
+                } else {
+                    task.due_date
+                        .and_then(|x| time_if_after(x, after))
+                        .map(|x| (x, task.title))
+                }
+            })
+        },
+        reminder_model::ReminderSourceModel::SprintStart => {
+            data::agile::sprint::lookup_sprint(client, source_id)
+                .map(|sprint| time_if_after(sprint.start_date, after).map(|x| (x, sprint.title)))
+        },
+        reminder_model::ReminderSourceModel::SprintEnd => {
+            data::agile::sprint::lookup_sprint(client, source_id)
+                .map(|sprint| time_if_after(sprint.end_date, after).map(|x| (x, sprint.title)))
+        },
+    }
+}
+
+fn fire_at(
+    source_time: Option<(i64, String)>,
+    minutes_before: i64,
+) -> (Option<i64>, Option<i64>) {
+    match source_time {
+        Some((time, _)) => (Some(time), Some(time - minutes_before * SECONDS_PER_MINUTE)),
+        None => (None, None),
+    }
+}
+
+fn describe(
+    source: &reminder_model::ReminderSourceModel,
+    title: &String,
+    time: String,
+) -> String {
+    match *source {
+        reminder_model::ReminderSourceModel::CalendarEvent => format!("{} begins at {}", title, time),
+        reminder_model::ReminderSourceModel::SimpleTask => format!("{} is due at {}", title, time),
+        reminder_model::ReminderSourceModel::SprintStart => format!("Sprint {} starts at {}", title, time),
+        reminder_model::ReminderSourceModel::SprintEnd => format!("Sprint {} ends at {}", title, time),
+    }
+}
+
+// Sprints belong to projects rather than users, so the user must contribute
+// to the sprint's project.
+fn check_sprint_access(
+    source_id: &String,
+    session_token_model: &model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Option<EvelynCoreError> {
+    let sprint = match data::agile::sprint::lookup_sprint(&processor_data.data_store, source_id) {
+        Ok(sprint) => sprint,
+        Err(EvelynDatabaseError::AgileSprintNotFound(e)) => return Some(EvelynCoreError::ReminderSourceNotFound(e)),
+        Err(e) => return Some(EvelynCoreError::FailedToAddReminder(e)),
+    };
+
+    let project_session_token_model = model::SessionTokenModel {
+        user_id: session_token_model.user_id.clone(),
+        server_session_token: session_token_model.server_session_token.clone(),
+    };
+
+    match project::lookup_contributing_to(project_session_token_model, processor_data) {
+        Ok(result) if result.projects.iter().any(|x| x.project_id == sprint.project_id) => None,
+        Ok(_) => Some(EvelynCoreError::ReminderSourceNotFound(EvelynBaseError::NothingElse)),
+        Err(e) => Some(e),
+    }
+}
+
+pub fn add_reminder(
+    model: reminder_model::AddReminderRequestModel,
+    session_token_model: model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Result<reminder_model::AddReminderResponseModel, EvelynCoreError> {
+    if model.minutes_before < 0 {
+        return Err(EvelynCoreError::InvalidReminder(EvelynBaseError::NothingElse));
+    }
+
+    let source = to_source_model(model.source);
+
+    match source {
+        reminder_model::ReminderSourceModel::SprintStart | reminder_model::ReminderSourceModel::SprintEnd => {
+            if let Some(e) = check_sprint_access(&model.source_id, &session_token_model, processor_data.clone()) {
+                return Err(e);
+            }
+        },
+        _ => {},
+    }
+
+    let ds = processor_data.data_store.clone();
+    let now = dts::get_timestamp();
+
+    // Items which are already in the past are accepted, the reminder fires
+    // if they are moved into the future.
+    let source_time = match next_source_time(&ds, &session_token_model.user_id, &source, &model.source_id, now) {
+        Ok(source_time) => source_time,
+        Err(ref e) if is_source_not_found(e) => return Err(EvelynCoreError::ReminderSourceNotFound(EvelynBaseError::NothingElse)),
+        Err(e) => return Err(EvelynCoreError::FailedToAddReminder(e)),
+    };
+    let (source_time, next_fire_at) = fire_at(source_time, model.minutes_before);
+
+    let reminder_model = reminder_model::ReminderModel {
+        reminder_id: format!("{}", Uuid::new_v4()),
+        user_id: session_token_model.user_id,
+        source: source,
+        source_id: model.source_id,
+        minutes_before: model.minutes_before,
+        source_time: source_time,
+        next_fire_at: next_fire_at,
+        date_created: now,
+    };
+
+    match reminder_data::insert_reminder(&ds, &reminder_model) {
+        None => {
+            Ok(reminder_model::AddReminderResponseModel {
+                reminder_id: Some(reminder_model.reminder_id),
+                error: None,
+            })
+        },
+        Some(e) => Err(EvelynCoreError::FailedToAddReminder(e)),
+    }
+}
+
+pub fn lookup_reminders(
+    session_token_model: model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Result<reminder_model::LookupRemindersResponseModel, EvelynCoreError> {
+    let ds = processor_data.data_store.clone();
+    let zone = user::lookup_time_zone(&ds, &session_token_model.user_id);
+
+    match reminder_data::lookup_reminders(&ds, &session_token_model.user_id) {
+        Ok(reminders) => {
+            Ok(reminder_model::LookupRemindersResponseModel {
+                reminders: reminders.into_iter().map(|x| reminder_model::ReminderExternalModel {
+                    reminder_id: x.reminder_id,
+                    source: to_source_external_model(x.source),
+                    source_id: x.source_id,
+                    minutes_before: x.minutes_before,
+                    next_fire_at: x.next_fire_at.map(|x| dts::timestamp_to_string(x, &zone)),
+                }).collect(),
+                error: None,
+            })
+        },
+        Err(e) => Err(EvelynCoreError::FailedToLookupReminders(e)),
+    }
+}
+
+pub fn remove_reminder(
+    model: reminder_model::RemoveReminderRequestModel,
+    session_token_model: model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Option<EvelynCoreError> {
+    let ds = processor_data.data_store.clone();
+
+    match reminder_data::remove_reminder(&ds, &session_token_model.user_id, &model.reminder_id) {
+        None => None,
+        Some(EvelynDatabaseError::ReminderNotFound(e)) => Some(EvelynCoreError::ReminderNotFound(e)),
+        Some(e) => Some(EvelynCoreError::FailedToRemoveReminder(e)),
+    }
+}
+
+// Works out when the reminders for an item should next fire after the item
+// has been changed. Failures are only logged, the reminders catch up with
+// the change when they next fire.
+pub fn source_changed(
+    client: &Client,
+    source: reminder_model::ReminderSourceModel,
+    source_id: &String,
+) {
+    let reminders = match reminder_data::lookup_reminders_for_source(client, &source, source_id) {
+        Ok(reminders) => reminders,
+        Err(e) => {
+            warn!("Failed to reschedule reminders for [{}]: {}", source_id, e);
+            return;
+        },
+    };
+
+    let now = dts::get_timestamp();
+
+    for reminder in reminders {
+        let (source_time, next_fire_at) = match next_source_time(client, &reminder.user_id, &source, source_id, now) {
+            Ok(source_time) => fire_at(source_time, reminder.minutes_before),
+            Err(_) => (None, None),
+        };
+
+        if let Err(e) = reminder_data::reschedule(client, &reminder.reminder_id, reminder.next_fire_at, source_time, next_fire_at) {
+            warn!("Failed to reschedule reminder [{}]: {}", reminder.reminder_id, e);
+        }
+    }
+}
+
+fn fire(
+    client: &Client,
+    notifier: &Notifier,
+    reminder: reminder_model::ReminderModel,
+    now: i64,
+) -> Option<EvelynDatabaseError> {
+    let ref user_id = reminder.user_id;
+    let armed_time = reminder.source_time.unwrap_or(now);
+
+    // The item may have changed since the reminder was scheduled, in which
+    // case the reminder is moved to the new time instead of firing.
+    let current = match next_source_time(client, user_id, &reminder.source, &reminder.source_id, armed_time) {
+        Ok(current) => current,
+        Err(ref e) if is_source_not_found(e) => {
+            info!("Removing reminder [{}] for an item which no longer exists", reminder.reminder_id);
+            return reminder_data::delete_reminder(client, &reminder.reminder_id);
+        },
+        Err(e) => return Some(e),
+    };
+
+    let fired = match current {
+        Some((time, title)) => if Some(time) == reminder.source_time { Some((time, title)) } else { None },
+        None => None,
+    };
+
+    // Occurrences which were missed while the server was down are skipped,
+    // only the one being fired is delivered late.
+    let after = match fired {
+        Some((time, _)) => cmp::max(time + 1, now),
+        None => now,
+    };
+
+    let (source_time, next_fire_at) = match next_source_time(client, user_id, &reminder.source, &reminder.source_id, after) {
+        Ok(next) => fire_at(next, reminder.minutes_before),
+        Err(e) => return Some(e),
+    };
+
+    match reminder_data::reschedule(client, &reminder.reminder_id, reminder.next_fire_at, source_time, next_fire_at) {
+        Ok(true) => {
+            if let Some((time, title)) = fired {
+                let zone = user::lookup_time_zone(client, user_id);
+                let body = describe(&reminder.source, &title, dts::timestamp_to_string(time, &zone));
+                notifier.notify(client, user_id, notification_model::NotificationKindModel::Reminder, format!("Reminder: {}", title), body);
+            }
+            None
+        },
+        // Another server has already fired it.
+        Ok(false) => None,
+        Err(e) => Some(e),
+    }
+}
+
+// Run by the scheduler.
+pub fn fire_due(
+    client: &Client,
+    notifier: &Notifier,
+) -> Option<EvelynCoreError> {
+    let now = dts::get_timestamp();
+
+    let reminders = match reminder_data::lookup_due(client, now) {
+        Ok(reminders) => reminders,
+        Err(e) => return Some(EvelynCoreError::FailedToFireReminders(e)),
+    };
+
+    for reminder in reminders {
+        let reminder_id = reminder.reminder_id.clone();
+
+        if let Some(e) = fire(client, notifier, reminder, now) {
+            error!("Failed to fire reminder [{}]: {}", reminder_id, e);
+        }
+    }
+
+    None
+}
@@ -0,0 +1,109 @@
+// Evelyn: Your personal assistant, project manager and calendar
+// Copyright (C) 2017 Gregory Jensen
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+// Runs jobs in the background of the server process. The jobs of a scheduler
+// share a single thread so that they never overlap, each runs once at start up
+// and then again every interval. Jobs which wait on other servers are given a
+// scheduler of their own so that a slow server can't hold up the rest.
+
+use core::date_time_service as dts;
+use mongodb::Client;
+use std::panic;
+use std::thread;
+use std::time::Duration;
+
+// How often the scheduler checks whether a job is due.
+const TICK_SECONDS: u64 = 1;
+
+const FIRST_RETRY_DELAY_SECONDS: i64 = 60;
+const MAX_RETRY_DELAY_SECONDS: i64 = 60 * 60;
+
+pub type Job = Box<Fn(&Client) + Send>;
+
+// How long a job should wait before trying again after the given number of
+// failed attempts at sending something. The delay doubles each time up to an
+// hour.
+pub fn retry_delay(attempts: i32) -> i64 {
+    let mut delay = FIRST_RETRY_DELAY_SECONDS;
+    for _ in 1..attempts {
+        delay *= 2;
+        if delay >= MAX_RETRY_DELAY_SECONDS {
+            return MAX_RETRY_DELAY_SECONDS;
+        }
+    }
+    delay
+}
+
+struct ScheduledJob {
+    name: &'static str,
+    interval_seconds: i64,
+    next_run: i64,
+    job: Job,
+}
+
+pub struct Scheduler {
+    client: Client,
+    jobs: Vec<ScheduledJob>,
+}
+
+impl Scheduler {
+    pub fn new(client: Client) -> Self {
+        Scheduler {
+            client: client,
+            jobs: Vec::new(),
+        }
+    }
+
+    pub fn add_job(
+        &mut self,
+        name: &'static str,
+        interval_seconds: i64,
+        job: Job,
+    ) {
+        self.jobs.push(ScheduledJob {
+            name: name,
+            interval_seconds: interval_seconds,
+            next_run: 0,
+            job: job,
+        });
+    }
+
+    pub fn start(mut self) {
+        thread::spawn(move || {
+            loop {
+                let now = dts::get_timestamp();
+
+                for scheduled in self.jobs.iter_mut() {
+                    if scheduled.next_run > now {
+                        continue;
+                    }
+
+                    // A job which panics is tried again at its next interval
+                    // rather than stopping every other job.
+                    let client = &self.client;
+                    let job = &*scheduled.job;
+                    if panic::catch_unwind(panic::AssertUnwindSafe(|| job(client))).is_err() {
+                        error!("Scheduled job {} panicked", scheduled.name);
+                    }
+
+                    scheduled.next_run = dts::get_timestamp() + scheduled.interval_seconds;
+                }
+
+                thread::sleep(Duration::from_secs(TICK_SECONDS));
+            }
+        });
+    }
+}
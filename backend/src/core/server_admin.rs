@@ -14,11 +14,20 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use core::error_messages::EvelynCoreError;
+use core::date_time_service;
+use core::error_messages::{EvelynCoreError, EvelynDatabaseError};
 use data;
+use data::server_admin::{Scope, ScopeAction};
+use model;
+use model::server_admin as server_admin_model;
+use mongodb::Client;
 use processing::ProcessorData;
 use std::sync::Arc;
 
+// Purge requests are not tied to a user yet so deletions made by a purge are
+// recorded against the server.
+const SERVER_USER_ID: &'static str = "server";
+
 pub fn purge_database(processor_data: Arc<ProcessorData>) -> Option<EvelynCoreError> {
     let ds = processor_data.data_store.clone();
 
@@ -34,8 +43,106 @@ pub fn purge_database_area(
 ) -> Option<EvelynCoreError> {
     let ds = processor_data.data_store.clone();
 
-    match data::server_admin::purge_collection(target, &ds) {
+    let deletion = model::trash::DeletionModel {
+        deleted_by_user_id: String::from(SERVER_USER_ID),
+        date_deleted: date_time_service::get_timestamp(),
+    };
+
+    match data::server_admin::purge_collection(target, &deletion, &ds) {
         None => None,
         Some(error) => Some(EvelynCoreError::FailedToPurgeDatabaseArea(error)),
     }
 }
+
+// All scopes are counted before anything is changed so that a dry run reports
+// exactly what a real run would start from.
+fn run_scopes(
+    ds: &Client,
+    scopes: Vec<Scope>,
+    dry_run: bool,
+) -> Result<Vec<server_admin_model::AffectedDataExternalModel>, EvelynDatabaseError> {
+    let mut affected = Vec::new();
+
+    for scope in &scopes {
+        let count = match data::server_admin::count_scope(ds, scope) {
+            Ok(count) => count,
+            Err(e) => return Err(e),
+        };
+
+        affected.push(server_admin_model::AffectedDataExternalModel {
+            collection: scope.collection.to_owned(),
+            action: match scope.action {
+                ScopeAction::Delete => server_admin_model::ScopeActionExternalModel::Delete,
+                ScopeAction::Detach(_) => server_admin_model::ScopeActionExternalModel::Detach,
+            },
+            count: count,
+        });
+    }
+
+    if dry_run {
+        return Ok(affected);
+    }
+
+    for (scope, affected_data) in scopes.iter().zip(affected.iter_mut()) {
+        match data::server_admin::apply_scope(ds, scope) {
+            Ok(count) => affected_data.count = count,
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(affected)
+}
+
+pub fn delete_user(
+    request_model: server_admin_model::DeleteUserRequestModel,
+    session_token_model: model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Result<server_admin_model::DeleteUserResponseModel, EvelynCoreError> {
+    let ds = processor_data.data_store.clone();
+
+    let scopes = data::server_admin::build_user_scopes(&session_token_model.user_id);
+
+    match run_scopes(&ds, scopes, request_model.dry_run) {
+        Ok(affected) => {
+            if !request_model.dry_run {
+                info!("Deleted user [{}] and their data", session_token_model.user_id);
+            }
+
+            Ok(server_admin_model::DeleteUserResponseModel {
+                affected: affected,
+                error: None,
+            })
+        },
+        Err(e) => Err(EvelynCoreError::FailedToDeleteUserData(e)),
+    }
+}
+
+pub fn delete_project(
+    request_model: server_admin_model::DeleteProjectRequestModel,
+    session_token_model: model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Result<server_admin_model::DeleteProjectResponseModel, EvelynCoreError> {
+    let ds = processor_data.data_store.clone();
+
+    match data::agile::project::lookup_created_by(&ds, &request_model.project_id, &session_token_model.user_id) {
+        Ok(_) => (),
+        Err(EvelynDatabaseError::AgileProjectNotFound(e)) => return Err(EvelynCoreError::NotPermittedToDeleteAgileProject(e)),
+        Err(e) => return Err(EvelynCoreError::FailedToDeleteAgileProject(e)),
+    }
+
+    let scopes = data::server_admin::build_project_scopes(&request_model.project_id);
+
+    match run_scopes(&ds, scopes, request_model.dry_run) {
+        Ok(affected) => {
+            if !request_model.dry_run {
+                info!("User [{}] deleted project [{}]", session_token_model.user_id, request_model.project_id);
+            }
+
+            Ok(server_admin_model::DeleteProjectResponseModel {
+                affected: affected,
+                error: None,
+            })
+        },
+        Err(e) => Err(EvelynCoreError::FailedToDeleteAgileProject(e)),
+    }
+}
@@ -15,15 +15,375 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use chrono::prelude::*;
-
-use core::error_messages::EvelynCoreError;
+use chrono::Duration;
+use chrono_tz::Tz;
+use core::calendar;
+use core::conversion;
+use core::date_time_service;
+use core::error_messages::{EvelynBaseError, EvelynCoreError, EvelynDatabaseError};
+use core::recurrence;
+use core::reminder;
+use core::user;
 use data;
 use model;
+use mongodb::Client;
 use processing::ProcessorData;
-use std::cmp::Ordering;
+use std::cmp;
 use std::sync::Arc;
 use uuid::Uuid;
 
+// Limits how far up the tree of parent tasks is followed.
+const MAX_TASK_DEPTH: usize = 32;
+
+// Due dates are stored as timestamps with the zone they were given in kept
+// alongside them. Returns the due date, whether it was a date on its own and
+// the zone. An empty due date means the task doesn't have one.
+pub fn normalise_due_date(
+    due_date: &str,
+    time_zone: Option<String>,
+    user_id: &String,
+    ds: &Client,
+) -> Result<(Option<i64>, bool, Option<String>), EvelynCoreError> {
+    let time_zone = time_zone.or_else(|| user::lookup_preferred_time_zone(ds, user_id));
+
+    let zone = match date_time_service::parse_optional_time_zone(&time_zone) {
+        Ok(zone) => zone,
+        Err(e) => return Err(e),
+    };
+
+    let due_date = due_date.trim();
+    if due_date.is_empty() {
+        return Ok((None, false, time_zone));
+    }
+
+    if let Some(date) = date_time_service::parse_date(due_date) {
+        return match date.succ_opt() {
+            Some(next_day) => Ok((Some(date_time_service::start_of_day(&next_day, &zone) - 1), true, time_zone)),
+            None => Err(EvelynCoreError::InvalidDateTime(EvelynBaseError::NothingElse)),
+        };
+    }
+
+    match date_time_service::parse_timestamp(due_date, &zone) {
+        Ok(timestamp) => Ok((Some(timestamp), false, time_zone)),
+        Err(e) => Err(e),
+    }
+}
+
+// The day a task given a date on its own is due, in the zone it was given in.
+pub fn due_day(task: &model::simple_task::SimpleTaskModel) -> Option<NaiveDate> {
+    if !task.due_date_only {
+        return None;
+    }
+
+    let zone = date_time_service::parse_optional_time_zone(&task.time_zone).unwrap_or(date_time_service::UTC);
+    task.due_date.map(|x| date_time_service::timestamp_to_local(x, &zone).date())
+}
+
+fn render_due_date(task: &model::simple_task::SimpleTaskModel, zone: &Tz) -> Option<String> {
+    match due_day(task) {
+        Some(day) => Some(date_time_service::date_to_string(&day)),
+        None => task.due_date.map(|x| date_time_service::timestamp_to_string(x, zone)),
+    }
+}
+
+fn priority_to_rank(priority: &model::simple_task::SimpleTaskPriorityExternalModel) -> i32 {
+    match *priority {
+        model::simple_task::SimpleTaskPriorityExternalModel::Low => model::simple_task::PRIORITY_LOW,
+        model::simple_task::SimpleTaskPriorityExternalModel::Normal => model::simple_task::PRIORITY_NORMAL,
+        model::simple_task::SimpleTaskPriorityExternalModel::High => model::simple_task::PRIORITY_HIGH,
+        model::simple_task::SimpleTaskPriorityExternalModel::Urgent => model::simple_task::PRIORITY_URGENT,
+    }
+}
+
+fn rank_to_priority(rank: i32) -> model::simple_task::SimpleTaskPriorityExternalModel {
+    match rank {
+        model::simple_task::PRIORITY_LOW => model::simple_task::SimpleTaskPriorityExternalModel::Low,
+        model::simple_task::PRIORITY_HIGH => model::simple_task::SimpleTaskPriorityExternalModel::High,
+        model::simple_task::PRIORITY_URGENT => model::simple_task::SimpleTaskPriorityExternalModel::Urgent,
+        _ => model::simple_task::SimpleTaskPriorityExternalModel::Normal,
+    }
+}
+
+fn to_sort_model(sort_by: model::simple_task::SimpleTaskSortExternalModel) -> model::simple_task::SimpleTaskSortModel {
+    match sort_by {
+        model::simple_task::SimpleTaskSortExternalModel::DueDate => model::simple_task::SimpleTaskSortModel::DueDate,
+        model::simple_task::SimpleTaskSortExternalModel::Priority => model::simple_task::SimpleTaskSortModel::Priority,
+        model::simple_task::SimpleTaskSortExternalModel::Title => model::simple_task::SimpleTaskSortModel::Title,
+    }
+}
+
+// Tags are trimmed and kept once each, in the order they were given.
+fn normalise_tags(tags: Vec<String>) -> Vec<String> {
+    let mut normalised: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = String::from(tag.trim());
+        if !tag.is_empty() && !normalised.contains(&tag) {
+            normalised.push(tag);
+        }
+    }
+    normalised
+}
+
+fn parse_date_filter(date: Option<String>, zone: &Tz) -> Result<Option<i64>, EvelynCoreError> {
+    match date {
+        Some(ref date) if !date.is_empty() => date_time_service::parse_timestamp(date, zone).map(Some),
+        _ => Ok(None),
+    }
+}
+
+// The due dates a view covers, from the start of the current day in the
+// user's time zone.
+fn view_range(view: &model::simple_task::SimpleTaskViewExternalModel, zone: &Tz) -> (Option<i64>, Option<i64>) {
+    let now = date_time_service::get_timestamp();
+    let today = date_time_service::timestamp_to_local(now, zone).date();
+    let start_of_today = date_time_service::start_of_day(&today, zone);
+
+    match *view {
+        model::simple_task::SimpleTaskViewExternalModel::Overdue => (None, Some(now)),
+        model::simple_task::SimpleTaskViewExternalModel::DueToday => {
+            (Some(start_of_today), Some(date_time_service::start_of_day(&(today + Duration::days(1)), zone)))
+        },
+        model::simple_task::SimpleTaskViewExternalModel::DueThisWeek => {
+            let days_left = 7 - today.weekday().num_days_from_monday() as i64;
+            (Some(start_of_today), Some(date_time_service::start_of_day(&(today + Duration::days(days_left)), zone)))
+        },
+    }
+}
+
+// An empty rule means the task doesn't recur.
+fn parse_recurrence(recurrence: Option<String>) -> Result<Option<String>, EvelynCoreError> {
+    match recurrence {
+        Some(ref rule) if !rule.trim().is_empty() => recurrence::parse(rule.trim()).map(|_| Some(String::from(rule.trim()))),
+        _ => Ok(None),
+    }
+}
+
+// The completed task is kept as the history of its occurrence and the next
+// occurrence is added as a new task. Returns None once the series has ended.
+fn add_next_occurrence(
+    ds: &Client,
+    task: model::simple_task::SimpleTaskModel,
+) -> Result<Option<String>, EvelynCoreError> {
+    // An occurrence which is completed again after being reopened already
+    // has its next one.
+    if let Some(next_task_id) = task.next_task_id {
+        return Ok(Some(next_task_id));
+    }
+
+    let (rule, due_date) = match (task.recurrence.as_ref().and_then(|x| recurrence::parse(x).ok()), task.due_date) {
+        (Some(rule), Some(due_date)) => (rule, due_date),
+        _ => return Ok(None),
+    };
+
+    let completed_task_id = task.task_id.clone();
+
+    let zone = date_time_service::parse_optional_time_zone(&task.time_zone).unwrap_or(date_time_service::UTC);
+    let series_begin = task.series_begin.unwrap_or(due_date);
+
+    let next_due_date = match recurrence::next_occurrence_after(&rule, series_begin, due_date, &zone) {
+        Some(x) => x,
+        None => return Ok(None),
+    };
+
+    let task_id = format!("{}", Uuid::new_v4());
+
+    let next_task = model::simple_task::SimpleTaskModel {
+        user_id: task.user_id,
+        task_id: task_id.clone(),
+        title: task.title,
+        description: task.description,
+        due_date: Some(next_due_date),
+        due_date_only: task.due_date_only,
+        time_zone: task.time_zone,
+        completed: false,
+        priority: task.priority,
+        tags: task.tags,
+        recurrence: task.recurrence,
+        series_id: Some(task.series_id.unwrap_or(task.task_id)),
+        series_begin: Some(series_begin),
+        date_completed: None,
+        checklist: task.checklist.into_iter().map(|x| model::todo_list::item::TodoListItemModel {
+            item_id: x.item_id,
+            text: x.text,
+            is_done: false,
+            done_by_user_id: None,
+            date_done: None,
+            converted_to: None,
+        }).collect(),
+        parent_task_id: task.parent_task_id,
+        complete_with_children: task.complete_with_children,
+        origin: None,
+        converted_to: None,
+        next_task_id: None,
+        version: data::version::INITIAL_VERSION,
+        deleted: None,
+    };
+
+    if let Some(e) = data::simple_task::insert_simple_task(ds, &next_task) {
+        return Err(EvelynCoreError::FailedToCreateSimpleTask(e));
+    }
+
+    match data::simple_task::set_next_task_id(ds, &next_task.user_id, &completed_task_id, &task_id) {
+        None => Ok(Some(task_id)),
+        Some(e) => Err(EvelynCoreError::FailedToUpdateSimpleTask(e)),
+    }
+}
+
+fn to_checklist_model(checklist: Vec<model::todo_list::item::TodoListItemExternalModel>) -> Vec<model::todo_list::item::TodoListItemModel> {
+    checklist.into_iter().map(|x| model::todo_list::item::TodoListItemModel {
+        item_id: format!("{}", Uuid::new_v4()),
+        text: x.text,
+        is_done: x.is_done,
+        done_by_user_id: None,
+        date_done: None,
+        converted_to: None,
+    }).collect()
+}
+
+// The parent has to exist, and following the parents up from it mustn't lead
+// back to the task or the tasks would form a loop.
+fn check_parent(
+    ds: &Client,
+    user_id: &String,
+    task_id: Option<&String>,
+    parent_task_id: &String,
+) -> Option<EvelynCoreError> {
+    let mut next = Some(parent_task_id.clone());
+
+    for depth in 0..MAX_TASK_DEPTH {
+        let current = match next {
+            Some(x) => x,
+            None => return None,
+        };
+
+        if task_id.map_or(false, |x| *x == current) {
+            return Some(EvelynCoreError::InvalidParentSimpleTask(EvelynBaseError::NothingElse));
+        }
+
+        match data::simple_task::lookup_simple_task(ds, user_id, &current) {
+            Ok(task) => next = task.parent_task_id,
+            // Tasks further up may have been removed, which ends the tree.
+            Err(EvelynDatabaseError::SimpleTaskNotFound(_)) if depth > 0 => return None,
+            Err(EvelynDatabaseError::SimpleTaskNotFound(e)) => return Some(EvelynCoreError::InvalidParentSimpleTask(e)),
+            Err(e) => return Some(EvelynCoreError::FailedToLookupSimpleTask(e)),
+        }
+    }
+
+    Some(EvelynCoreError::InvalidParentSimpleTask(EvelynBaseError::NothingElse))
+}
+
+// Completes the parents which complete with their children once all of
+// their children are complete, working up the tree.
+pub fn complete_parents(
+    ds: &Client,
+    user_id: &String,
+    parent_task_id: Option<String>,
+) -> Option<EvelynCoreError> {
+    let mut next = parent_task_id;
+
+    for _ in 0..MAX_TASK_DEPTH {
+        let parent_task_id = match next {
+            Some(x) => x,
+            None => return None,
+        };
+
+        let parent = match data::simple_task::lookup_simple_task(ds, user_id, &parent_task_id) {
+            Ok(parent) => parent,
+            Err(EvelynDatabaseError::SimpleTaskNotFound(_)) => return None,
+            Err(e) => return Some(EvelynCoreError::FailedToUpdateSimpleTask(e)),
+        };
+
+        if parent.completed || !parent.complete_with_children {
+            return None;
+        }
+
+        match data::simple_task::lookup_children(ds, user_id, vec![parent_task_id.clone()]) {
+            Ok(children) => {
+                if children.iter().any(|x| !x.completed) {
+                    return None;
+                }
+            },
+            Err(e) => return Some(EvelynCoreError::FailedToUpdateSimpleTask(e)),
+        }
+
+        // If the parent was changed in the meantime it is left as it is.
+        match data::simple_task::complete_simple_task(ds, user_id, &parent_task_id, parent.version, date_time_service::get_timestamp()) {
+            Ok(true) => {},
+            Ok(false) => return None,
+            Err(e) => return Some(EvelynCoreError::FailedToUpdateSimpleTask(e)),
+        }
+
+        reminder::source_changed(ds, model::reminder::ReminderSourceModel::SimpleTask, &parent_task_id);
+
+        next = parent.parent_task_id.clone();
+
+        if parent.recurrence.is_some() {
+            if let Err(e) = add_next_occurrence(ds, parent) {
+                return Some(e);
+            }
+        }
+    }
+
+    None
+}
+
+fn narrowest(a: Option<i64>, b: Option<i64>, pick: fn(i64, i64) -> i64) -> Option<i64> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(pick(a, b)),
+        (a, b) => a.or(b),
+    }
+}
+
+fn to_external_model(
+    task: model::simple_task::SimpleTaskModel,
+    children: &Vec<model::simple_task::SimpleTaskModel>,
+    zone: &Tz,
+) -> model::simple_task::SimpleTaskExternalModel {
+    let due_date = render_due_date(&task, zone);
+
+    let progress = {
+        let task_children: Vec<&model::simple_task::SimpleTaskModel> = children.iter()
+            .filter(|x| x.parent_task_id.as_ref() == Some(&task.task_id))
+            .collect();
+
+        model::simple_task::SimpleTaskProgressExternalModel {
+            checklist_items_done: task.checklist.iter().filter(|x| x.is_done).count() as u32,
+            checklist_items: task.checklist.len() as u32,
+            children_done: task_children.iter().filter(|x| x.completed).count() as u32,
+            children: task_children.len() as u32,
+        }
+    };
+
+    model::simple_task::SimpleTaskExternalModel {
+        task_id: task.task_id,
+        title: task.title,
+        description: task.description,
+        due_date: due_date,
+        due_date_only: task.due_date_only,
+        time_zone: task.time_zone,
+        completed: task.completed,
+        priority: rank_to_priority(task.priority),
+        tags: task.tags,
+        recurrence: task.recurrence,
+        series_id: task.series_id,
+        date_completed: task.date_completed.map(|x| date_time_service::timestamp_to_string(x, zone)),
+        checklist: task.checklist.into_iter().map(|x| model::todo_list::item::TodoListItemExternalModel {
+            item_id: x.item_id,
+            text: x.text,
+            is_done: x.is_done,
+            done_by_user_id: x.done_by_user_id,
+            date_done: x.date_done.map(|x| date_time_service::timestamp_to_string(x, zone)),
+            converted_to: x.converted_to.map(conversion::to_link_external_model),
+        }).collect(),
+        parent_task_id: task.parent_task_id,
+        complete_with_children: task.complete_with_children,
+        progress: progress,
+        origin: task.origin.map(conversion::to_link_external_model),
+        converted_to: task.converted_to.map(conversion::to_link_external_model),
+        version: task.version,
+    }
+}
+
 pub fn create_simple_task(
     model: model::simple_task::CreateSimpleTaskRequestModel,
     session_token_model: model::SessionTokenModel,
@@ -31,17 +391,57 @@ pub fn create_simple_task(
 ) -> Result<model::simple_task::CreateSimpleTaskResponseModel, EvelynCoreError> {
     let task_id = Uuid::new_v4();
 
+    let ds = processor_data.data_store.clone();
+
+    let (due_date, due_date_only, time_zone) = match normalise_due_date(model.due_date.as_ref().map_or("", |x| x.as_str()), model.time_zone, &session_token_model.user_id, &ds) {
+        Ok(x) => x,
+        Err(e) => return Err(e),
+    };
+
+    let recurrence = match parse_recurrence(model.recurrence) {
+        Ok(x) => x,
+        Err(e) => return Err(e),
+    };
+
+    if recurrence.is_some() && due_date.is_none() {
+        return Err(EvelynCoreError::RecurringSimpleTaskWithoutDueDate(EvelynBaseError::NothingElse));
+    }
+
+    let parent_task_id = model.parent_task_id.and_then(|x| if x.is_empty() { None } else { Some(x) });
+    if let Some(ref parent_task_id) = parent_task_id {
+        if let Some(e) = check_parent(&ds, &session_token_model.user_id, None, parent_task_id) {
+            return Err(e);
+        }
+    }
+
+    let series_id = recurrence.as_ref().map(|_| format!("{}", task_id));
+    let series_begin = recurrence.as_ref().and(due_date);
+
     let simple_task_model = model::simple_task::SimpleTaskModel {
         user_id: session_token_model.user_id,
         task_id: format!("{}", task_id),
         title: model.title,
         description: model.description,
-        due_date: model.due_date,
+        due_date: due_date,
+        due_date_only: due_date_only,
+        time_zone: time_zone,
         completed: false,
+        priority: model.priority.as_ref().map_or(model::simple_task::PRIORITY_NORMAL, priority_to_rank),
+        tags: normalise_tags(model.tags),
+        recurrence: recurrence,
+        series_id: series_id,
+        series_begin: series_begin,
+        date_completed: None,
+        checklist: to_checklist_model(model.checklist),
+        parent_task_id: parent_task_id,
+        complete_with_children: model.complete_with_children,
+        origin: None,
+        converted_to: None,
+        next_task_id: None,
+        version: data::version::INITIAL_VERSION,
+        deleted: None,
     };
 
-    let ds = processor_data.data_store.clone();
-
     match data::simple_task::insert_simple_task(&ds, &simple_task_model) {
         Some(e) => Err(EvelynCoreError::FailedToCreateSimpleTask(e)),
         None => {
@@ -58,60 +458,99 @@ pub fn lookup_simple_tasks(
     session_token_model: model::SessionTokenModel,
     processor_data: Arc<ProcessorData>,
 ) -> Result<model::simple_task::LookupSimpleTaskResponseModel, EvelynCoreError> {
+    let ds = processor_data.data_store.clone();
+    let zone = user::lookup_time_zone(&ds, &session_token_model.user_id);
+
+    let mut due_after = match parse_date_filter(model.due_after, &zone) {
+        Ok(x) => x,
+        Err(e) => return Err(e),
+    };
+    let mut due_before = match parse_date_filter(model.due_before, &zone) {
+        Ok(x) => x,
+        Err(e) => return Err(e),
+    };
+
+    let mut show_completed = model.show_completed;
+    if let Some(ref view) = model.view {
+        let (view_after, view_before) = view_range(view, &zone);
+        due_after = narrowest(due_after, view_after, cmp::max);
+        due_before = narrowest(due_before, view_before, cmp::min);
+
+        if let model::simple_task::SimpleTaskViewExternalModel::Overdue = *view {
+            show_completed = false;
+        }
+    }
+
+    let user_id = session_token_model.user_id.clone();
+
     let simple_task_lookup_model = model::simple_task::SimpleTaskLookupModel {
         user_id: session_token_model.user_id,
         limit: model.limit,
-        show_completed: model.show_completed,
+        show_completed: show_completed,
+        tag: model.tag.map(|x| String::from(x.trim())).and_then(|x| if x.is_empty() { None } else { Some(x) }),
+        priority: model.priority.as_ref().map(priority_to_rank),
+        due_after: due_after,
+        due_before: due_before,
+        series_id: model.series_id,
+        parent_task_id: model.parent_task_id,
+        search: model.search.and_then(|x| if x.trim().is_empty() { None } else { Some(x) }),
+        sort_by: to_sort_model(model.sort_by),
+        sort_descending: model.sort_descending,
+        cursor: model.cursor,
     };
 
-    let ds = processor_data.data_store.clone();
-
     match data::simple_task::lookup_simple_tasks(&ds, &simple_task_lookup_model) {
-        Ok(mut tasks) => {
-            tasks.sort_by(|a, b| {
-                let a_date = a.due_date.parse::<DateTime<Utc>>();
-                let b_date = b.due_date.parse::<DateTime<Utc>>();
-
-                // TODO unsafe
-                if a_date.unwrap().eq(&b_date.unwrap()) {
-                    if a.title < b.title {
-                        Ordering::Less
-                    } else {
-                        Ordering::Greater
-                    }
-                } else if a_date.unwrap().lt(&b_date.unwrap()) {
-                    Ordering::Less
-                } else {
-                    Ordering::Greater
-                }
-            });
-
-            let mut filtered_tasks: Vec<model::simple_task::SimpleTaskModel> = Vec::new();
-            for x in tasks {
-                if simple_task_lookup_model.show_completed {
-                    filtered_tasks.push(x);
-                } else if !simple_task_lookup_model.show_completed && !x.completed {
-                    filtered_tasks.push(x);
-                }
-            }
+        Ok((tasks, more)) => {
+            let next_cursor = if more { tasks.last().map(|x| x.task_id.clone()) } else { None };
 
-            if simple_task_lookup_model.limit > 0 {
-                filtered_tasks.truncate(simple_task_lookup_model.limit as usize);
-            }
+            let children = match data::simple_task::lookup_children(&ds, &user_id, tasks.iter().map(|x| x.task_id.clone()).collect()) {
+                Ok(children) => children,
+                Err(e) => return Err(EvelynCoreError::FailedToLookupSimpleTask(e)),
+            };
 
             Ok(model::simple_task::LookupSimpleTaskResponseModel {
-                   simple_tasks: filtered_tasks.into_iter().map(|x| {
-                       model::simple_task::SimpleTaskExternalModel {
-                           task_id: x.task_id,
-                           title: x.title,
-                           description: x.description,
-                           due_date: x.due_date,
-                           completed: x.completed,
-                       }
-                   }).collect(),
+                   simple_tasks: tasks.into_iter().map(|x| to_external_model(x, &children, &zone)).collect(),
+                   next_cursor: next_cursor,
                    error: None,
             })
         },
+        Err(EvelynDatabaseError::SimpleTaskCursorNotFound(e)) => Err(EvelynCoreError::InvalidSimpleTaskCursor(e)),
+        Err(e) => Err(EvelynCoreError::FailedToLookupSimpleTask(e)),
+    }
+}
+
+// The incomplete tasks due before the given time, however overdue they are.
+pub fn lookup_due_before(
+    ds: &Client,
+    user_id: &String,
+    due_before: i64,
+    zone: &Tz,
+) -> Result<Vec<model::simple_task::SimpleTaskExternalModel>, EvelynCoreError> {
+    let simple_task_lookup_model = model::simple_task::SimpleTaskLookupModel {
+        user_id: user_id.clone(),
+        limit: 0,
+        show_completed: false,
+        tag: None,
+        priority: None,
+        due_after: None,
+        due_before: Some(due_before),
+        series_id: None,
+        parent_task_id: None,
+        search: None,
+        sort_by: model::simple_task::SimpleTaskSortModel::DueDate,
+        sort_descending: false,
+        cursor: None,
+    };
+
+    match data::simple_task::lookup_simple_tasks(ds, &simple_task_lookup_model) {
+        Ok((tasks, _)) => {
+            let children = match data::simple_task::lookup_children(ds, user_id, tasks.iter().map(|x| x.task_id.clone()).collect()) {
+                Ok(children) => children,
+                Err(e) => return Err(EvelynCoreError::FailedToLookupSimpleTask(e)),
+            };
+
+            Ok(tasks.into_iter().map(|x| to_external_model(x, &children, zone)).collect())
+        },
         Err(e) => Err(EvelynCoreError::FailedToLookupSimpleTask(e)),
     }
 }
@@ -120,32 +559,198 @@ pub fn update_simple_task(
     model: model::simple_task::UpdateSimpleTaskRequestModel,
     session_token_model: model::SessionTokenModel,
     processor_data: Arc<ProcessorData>,
-) -> Option<EvelynCoreError> {
+) -> Result<model::simple_task::UpdateSimpleTaskResponseModel, EvelynCoreError> {
+    let ds = processor_data.data_store.clone();
+
+    let (due_date, due_date_only, time_zone) = match model.new_due_date {
+        Some(ref new_due_date) => match normalise_due_date(new_due_date, model.new_time_zone, &session_token_model.user_id, &ds) {
+            Ok((due_date, due_date_only, time_zone)) => (Some(due_date), Some(due_date_only), time_zone),
+            Err(e) => return Err(e),
+        },
+        None => match date_time_service::parse_optional_time_zone(&model.new_time_zone) {
+            Ok(_) => (None, None, model.new_time_zone),
+            Err(e) => return Err(e),
+        },
+    };
+
+    let recurrence = if model.clear_recurrence {
+        Some(None)
+    } else if model.new_recurrence.is_some() {
+        match parse_recurrence(model.new_recurrence) {
+            Ok(x) => Some(x),
+            Err(e) => return Err(e),
+        }
+    } else {
+        None
+    };
+
+    let task = match data::simple_task::lookup_simple_task(&ds, &session_token_model.user_id, &model.task_id) {
+        Ok(task) => task,
+        Err(EvelynDatabaseError::SimpleTaskNotFound(e)) => return Err(EvelynCoreError::SimpleTaskNotFound(e)),
+        Err(e) => return Err(EvelynCoreError::FailedToUpdateSimpleTask(e)),
+    };
+
+    let parent_task_id = match model.new_parent_task_id {
+        Some(ref x) if x.is_empty() => Some(None),
+        Some(ref x) => match check_parent(&ds, &session_token_model.user_id, Some(&model.task_id), x) {
+            None => Some(Some(x.clone())),
+            Some(e) => return Err(e),
+        },
+        None => None,
+    };
+    let new_parent_task_id = match parent_task_id {
+        Some(ref x) => x.clone(),
+        None => task.parent_task_id.clone(),
+    };
+
+    let recurring = match recurrence {
+        Some(ref x) => x.is_some(),
+        None => task.recurrence.is_some(),
+    };
+    let new_due_date = due_date.unwrap_or(task.due_date);
+
+    if recurring && new_due_date.is_none() {
+        return Err(EvelynCoreError::RecurringSimpleTaskWithoutDueDate(EvelynBaseError::NothingElse));
+    }
+
+    // The occurrences are counted again from the due date when either the
+    // rule or the due date changes.
+    let restart_series = recurring && (recurrence.is_some() || due_date.is_some());
+
+    let completing = model.new_completed == Some(true) && !task.completed;
+    let date_completed = match model.new_completed {
+        Some(true) if !task.completed => Some(Some(date_time_service::get_timestamp())),
+        Some(false) => Some(None),
+        _ => None,
+    };
+
+    // Pinning the update to the version which was read stops two requests
+    // which complete the same occurrence from both adding the next one.
+    let expected_version = if completing && recurring {
+        model.expected_version.or(Some(task.version))
+    } else {
+        model.expected_version
+    };
+
+    let task_id = model.task_id.clone();
+    let user_id = session_token_model.user_id.clone();
+    let reschedule = due_date.is_some() || model.new_completed.is_some();
+
     let simple_task_update_model = model::simple_task::SimpleTaskUpdateModel {
         user_id: session_token_model.user_id,
         task_id: model.task_id,
         title: model.new_title,
         description: model.new_description,
-        due_date: model.new_due_date,
+        due_date: due_date,
+        due_date_only: due_date_only,
+        time_zone: time_zone,
         completed: model.new_completed,
+        priority: model.new_priority.as_ref().map(priority_to_rank),
+        tags: model.new_tags.map(normalise_tags),
+        recurrence: recurrence,
+        series_id: if restart_series { Some(task.series_id.unwrap_or(task.task_id)) } else { None },
+        series_begin: if restart_series { new_due_date } else { None },
+        date_completed: date_completed,
+        checklist: model.new_checklist.map(to_checklist_model),
+        parent_task_id: parent_task_id,
+        complete_with_children: model.new_complete_with_children,
+        expected_version: expected_version,
+    };
+
+    match data::simple_task::update_simple_task(&ds, simple_task_update_model) {
+        None => {},
+        Some(EvelynDatabaseError::SimpleTaskNotFound(e)) => return Err(EvelynCoreError::SimpleTaskNotFound(e)),
+        Some(EvelynDatabaseError::SimpleTaskVersionConflict(e)) => return Err(EvelynCoreError::SimpleTaskVersionConflict(e)),
+        Some(e) => return Err(EvelynCoreError::FailedToUpdateSimpleTask(e)),
+    }
+
+    if reschedule {
+        reminder::source_changed(&ds, model::reminder::ReminderSourceModel::SimpleTask, &task_id);
+    }
+
+    let next_task_id = if completing && recurring {
+        match data::simple_task::lookup_simple_task(&ds, &user_id, &task_id) {
+            Ok(completed_task) => match add_next_occurrence(&ds, completed_task) {
+                Ok(x) => x,
+                Err(e) => return Err(e),
+            },
+            Err(e) => return Err(EvelynCoreError::FailedToUpdateSimpleTask(e)),
+        }
+    } else {
+        None
     };
 
+    if completing {
+        if let Some(e) = complete_parents(&ds, &user_id, new_parent_task_id) {
+            return Err(e);
+        }
+    }
+
+    Ok(model::simple_task::UpdateSimpleTaskResponseModel {
+        error: None,
+        next_task_id: next_task_id,
+    })
+}
+
+pub fn update_checklist_item(
+    model: model::simple_task::UpdateSimpleTaskChecklistItemRequestModel,
+    session_token_model: model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Option<EvelynCoreError> {
     let ds = processor_data.data_store.clone();
 
-    match data::simple_task::update_simple_task(&ds, simple_task_update_model) {
+    let update_model = model::simple_task::SimpleTaskChecklistItemUpdateModel {
+        user_id: session_token_model.user_id,
+        task_id: model.task_id,
+        item_index: model.item_index,
+        is_done: model.is_done,
+        expected_version: model.expected_version,
+    };
+
+    match data::simple_task::update_checklist_item(&ds, &update_model) {
         None => None,
-        Some(e) => Some(EvelynCoreError::FailedToUpdateSimpleTask(e)),
+        Some(EvelynDatabaseError::SimpleTaskNotFound(e)) => Some(EvelynCoreError::SimpleTaskNotFound(e)),
+        Some(EvelynDatabaseError::SimpleTaskVersionConflict(e)) => Some(EvelynCoreError::SimpleTaskVersionConflict(e)),
+        Some(e) => Some(EvelynCoreError::FailedToUpdateSimpleTaskChecklist(e)),
     }
 }
 
 pub fn remove(
     model: model::simple_task::RemoveSimpleTaskRequestModel,
+    session_token_model: model::SessionTokenModel,
     processor_data: Arc<ProcessorData>,
 ) -> Option<EvelynCoreError> {
     let ds = processor_data.data_store.clone();
 
-    match data::simple_task::remove(&ds, model.task_id) {
+    let deletion = model::trash::DeletionModel {
+        deleted_by_user_id: session_token_model.user_id.clone(),
+        date_deleted: date_time_service::get_timestamp(),
+    };
+
+    match data::simple_task::remove(&ds, &session_token_model.user_id, model.task_id, &deletion) {
         None => None,
+        Some(EvelynDatabaseError::SimpleTaskNotFound(e)) => Some(EvelynCoreError::SimpleTaskNotFound(e)),
         Some(e) => Some(EvelynCoreError::FailedToRemoveSimpleTask(e)),
     }
 }
+
+pub fn migrate_simple_tasks(client: &Client) -> Option<EvelynCoreError> {
+    match data::simple_task::migrate_string_due_dates(client, calendar::legacy_string_to_timestamp) {
+        Ok(migrated) => {
+            if migrated > 0 {
+                info!("Converted the due dates of {} simple task(s) to timestamps", migrated);
+            }
+        },
+        Err(e) => return Some(EvelynCoreError::FailedToMigrateSimpleTasks(e)),
+    }
+
+    match data::simple_task::migrate_defaults(client) {
+        Ok(migrated) => {
+            if migrated > 0 {
+                info!("Set the default priority and tags on {} simple task field(s)", migrated);
+            }
+            None
+        },
+        Err(e) => Some(EvelynCoreError::FailedToMigrateSimpleTasks(e)),
+    }
+}
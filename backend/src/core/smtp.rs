@@ -0,0 +1,256 @@
+// Evelyn: Your personal assistant, project manager and calendar
+// Copyright (C) 2017 Gregory Jensen
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+// A small SMTP client for handing mail to a relay. There is no TLS support so
+// the relay should be local, or on a trusted network, and pass mail on from
+// there. Credentials are sent with AUTH PLAIN for relays which need them.
+
+use std::fmt;
+use std::io;
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+const TIMEOUT_SECONDS: u64 = 30;
+
+// Encoded body lines are wrapped at this length, as RFC 2045 asks.
+const BASE64_LINE_LENGTH: usize = 76;
+
+const BASE64_ALPHABET: &'static [u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    // Empty if the relay doesn't need authentication.
+    pub user_name: String,
+    pub password: String,
+}
+
+pub struct EmailMessage {
+    pub from: String,
+    pub to: String,
+    pub subject: String,
+    pub text_body: String,
+    pub html_body: String,
+    pub message_id: String,
+    // RFC 2822 date.
+    pub date: String,
+}
+
+#[derive(Debug)]
+pub enum SmtpError {
+    Io(io::Error),
+    // The relay replied with something other than what was expected.
+    Rejected(String),
+    // An address which could break out of the command or header it is in.
+    InvalidAddress(String),
+}
+
+impl fmt::Display for SmtpError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SmtpError::Io(ref e) => write!(f, "SMTP connection failed: {}", e),
+            SmtpError::Rejected(ref reply) => write!(f, "SMTP relay rejected the mail: {}", reply),
+            SmtpError::InvalidAddress(ref address) => write!(f, "Will not send mail to or from {:?}", address),
+        }
+    }
+}
+
+// Addresses go into the envelope commands and the headers as they are, so
+// they must not hold line breaks or the angle brackets around them.
+pub fn is_safe_address(address: &str) -> bool {
+    !address.is_empty() && !address.contains(|x| x == '\r' || x == '\n' || x == '<' || x == '>')
+}
+
+pub fn base64_encode(input: &[u8]) -> String {
+    let mut encoded = String::new();
+
+    for chunk in input.chunks(3) {
+        let b0 = chunk[0] as usize;
+        let b1 = if chunk.len() > 1 { chunk[1] as usize } else { 0 };
+        let b2 = if chunk.len() > 2 { chunk[2] as usize } else { 0 };
+
+        encoded.push(BASE64_ALPHABET[b0 >> 2] as char);
+        encoded.push(BASE64_ALPHABET[((b0 & 0x03) << 4) | (b1 >> 4)] as char);
+        encoded.push(if chunk.len() > 1 { BASE64_ALPHABET[((b1 & 0x0f) << 2) | (b2 >> 6)] as char } else { '=' });
+        encoded.push(if chunk.len() > 2 { BASE64_ALPHABET[b2 & 0x3f] as char } else { '=' });
+    }
+
+    encoded
+}
+
+fn wrap_lines(value: &str, length: usize) -> String {
+    let lines: Vec<&str> = value.as_bytes()
+        .chunks(length)
+        .map(|x| ::std::str::from_utf8(x).unwrap_or(""))
+        .collect();
+
+    lines.join("\r\n")
+}
+
+// Headers are ASCII, anything else is sent as an RFC 2047 encoded word.
+fn encode_header(value: &str) -> String {
+    if value.chars().all(|x| (x as u32) < 128 && !x.is_control()) {
+        String::from(value)
+    } else {
+        format!("=?UTF-8?B?{}?=", base64_encode(value.as_bytes()))
+    }
+}
+
+fn encode_part(content_type: &str, body: &str) -> String {
+    format!("Content-Type: {}; charset=utf-8\r\nContent-Transfer-Encoding: base64\r\n\r\n{}\r\n",
+            content_type,
+            wrap_lines(&base64_encode(body.as_bytes()), BASE64_LINE_LENGTH))
+}
+
+// The message with plain text and HTML alternatives. Lines beginning with a
+// dot are escaped so they can't end the DATA command early.
+pub fn format_message(message: &EmailMessage) -> String {
+    let boundary: String = format!("evelyn-{}", message.message_id).chars()
+        .map(|x| if x.is_alphanumeric() && (x as u32) < 128 { x } else { '-' })
+        .collect();
+
+    let document = format!(
+        "From: {}\r\nTo: {}\r\nSubject: {}\r\nDate: {}\r\nMessage-ID: <{}>\r\nMIME-Version: 1.0\r\n\
+         Content-Type: multipart/alternative; boundary=\"{}\"\r\n\r\n\
+         --{}\r\n{}--{}\r\n{}--{}--\r\n",
+        message.from,
+        message.to,
+        encode_header(&message.subject),
+        message.date,
+        message.message_id,
+        boundary,
+        boundary,
+        encode_part("text/plain", &message.text_body),
+        boundary,
+        encode_part("text/html", &message.html_body),
+        boundary);
+
+    document.split("\r\n")
+        .map(|x| if x.starts_with('.') { format!(".{}", x) } else { String::from(x) })
+        .collect::<Vec<String>>()
+        .join("\r\n")
+}
+
+struct Connection {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Connection {
+    // Reads a reply, which may be spread over several lines, and checks that
+    // it is in the same class as the expected code.
+    fn expect_reply(&mut self, expected: u16) -> Result<(), SmtpError> {
+        loop {
+            let mut line = String::new();
+            match self.reader.read_line(&mut line) {
+                Ok(0) => return Err(SmtpError::Rejected(String::from("connection closed"))),
+                Ok(_) => {},
+                Err(e) => return Err(SmtpError::Io(e)),
+            }
+
+            let line = line.trim_right();
+            let code = match line.chars().take(3).collect::<String>().parse::<u16>() {
+                Ok(code) => code,
+                Err(_) => return Err(SmtpError::Rejected(String::from(line))),
+            };
+
+            // A dash after the code means more lines follow.
+            if line.chars().nth(3) == Some('-') {
+                continue;
+            }
+
+            if code / 100 != expected / 100 {
+                return Err(SmtpError::Rejected(String::from(line)));
+            }
+
+            return Ok(());
+        }
+    }
+
+    fn command(&mut self, command: &str, expected: u16) -> Result<(), SmtpError> {
+        if let Err(e) = write!(self.writer, "{}\r\n", command) {
+            return Err(SmtpError::Io(e));
+        }
+
+        self.expect_reply(expected)
+    }
+}
+
+fn connect(config: &SmtpConfig) -> Result<Connection, SmtpError> {
+    let stream = match TcpStream::connect((config.host.as_str(), config.port)) {
+        Ok(stream) => stream,
+        Err(e) => return Err(SmtpError::Io(e)),
+    };
+
+    let timeout = Some(Duration::from_secs(TIMEOUT_SECONDS));
+    let configured = stream.set_read_timeout(timeout)
+        .and_then(|_| stream.set_write_timeout(timeout))
+        .and_then(|_| stream.try_clone());
+
+    match configured {
+        Ok(writer) => Ok(Connection {
+            reader: BufReader::new(stream),
+            writer: writer,
+        }),
+        Err(e) => Err(SmtpError::Io(e)),
+    }
+}
+
+pub fn send(
+    config: &SmtpConfig,
+    message: &EmailMessage,
+) -> Result<(), SmtpError> {
+    for address in &[&message.from, &message.to] {
+        if !is_safe_address(address) {
+            return Err(SmtpError::InvalidAddress(address.to_string()));
+        }
+    }
+
+    let mut connection = match connect(config) {
+        Ok(connection) => connection,
+        Err(e) => return Err(e),
+    };
+
+    let mut steps: Vec<(String, u16)> = vec![(String::from("EHLO evelyn"), 250)];
+
+    if !config.user_name.is_empty() {
+        let credentials = format!("\u{0}{}\u{0}{}", config.user_name, config.password);
+        steps.push((format!("AUTH PLAIN {}", base64_encode(credentials.as_bytes())), 235));
+    }
+
+    steps.push((format!("MAIL FROM:<{}>", message.from), 250));
+    steps.push((format!("RCPT TO:<{}>", message.to), 250));
+    steps.push((String::from("DATA"), 354));
+    steps.push((format!("{}\r\n.", format_message(message)), 250));
+
+    // The greeting comes before any command is sent.
+    if let Err(e) = connection.expect_reply(220) {
+        return Err(e);
+    }
+
+    for (command, expected) in steps {
+        if let Err(e) = connection.command(&command, expected) {
+            return Err(e);
+        }
+    }
+
+    // The mail has been accepted by now, so a failure to say goodbye doesn't
+    // matter.
+    let _ = connection.command("QUIT", 221);
+
+    Ok(())
+}
@@ -14,13 +14,123 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use core::error_messages::EvelynCoreError;
+use chrono_tz::Tz;
+use core::conversion;
+use core::date_time_service;
+use core::error_messages::{EvelynBaseError, EvelynCoreError, EvelynDatabaseError};
+use core::user;
 use data;
 use model;
+use mongodb::Client;
 use processing::ProcessorData;
+use std::cmp;
+use std::collections::BTreeMap;
 use std::sync::Arc;
 use uuid::Uuid;
 
+fn new_item_id() -> String {
+    format!("{}", Uuid::new_v4())
+}
+
+// Finds an item by its id, or by its position for clients which don't send ids.
+pub fn find_item<'a>(
+    todo_list: &'a model::todo_list::TodoListModel,
+    item_id: Option<&String>,
+    item_index: Option<i32>,
+) -> Option<&'a model::todo_list::item::TodoListItemModel> {
+    match (item_id, item_index) {
+        (Some(item_id), _) => todo_list.todo_list_items.iter().find(|x| &x.item_id == item_id),
+        (None, Some(item_index)) if item_index >= 0 => todo_list.todo_list_items.get(item_index as usize),
+        _ => None,
+    }
+}
+
+// The user and the groups they belong to, which decide the lists shared with
+// them.
+fn lookup_access(client: &Client, user_id: &String) -> Result<model::todo_list::TodoListAccessModel, EvelynDatabaseError> {
+    match data::user_group::lookup_user_groups(user_id, client) {
+        Ok(user_groups) => {
+            Ok(model::todo_list::TodoListAccessModel {
+                user_id: user_id.clone(),
+                user_group_ids: user_groups.into_iter().map(|x| x.user_group_id).collect(),
+            })
+        },
+        Err(e) => Err(e),
+    }
+}
+
+fn access_level(
+    access: &model::todo_list::TodoListAccessModel,
+    owner_user_id: &String,
+    user_shares: &Vec<model::todo_list::TodoListUserShareModel>,
+    user_group_shares: &Vec<model::todo_list::TodoListUserGroupShareModel>,
+) -> model::todo_list::TodoListAccessExternalModel {
+    if &access.user_id == owner_user_id {
+        return model::todo_list::TodoListAccessExternalModel::Owner;
+    }
+
+    let shared_for_edit = user_shares
+        .iter()
+        .any(|x| x.user_id == access.user_id && x.permission == model::todo_list::TodoListPermissionModel::Edit) ||
+                          user_group_shares.iter().any(|x| {
+                                                            access.user_group_ids.contains(&x.user_group_id) &&
+                                                            x.permission == model::todo_list::TodoListPermissionModel::Edit
+                                                        });
+
+    if shared_for_edit {
+        model::todo_list::TodoListAccessExternalModel::Edit
+    } else {
+        model::todo_list::TodoListAccessExternalModel::Read
+    }
+}
+
+// Looks up a list the user may change. Lists only shared with them for
+// reading are treated as not found.
+pub fn lookup_editable_todo_list(
+    client: &Client,
+    user_id: &String,
+    todo_list_id: &String,
+) -> Result<model::todo_list::TodoListModel, EvelynDatabaseError> {
+    let lookup_todo_list_model = model::todo_list::LookupTodoListModel {
+        access: match lookup_access(client, user_id) {
+            Ok(access) => access,
+            Err(e) => return Err(e),
+        },
+        todo_list_id: todo_list_id.clone(),
+    };
+
+    let todo_list = match data::todo_list::lookup_todo_list(client, &lookup_todo_list_model) {
+        Ok(todo_list) => todo_list,
+        Err(e) => return Err(e),
+    };
+
+    match access_level(&lookup_todo_list_model.access, &todo_list.user_id, &todo_list.user_shares,
+                       &todo_list.user_group_shares) {
+        model::todo_list::TodoListAccessExternalModel::Read => {
+            Err(EvelynDatabaseError::TodoListNotFound(EvelynBaseError::NothingElse))
+        },
+        _ => Ok(todo_list),
+    }
+}
+
+fn to_permission_external_model(permission: model::todo_list::TodoListPermissionModel) -> model::todo_list::TodoListPermissionExternalModel {
+    match permission {
+        model::todo_list::TodoListPermissionModel::Read => model::todo_list::TodoListPermissionExternalModel::Read,
+        model::todo_list::TodoListPermissionModel::Edit => model::todo_list::TodoListPermissionExternalModel::Edit,
+    }
+}
+
+fn to_item_external_model(item: model::todo_list::item::TodoListItemModel, zone: &Tz) -> model::todo_list::item::TodoListItemExternalModel {
+    model::todo_list::item::TodoListItemExternalModel {
+        item_id: item.item_id,
+        text: item.text,
+        is_done: item.is_done,
+        done_by_user_id: item.done_by_user_id,
+        date_done: item.date_done.map(|x| date_time_service::timestamp_to_string(x, zone)),
+        converted_to: item.converted_to.map(conversion::to_link_external_model),
+    }
+}
+
 pub fn create_todo_list(
     model: model::todo_list::CreateTodoListRequestModel,
     session_token_model: model::SessionTokenModel,
@@ -33,15 +143,25 @@ pub fn create_todo_list(
         todo_list_id: format!("{}", todo_list_id),
         title: model.title,
         todo_list_items: Vec::new(),
+        archived: false,
+        user_shares: Vec::new(),
+        user_group_shares: Vec::new(),
+        version: data::version::INITIAL_VERSION,
+        deleted: None,
     };
 
     if let Some(todo_list_items) = model.todo_list_items {
+        let date_created = date_time_service::get_timestamp();
         for i in todo_list_items {
             todo_list_model
                 .todo_list_items
                 .push(model::todo_list::item::TodoListItemModel {
+                          item_id: new_item_id(),
                           text: i.text,
                           is_done: i.is_done,
+                          done_by_user_id: if i.is_done { Some(todo_list_model.user_id.clone()) } else { None },
+                          date_done: if i.is_done { Some(date_created) } else { None },
+                          converted_to: None,
                       });
         }
     }
@@ -63,52 +183,100 @@ pub fn add_item_to_todo_list(
     model: model::todo_list::item::AddItemTodoListRequestModel,
     session_token_model: model::SessionTokenModel,
     processor_data: Arc<ProcessorData>,
-) -> Option<EvelynCoreError> {
+) -> Result<model::todo_list::item::AddItemTodoListResponseModel, EvelynCoreError> {
+    let item_id = new_item_id();
+
+    let data_store = processor_data.data_store.clone();
+
+    let access = match lookup_access(&data_store, &session_token_model.user_id) {
+        Ok(access) => access,
+        Err(e) => return Err(EvelynCoreError::FailedToAddItemToTodoList(e)),
+    };
+
+    let is_done = model.todo_list_item.is_done;
     let todo_list_model = model::todo_list::item::AddItemTodoListModel {
-        user_id: session_token_model.user_id,
         todo_list_id: model.todo_list_id,
         todo_list_item: model::todo_list::item::TodoListItemModel {
+            item_id: item_id.clone(),
             text: model.todo_list_item.text,
-            is_done: model.todo_list_item.is_done,
+            is_done: is_done,
+            done_by_user_id: if is_done { Some(access.user_id.clone()) } else { None },
+            date_done: if is_done { Some(date_time_service::get_timestamp()) } else { None },
+            converted_to: None,
         },
+        access: access,
     };
 
-    let data_store = processor_data.data_store.clone();
-
-    let error = data::todo_list::add_item_to_todo_list(&data_store, &todo_list_model);
-    if let Some(e) = error {
-        Some(EvelynCoreError::FailedToAddItemToTodoList(e))
-    } else {
-        None
+    match data::todo_list::add_item_to_todo_list(&data_store, &todo_list_model) {
+        None => {
+            Ok(model::todo_list::item::AddItemTodoListResponseModel {
+                item_id: Some(item_id),
+                error: None,
+            })
+        },
+        Some(EvelynDatabaseError::TodoListNotFound(e)) => Err(EvelynCoreError::TodoListNotFound(e)),
+        Some(e) => Err(EvelynCoreError::FailedToAddItemToTodoList(e)),
     }
 }
 
 pub fn lookup_todo_lists(
+    model: model::todo_list::LookupTodoListsRequestModel,
     session_token_model: model::SessionTokenModel,
     processor_data: Arc<ProcessorData>,
 ) -> Result<model::todo_list::LookupTodoListsResponseModel, EvelynCoreError> {
-    let lookup_todo_lists_model = model::todo_list::LookupTodoListsModel {
-        user_id: session_token_model.user_id,
+    let data_store = processor_data.data_store.clone();
+
+    match find_todo_lists(&data_store, &session_token_model.user_id, model.show_archived, false) {
+        Ok(todo_lists) => {
+            Ok(model::todo_list::LookupTodoListsResponseModel {
+                   todo_lists: Some(todo_lists),
+                   error: None,
+               })
+        },
+        Err(e) => Err(e),
+    }
+}
+
+// The unarchived lists the user can see which still have items to do.
+pub fn lookup_incomplete_todo_lists(
+    client: &Client,
+    user_id: &String,
+) -> Result<Vec<model::todo_list::TodoListsExternalModel>, EvelynCoreError> {
+    find_todo_lists(client, user_id, false, true)
+}
+
+fn find_todo_lists(
+    client: &Client,
+    user_id: &String,
+    show_archived: bool,
+    incomplete_only: bool,
+) -> Result<Vec<model::todo_list::TodoListsExternalModel>, EvelynCoreError> {
+    let access = match lookup_access(client, user_id) {
+        Ok(access) => access,
+        Err(e) => return Err(EvelynCoreError::FailedToLookupTodoLists(e)),
     };
 
-    let data_store = processor_data.data_store.clone();
+    let lookup_todo_lists_model = model::todo_list::LookupTodoListsModel {
+        access: access,
+        show_archived: show_archived,
+        incomplete_only: incomplete_only,
+    };
 
-    match data::todo_list::lookup_todo_lists(&data_store, &lookup_todo_lists_model) {
+    match data::todo_list::lookup_todo_lists(client, &lookup_todo_lists_model) {
         Ok(result) => {
-            let todo_lists = result
+            let ref access = lookup_todo_lists_model.access;
+            Ok(result
                 .into_iter()
                 .map(|x| {
                          model::todo_list::TodoListsExternalModel {
+                             access: access_level(access, &x.user_id, &x.user_shares, &x.user_group_shares),
                              title: x.title,
                              todo_list_id: x.todo_list_id,
+                             owner_user_id: x.user_id,
+                             archived: x.archived,
                          }
                      })
-                .collect();
-
-            Ok(model::todo_list::LookupTodoListsResponseModel {
-                   todo_lists: Some(todo_lists),
-                   error: None,
-               })
+                .collect())
         },
         Err(e) => Err(EvelynCoreError::FailedToLookupTodoLists(e)),
     }
@@ -119,29 +287,45 @@ pub fn lookup_todo_list(
     session_token_model: model::SessionTokenModel,
     processor_data: Arc<ProcessorData>,
 ) -> Result<model::todo_list::LookupTodoListResponseModel, EvelynCoreError> {
+    let data_store = processor_data.data_store.clone();
+
+    let access = match lookup_access(&data_store, &session_token_model.user_id) {
+        Ok(access) => access,
+        Err(e) => return Err(EvelynCoreError::FailedToLookupTodoList(e)),
+    };
+
     let lookup_todo_list_model = model::todo_list::LookupTodoListModel {
-        user_id: session_token_model.user_id,
+        access: access,
         todo_list_id: model.todo_list_id,
     };
 
-    let data_store = processor_data.data_store.clone();
-
     match data::todo_list::lookup_todo_list(&data_store, &lookup_todo_list_model) {
         Ok(result) => {
-            let mut todo_list_model = model::todo_list::TodoListExternalModel {
+            let zone = user::lookup_time_zone(&data_store, &session_token_model.user_id);
+
+            let todo_list_model = model::todo_list::TodoListExternalModel {
+                access: access_level(&lookup_todo_list_model.access, &result.user_id, &result.user_shares,
+                                     &result.user_group_shares),
+                todo_list_id: result.todo_list_id,
+                owner_user_id: result.user_id,
                 title: result.title,
-                todo_list_items: Vec::new(),
+                todo_list_items: result.todo_list_items.into_iter().map(|x| to_item_external_model(x, &zone)).collect(),
+                archived: result.archived,
+                user_shares: result.user_shares.into_iter().map(|x| {
+                    model::todo_list::TodoListUserShareExternalModel {
+                        user_id: x.user_id,
+                        permission: to_permission_external_model(x.permission),
+                    }
+                }).collect(),
+                user_group_shares: result.user_group_shares.into_iter().map(|x| {
+                    model::todo_list::TodoListUserGroupShareExternalModel {
+                        user_group_id: x.user_group_id,
+                        permission: to_permission_external_model(x.permission),
+                    }
+                }).collect(),
+                version: result.version,
             };
 
-            for i in result.todo_list_items {
-                todo_list_model
-                    .todo_list_items
-                    .push(model::todo_list::item::TodoListItemExternalModel {
-                              text: i.text,
-                              is_done: i.is_done,
-                          });
-            }
-
             Ok(model::todo_list::LookupTodoListResponseModel {
                    todo_list: Some(todo_list_model),
                    error: None,
@@ -156,17 +340,526 @@ pub fn update_todo_list_item(
     session_token_model: model::SessionTokenModel,
     processor_data: Arc<ProcessorData>,
 ) -> Option<EvelynCoreError> {
+    let data_store = processor_data.data_store.clone();
+
+    let todo_list = match lookup_editable_todo_list(&data_store, &session_token_model.user_id, &model.todo_list_id) {
+        Ok(todo_list) => todo_list,
+        Err(EvelynDatabaseError::TodoListNotFound(e)) => return Some(EvelynCoreError::TodoListNotFound(e)),
+        Err(e) => return Some(EvelynCoreError::FailedToUpdateTodoListItem(e)),
+    };
+
+    let item_id = match find_item(&todo_list, model.item_id.as_ref(), model.item_index) {
+        Some(item) => item.item_id.clone(),
+        None => return Some(EvelynCoreError::TodoListNotFound(EvelynBaseError::NothingElse)),
+    };
+
+    if model.is_done.is_none() && model.new_text.is_none() {
+        return None;
+    }
+
     let update_todo_list_item_model = model::todo_list::item::UpdateTodoListItemModel {
         user_id: session_token_model.user_id,
         todo_list_id: model.todo_list_id,
-        item_index: model.item_index,
+        item_id: item_id,
         is_done: model.is_done,
+        date_done: date_time_service::get_timestamp(),
+        text: model.new_text,
+        expected_version: model.expected_version,
     };
 
-    let data_store = processor_data.data_store.clone();
-
     match data::todo_list::update_todo_list_item(&data_store, &update_todo_list_item_model) {
         None => None,
+        Some(EvelynDatabaseError::TodoListNotFound(e)) => Some(EvelynCoreError::TodoListNotFound(e)),
+        Some(EvelynDatabaseError::TodoListVersionConflict(e)) => Some(EvelynCoreError::TodoListVersionConflict(e)),
         Some(e) => Some(EvelynCoreError::FailedToUpdateTodoListItem(e)),
     }
 }
+
+pub fn remove_todo_list_item(
+    model: model::todo_list::item::RemoveItemTodoListRequestModel,
+    session_token_model: model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Option<EvelynCoreError> {
+    let data_store = processor_data.data_store.clone();
+
+    let access = match lookup_access(&data_store, &session_token_model.user_id) {
+        Ok(access) => access,
+        Err(e) => return Some(EvelynCoreError::FailedToRemoveTodoListItem(e)),
+    };
+
+    match data::todo_list::remove_todo_list_item(&data_store, &access, &model.todo_list_id, &model.item_id,
+                                                 model.expected_version) {
+        None => None,
+        Some(EvelynDatabaseError::TodoListNotFound(e)) => Some(EvelynCoreError::TodoListNotFound(e)),
+        Some(EvelynDatabaseError::TodoListVersionConflict(e)) => Some(EvelynCoreError::TodoListVersionConflict(e)),
+        Some(e) => Some(EvelynCoreError::FailedToRemoveTodoListItem(e)),
+    }
+}
+
+// Moving an item shifts the ones between its old and new position, so the
+// whole list is written back as long as nothing changed it in the meantime.
+pub fn move_todo_list_item(
+    model: model::todo_list::item::MoveItemTodoListRequestModel,
+    session_token_model: model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Option<EvelynCoreError> {
+    let data_store = processor_data.data_store.clone();
+
+    let mut todo_list = match lookup_editable_todo_list(&data_store, &session_token_model.user_id,
+                                                        &model.todo_list_id) {
+        Ok(todo_list) => todo_list,
+        Err(EvelynDatabaseError::TodoListNotFound(e)) => return Some(EvelynCoreError::TodoListNotFound(e)),
+        Err(e) => return Some(EvelynCoreError::FailedToMoveTodoListItem(e)),
+    };
+
+    if let Some(expected_version) = model.expected_version {
+        if expected_version != todo_list.version {
+            return Some(EvelynCoreError::TodoListVersionConflict(EvelynBaseError::NothingElse));
+        }
+    }
+
+    let from = match todo_list.todo_list_items.iter().position(|x| x.item_id == model.item_id) {
+        Some(from) => from,
+        None => return Some(EvelynCoreError::TodoListNotFound(EvelynBaseError::NothingElse)),
+    };
+
+    let item = todo_list.todo_list_items.remove(from);
+    let to = cmp::min(cmp::max(model.position, 0) as usize, todo_list.todo_list_items.len());
+    todo_list.todo_list_items.insert(to, item);
+
+    match data::todo_list::replace_todo_list_items(&data_store, &model.todo_list_id, &todo_list.todo_list_items,
+                                                   todo_list.version) {
+        None => None,
+        Some(EvelynDatabaseError::TodoListNotFound(e)) => Some(EvelynCoreError::TodoListNotFound(e)),
+        Some(EvelynDatabaseError::TodoListVersionConflict(e)) => Some(EvelynCoreError::TodoListVersionConflict(e)),
+        Some(e) => Some(EvelynCoreError::FailedToMoveTodoListItem(e)),
+    }
+}
+
+pub fn update_todo_list(
+    model: model::todo_list::UpdateTodoListRequestModel,
+    session_token_model: model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Option<EvelynCoreError> {
+    if model.new_title.is_none() && model.archived.is_none() {
+        return None;
+    }
+
+    let update_todo_list_model = model::todo_list::UpdateTodoListModel {
+        user_id: session_token_model.user_id,
+        todo_list_id: model.todo_list_id,
+        title: model.new_title,
+        archived: model.archived,
+        expected_version: model.expected_version,
+    };
+
+    let data_store = processor_data.data_store.clone();
+
+    match data::todo_list::update_todo_list(&data_store, &update_todo_list_model) {
+        None => None,
+        Some(EvelynDatabaseError::TodoListNotFound(e)) => Some(EvelynCoreError::TodoListNotFound(e)),
+        Some(EvelynDatabaseError::TodoListVersionConflict(e)) => Some(EvelynCoreError::TodoListVersionConflict(e)),
+        Some(e) => Some(EvelynCoreError::FailedToUpdateTodoList(e)),
+    }
+}
+
+// Only the owner sees who a list is shared with and changes it, so lists
+// shared with the user are treated as not found.
+fn lookup_owned_todo_list(
+    client: &Client,
+    user_id: &String,
+    todo_list_id: &String,
+) -> Result<model::todo_list::TodoListModel, EvelynCoreError> {
+    let lookup_todo_list_model = model::todo_list::LookupTodoListModel {
+        access: model::todo_list::TodoListAccessModel {
+            user_id: user_id.clone(),
+            user_group_ids: Vec::new(),
+        },
+        todo_list_id: todo_list_id.clone(),
+    };
+
+    match data::todo_list::lookup_todo_list(client, &lookup_todo_list_model) {
+        Ok(ref todo_list) if &todo_list.user_id != user_id => {
+            Err(EvelynCoreError::TodoListNotFound(EvelynBaseError::NothingElse))
+        },
+        Ok(todo_list) => Ok(todo_list),
+        Err(EvelynDatabaseError::TodoListNotFound(e)) => Err(EvelynCoreError::TodoListNotFound(e)),
+        Err(e) => Err(EvelynCoreError::FailedToUpdateTodoListShares(e)),
+    }
+}
+
+fn save_todo_list_shares(
+    client: &Client,
+    todo_list: &model::todo_list::TodoListModel,
+    expected_version: Option<i64>,
+) -> Option<EvelynCoreError> {
+    if let Some(expected_version) = expected_version {
+        if expected_version != todo_list.version {
+            return Some(EvelynCoreError::TodoListVersionConflict(EvelynBaseError::NothingElse));
+        }
+    }
+
+    match data::todo_list::update_todo_list_shares(client, todo_list) {
+        None => None,
+        Some(EvelynDatabaseError::TodoListNotFound(e)) => Some(EvelynCoreError::TodoListNotFound(e)),
+        Some(EvelynDatabaseError::TodoListVersionConflict(e)) => Some(EvelynCoreError::TodoListVersionConflict(e)),
+        Some(e) => Some(EvelynCoreError::FailedToUpdateTodoListShares(e)),
+    }
+}
+
+pub fn share_todo_list(
+    model: model::todo_list::ShareTodoListRequestModel,
+    session_token_model: model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Option<EvelynCoreError> {
+    let data_store = processor_data.data_store.clone();
+    let ref user_id = session_token_model.user_id;
+
+    let mut todo_list = match lookup_owned_todo_list(&data_store, user_id, &model.todo_list_id) {
+        Ok(todo_list) => todo_list,
+        Err(e) => return Some(e),
+    };
+
+    let permission = match model.permission {
+        model::todo_list::TodoListPermissionExternalModel::Read => model::todo_list::TodoListPermissionModel::Read,
+        model::todo_list::TodoListPermissionExternalModel::Edit => model::todo_list::TodoListPermissionModel::Edit,
+    };
+
+    match (model.user_id, model.user_group_id) {
+        (Some(share_user_id), None) => {
+            if &share_user_id == user_id {
+                return Some(EvelynCoreError::InvalidTodoListShare(EvelynBaseError::NothingElse));
+            }
+
+            match data::user::find_user_by_id(&data_store, &share_user_id) {
+                Ok(Some(_)) => {},
+                Ok(None) => return Some(EvelynCoreError::InvalidTodoListShare(EvelynBaseError::NothingElse)),
+                Err(e) => return Some(EvelynCoreError::FailedToUpdateTodoListShares(e)),
+            }
+
+            todo_list.user_shares.retain(|x| x.user_id != share_user_id);
+            todo_list.user_shares.push(model::todo_list::TodoListUserShareModel {
+                user_id: share_user_id,
+                permission: permission,
+            });
+        },
+        (None, Some(share_user_group_id)) => {
+            match data::user_group::lookup_user_group(&data_store, user_id, &share_user_group_id) {
+                Ok(_) => {},
+                Err(EvelynDatabaseError::UserGroupNotFound(_)) => {
+                    return Some(EvelynCoreError::InvalidTodoListShare(EvelynBaseError::NothingElse))
+                },
+                Err(e) => return Some(EvelynCoreError::FailedToUpdateTodoListShares(e)),
+            }
+
+            todo_list.user_group_shares.retain(|x| x.user_group_id != share_user_group_id);
+            todo_list.user_group_shares.push(model::todo_list::TodoListUserGroupShareModel {
+                user_group_id: share_user_group_id,
+                permission: permission,
+            });
+        },
+        _ => return Some(EvelynCoreError::InvalidTodoListShare(EvelynBaseError::NothingElse)),
+    }
+
+    save_todo_list_shares(&data_store, &todo_list, model.expected_version)
+}
+
+pub fn unshare_todo_list(
+    model: model::todo_list::UnshareTodoListRequestModel,
+    session_token_model: model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Option<EvelynCoreError> {
+    let data_store = processor_data.data_store.clone();
+
+    let mut todo_list = match lookup_owned_todo_list(&data_store, &session_token_model.user_id, &model.todo_list_id) {
+        Ok(todo_list) => todo_list,
+        Err(e) => return Some(e),
+    };
+
+    match (model.user_id, model.user_group_id) {
+        (Some(share_user_id), None) => todo_list.user_shares.retain(|x| x.user_id != share_user_id),
+        (None, Some(share_user_group_id)) => {
+            todo_list.user_group_shares.retain(|x| x.user_group_id != share_user_group_id)
+        },
+        _ => return Some(EvelynCoreError::InvalidTodoListShare(EvelynBaseError::NothingElse)),
+    }
+
+    save_todo_list_shares(&data_store, &todo_list, model.expected_version)
+}
+
+pub fn remove(
+    model: model::todo_list::RemoveTodoListRequestModel,
+    session_token_model: model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Option<EvelynCoreError> {
+    let data_store = processor_data.data_store.clone();
+
+    let deletion = model::trash::DeletionModel {
+        deleted_by_user_id: session_token_model.user_id.clone(),
+        date_deleted: date_time_service::get_timestamp(),
+    };
+
+    match data::todo_list::remove(&data_store, &session_token_model.user_id, &model.todo_list_id, &deletion) {
+        None => None,
+        Some(EvelynDatabaseError::TodoListNotFound(e)) => Some(EvelynCoreError::TodoListNotFound(e)),
+        Some(e) => Some(EvelynCoreError::FailedToRemoveTodoList(e)),
+    }
+}
+
+// Gives the items of lists saved before items had ids an id each. A list
+// changed while this runs is picked up again on the next start.
+pub fn migrate_todo_lists(client: &Client) -> Option<EvelynCoreError> {
+    let todo_lists = match data::todo_list::lookup_lists_without_item_ids(client) {
+        Ok(todo_lists) => todo_lists,
+        Err(e) => return Some(EvelynCoreError::FailedToMigrateTodoLists(e)),
+    };
+
+    let mut migrated = 0;
+    for mut todo_list in todo_lists {
+        for item in todo_list.todo_list_items.iter_mut() {
+            if item.item_id.is_empty() {
+                item.item_id = new_item_id();
+            }
+        }
+
+        match data::todo_list::replace_todo_list_items(client, &todo_list.todo_list_id, &todo_list.todo_list_items,
+                                                       todo_list.version) {
+            None => migrated += 1,
+            Some(EvelynDatabaseError::TodoListVersionConflict(_)) => {},
+            Some(e) => return Some(EvelynCoreError::FailedToMigrateTodoLists(e)),
+        }
+    }
+
+    if migrated > 0 {
+        info!("Gave the items of {} todo list(s) ids", migrated);
+    }
+
+    None
+}
+
+// Finds the `{{name}}` placeholders in a piece of text, as (start, end, name)
+// with `end` just past the closing braces.
+fn placeholder_spans(text: &str) -> Vec<(usize, usize, String)> {
+    let mut spans = Vec::new();
+    let mut position = 0;
+
+    while let Some(start) = text[position..].find("{{").map(|x| x + position) {
+        match text[start + 2..].find("}}") {
+            Some(length) => {
+                let end = start + 2 + length + 2;
+                spans.push((start, end, text[start + 2..end - 2].trim().to_owned()));
+                position = end;
+            },
+            None => break,
+        }
+    }
+
+    spans
+}
+
+// The names of the placeholders in the text, in the order they first appear.
+pub fn find_placeholders(text: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+
+    for (_, _, name) in placeholder_spans(text) {
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+
+    names
+}
+
+// Replaces placeholders with their values. Placeholders without a value are
+// left as they are for the user to fill in.
+pub fn fill_placeholders(text: &str, values: &BTreeMap<String, String>) -> String {
+    let mut filled = String::new();
+    let mut position = 0;
+
+    for (start, end, name) in placeholder_spans(text) {
+        if let Some(value) = values.get(&name) {
+            filled.push_str(&text[position..start]);
+            filled.push_str(value);
+            position = end;
+        }
+    }
+
+    filled.push_str(&text[position..]);
+    filled
+}
+
+fn to_template_external_model(
+    template: model::todo_list::template::TodoListTemplateModel,
+) -> model::todo_list::template::TodoListTemplateExternalModel {
+    let mut placeholders = find_placeholders(&template.title);
+    for item in template.items.iter() {
+        for name in find_placeholders(&item.text) {
+            if !placeholders.contains(&name) {
+                placeholders.push(name);
+            }
+        }
+    }
+
+    model::todo_list::template::TodoListTemplateExternalModel {
+        template_id: template.template_id,
+        owner_user_id: template.user_id,
+        title: template.title,
+        items: template.items.into_iter().map(|x| {
+            model::todo_list::template::TodoListTemplateItemExternalModel {
+                text: x.text,
+            }
+        }).collect(),
+        user_group_id: template.user_group_id,
+        placeholders: placeholders,
+    }
+}
+
+// Templates keep only the text of the items, so every list made from one
+// starts with nothing done.
+pub fn create_template(
+    model: model::todo_list::template::CreateTodoListTemplateRequestModel,
+    session_token_model: model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Result<model::todo_list::template::CreateTodoListTemplateResponseModel, EvelynCoreError> {
+    let data_store = processor_data.data_store.clone();
+    let ref user_id = session_token_model.user_id;
+
+    let access = match lookup_access(&data_store, user_id) {
+        Ok(access) => access,
+        Err(e) => return Err(EvelynCoreError::FailedToCreateTodoListTemplate(e)),
+    };
+
+    let lookup_todo_list_model = model::todo_list::LookupTodoListModel {
+        access: access,
+        todo_list_id: model.todo_list_id,
+    };
+
+    let todo_list = match data::todo_list::lookup_todo_list(&data_store, &lookup_todo_list_model) {
+        Ok(todo_list) => todo_list,
+        Err(EvelynDatabaseError::TodoListNotFound(e)) => return Err(EvelynCoreError::TodoListNotFound(e)),
+        Err(e) => return Err(EvelynCoreError::FailedToCreateTodoListTemplate(e)),
+    };
+
+    if let Some(ref user_group_id) = model.user_group_id {
+        match data::user_group::lookup_user_group(&data_store, user_id, user_group_id) {
+            Ok(_) => {},
+            Err(EvelynDatabaseError::UserGroupNotFound(e)) => {
+                return Err(EvelynCoreError::InvalidTodoListTemplateUserGroup(e))
+            },
+            Err(e) => return Err(EvelynCoreError::FailedToCreateTodoListTemplate(e)),
+        }
+    }
+
+    let template_model = model::todo_list::template::TodoListTemplateModel {
+        user_id: user_id.clone(),
+        template_id: format!("{}", Uuid::new_v4()),
+        title: model.title.unwrap_or(todo_list.title),
+        items: todo_list.todo_list_items.into_iter().map(|x| {
+            model::todo_list::template::TodoListTemplateItemModel {
+                text: x.text,
+            }
+        }).collect(),
+        user_group_id: model.user_group_id,
+    };
+
+    match data::todo_list::insert_template(&data_store, &template_model) {
+        None => {
+            Ok(model::todo_list::template::CreateTodoListTemplateResponseModel {
+                template_id: Some(template_model.template_id),
+                error: None,
+            })
+        },
+        Some(e) => Err(EvelynCoreError::FailedToCreateTodoListTemplate(e)),
+    }
+}
+
+pub fn lookup_templates(
+    session_token_model: model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Result<model::todo_list::template::LookupTodoListTemplatesResponseModel, EvelynCoreError> {
+    let data_store = processor_data.data_store.clone();
+
+    let access = match lookup_access(&data_store, &session_token_model.user_id) {
+        Ok(access) => access,
+        Err(e) => return Err(EvelynCoreError::FailedToLookupTodoListTemplates(e)),
+    };
+
+    match data::todo_list::lookup_templates(&data_store, &access) {
+        Ok(templates) => {
+            Ok(model::todo_list::template::LookupTodoListTemplatesResponseModel {
+                templates: Some(templates.into_iter().map(to_template_external_model).collect()),
+                error: None,
+            })
+        },
+        Err(e) => Err(EvelynCoreError::FailedToLookupTodoListTemplates(e)),
+    }
+}
+
+// The new list belongs to whoever made it from the template.
+pub fn instantiate_template(
+    model: model::todo_list::template::InstantiateTodoListTemplateRequestModel,
+    session_token_model: model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Result<model::todo_list::template::InstantiateTodoListTemplateResponseModel, EvelynCoreError> {
+    let data_store = processor_data.data_store.clone();
+
+    let access = match lookup_access(&data_store, &session_token_model.user_id) {
+        Ok(access) => access,
+        Err(e) => return Err(EvelynCoreError::FailedToInstantiateTodoListTemplate(e)),
+    };
+
+    let template = match data::todo_list::lookup_template(&data_store, &access, &model.template_id) {
+        Ok(template) => template,
+        Err(EvelynDatabaseError::TodoListTemplateNotFound(e)) => return Err(EvelynCoreError::TodoListTemplateNotFound(e)),
+        Err(e) => return Err(EvelynCoreError::FailedToInstantiateTodoListTemplate(e)),
+    };
+
+    let title = model.title.unwrap_or(template.title);
+    let ref placeholders = model.placeholders;
+
+    let todo_list_model = model::todo_list::TodoListModel {
+        user_id: session_token_model.user_id,
+        todo_list_id: format!("{}", Uuid::new_v4()),
+        title: fill_placeholders(&title, placeholders),
+        todo_list_items: template.items.into_iter().map(|x| {
+            model::todo_list::item::TodoListItemModel {
+                item_id: new_item_id(),
+                text: fill_placeholders(&x.text, placeholders),
+                is_done: false,
+                done_by_user_id: None,
+                date_done: None,
+                converted_to: None,
+            }
+        }).collect(),
+        archived: false,
+        user_shares: Vec::new(),
+        user_group_shares: Vec::new(),
+        version: data::version::INITIAL_VERSION,
+        deleted: None,
+    };
+
+    match data::todo_list::insert_todo_list(&data_store, &todo_list_model) {
+        None => {
+            Ok(model::todo_list::template::InstantiateTodoListTemplateResponseModel {
+                todo_list_id: Some(todo_list_model.todo_list_id),
+                error: None,
+            })
+        },
+        Some(e) => Err(EvelynCoreError::FailedToInstantiateTodoListTemplate(e)),
+    }
+}
+
+// Only the user who made a template can remove it.
+pub fn remove_template(
+    model: model::todo_list::template::RemoveTodoListTemplateRequestModel,
+    session_token_model: model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Option<EvelynCoreError> {
+    let data_store = processor_data.data_store.clone();
+
+    match data::todo_list::remove_template(&data_store, &session_token_model.user_id, &model.template_id) {
+        None => None,
+        Some(EvelynDatabaseError::TodoListTemplateNotFound(e)) => Some(EvelynCoreError::TodoListTemplateNotFound(e)),
+        Some(e) => Some(EvelynCoreError::FailedToRemoveTodoListTemplate(e)),
+    }
+}
@@ -0,0 +1,163 @@
+// Evelyn: Your personal assistant, project manager and calendar
+// Copyright (C) 2017 Gregory Jensen
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use chrono_tz::Tz;
+use core::date_time_service;
+use core::error_messages::{EvelynBaseError, EvelynCoreError, EvelynDatabaseError};
+use core::user;
+use data;
+use model;
+use mongodb::Client;
+use processing::ProcessorData;
+use std::sync::Arc;
+
+pub const SIMPLE_TASK_ITEM_TYPE: &'static str = "SimpleTask";
+pub const USER_GROUP_ITEM_TYPE: &'static str = "UserGroup";
+pub const CALENDAR_EVENT_ITEM_TYPE: &'static str = "CalendarEvent";
+pub const TODO_LIST_ITEM_TYPE: &'static str = "TodoList";
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+fn oldest_restorable(retention_days: i64) -> i64 {
+    date_time_service::get_timestamp() - retention_days * SECONDS_PER_DAY
+}
+
+fn to_external_model(
+    item_type: &str,
+    item_id: String,
+    title: String,
+    deletion: model::trash::DeletionModel,
+    retention_days: i64,
+    zone: &Tz,
+) -> (i64, model::trash::TrashItemExternalModel) {
+    (deletion.date_deleted, model::trash::TrashItemExternalModel {
+        item_type: String::from(item_type),
+        item_id: item_id,
+        title: title,
+        deleted_by_user_id: deletion.deleted_by_user_id,
+        date_deleted: date_time_service::timestamp_to_string(deletion.date_deleted, zone),
+        restorable_until: date_time_service::timestamp_to_string(deletion.date_deleted + retention_days * SECONDS_PER_DAY, zone),
+    })
+}
+
+pub fn lookup(
+    session_token_model: model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Result<model::trash::LookupTrashResponseModel, EvelynCoreError> {
+    let ds = processor_data.data_store.clone();
+    let retention_days = processor_data.conf.get_trash_retention_days();
+    let deleted_since = oldest_restorable(retention_days);
+    let zone = user::lookup_time_zone(&ds, &session_token_model.user_id);
+
+    let mut items = Vec::new();
+
+    match data::simple_task::lookup_deleted(&ds, &session_token_model.user_id, deleted_since) {
+        Ok(tasks) => {
+            for task in tasks {
+                if let Some(deletion) = task.deleted {
+                    items.push(to_external_model(SIMPLE_TASK_ITEM_TYPE, task.task_id, task.title, deletion, retention_days, &zone));
+                }
+            }
+        },
+        Err(e) => return Err(EvelynCoreError::FailedToLookupTrash(e)),
+    }
+
+    match data::user_group::lookup_deleted(&ds, &session_token_model.user_id, deleted_since) {
+        Ok(user_groups) => {
+            for user_group in user_groups {
+                if let Some(deletion) = user_group.deleted {
+                    items.push(to_external_model(USER_GROUP_ITEM_TYPE, user_group.user_group_id, user_group.name, deletion, retention_days, &zone));
+                }
+            }
+        },
+        Err(e) => return Err(EvelynCoreError::FailedToLookupTrash(e)),
+    }
+
+    match data::calendar::lookup_deleted(&ds, &session_token_model.user_id, deleted_since) {
+        Ok(events) => {
+            for event in events {
+                if let Some(deletion) = event.deleted {
+                    items.push(to_external_model(CALENDAR_EVENT_ITEM_TYPE, event.event_id, event.title, deletion, retention_days, &zone));
+                }
+            }
+        },
+        Err(e) => return Err(EvelynCoreError::FailedToLookupTrash(e)),
+    }
+
+    match data::todo_list::lookup_deleted(&ds, &session_token_model.user_id, deleted_since) {
+        Ok(todo_lists) => {
+            for todo_list in todo_lists {
+                if let Some(deletion) = todo_list.deleted {
+                    items.push(to_external_model(TODO_LIST_ITEM_TYPE, todo_list.todo_list_id, todo_list.title, deletion, retention_days, &zone));
+                }
+            }
+        },
+        Err(e) => return Err(EvelynCoreError::FailedToLookupTrash(e)),
+    }
+
+    // Most recently deleted first.
+    items.sort_by(|a, b| b.0.cmp(&a.0));
+
+    Ok(model::trash::LookupTrashResponseModel {
+        items: items.into_iter().map(|x| x.1).collect(),
+        error: None,
+    })
+}
+
+pub fn restore(
+    model: model::trash::RestoreTrashItemRequestModel,
+    session_token_model: model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Option<EvelynCoreError> {
+    let ds = processor_data.data_store.clone();
+    let deleted_since = oldest_restorable(processor_data.conf.get_trash_retention_days());
+
+    let error = match model.item_type.as_str() {
+        SIMPLE_TASK_ITEM_TYPE => data::simple_task::restore(&ds, &session_token_model.user_id, &model.item_id, deleted_since),
+        USER_GROUP_ITEM_TYPE => data::user_group::restore(&ds, &session_token_model.user_id, &model.item_id, deleted_since),
+        CALENDAR_EVENT_ITEM_TYPE => data::calendar::restore(&ds, &session_token_model.user_id, &model.item_id, deleted_since),
+        TODO_LIST_ITEM_TYPE => data::todo_list::restore(&ds, &session_token_model.user_id, &model.item_id, deleted_since),
+        _ => return Some(EvelynCoreError::InvalidTrashItemType(EvelynBaseError::NothingElse)),
+    };
+
+    match error {
+        None => None,
+        Some(EvelynDatabaseError::TrashItemNotFound(e)) => Some(EvelynCoreError::TrashItemNotFound(e)),
+        Some(e) => Some(EvelynCoreError::FailedToRestoreFromTrash(e)),
+    }
+}
+
+pub fn purge_expired(
+    client: &Client,
+    retention_days: i64,
+) -> Option<EvelynCoreError> {
+    let deleted_before = oldest_restorable(retention_days);
+
+    let purged = data::simple_task::purge_deleted(client, deleted_before)
+        .and_then(|tasks| data::user_group::purge_deleted(client, deleted_before).map(|user_groups| tasks + user_groups))
+        .and_then(|items| data::calendar::purge_deleted(client, deleted_before).map(|events| items + events))
+        .and_then(|items| data::todo_list::purge_deleted(client, deleted_before).map(|todo_lists| items + todo_lists));
+
+    match purged {
+        Ok(count) => {
+            if count > 0 {
+                info!("Purged {} item(s) from the trash", count);
+            }
+            None
+        },
+        Err(e) => Some(EvelynCoreError::FailedToPurgeTrash(e)),
+    }
+}
@@ -14,9 +14,15 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use core::error_messages::{EvelynBaseError, EvelynCoreError};
+use chrono_tz::Tz;
+use core::date_time_service as dts;
+use core::error_messages::{EvelynBaseError, EvelynCoreError, EvelynDatabaseError};
+use core::notification;
+use core::smtp;
 use data;
 use model::user::{CreateUserRequestModel, LogonUserRequestModel, LogonUserResponseModel, UserModel, SearchRequestModel, SearchResponseModel, SearchResultExternal};
+use model::user::{LookupProfileResponseModel, UpdateProfileRequestModel};
+use mongodb::Client;
 use processing::ProcessorData;
 use std::sync::Arc;
 use uuid::Uuid;
@@ -27,11 +33,22 @@ pub fn create_user(
 ) -> Option<EvelynCoreError> {
     let user_id = Uuid::new_v4();
 
+    if let Err(e) = dts::parse_optional_time_zone(&model.time_zone) {
+        return Some(e);
+    }
+
+    // The address is used as it is when mail is sent.
+    if !smtp::is_safe_address(&model.email_address) {
+        return Some(EvelynCoreError::InvalidEmailAddress(EvelynBaseError::NothingElse));
+    }
+
     let user_model = UserModel {
         user_id: format!("{}", user_id),
         user_name: model.user_name,
         email_address: model.email_address,
         password: model.password,
+        time_zone: model.time_zone,
+        email_notifications: None,
     };
 
     let ds = processor_data.data_store.clone();
@@ -56,25 +73,19 @@ pub fn create_user(
     }
 }
 
-pub fn logon_user(
-    model: LogonUserRequestModel,
+pub fn authenticate(
+    email_address: &String,
+    password: &String,
     processor_data: Arc<ProcessorData>,
-) -> Result<LogonUserResponseModel, EvelynCoreError> {
+) -> Result<UserModel, EvelynCoreError> {
     let ds = processor_data.data_store.clone();
 
-    match data::user::find_user(&ds, &model.email_address) {
+    match data::user::find_user(&ds, email_address) {
         Ok(user) => {
             if user.is_some() {
                 let user = user.unwrap();
-                if user.password == model.password {
-                    let token = processor_data
-                        .token_service
-                        .create_session_token(&processor_data.server_session_token, &user);
-
-                    Ok(LogonUserResponseModel {
-                           token: Some(token),
-                           error: None,
-                       })
+                if user.password == *password {
+                    Ok(user)
                 } else {
                     Err(EvelynCoreError::InvalidLogon(EvelynBaseError::NothingElse))
                 }
@@ -86,6 +97,25 @@ pub fn logon_user(
     }
 }
 
+pub fn logon_user(
+    model: LogonUserRequestModel,
+    processor_data: Arc<ProcessorData>,
+) -> Result<LogonUserResponseModel, EvelynCoreError> {
+    match authenticate(&model.email_address, &model.password, processor_data.clone()) {
+        Ok(user) => {
+            let token = processor_data
+                .token_service
+                .create_session_token(&processor_data.server_session_token, &user);
+
+            Ok(LogonUserResponseModel {
+                   token: Some(token),
+                   error: None,
+               })
+        },
+        Err(e) => Err(e),
+    }
+}
+
 pub fn search_for_users(
     model: SearchRequestModel,
     processor_data: Arc<ProcessorData>,
@@ -105,3 +135,86 @@ pub fn search_for_users(
         Err(e) => Err(EvelynCoreError::FailedToSearchForUsers(e)),
     }
 }
+
+pub fn lookup_profile(
+    user_id: &String,
+    processor_data: Arc<ProcessorData>,
+) -> Result<LookupProfileResponseModel, EvelynCoreError> {
+    let ds = processor_data.data_store.clone();
+
+    match data::user::find_user_by_id(&ds, user_id) {
+        Ok(Some(user)) => Ok(LookupProfileResponseModel {
+            user_name: user.user_name,
+            email_address: user.email_address,
+            time_zone: user.time_zone,
+            email_notifications: user.email_notifications.unwrap_or(notification::all_kinds()).into_iter()
+                .map(notification::to_kind_external_model)
+                .collect(),
+            error: None,
+        }),
+        Ok(None) => Err(EvelynCoreError::FailedToLookupUserProfile(EvelynDatabaseError::UserNotFound(EvelynBaseError::NothingElse))),
+        Err(e) => Err(EvelynCoreError::FailedToLookupUserProfile(e)),
+    }
+}
+
+pub fn update_profile(
+    request_model: UpdateProfileRequestModel,
+    user_id: &String,
+    processor_data: Arc<ProcessorData>,
+) -> Option<EvelynCoreError> {
+    // An empty name clears the preference.
+    let time_zone = match request_model.new_time_zone {
+        Some(ref name) if name.is_empty() => Some(None),
+        Some(name) => match dts::parse_time_zone(&name) {
+            Ok(_) => Some(Some(name)),
+            Err(e) => return Some(e),
+        },
+        None => None,
+    };
+
+    let ds = processor_data.data_store.clone();
+
+    if let Some(time_zone) = time_zone {
+        if let Some(e) = data::user::update_time_zone(&ds, user_id, &time_zone) {
+            return Some(EvelynCoreError::FailedToUpdateUserProfile(e));
+        }
+    }
+
+    if let Some(kinds) = request_model.new_email_notifications {
+        let kinds = kinds.into_iter().map(notification::to_kind_model).collect();
+
+        if let Some(e) = data::user::update_email_notifications(&ds, user_id, &kinds) {
+            return Some(EvelynCoreError::FailedToUpdateUserProfile(e));
+        }
+    }
+
+    None
+}
+
+// The name of the zone a user has chosen. A profile which can't be read is
+// treated as having no preference so that a failed lookup never stops a
+// response being sent.
+pub fn lookup_preferred_time_zone(client: &Client, user_id: &String) -> Option<String> {
+    match data::user::find_user_by_id(client, user_id) {
+        Ok(Some(user)) => user.time_zone,
+        Ok(None) => None,
+        Err(e) => {
+            warn!("Failed to lookup the time zone for user [{}]: {}", user_id, e);
+            None
+        },
+    }
+}
+
+// The zone which dates are rendered in for a user, UTC if they haven't chosen
+// one.
+pub fn lookup_time_zone(client: &Client, user_id: &String) -> Tz {
+    let time_zone = lookup_preferred_time_zone(client, user_id);
+
+    match dts::parse_optional_time_zone(&time_zone) {
+        Ok(zone) => zone,
+        Err(_) => {
+            warn!("User [{}] has an unknown time zone {:?}", user_id, time_zone);
+            dts::UTC
+        },
+    }
+}
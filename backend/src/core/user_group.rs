@@ -14,9 +14,11 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use core::date_time_service;
 use core::error_messages::EvelynCoreError;
 use data;
 use model;
+use model::notification::NotificationKindModel;
 use processing::ProcessorData;
 use std::sync::Arc;
 use uuid::Uuid;
@@ -34,6 +36,7 @@ pub fn create_user_group(
         name: model.name,
         description: model.description,
         members: Vec::new(),
+        deleted: None,
     };
 
     let data_store = processor_data.data_store.clone();
@@ -52,15 +55,18 @@ pub fn create_user_group(
 
 pub fn remove_user_group(
     model: model::user_group::RemoveUserGroupRequestModel,
-    _session_token_model: model::SessionTokenModel,
+    session_token_model: model::SessionTokenModel,
     processor_data: Arc<ProcessorData>,
 ) -> Result<model::user_group::RemoveUserGroupResponseModel, EvelynCoreError> {
-
-
     let user_group_id = model.user_group_id;
     let data_store = processor_data.data_store.clone();
 
-    let error = data::user_group::remove_user_group(&data_store, user_group_id);
+    let deletion = model::trash::DeletionModel {
+        deleted_by_user_id: session_token_model.user_id.clone(),
+        date_deleted: date_time_service::get_timestamp(),
+    };
+
+    let error = data::user_group::remove_user_group(&data_store, &session_token_model.user_id, user_group_id, &deletion);
     if let Some(e) = error {
         Err(EvelynCoreError::FailedToRemoveUserGroup(e))
     } else {
@@ -134,6 +140,11 @@ pub fn add_member(
 ) -> Option<EvelynCoreError> {
     let data_store = processor_data.data_store.clone();
 
+    let user_group_id = model.user_group_id.clone();
+    let member_user_id = model.member.user_id.clone();
+    // Adding an existing member again doesn't change anything to tell them about.
+    let already_member = data::user_group::lookup_user_group(&data_store, &member_user_id, &user_group_id).is_ok();
+
     let add_member_model = model::user_group::member::AddMemberModel {
         user_group_id: model.user_group_id,
         user_group_member_model: model::user_group::member::UserGroupMemberModel {
@@ -142,7 +153,16 @@ pub fn add_member(
     };
 
     match data::user_group::add_member(&data_store, add_member_model) {
-        None => None,
+        None if already_member => None,
+        None => {
+            // Now a member, the user can see the group's name.
+            if let Ok(user_group) = data::user_group::lookup_user_group(&data_store, &member_user_id, &user_group_id) {
+                processor_data.notifier.notify(&data_store, &member_user_id, NotificationKindModel::AddedToUserGroup,
+                                               format!("Added to {}", user_group.name),
+                                               format!("You have been added to the user group \"{}\".", user_group.name));
+            }
+            None
+        },
         Some(e) => Some(EvelynCoreError::FailedToAddMemberToUserGroup(e)),
     }
 }
@@ -154,6 +174,12 @@ pub fn remove_member(
 ) -> Option<EvelynCoreError> {
     let data_store = processor_data.data_store.clone();
 
+    // Looked up while the user is still a member and can see the group.
+    let user_group_name = data::user_group::lookup_user_group(&data_store, &model.member.user_id, &model.user_group_id)
+        .ok()
+        .map(|x| x.name);
+    let member_user_id = model.member.user_id.clone();
+
     let remove_member_model = model::user_group::member::RemoveMemberModel {
         user_group_id: model.user_group_id,
         user_group_member_model: model::user_group::member::UserGroupMemberModel {
@@ -162,7 +188,14 @@ pub fn remove_member(
     };
 
     match data::user_group::remove_member(&data_store, remove_member_model) {
-        None => None,
+        None => {
+            if let Some(name) = user_group_name {
+                processor_data.notifier.notify(&data_store, &member_user_id, NotificationKindModel::RemovedFromUserGroup,
+                                               format!("Removed from {}", name),
+                                               format!("You have been removed from the user group \"{}\".", name));
+            }
+            None
+        },
         Some(e) => Some(EvelynCoreError::FailedToRemoveMemberFromUserGroup(e)),
     }
 }
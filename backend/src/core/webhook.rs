@@ -0,0 +1,547 @@
+// Evelyn: Your personal assistant, project manager and calendar
+// Copyright (C) 2017 Gregory Jensen
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+// Subscribers are told about changes to agile projects by posting a JSON
+// payload to their URL. Each event is queued as a delivery which a scheduled
+// job sends, retrying with a growing delay until the subscriber accepts it
+// or it is given up on. The deliveries are kept as a log for the subscriber.
+//
+// Payloads are signed with HMAC-SHA256 using the subscription's secret, the
+// hex digest is sent in the X-Evelyn-Signature header as "sha256=<digest>".
+
+use core::agile::project;
+use core::date_time_service as dts;
+use core::error_messages::{EvelynBaseError, EvelynCoreError, EvelynDatabaseError};
+use core::scheduler;
+use core::user;
+use data;
+use data::webhook as webhook_data;
+use hyper;
+use hyper::header::{ContentType, Headers};
+use hyper::net::HttpsConnector;
+use hyper_openssl::OpensslClient;
+use model;
+use model::webhook as webhook_model;
+use mongodb::Client;
+use openssl::hash::MessageDigest;
+use openssl::pkey::PKey;
+use openssl::sign::Signer;
+use processing::ProcessorData;
+use serde_json;
+use std::collections::BTreeMap;
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+pub const SIGNATURE_HEADER: &'static str = "X-Evelyn-Signature";
+pub const EVENT_HEADER: &'static str = "X-Evelyn-Event";
+pub const DELIVERY_HEADER: &'static str = "X-Evelyn-Delivery";
+
+const TIMEOUT_SECONDS: u64 = 30;
+
+// Sprints which started longer ago than this when first noticed, for example
+// because the server was down, aren't announced.
+const SPRINT_START_GRACE_SECONDS: i64 = 24 * 60 * 60;
+
+fn to_event_type_model(event_type: webhook_model::WebhookEventTypeExternalModel) -> webhook_model::WebhookEventTypeModel {
+    match event_type {
+        webhook_model::WebhookEventTypeExternalModel::TaskCreated => webhook_model::WebhookEventTypeModel::TaskCreated,
+        webhook_model::WebhookEventTypeExternalModel::TaskUpdated => webhook_model::WebhookEventTypeModel::TaskUpdated,
+        webhook_model::WebhookEventTypeExternalModel::TaskAssigned => webhook_model::WebhookEventTypeModel::TaskAssigned,
+        webhook_model::WebhookEventTypeExternalModel::StoryCreated => webhook_model::WebhookEventTypeModel::StoryCreated,
+        webhook_model::WebhookEventTypeExternalModel::SprintStarted => webhook_model::WebhookEventTypeModel::SprintStarted,
+        webhook_model::WebhookEventTypeExternalModel::LinkMade => webhook_model::WebhookEventTypeModel::LinkMade,
+    }
+}
+
+fn to_event_type_external_model(event_type: webhook_model::WebhookEventTypeModel) -> webhook_model::WebhookEventTypeExternalModel {
+    match event_type {
+        webhook_model::WebhookEventTypeModel::TaskCreated => webhook_model::WebhookEventTypeExternalModel::TaskCreated,
+        webhook_model::WebhookEventTypeModel::TaskUpdated => webhook_model::WebhookEventTypeExternalModel::TaskUpdated,
+        webhook_model::WebhookEventTypeModel::TaskAssigned => webhook_model::WebhookEventTypeExternalModel::TaskAssigned,
+        webhook_model::WebhookEventTypeModel::StoryCreated => webhook_model::WebhookEventTypeExternalModel::StoryCreated,
+        webhook_model::WebhookEventTypeModel::SprintStarted => webhook_model::WebhookEventTypeExternalModel::SprintStarted,
+        webhook_model::WebhookEventTypeModel::LinkMade => webhook_model::WebhookEventTypeExternalModel::LinkMade,
+    }
+}
+
+fn to_status_external_model(status: webhook_model::WebhookDeliveryStatusModel) -> webhook_model::WebhookDeliveryStatusExternalModel {
+    match status {
+        webhook_model::WebhookDeliveryStatusModel::Pending => webhook_model::WebhookDeliveryStatusExternalModel::Pending,
+        webhook_model::WebhookDeliveryStatusModel::Delivered => webhook_model::WebhookDeliveryStatusExternalModel::Delivered,
+        webhook_model::WebhookDeliveryStatusModel::Failed => webhook_model::WebhookDeliveryStatusExternalModel::Failed,
+    }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|x| format!("{:02x}", x)).collect::<Vec<String>>().join("")
+}
+
+// The hex HMAC-SHA256 of the payload.
+pub fn sign(secret: &str, payload: &str) -> Result<String, String> {
+    let key = match PKey::hmac(secret.as_bytes()) {
+        Ok(key) => key,
+        Err(e) => return Err(format!("Failed to sign the payload: {}", e)),
+    };
+
+    let mut signer = match Signer::new(MessageDigest::sha256(), &key) {
+        Ok(signer) => signer,
+        Err(e) => return Err(format!("Failed to sign the payload: {}", e)),
+    };
+
+    if let Err(e) = signer.update(payload.as_bytes()) {
+        return Err(format!("Failed to sign the payload: {}", e));
+    }
+
+    match signer.finish() {
+        Ok(signature) => Ok(to_hex(&signature)),
+        Err(e) => Err(format!("Failed to sign the payload: {}", e)),
+    }
+}
+
+// Loopback, private, link local and other addresses which aren't reachable
+// from the internet.
+pub fn is_public_address(address: &IpAddr) -> bool {
+    match *address {
+        IpAddr::V4(ref address) => {
+            let octets = address.octets();
+            !(address.is_loopback() || address.is_private() || address.is_link_local() ||
+              address.is_unspecified() || address.is_broadcast() || address.is_multicast() ||
+              octets[0] == 0 || (octets[0] == 100 && (octets[1] & 0xc0) == 64))
+        },
+        IpAddr::V6(ref address) => {
+            if let Some(ref mapped) = address.to_ipv4() {
+                return is_public_address(&IpAddr::V4(*mapped));
+            }
+
+            let first_segment = address.segments()[0];
+            !(address.is_loopback() || address.is_unspecified() || address.is_multicast() ||
+              (first_segment & 0xfe00) == 0xfc00 || (first_segment & 0xffc0) == 0xfe80)
+        },
+    }
+}
+
+// Subscribers must be reachable from the internet, so that webhooks can't be
+// used to make requests to services only the server can see. Hosts such as
+// an internal CI server can be allowed in the config.
+pub fn is_allowed_url(url: &str, allowed_hosts: &[String]) -> bool {
+    let url = match hyper::Url::parse(url) {
+        Ok(url) => url,
+        Err(_) => return false,
+    };
+
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return false;
+    }
+
+    match url.host_str() {
+        Some(host) if allowed_hosts.iter().any(|x| x == host) => return true,
+        Some(_) => {},
+        None => return false,
+    }
+
+    let addresses: Vec<SocketAddr> = match url.with_default_port(|_| Err(())).and_then(|x| x.to_socket_addrs()) {
+        Ok(addresses) => addresses.collect(),
+        Err(_) => return false,
+    };
+
+    !addresses.is_empty() && addresses.iter().all(|x| is_public_address(&x.ip()))
+}
+
+// Contributors can be removed from a project, or leave a contributing user
+// group, after subscribing to it.
+fn still_contributes(
+    client: &Client,
+    subscription: &webhook_model::WebhookSubscriptionModel,
+) -> Result<bool, EvelynDatabaseError> {
+    let user_groups = match data::user_group::lookup_user_groups(&subscription.user_id, client) {
+        Ok(user_groups) => user_groups.into_iter().map(|x| model::user_group::UserGroupsExternalModel {
+            user_group_id: x.user_group_id,
+            name: x.name,
+            description: x.description,
+        }).collect(),
+        Err(e) => return Err(e),
+    };
+
+    match data::agile::project::lookup(client, &subscription.project_id, &subscription.user_id, user_groups) {
+        Ok(_) => Ok(true),
+        Err(EvelynDatabaseError::AgileProjectNotFound(_)) => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+// Subscriptions of users who no longer contribute to the project are
+// removed rather than sent anything more.
+fn check_subscription_access(
+    client: &Client,
+    subscription: &webhook_model::WebhookSubscriptionModel,
+) -> Result<bool, EvelynDatabaseError> {
+    match still_contributes(client, subscription) {
+        Ok(true) => Ok(true),
+        Ok(false) => {
+            info!("Removing webhook [{}] as its user no longer contributes to project [{}]",
+                  subscription.subscription_id, subscription.project_id);
+
+            match webhook_data::remove_subscription(client, &subscription.user_id, &subscription.subscription_id) {
+                None | Some(EvelynDatabaseError::WebhookNotFound(_)) => Ok(false),
+                Some(e) => Err(e),
+            }
+        },
+        Err(e) => Err(e),
+    }
+}
+
+// Changes must not be held up by their webhooks, failures to queue a
+// delivery are logged rather than returned.
+pub fn dispatch(
+    client: &Client,
+    project_id: &String,
+    event_type: webhook_model::WebhookEventTypeModel,
+    entity_id: &String,
+    title: &String,
+    user_id: Option<&String>,
+    details: Vec<(&'static str, String)>,
+) {
+    let subscriptions = match webhook_data::lookup_subscriptions_for_event(client, project_id, &event_type) {
+        Ok(subscriptions) => subscriptions,
+        Err(e) => {
+            error!("Failed to lookup webhooks for {:?} [{}]: {}", event_type, entity_id, e);
+            return;
+        },
+    };
+
+    let now = dts::get_timestamp();
+    let details: BTreeMap<String, String> = details.into_iter().map(|(name, value)| (String::from(name), value)).collect();
+
+    for subscription in subscriptions {
+        match check_subscription_access(client, &subscription) {
+            Ok(true) => {},
+            Ok(false) => continue,
+            Err(e) => {
+                error!("Failed to check access to webhook [{}] for {:?} [{}]: {}", subscription.subscription_id, event_type, entity_id, e);
+                continue;
+            },
+        }
+
+        let delivery_id = format!("{}", Uuid::new_v4());
+
+        let payload = webhook_model::WebhookPayloadExternalModel {
+            delivery_id: delivery_id.clone(),
+            event_type: to_event_type_external_model(event_type.clone()),
+            project_id: project_id.clone(),
+            entity_id: entity_id.clone(),
+            title: title.clone(),
+            user_id: user_id.cloned(),
+            date: dts::timestamp_to_string(now, &dts::UTC),
+            details: details.clone(),
+        };
+
+        let payload = match serde_json::to_string(&payload) {
+            Ok(payload) => payload,
+            Err(e) => {
+                error!("Failed to encode webhook payload for {:?} [{}]: {}", event_type, entity_id, e);
+                continue;
+            },
+        };
+
+        let delivery = webhook_model::WebhookDeliveryModel {
+            delivery_id: delivery_id,
+            subscription_id: subscription.subscription_id,
+            user_id: subscription.user_id,
+            project_id: project_id.clone(),
+            event_type: event_type.clone(),
+            payload: payload,
+            status: webhook_model::WebhookDeliveryStatusModel::Pending,
+            attempts: 0,
+            next_attempt_at: now,
+            last_error: None,
+            response_status: None,
+            date_created: now,
+            date_delivered: None,
+        };
+
+        if let Some(e) = webhook_data::insert_delivery(client, &delivery) {
+            error!("Failed to queue webhook delivery for {:?} [{}]: {}", event_type, entity_id, e);
+        }
+    }
+}
+
+// Subscriptions can only be made to projects the user contributes to.
+fn check_project_access(
+    project_id: &String,
+    session_token_model: &model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Option<EvelynCoreError> {
+    let project_session_token_model = model::SessionTokenModel {
+        user_id: session_token_model.user_id.clone(),
+        server_session_token: session_token_model.server_session_token.clone(),
+    };
+
+    match project::lookup_contributing_to(project_session_token_model, processor_data) {
+        Ok(result) if result.projects.iter().any(|x| &x.project_id == project_id) => None,
+        Ok(_) => Some(EvelynCoreError::InvalidWebhook(EvelynBaseError::NothingElse)),
+        Err(e) => Some(e),
+    }
+}
+
+pub fn add_webhook(
+    model: webhook_model::AddWebhookRequestModel,
+    session_token_model: model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Result<webhook_model::AddWebhookResponseModel, EvelynCoreError> {
+    let allowed_hosts = processor_data.conf.get_webhook_allowed_hosts();
+    if !is_allowed_url(&model.url, &allowed_hosts) || model.secret.is_empty() || model.event_types.is_empty() {
+        return Err(EvelynCoreError::InvalidWebhook(EvelynBaseError::NothingElse));
+    }
+
+    if let Some(e) = check_project_access(&model.project_id, &session_token_model, processor_data.clone()) {
+        return Err(e);
+    }
+
+    let mut event_types: Vec<webhook_model::WebhookEventTypeModel> = Vec::new();
+    for event_type in model.event_types.into_iter().map(to_event_type_model) {
+        if !event_types.contains(&event_type) {
+            event_types.push(event_type);
+        }
+    }
+
+    let subscription = webhook_model::WebhookSubscriptionModel {
+        subscription_id: format!("{}", Uuid::new_v4()),
+        user_id: session_token_model.user_id,
+        project_id: model.project_id,
+        url: model.url,
+        secret: model.secret,
+        event_types: event_types,
+        date_created: dts::get_timestamp(),
+    };
+
+    match webhook_data::insert_subscription(&processor_data.data_store, &subscription) {
+        None => Ok(webhook_model::AddWebhookResponseModel {
+            subscription_id: Some(subscription.subscription_id),
+            error: None,
+        }),
+        Some(e) => Err(EvelynCoreError::FailedToAddWebhook(e)),
+    }
+}
+
+pub fn lookup_webhooks(
+    session_token_model: model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Result<webhook_model::LookupWebhooksResponseModel, EvelynCoreError> {
+    let ds = processor_data.data_store.clone();
+    let zone = user::lookup_time_zone(&ds, &session_token_model.user_id);
+
+    match webhook_data::lookup_subscriptions(&ds, &session_token_model.user_id) {
+        Ok(subscriptions) => Ok(webhook_model::LookupWebhooksResponseModel {
+            subscriptions: subscriptions.into_iter().map(|x| webhook_model::WebhookSubscriptionExternalModel {
+                subscription_id: x.subscription_id,
+                project_id: x.project_id,
+                url: x.url,
+                event_types: x.event_types.into_iter().map(to_event_type_external_model).collect(),
+                date_created: dts::timestamp_to_string(x.date_created, &zone),
+            }).collect(),
+            error: None,
+        }),
+        Err(e) => Err(EvelynCoreError::FailedToLookupWebhooks(e)),
+    }
+}
+
+pub fn remove_webhook(
+    model: webhook_model::RemoveWebhookRequestModel,
+    session_token_model: model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Option<EvelynCoreError> {
+    match webhook_data::remove_subscription(&processor_data.data_store, &session_token_model.user_id, &model.subscription_id) {
+        None => None,
+        Some(EvelynDatabaseError::WebhookNotFound(e)) => Some(EvelynCoreError::WebhookNotFound(e)),
+        Some(e) => Some(EvelynCoreError::FailedToRemoveWebhook(e)),
+    }
+}
+
+pub fn lookup_deliveries(
+    model: webhook_model::LookupWebhookDeliveriesRequestModel,
+    session_token_model: model::SessionTokenModel,
+    processor_data: Arc<ProcessorData>,
+) -> Result<webhook_model::LookupWebhookDeliveriesResponseModel, EvelynCoreError> {
+    let ds = processor_data.data_store.clone();
+    let zone = user::lookup_time_zone(&ds, &session_token_model.user_id);
+
+    match webhook_data::lookup_subscription(&ds, &session_token_model.user_id, &model.subscription_id) {
+        Ok(_) => {},
+        Err(EvelynDatabaseError::WebhookNotFound(e)) => return Err(EvelynCoreError::WebhookNotFound(e)),
+        Err(e) => return Err(EvelynCoreError::FailedToLookupWebhookDeliveries(e)),
+    }
+
+    match webhook_data::lookup_deliveries(&ds, &model.subscription_id, model.limit) {
+        Ok(deliveries) => Ok(webhook_model::LookupWebhookDeliveriesResponseModel {
+            deliveries: deliveries.into_iter().map(|x| {
+                let next_attempt_at = if x.status == webhook_model::WebhookDeliveryStatusModel::Pending {
+                    Some(dts::timestamp_to_string(x.next_attempt_at, &zone))
+                } else {
+                    None
+                };
+
+                webhook_model::WebhookDeliveryExternalModel {
+                    delivery_id: x.delivery_id,
+                    event_type: to_event_type_external_model(x.event_type),
+                    status: to_status_external_model(x.status),
+                    attempts: x.attempts,
+                    last_error: x.last_error,
+                    response_status: x.response_status,
+                    date_created: dts::timestamp_to_string(x.date_created, &zone),
+                    next_attempt_at: next_attempt_at,
+                    date_delivered: x.date_delivered.map(|date| dts::timestamp_to_string(date, &zone)),
+                }
+            }).collect(),
+            error: None,
+        }),
+        Err(e) => Err(EvelynCoreError::FailedToLookupWebhookDeliveries(e)),
+    }
+}
+
+// Returns the response status, anything but a 2xx is a failed attempt.
+fn post(
+    url: &str,
+    delivery: &webhook_model::WebhookDeliveryModel,
+    signature: String,
+) -> Result<u16, String> {
+    let ssl = match OpensslClient::new() {
+        Ok(ssl) => ssl,
+        Err(e) => return Err(format!("{}", e)),
+    };
+
+    let mut client = hyper::Client::with_connector(HttpsConnector::new(ssl));
+    client.set_read_timeout(Some(Duration::from_secs(TIMEOUT_SECONDS)));
+    client.set_write_timeout(Some(Duration::from_secs(TIMEOUT_SECONDS)));
+
+    let mut headers = Headers::new();
+    headers.set(ContentType::json());
+    headers.set_raw(SIGNATURE_HEADER, vec![format!("sha256={}", signature).into_bytes()]);
+    headers.set_raw(EVENT_HEADER, vec![format!("{:?}", to_event_type_external_model(delivery.event_type.clone())).into_bytes()]);
+    headers.set_raw(DELIVERY_HEADER, vec![delivery.delivery_id.clone().into_bytes()]);
+
+    match client.post(url).headers(headers).body(delivery.payload.as_str()).send() {
+        Ok(response) => Ok(response.status.to_u16()),
+        Err(e) => Err(format!("{}", e)),
+    }
+}
+
+// Sends the deliveries which are due. A delivery which fails is tried again
+// on a later run, unless it has failed too many times or its address is no
+// longer allowed.
+pub fn send_deliveries(
+    client: &Client,
+    max_attempts: i32,
+    allowed_hosts: &[String],
+) -> Option<EvelynCoreError> {
+    let now = dts::get_timestamp();
+
+    let deliveries = match webhook_data::lookup_pending_deliveries(client, now) {
+        Ok(deliveries) => deliveries,
+        Err(e) => return Some(EvelynCoreError::FailedToSendWebhooks(e)),
+    };
+
+    for delivery in deliveries {
+        match webhook_data::claim_delivery(client, &delivery.delivery_id, delivery.attempts) {
+            Ok(true) => {},
+            Ok(false) => continue,
+            Err(e) => return Some(EvelynCoreError::FailedToSendWebhooks(e)),
+        }
+
+        let attempts = delivery.attempts + 1;
+
+        let (result, give_up) = match webhook_data::find_subscription(client, &delivery.subscription_id) {
+            Ok(Some(subscription)) => {
+                // The delivery log goes with the subscription if access has
+                // been lost since the delivery was queued.
+                match check_subscription_access(client, &subscription) {
+                    Ok(true) => {},
+                    Ok(false) => continue,
+                    Err(e) => return Some(EvelynCoreError::FailedToSendWebhooks(e)),
+                }
+
+                // Checked again as the host may now resolve to somewhere
+                // else.
+                if !is_allowed_url(&subscription.url, allowed_hosts) {
+                    (Err(String::from("The webhook URL is not allowed")), true)
+                } else {
+                    (sign(&subscription.secret, &delivery.payload).and_then(|signature| post(&subscription.url, &delivery, signature)), false)
+                }
+            },
+            // Removed since the delivery was queued, so there is nowhere to
+            // send it.
+            Ok(None) => continue,
+            Err(e) => return Some(EvelynCoreError::FailedToSendWebhooks(e)),
+        };
+
+        let (response_status, last_error) = match result {
+            Ok(status) if status >= 200 && status < 300 => (Some(status as i32), None),
+            Ok(status) => (Some(status as i32), Some(format!("The subscriber responded with status {}", status))),
+            Err(e) => (None, Some(e)),
+        };
+
+        let error = match last_error {
+            None => {
+                webhook_data::record_attempt(client, &delivery.delivery_id, &webhook_model::WebhookDeliveryStatusModel::Delivered,
+                                             now, response_status, None, Some(dts::get_timestamp()))
+            },
+            Some(last_error) => {
+                let (status, next_attempt_at) = if give_up || attempts >= max_attempts {
+                    error!("Giving up on webhook delivery [{}] after {} attempt(s): {}", delivery.delivery_id, attempts, last_error);
+                    (webhook_model::WebhookDeliveryStatusModel::Failed, now)
+                } else {
+                    warn!("Failed to deliver webhook [{}], will retry: {}", delivery.delivery_id, last_error);
+                    (webhook_model::WebhookDeliveryStatusModel::Pending, now + scheduler::retry_delay(attempts))
+                };
+
+                webhook_data::record_attempt(client, &delivery.delivery_id, &status, next_attempt_at, response_status, Some(last_error), None)
+            },
+        };
+
+        if let Some(e) = error {
+            return Some(EvelynCoreError::FailedToSendWebhooks(e));
+        }
+    }
+
+    None
+}
+
+// Sprints don't change when they start, so they are looked for instead.
+pub fn announce_started_sprints(client: &Client) -> Option<EvelynCoreError> {
+    let now = dts::get_timestamp();
+
+    let sprints = match data::agile::sprint::lookup_unannounced_started(client, now - SPRINT_START_GRACE_SECONDS, now) {
+        Ok(sprints) => sprints,
+        Err(e) => return Some(EvelynCoreError::FailedToAnnounceSprints(e)),
+    };
+
+    for sprint in sprints {
+        match data::agile::sprint::mark_start_announced(client, &sprint.sprint_id) {
+            Ok(true) => {
+                dispatch(client, &sprint.project_id, webhook_model::WebhookEventTypeModel::SprintStarted, &sprint.sprint_id,
+                         &sprint.title, None, vec![
+                    ("StartDate", dts::timestamp_to_string(sprint.start_date, &dts::UTC)),
+                    ("EndDate", dts::timestamp_to_string(sprint.end_date, &dts::UTC)),
+                ]);
+            },
+            Ok(false) => {},
+            Err(e) => return Some(EvelynCoreError::FailedToAnnounceSprints(e)),
+        }
+    }
+
+    None
+}
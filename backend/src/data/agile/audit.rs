@@ -0,0 +1,66 @@
+// Evelyn: Your personal assistant, project manager and calendar
+// Copyright (C) 2017 Gregory Jensen
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use bson;
+use bson::{Bson, Document};
+use core::error_messages::{EvelynBaseError, EvelynDatabaseError};
+use data::cursor;
+use data::cursor::PartialResults;
+use model::agile::audit as audit_model;
+use mongodb::{Client, ThreadedClient};
+use mongodb::coll::options::FindOptions;
+use mongodb::db::ThreadedDatabase;
+use serde_json::to_string;
+
+pub fn insert_entry(
+    client: &Client,
+    model: &audit_model::AuditEntryModel,
+) -> Option<EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("agile_audit");
+
+    insert_model!(
+        collection,
+        model,
+        EvelynDatabaseError::InsertAgileAuditEntry
+    )
+}
+
+pub fn lookup_entries(
+    client: &Client,
+    project_id: &String,
+    entity_type: &audit_model::AuditEntityTypeModel,
+    entity_id: &String,
+) -> Result<Vec<audit_model::AuditEntryModel>, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("agile_audit");
+
+    let type_name = to_string(entity_type)
+        .unwrap()
+        .trim_matches('\"')
+        .to_owned();
+    let filter = doc!{"projectId" => project_id, "entityType" => type_name, "entityId" => entity_id};
+
+    let mut find_options = FindOptions::new();
+
+    let mut sort = Document::new();
+    sort.insert("date", Bson::I32(1));
+    sort.insert("_id", Bson::I32(1));
+    find_options.sort = Some(sort);
+
+    match collection.find(Some(filter), Some(find_options)) {
+        Ok(c) => cursor::collect(c, EvelynDatabaseError::LookupAgileAuditEntries, PartialResults::Deny, "lookup agile audit entries"),
+        Err(e) => Err(EvelynDatabaseError::LookupAgileAuditEntries(e)),
+    }
+}
@@ -17,6 +17,8 @@
 use bson;
 use bson::{Bson, Document};
 use core::error_messages::{EvelynBaseError, EvelynDatabaseError};
+use data::cursor;
+use data::cursor::PartialResults;
 use model::agile::heirarchy as heirarchy_model;
 use mongodb::{Client, ThreadedClient};
 use mongodb::coll::options::FindOptions;
@@ -51,26 +53,26 @@ pub fn lookup_link_to(
     projection.insert("_id", Bson::I32(1));
     find_options.projection = Some(projection);
 
-    let cursor = collection.find(Some(filter), Some(find_options));
-
-    match cursor {
-        Ok(cursor) => {
-            Ok(cursor.filter_map(|x| {
+    match collection.find(Some(filter), Some(find_options)) {
+        Ok(c) => {
+            let mut ids = Vec::new();
+            for x in c {
                 match x {
                     Ok(x) => {
                         match x.get("_id") {
-                            Some(&Bson::ObjectId(ref id)) => Some(heirarchy_model::LinkDbIdModel {
+                            Some(&Bson::ObjectId(ref id)) => ids.push(heirarchy_model::LinkDbIdModel {
                                 _id: id.to_hex(),
                             }),
-                            _ => None,
+                            _ => warn!("Skipping agile heirarchy link without a database id [{}]", cursor::document_id(&x)),
                         }
                     },
                     Err(e) => {
                         error!("Database error in lookup agile heirarchy link to {}", e);
-                        None
+                        return Err(EvelynDatabaseError::LookupAgileHeirarchyLinkTo(e));
                     },
                 }
-            }).collect())
+            }
+            Ok(ids)
         },
         Err(e) => Err(EvelynDatabaseError::LookupAgileHeirarchyLinkTo(e)),
     }
@@ -115,22 +117,8 @@ pub fn lookup_links(
         .to_owned();
     let filter = doc!{"projectId" => project_id, "linkFromTypeName" => type_name, "linkFromId" => link_from_id};
 
-    let cursor = collection.find(Some(filter), None);
-
-    match cursor {
-        Ok(cursor) => {
-            Ok(cursor.filter_map(|x| {
-                match x {
-                    Ok(x) => {
-                        Some(bson::from_bson(bson::Bson::Document(x)).unwrap())
-                    },
-                    Err(e) => {
-                        error!("Database error in lookup agile heirarchy links {}", e);
-                        None
-                    },
-                }
-            }).collect())
-        },
+    match collection.find(Some(filter), None) {
+        Ok(c) => cursor::collect(c, EvelynDatabaseError::LookupAgileHeirarchyLinks, PartialResults::Deny, "lookup agile heirarchy links"),
         Err(e) => Err(EvelynDatabaseError::LookupAgileHeirarchyLinks(e)),
     }
 }
@@ -148,22 +136,8 @@ pub fn lookup_links_to_type(
         .to_owned();
     let filter = doc!{"projectId" => project_id, "linkToTypeName" => type_name};
 
-    let cursor = collection.find(Some(filter), None);
-
-    match cursor {
-        Ok(cursor) => {
-            Ok(cursor.filter_map(|x| {
-                match x {
-                    Ok(x) => {
-                        Some(bson::from_bson(bson::Bson::Document(x)).unwrap())
-                    },
-                    Err(e) => {
-                        error!("Database error in lookup agile heirarchy links {}", e);
-                        None
-                    },
-                }
-            }).collect())
-        },
+    match collection.find(Some(filter), None) {
+        Ok(c) => cursor::collect(c, EvelynDatabaseError::LookupAgileHeirarchyLinksToType, PartialResults::Deny, "lookup agile heirarchy links to type"),
         Err(e) => Err(EvelynDatabaseError::LookupAgileHeirarchyLinksToType(e)),
     }
 }
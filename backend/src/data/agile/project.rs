@@ -17,6 +17,8 @@
 use bson;
 use bson::{Bson, Document};
 use core::error_messages::{EvelynBaseError, EvelynDatabaseError};
+use data::cursor;
+use data::cursor::PartialResults;
 use model;
 use model::agile::project as project_model;
 use mongodb::{Client, ThreadedClient};
@@ -138,27 +140,10 @@ pub fn lookup_contributing_to(
     projection.insert("name", Bson::I32(1));
     projection.insert("shortName", Bson::I32(1));
     projection.insert("description", Bson::I32(1));
-    projection.insert("_id", Bson::I32(0));
     find_options.projection = Some(projection);
 
-    let cursor = collection.find(Some(filter), Some(find_options));
-
-    match cursor {
-        Ok(cursor) => {
-            Ok(cursor
-                   .map(|x| {
-                match x {
-                    Ok(x) => {
-                        debug!("{}", x);
-                        bson::from_bson(bson::Bson::Document(x)).unwrap()
-                    },
-                    Err(e) => {
-                        error!("Database error in lookup agile projects {}", e);
-                        panic!() // need a better way to handle this ideally.
-                    },
-                }
-            }).collect())
-        },
+    match collection.find(Some(filter), Some(find_options)) {
+        Ok(c) => cursor::collect(c, EvelynDatabaseError::LookupContributingToAgileProjects, PartialResults::Deny, "lookup contributing to agile projects"),
         Err(e) => Err(EvelynDatabaseError::LookupContributingToAgileProjects(e)),
     }
 }
@@ -177,7 +162,7 @@ pub fn lookup(
     match collection.find_one(Some(filter), None) {
         Ok(result) => {
             if let Some(result) = result {
-                Ok(bson::from_bson(bson::Bson::Document(result)).unwrap())
+                cursor::decode_document(result)
             }
             else {
                 Err(EvelynDatabaseError::AgileProjectNotFound(EvelynBaseError::NothingElse))
@@ -186,3 +171,19 @@ pub fn lookup(
         Err(e) => Err(EvelynDatabaseError::LookupAgileProject(e)),
     }
 }
+
+pub fn lookup_created_by(
+    client: &Client,
+    project_id: &String,
+    user_id: &String,
+) -> Result<project_model::ProjectModel, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("agile_project");
+
+    let filter = doc!{"projectId" => project_id, "createdByUserId" => user_id};
+
+    match collection.find_one(Some(filter), None) {
+        Ok(Some(result)) => cursor::decode_document(result),
+        Ok(None) => Err(EvelynDatabaseError::AgileProjectNotFound(EvelynBaseError::NothingElse)),
+        Err(e) => Err(EvelynDatabaseError::LookupAgileProject(e)),
+    }
+}
@@ -17,6 +17,8 @@
 use bson;
 use bson::{Bson, Document};
 use core::error_messages::{EvelynBaseError, EvelynDatabaseError};
+use data::cursor;
+use data::cursor::PartialResults;
 use model::agile::sprint as sprint_model;
 use mongodb::{Client, ThreadedClient};
 use mongodb::db::ThreadedDatabase;
@@ -37,6 +39,19 @@ pub fn insert_sprint(
     )
 }
 
+pub fn lookup_sprint(
+    client: &Client,
+    sprint_id: &String,
+) -> Result<sprint_model::SprintModel, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("agile_sprint");
+
+    match collection.find_one(Some(doc!{"sprintId" => sprint_id}), None) {
+        Ok(Some(document)) => cursor::decode_document(document),
+        Ok(None) => Err(EvelynDatabaseError::AgileSprintNotFound(EvelynBaseError::NothingElse)),
+        Err(e) => Err(EvelynDatabaseError::LookupAgileSprints(e)),
+    }
+}
+
 pub fn find_active(
     client: &Client,
     project_ids: &Vec<String>,
@@ -59,18 +74,8 @@ pub fn find_active(
     let before_end_time = current_time.clone();
     query.insert("endDate", doc!{"$gte" => before_end_time});
 
-    let cursor = collection.find(Some(query), None);
-
-    match cursor {
-        Ok(c) => {
-            Ok(c.map(|x| match x {
-                Ok(x) => bson::from_bson(bson::Bson::Document(x)).unwrap(),
-                Err(e) => {
-                    println!("Database error in lookup active agile sprints {}", e);
-                    panic!()
-                },
-            }).collect())
-        },
+    match collection.find(Some(query), None) {
+        Ok(c) => cursor::collect(c, EvelynDatabaseError::LookupActiveAgileSprints, PartialResults::Deny, "lookup active agile sprints"),
         Err(e) => Err(EvelynDatabaseError::LookupActiveAgileSprints(e)),
     }
 }
@@ -88,15 +93,61 @@ pub fn lookup_backlog(
     query.insert("startDate", doc!{"$gt" => current_time});
 
     match collection.find(Some(query), None) {
-        Ok(cursor) => {
-            Ok(cursor.map(|x| match x {
-                Ok(x) => bson::from_bson(bson::Bson::Document(x)).unwrap(),
-                Err(e) => {
-                    println!("Database error in lookup backlog agile sprints {}", e);
-                    panic!()
-                },
-            }).collect())
-        },
+        Ok(c) => cursor::collect(c, EvelynDatabaseError::LookupBacklogAgileSprints, PartialResults::Deny, "lookup backlog agile sprints"),
         Err(e) => Err(EvelynDatabaseError::LookupBacklogAgileSprints(e)),
     }
 }
+
+pub fn lookup_for_projects(
+    client: &Client,
+    project_ids: &Vec<String>,
+) -> Result<Vec<sprint_model::SprintModel>, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("agile_sprint");
+
+    let mut projects = bson::Array::new();
+    for project in project_ids {
+        projects.push(Bson::String(project.to_owned()));
+    }
+
+    let query = doc!{"projectId" => doc!{"$in" => projects}};
+
+    match collection.find(Some(query), None) {
+        Ok(c) => cursor::collect(c, EvelynDatabaseError::LookupAgileSprints, PartialResults::Deny, "lookup agile sprints for projects"),
+        Err(e) => Err(EvelynDatabaseError::LookupAgileSprints(e)),
+    }
+}
+
+// Sprints which started after the given time and haven't been announced yet.
+pub fn lookup_unannounced_started(
+    client: &Client,
+    started_after: i64,
+    now: i64,
+) -> Result<Vec<sprint_model::SprintModel>, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("agile_sprint");
+
+    let query = doc!{
+        "startDate" => doc!{"$gt" => started_after, "$lte" => now},
+        "startAnnounced" => doc!{"$ne" => true}
+    };
+
+    match collection.find(Some(query), None) {
+        Ok(c) => cursor::collect(c, EvelynDatabaseError::LookupAgileSprints, PartialResults::AllowAndLog, "lookup unannounced started agile sprints"),
+        Err(e) => Err(EvelynDatabaseError::LookupAgileSprints(e)),
+    }
+}
+
+// Returns whether it was this caller which marked the sprint, so that it is
+// only announced once.
+pub fn mark_start_announced(
+    client: &Client,
+    sprint_id: &String,
+) -> Result<bool, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("agile_sprint");
+
+    let filter = doc!{"sprintId" => sprint_id, "startAnnounced" => doc!{"$ne" => true}};
+
+    match collection.update_one(filter, doc!{"$set" => doc!{"startAnnounced" => true}}, None) {
+        Ok(result) => Ok(result.modified_count > 0),
+        Err(e) => Err(EvelynDatabaseError::UpdateAgileSprint(e)),
+    }
+}
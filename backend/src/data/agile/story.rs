@@ -17,6 +17,8 @@
 use bson;
 use bson::{Bson, Document};
 use core::error_messages::{EvelynBaseError, EvelynDatabaseError};
+use data::cursor;
+use data::cursor::PartialResults;
 use model::agile::story as story_model;
 use mongodb::{Client, ThreadedClient};
 use mongodb::db::ThreadedDatabase;
@@ -45,11 +47,9 @@ pub fn lookup_story(
 
     match collection.find_one(Some(query), None) {
         Ok(result) => {
-            if result.is_some() {
-                Ok(bson::from_bson(bson::Bson::Document(result.unwrap())).unwrap())
-            }
-            else {
-                Ok(None)
+            match result {
+                Some(result) => cursor::decode_document(result).map(Some),
+                None => Ok(None),
             }
         },
         Err(e) => Err(EvelynDatabaseError::LookupAgileStory(e)),
@@ -74,18 +74,8 @@ pub fn lookup_backlog(
 
     let query = doc!{"projectId" => project_id, "storyId" => not_in_exclude_story_ids_query};
 
-    let cursor = collection.find(Some(query), None);
-
-    match cursor {
-        Ok(c) => {
-            Ok(c.map(|x| match x {
-                Ok(x) => bson::from_bson(bson::Bson::Document(x)).unwrap(),
-                Err(e) => {
-                    println!("Database error in lookup backlog agile stories {}", e);
-                    panic!()
-                },
-            }).collect())
-        },
+    match collection.find(Some(query), None) {
+        Ok(c) => cursor::collect(c, EvelynDatabaseError::LookupBacklogAgileStories, PartialResults::Deny, "lookup backlog agile stories"),
         Err(e) => Err(EvelynDatabaseError::LookupBacklogAgileStories(e)),
     }
 }
@@ -17,8 +17,12 @@
 use bson;
 use bson::{Bson, Document};
 use core::error_messages::{EvelynBaseError, EvelynDatabaseError};
+use data::cursor;
+use data::cursor::PartialResults;
+use data::version;
 use model::agile::task as task_model;
 use mongodb::{Client, ThreadedClient};
+use mongodb::coll::options::FindOptions;
 use mongodb::db::ThreadedDatabase;
 
 pub fn insert_task(
@@ -46,11 +50,9 @@ pub fn find_task_by_id(
 
     match result {
         Ok(r) => {
-            if r.is_some() {
-                Ok(bson::from_bson(bson::Bson::Document(r.unwrap())).unwrap())
-            } else {
-                // TODO fix me.
-                Ok(None)
+            match r {
+                Some(r) => cursor::decode_document(r).map(Some),
+                None => Ok(None),
             }
         },
         Err(e) => Err(EvelynDatabaseError::LookupAgileTaskById(e)),
@@ -74,27 +76,88 @@ pub fn lookup_backlog(
     let query = doc!{"projectId" => project_id, "taskId" => not_in_exclude_task_ids};
 
     match collection.find(Some(query), None) {
-        Ok(cursor) => {
-            Ok(cursor.map(|x| match x {
-                Ok(x) => bson::from_bson(bson::Bson::Document(x)).unwrap(),
-                Err(e) => {
-                    println!("Database error in lookup backlog agile tasks {}", e);
-                    panic!()
-                },
-            }).collect())
-        },
+        Ok(c) => cursor::collect(c, EvelynDatabaseError::LookupBacklogAgileTasks, PartialResults::Deny, "lookup backlog agile tasks"),
         Err(e) => Err(EvelynDatabaseError::LookupBacklogAgileTasks(e)),
     }
 }
 
+// The tasks among those given which are assigned to the user.
+pub fn lookup_assigned_in(
+    client: &Client,
+    user_id: &String,
+    task_ids: &Vec<String>,
+) -> Result<Vec<task_model::TaskModel>, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("agile_task");
+
+    let ids: bson::Array = task_ids.iter().map(|x| Bson::String(x.clone())).collect();
+    let query = doc!{"taskId" => doc!{"$in" => ids}, "assignment.assignedToUserId" => user_id};
+
+    match collection.find(Some(query), None) {
+        Ok(c) => cursor::collect(c, EvelynDatabaseError::LookupAssignedAgileTasks, PartialResults::Deny, "lookup assigned agile tasks"),
+        Err(e) => Err(EvelynDatabaseError::LookupAssignedAgileTasks(e)),
+    }
+}
+
+// Returns a page of the tasks assigned to the user, in task id order, and
+// whether there are more after it.
+pub fn lookup_assigned(
+    client: &Client,
+    lookup_model: &task_model::AssignedTaskLookupModel,
+) -> Result<(Vec<task_model::TaskModel>, bool), EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("agile_task");
+
+    let ref user_id = lookup_model.user_id;
+    let to_array = |ids: &Vec<String>| -> bson::Array { ids.iter().map(|x| Bson::String(x.clone())).collect() };
+
+    let mut clauses = vec![
+        doc!{"projectId" => doc!{"$in" => to_array(&lookup_model.project_ids)}},
+        doc!{"assignment.assignedToUserId" => user_id},
+        doc!{"taskId" => doc!{"$nin" => to_array(&lookup_model.task_ids_not_in)}},
+    ];
+    if let Some(ref task_ids_in) = lookup_model.task_ids_in {
+        clauses.push(doc!{"taskId" => doc!{"$in" => to_array(task_ids_in)}});
+    }
+    if let Some(ref cursor) = lookup_model.cursor {
+        clauses.push(doc!{"taskId" => doc!{"$gt" => cursor}});
+    }
+
+    let query = doc!{"$and" => Bson::Array(clauses.into_iter().map(Bson::Document).collect())};
+
+    let mut find_options = FindOptions::new();
+    find_options.sort = Some(doc!{"taskId" => Bson::I32(1)});
+
+    // One more than the page size, to find out whether there is another page.
+    if lookup_model.limit > 0 {
+        find_options.limit = Some(lookup_model.limit as i64 + 1);
+    }
+
+    let mut tasks = match collection.find(Some(query), Some(find_options)) {
+        Ok(c) => match cursor::collect(c, EvelynDatabaseError::LookupAssignedAgileTasks, PartialResults::Deny, "lookup assigned agile tasks") {
+            Ok(tasks) => tasks,
+            Err(e) => return Err(e),
+        },
+        Err(e) => return Err(EvelynDatabaseError::LookupAssignedAgileTasks(e)),
+    };
+
+    let more = lookup_model.limit > 0 && tasks.len() > lookup_model.limit as usize;
+    if more {
+        tasks.truncate(lookup_model.limit as usize);
+    }
+
+    Ok((tasks, more))
+}
+
+// Returns the task as it was before the update.
 pub fn update(
     client: &Client,
     update_model: task_model::UpdateTaskModel,
-) -> Option<EvelynDatabaseError> {
+) -> Result<task_model::TaskModel, EvelynDatabaseError> {
     let collection = client.db("evelyn").collection("agile_task");
 
     let ref task_id = update_model.task_id;
-    let filter = doc!("taskId" => task_id);
+    let identity_filter = doc!("taskId" => task_id);
+    let mut filter = identity_filter.clone();
+    version::add_expected_version(&mut filter, update_model.expected_version);
 
     let mut update_query = Document::new();
 
@@ -120,9 +183,20 @@ pub fn update(
 
     let mut set_update_query = Document::new();
     set_update_query.insert("$set", update_query);
-
-    match collection.update_one(filter, set_update_query, None) {
-        Ok(_) => None,
-        Err(e) => Some(EvelynDatabaseError::UpdateAgileTask(e)),
+    version::add_version_increment(&mut set_update_query);
+
+    match collection.find_one_and_update(filter, set_update_query, None) {
+        Ok(Some(before)) => cursor::decode_document(before),
+        Ok(None) => {
+            Err(version::explain_no_match(
+                &collection,
+                identity_filter,
+                update_model.expected_version,
+                EvelynDatabaseError::AgileTaskNotFound(EvelynBaseError::NothingElse),
+                EvelynDatabaseError::AgileTaskVersionConflict(EvelynBaseError::NothingElse),
+                EvelynDatabaseError::UpdateAgileTask,
+            ))
+        },
+        Err(e) => Err(EvelynDatabaseError::UpdateAgileTask(e)),
     }
 }
@@ -15,9 +15,17 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use bson;
+use bson::{Bson, Document};
 use core::error_messages::{EvelynBaseError, EvelynDatabaseError};
+use data::cursor;
+use data::cursor::PartialResults;
+use data::trash;
+use data::version;
 use model::calendar as calendar_model;
+use model::trash::DeletionModel;
 use mongodb::{Client, ThreadedClient};
+use mongodb::coll::Collection;
+use mongodb::coll::options::FindOptions;
 use mongodb::db::ThreadedDatabase;
 
 pub fn insert_calendar_event(
@@ -37,3 +45,511 @@ pub fn insert_calendar_event(
         Some(EvelynDatabaseError::SerialisationFailed(EvelynBaseError::NothingElse))
     }
 }
+
+fn optional_timestamp(timestamp: Option<i64>) -> Bson {
+    match timestamp {
+        Some(timestamp) => Bson::I64(timestamp),
+        None => Bson::Null,
+    }
+}
+
+// Matches events overlapping the range, including events which touch either
+// end. Recurring events match if any part of the series might overlap, the
+// caller works out which occurrences actually do.
+fn build_range_filter(
+    range_begin: i64,
+    range_end: i64,
+) -> Document {
+    let mut ends_in_range = bson::Array::new();
+    ends_in_range.push(Bson::Document(doc!{"eventEnd" => doc!{"$gte" => range_begin}}));
+    ends_in_range.push(Bson::Document(doc!{"recurrence" => doc!{"$ne" => Bson::Null}, "seriesEnd" => Bson::Null}));
+    ends_in_range.push(Bson::Document(doc!{"seriesEnd" => doc!{"$gte" => range_begin}}));
+
+    let mut filter = doc!{"eventBegin" => doc!{"$lte" => range_end}};
+    filter.insert("$or", Bson::Array(ends_in_range));
+    trash::add_not_deleted(&mut filter);
+
+    filter
+}
+
+fn find_in_range(
+    collection: &Collection,
+    filter: Document,
+) -> Result<Vec<calendar_model::CalendarEventModel>, EvelynDatabaseError> {
+    let mut find_options = FindOptions::new();
+
+    let mut sort = Document::new();
+    sort.insert("eventBegin", Bson::I32(1));
+    sort.insert("eventEnd", Bson::I32(1));
+    find_options.sort = Some(sort);
+
+    match collection.find(Some(filter), Some(find_options)) {
+        Ok(c) => cursor::collect(c, EvelynDatabaseError::LookupCalendarEvents, PartialResults::Deny, "lookup calendar events"),
+        Err(e) => Err(EvelynDatabaseError::LookupCalendarEvents(e)),
+    }
+}
+
+// The user's own events in the range.
+pub fn lookup_events(
+    client: &Client,
+    user_id: &String,
+    range_begin: i64,
+    range_end: i64,
+) -> Result<Vec<calendar_model::CalendarEventModel>, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("calendar");
+
+    let mut filter = build_range_filter(range_begin, range_end);
+    filter.insert("userId", user_id);
+
+    find_in_range(&collection, filter)
+}
+
+// Events in the range which any of the users either organise or have been
+// invited to.
+pub fn lookup_events_attended_by(
+    client: &Client,
+    user_ids: &Vec<String>,
+    range_begin: i64,
+    range_end: i64,
+) -> Result<Vec<calendar_model::CalendarEventModel>, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("calendar");
+
+    let ids: bson::Array = user_ids.iter().map(|x| Bson::String(x.clone())).collect();
+
+    let mut attended_by = bson::Array::new();
+    attended_by.push(Bson::Document(doc!{"userId" => doc!{"$in" => ids.clone()}}));
+    attended_by.push(Bson::Document(doc!{"attendees.userId" => doc!{"$in" => ids}}));
+
+    // The range filter already uses $or so the two are combined with $and.
+    let mut conditions = bson::Array::new();
+    conditions.push(Bson::Document(doc!{"$or" => attended_by}));
+
+    let mut filter = build_range_filter(range_begin, range_end);
+    filter.insert("$and", conditions);
+
+    find_in_range(&collection, filter)
+}
+
+pub fn lookup_event(
+    client: &Client,
+    user_id: &String,
+    event_id: &String,
+) -> Result<calendar_model::CalendarEventModel, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("calendar");
+
+    let mut filter = doc!{"userId" => user_id, "eventId" => event_id};
+    trash::add_not_deleted(&mut filter);
+
+    match collection.find_one(Some(filter), None) {
+        Ok(Some(document)) => cursor::decode_document(document),
+        Ok(None) => Err(EvelynDatabaseError::CalendarEventNotFound(EvelynBaseError::NothingElse)),
+        Err(e) => Err(EvelynDatabaseError::LookupCalendarEvent(e)),
+    }
+}
+
+// Whether any event has the id, whoever it belongs to and even if it has
+// been deleted.
+pub fn event_id_exists(
+    client: &Client,
+    event_id: &String,
+) -> Result<bool, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("calendar");
+
+    match collection.count(Some(doc!{"eventId" => event_id}), None) {
+        Ok(count) => Ok(count > 0),
+        Err(e) => Err(EvelynDatabaseError::LookupCalendarEvent(e)),
+    }
+}
+
+pub fn has_invited(
+    client: &Client,
+    organizer_user_id: &String,
+    user_id: &String,
+) -> Result<bool, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("calendar");
+
+    let mut filter = doc!{"userId" => organizer_user_id, "attendees.userId" => user_id};
+    trash::add_not_deleted(&mut filter);
+
+    match collection.count(Some(filter), None) {
+        Ok(count) => Ok(count > 0),
+        Err(e) => Err(EvelynDatabaseError::LookupCalendarEvents(e)),
+    }
+}
+
+// An event the user either organises or has been invited to.
+pub fn lookup_attended_event(
+    client: &Client,
+    user_id: &String,
+    event_id: &String,
+) -> Result<calendar_model::CalendarEventModel, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("calendar");
+
+    let mut attended_by = bson::Array::new();
+    attended_by.push(Bson::Document(doc!{"userId" => user_id}));
+    attended_by.push(Bson::Document(doc!{"attendees.userId" => user_id}));
+
+    let mut filter = doc!{"eventId" => event_id};
+    filter.insert("$or", Bson::Array(attended_by));
+    trash::add_not_deleted(&mut filter);
+
+    match collection.find_one(Some(filter), None) {
+        Ok(Some(document)) => cursor::decode_document(document),
+        Ok(None) => Err(EvelynDatabaseError::CalendarEventNotFound(EvelynBaseError::NothingElse)),
+        Err(e) => Err(EvelynDatabaseError::LookupCalendarEvent(e)),
+    }
+}
+
+pub fn lookup_all_events(
+    client: &Client,
+    user_id: &String,
+) -> Result<Vec<calendar_model::CalendarEventModel>, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("calendar");
+
+    let mut filter = doc!{"userId" => user_id};
+    trash::add_not_deleted(&mut filter);
+
+    match collection.find(Some(filter), None) {
+        Ok(c) => cursor::collect(c, EvelynDatabaseError::LookupCalendarEvents, PartialResults::Deny, "lookup all calendar events"),
+        Err(e) => Err(EvelynDatabaseError::LookupCalendarEvents(e)),
+    }
+}
+
+pub fn lookup_event_by_uid(
+    client: &Client,
+    user_id: &String,
+    uid: &String,
+) -> Result<Option<calendar_model::CalendarEventModel>, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("calendar");
+
+    let mut filter = doc!{"userId" => user_id, "uid" => uid};
+    trash::add_not_deleted(&mut filter);
+
+    match collection.find_one(Some(filter), None) {
+        Ok(Some(document)) => cursor::decode_document(document).map(Some),
+        Ok(None) => Ok(None),
+        Err(e) => Err(EvelynDatabaseError::LookupCalendarEvent(e)),
+    }
+}
+
+// The event is only replaced if it is still at the version it was read at,
+// so changes made in the meantime aren't lost. Attendees are left as stored,
+// responses don't change the version so they could otherwise be overwritten.
+pub fn replace_event(
+    client: &Client,
+    calendar_event_model: &calendar_model::CalendarEventModel,
+    expected_version: i64,
+) -> Option<EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("calendar");
+
+    let ref user_id = calendar_event_model.user_id;
+    let ref event_id = calendar_event_model.event_id;
+    let mut identity_filter = doc!{"userId" => user_id, "eventId" => event_id};
+    trash::add_not_deleted(&mut identity_filter);
+    let mut filter = identity_filter.clone();
+    version::add_expected_version(&mut filter, Some(expected_version));
+
+    match bson::to_bson(calendar_event_model) {
+        Ok(Bson::Document(mut document)) => {
+            document.remove("attendees");
+
+            match collection.update_one(filter, doc!{"$set" => document}, None) {
+                Ok(result) => {
+                    version::check_update_result(
+                        &collection,
+                        result,
+                        identity_filter,
+                        Some(expected_version),
+                        EvelynDatabaseError::CalendarEventNotFound(EvelynBaseError::NothingElse),
+                        EvelynDatabaseError::CalendarEventVersionConflict(EvelynBaseError::NothingElse),
+                        EvelynDatabaseError::ReplaceCalendarEvent,
+                    )
+                },
+                Err(e) => Some(EvelynDatabaseError::ReplaceCalendarEvent(e)),
+            }
+        },
+        Ok(_) => Some(EvelynDatabaseError::SerialisationFailed(EvelynBaseError::NothingElse)),
+        Err(e) => Some(EvelynDatabaseError::BSONEncodeFailed(e)),
+    }
+}
+
+pub fn update_event(
+    client: &Client,
+    update_model: calendar_model::CalendarEventUpdateModel,
+) -> Option<EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("calendar");
+
+    let ref user_id = update_model.user_id;
+    let ref event_id = update_model.event_id;
+    let mut identity_filter = doc!{"userId" => user_id, "eventId" => event_id};
+    trash::add_not_deleted(&mut identity_filter);
+    let mut filter = identity_filter.clone();
+    version::add_expected_version(&mut filter, update_model.expected_version);
+
+    let mut update_query = Document::new();
+
+    if let Some(title) = update_model.title {
+        update_query.insert("title", Bson::String(title));
+    }
+    if let Some(schedule) = update_model.schedule {
+        update_query.insert("eventBegin", Bson::I64(schedule.event_begin));
+        update_query.insert("eventEnd", Bson::I64(schedule.event_end));
+        update_query.insert("recurrence", match schedule.recurrence {
+            Some(recurrence) => Bson::String(recurrence),
+            None => Bson::Null,
+        });
+        update_query.insert("timeZone", match schedule.time_zone {
+            Some(time_zone) => Bson::String(time_zone),
+            None => Bson::Null,
+        });
+        update_query.insert("seriesEnd", optional_timestamp(schedule.series_end));
+
+        if schedule.clear_exceptions {
+            update_query.insert("exceptionDates", Bson::Array(bson::Array::new()));
+            update_query.insert("overrides", Bson::Array(bson::Array::new()));
+        }
+    }
+
+    let mut set_update_query = Document::new();
+    set_update_query.insert("$set", update_query);
+    version::add_version_increment(&mut set_update_query);
+
+    match collection.update_one(filter, set_update_query, None) {
+        Ok(result) => {
+            version::check_update_result(
+                &collection,
+                result,
+                identity_filter,
+                update_model.expected_version,
+                EvelynDatabaseError::CalendarEventNotFound(EvelynBaseError::NothingElse),
+                EvelynDatabaseError::CalendarEventVersionConflict(EvelynBaseError::NothingElse),
+                EvelynDatabaseError::UpdateCalendarEvent,
+            )
+        },
+        Err(e) => Some(EvelynDatabaseError::UpdateCalendarEvent(e)),
+    }
+}
+
+pub fn update_occurrences(
+    client: &Client,
+    update_model: calendar_model::CalendarEventOccurrencesUpdateModel,
+) -> Option<EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("calendar");
+
+    let ref user_id = update_model.user_id;
+    let ref event_id = update_model.event_id;
+    let mut identity_filter = doc!{"userId" => user_id, "eventId" => event_id};
+    trash::add_not_deleted(&mut identity_filter);
+    let mut filter = identity_filter.clone();
+    version::add_expected_version(&mut filter, Some(update_model.expected_version));
+
+    let overrides = match bson::to_bson(&update_model.overrides) {
+        Ok(overrides) => overrides,
+        Err(e) => return Some(EvelynDatabaseError::BSONEncodeFailed(e)),
+    };
+
+    let mut update_query = Document::new();
+    update_query.insert("exceptionDates", Bson::Array(update_model.exception_dates.iter().map(|x| Bson::I64(*x)).collect()));
+    update_query.insert("overrides", overrides);
+    update_query.insert("seriesEnd", optional_timestamp(update_model.series_end));
+
+    let mut set_update_query = Document::new();
+    set_update_query.insert("$set", update_query);
+    version::add_version_increment(&mut set_update_query);
+
+    match collection.update_one(filter, set_update_query, None) {
+        Ok(result) => {
+            version::check_update_result(
+                &collection,
+                result,
+                identity_filter,
+                Some(update_model.expected_version),
+                EvelynDatabaseError::CalendarEventNotFound(EvelynBaseError::NothingElse),
+                EvelynDatabaseError::CalendarEventVersionConflict(EvelynBaseError::NothingElse),
+                EvelynDatabaseError::UpdateCalendarEventOccurrences,
+            )
+        },
+        Err(e) => Some(EvelynDatabaseError::UpdateCalendarEventOccurrences(e)),
+    }
+}
+
+pub fn remove_event(
+    client: &Client,
+    user_id: &String,
+    event_id: &String,
+    deletion: &DeletionModel,
+) -> Option<EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("calendar");
+
+    let mut filter = doc!{"userId" => user_id, "eventId" => event_id};
+    trash::add_not_deleted(&mut filter);
+
+    let update_query = match trash::build_mark_deleted_update(deletion) {
+        Ok(update_query) => update_query,
+        Err(e) => return Some(e),
+    };
+
+    match collection.update_one(filter, update_query, None) {
+        Ok(ref result) if result.matched_count == 0 => Some(EvelynDatabaseError::CalendarEventNotFound(EvelynBaseError::NothingElse)),
+        Ok(_) => None,
+        Err(e) => Some(EvelynDatabaseError::RemoveCalendarEvent(e)),
+    }
+}
+
+pub fn lookup_deleted(
+    client: &Client,
+    user_id: &String,
+    deleted_since: i64,
+) -> Result<Vec<calendar_model::CalendarEventModel>, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("calendar");
+
+    let mut filter = doc!{"userId" => user_id};
+    trash::add_deleted_since(&mut filter, deleted_since);
+
+    match collection.find(Some(filter), None) {
+        Ok(c) => cursor::collect(c, EvelynDatabaseError::LookupTrash, PartialResults::Deny, "lookup deleted calendar events"),
+        Err(e) => Err(EvelynDatabaseError::LookupTrash(e)),
+    }
+}
+
+pub fn restore(
+    client: &Client,
+    user_id: &String,
+    event_id: &String,
+    deleted_since: i64,
+) -> Option<EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("calendar");
+
+    let mut filter = doc!{"userId" => user_id, "eventId" => event_id};
+    trash::add_deleted_since(&mut filter, deleted_since);
+
+    match collection.update_one(filter, trash::build_restore_update(), None) {
+        Ok(ref result) if result.matched_count == 0 => Some(EvelynDatabaseError::TrashItemNotFound(EvelynBaseError::NothingElse)),
+        Ok(_) => None,
+        Err(e) => Some(EvelynDatabaseError::RestoreFromTrash(e)),
+    }
+}
+
+pub fn purge_deleted(
+    client: &Client,
+    deleted_before: i64,
+) -> Result<i32, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("calendar");
+
+    trash::purge_deleted_before(&collection, deleted_before).map_err(EvelynDatabaseError::PurgeTrash)
+}
+
+// Events used to be stored with their times as strings. Converts any which
+// are left, returning how many were converted.
+pub fn migrate_string_times(
+    client: &Client,
+    parse: fn(&str) -> Option<i64>,
+) -> Result<i32, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("calendar");
+
+    let filter = doc!{"eventBegin" => doc!{"$type" => cursor::BSON_TYPE_STRING}};
+
+    let c = match collection.find(Some(filter), None) {
+        Ok(c) => c,
+        Err(e) => return Err(EvelynDatabaseError::MigrateCalendarEvents(e)),
+    };
+
+    let mut migrated = 0;
+    for item in c {
+        let document = match item {
+            Ok(document) => document,
+            Err(e) => return Err(EvelynDatabaseError::MigrateCalendarEvents(e)),
+        };
+
+        let times = match (document.get("eventBegin"), document.get("eventEnd")) {
+            (Some(&Bson::String(ref begin)), Some(&Bson::String(ref end))) => (parse(begin), parse(end)),
+            _ => (None, None),
+        };
+
+        if let (Some(begin), Some(end)) = times {
+            let mut update_query = Document::new();
+            update_query.insert("eventBegin", Bson::I64(begin));
+            update_query.insert("eventEnd", Bson::I64(end));
+
+            let identity_filter = match document.get("_id") {
+                Some(id) => doc!{"_id" => id.clone()},
+                None => continue,
+            };
+
+            match collection.update_one(identity_filter, doc!{"$set" => update_query}, None) {
+                Ok(_) => migrated += 1,
+                Err(e) => return Err(EvelynDatabaseError::MigrateCalendarEvents(e)),
+            }
+        } else {
+            warn!("Skipping calendar event with unreadable times [{}]", cursor::document_id(&document));
+        }
+    }
+
+    Ok(migrated)
+}
+
+// Only the new attendees are pushed, so responses given since the event was
+// read are kept.
+pub fn add_attendees(
+    client: &Client,
+    update_model: calendar_model::CalendarEventAttendeesUpdateModel,
+) -> Option<EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("calendar");
+
+    let ref user_id = update_model.user_id;
+    let ref event_id = update_model.event_id;
+    let mut identity_filter = doc!{"userId" => user_id, "eventId" => event_id};
+    trash::add_not_deleted(&mut identity_filter);
+    let mut filter = identity_filter.clone();
+    version::add_expected_version(&mut filter, Some(update_model.expected_version));
+
+    let attendees = match bson::to_bson(&update_model.attendees) {
+        Ok(attendees) => attendees,
+        Err(e) => return Some(EvelynDatabaseError::BSONEncodeFailed(e)),
+    };
+
+    let mut push_update_query = doc!{"$push" => doc!{"attendees" => doc!{"$each" => attendees}}};
+    version::add_version_increment(&mut push_update_query);
+
+    match collection.update_one(filter, push_update_query, None) {
+        Ok(result) => {
+            version::check_update_result(
+                &collection,
+                result,
+                identity_filter,
+                Some(update_model.expected_version),
+                EvelynDatabaseError::CalendarEventNotFound(EvelynBaseError::NothingElse),
+                EvelynDatabaseError::CalendarEventVersionConflict(EvelynBaseError::NothingElse),
+                EvelynDatabaseError::UpdateCalendarEventAttendees,
+            )
+        },
+        Err(e) => Some(EvelynDatabaseError::UpdateCalendarEventAttendees(e)),
+    }
+}
+
+// Responses belong to the attendee so they don't change the event's version,
+// an organizer editing the event at the same time shouldn't get a conflict.
+// Nothing else writes the stored attendees back, so responses aren't lost to
+// an edit or invite made against an older version.
+pub fn respond(
+    client: &Client,
+    user_id: &String,
+    event_id: &String,
+    response: &calendar_model::AttendeeResponseModel,
+) -> Option<EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("calendar");
+
+    let mut filter = doc!{"eventId" => event_id, "attendees.userId" => user_id};
+    trash::add_not_deleted(&mut filter);
+
+    let response = match bson::to_bson(response) {
+        Ok(response) => response,
+        Err(e) => return Some(EvelynDatabaseError::BSONEncodeFailed(e)),
+    };
+
+    match collection.update_one(filter, doc!{"$set" => doc!{"attendees.$.response" => response}}, None) {
+        Ok(ref result) if result.matched_count == 0 => Some(EvelynDatabaseError::NotInvitedToCalendarEvent(EvelynBaseError::NothingElse)),
+        Ok(_) => None,
+        Err(e) => Some(EvelynDatabaseError::RespondToCalendarEvent(e)),
+    }
+}
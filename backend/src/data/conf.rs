@@ -27,6 +27,23 @@ impl Conf {
         c.set_default("host", "localhost").unwrap();
         c.set_default("db_connection_string", "mongodb://localhost:27017").unwrap();
         c.set_default("use_ssl", "true").unwrap();
+        c.set_default("allow_destructive_purge", "false").unwrap();
+        c.set_default("trash_retention_days", "30").unwrap();
+        c.set_default("trash_purge_interval_seconds", "3600").unwrap();
+        c.set_default("reminder_interval_seconds", "60").unwrap();
+        // Email is only sent when an SMTP relay is configured.
+        c.set_default("smtp_host", "").unwrap();
+        c.set_default("smtp_port", "25").unwrap();
+        c.set_default("smtp_user_name", "").unwrap();
+        c.set_default("smtp_password", "").unwrap();
+        c.set_default("email_from_address", "evelyn@localhost").unwrap();
+        c.set_default("email_outbox_interval_seconds", "30").unwrap();
+        c.set_default("email_max_attempts", "10").unwrap();
+        c.set_default("webhook_interval_seconds", "10").unwrap();
+        c.set_default("webhook_max_attempts", "8").unwrap();
+        // Comma separated hosts which webhooks may be sent to even though
+        // they aren't reachable from the internet.
+        c.set_default("webhook_allowed_hosts", "").unwrap();
 
         println!("Reading config from {}", filename);
         c.merge(config::File::new(filename, config::FileFormat::Json).required(false)).unwrap();
@@ -51,4 +68,64 @@ impl Conf {
     pub fn is_use_ssl(&self) -> bool {
         self.internal.get_bool("use_ssl").unwrap()
     }
+
+    pub fn is_allow_destructive_purge(&self) -> bool {
+        self.internal.get_bool("allow_destructive_purge").unwrap()
+    }
+
+    pub fn get_trash_retention_days(&self) -> i64 {
+        self.internal.get_int("trash_retention_days").unwrap()
+    }
+
+    pub fn get_trash_purge_interval_seconds(&self) -> i64 {
+        self.internal.get_int("trash_purge_interval_seconds").unwrap()
+    }
+
+    pub fn get_reminder_interval_seconds(&self) -> i64 {
+        self.internal.get_int("reminder_interval_seconds").unwrap()
+    }
+
+    pub fn get_smtp_host(&self) -> String {
+        self.internal.get_str("smtp_host").unwrap()
+    }
+
+    pub fn get_smtp_port(&self) -> i64 {
+        self.internal.get_int("smtp_port").unwrap()
+    }
+
+    pub fn get_smtp_user_name(&self) -> String {
+        self.internal.get_str("smtp_user_name").unwrap()
+    }
+
+    pub fn get_smtp_password(&self) -> String {
+        self.internal.get_str("smtp_password").unwrap()
+    }
+
+    pub fn get_email_from_address(&self) -> String {
+        self.internal.get_str("email_from_address").unwrap()
+    }
+
+    pub fn get_email_outbox_interval_seconds(&self) -> i64 {
+        self.internal.get_int("email_outbox_interval_seconds").unwrap()
+    }
+
+    pub fn get_email_max_attempts(&self) -> i64 {
+        self.internal.get_int("email_max_attempts").unwrap()
+    }
+
+    pub fn get_webhook_interval_seconds(&self) -> i64 {
+        self.internal.get_int("webhook_interval_seconds").unwrap()
+    }
+
+    pub fn get_webhook_max_attempts(&self) -> i64 {
+        self.internal.get_int("webhook_max_attempts").unwrap()
+    }
+
+    pub fn get_webhook_allowed_hosts(&self) -> Vec<String> {
+        self.internal.get_str("webhook_allowed_hosts").unwrap()
+            .split(',')
+            .map(|x| x.trim().to_owned())
+            .filter(|x| !x.is_empty())
+            .collect()
+    }
 }
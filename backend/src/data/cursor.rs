@@ -0,0 +1,113 @@
+// Evelyn: Your personal assistant, project manager and calendar
+// Copyright (C) 2017 Gregory Jensen
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use bson;
+use bson::{Bson, Document};
+use core::error_messages::{EvelynDatabaseError, EvelynDecodeError};
+use mongodb::cursor::Cursor;
+use mongodb::error::Error as MongoDbError;
+use serde::de::DeserializeOwned;
+
+// Mongo's type number for strings, for finding fields still in an old format.
+pub const BSON_TYPE_STRING: i32 = 2;
+
+// What to do when a document read from a cursor cannot be decoded.
+pub enum PartialResults {
+    Deny,
+    AllowAndLog,
+}
+
+// Prefer the database id, but some lookups project that away so fall back to
+// any of the Evelyn ids.
+pub fn document_id(document: &Document) -> String {
+    if let Some(&Bson::ObjectId(ref id)) = document.get("_id") {
+        return id.to_hex();
+    }
+
+    for key in document.keys() {
+        if key.ends_with("Id") {
+            if let Some(&Bson::String(ref id)) = document.get(key) {
+                return format!("{}={}", key, id);
+            }
+        }
+    }
+
+    "unknown".to_owned()
+}
+
+pub fn decode_document<T>(document: Document) -> Result<T, EvelynDatabaseError>
+where
+    T: DeserializeOwned,
+{
+    let document_id = document_id(&document);
+
+    match bson::from_bson(Bson::Document(document)) {
+        Ok(model) => Ok(model),
+        Err(e) => {
+            Err(EvelynDatabaseError::BSONDecodeFailed(EvelynDecodeError {
+                document_id: document_id,
+                error: e,
+            }))
+        },
+    }
+}
+
+// Errors from the cursor itself always fail the lookup, decode failures are
+// handled according to partial_results.
+pub fn collect<T>(
+    cursor: Cursor,
+    cursor_error: fn(MongoDbError) -> EvelynDatabaseError,
+    partial_results: PartialResults,
+    context: &str,
+) -> Result<Vec<T>, EvelynDatabaseError>
+where
+    T: DeserializeOwned,
+{
+    let mut models = Vec::new();
+    let mut skipped = 0;
+
+    for item in cursor {
+        let document = match item {
+            Ok(document) => document,
+            Err(e) => {
+                error!("Database error while reading results for {}: {}", context, e);
+                return Err(cursor_error(e));
+            },
+        };
+
+        match decode_document(document) {
+            Ok(model) => models.push(model),
+            Err(e) => {
+                match partial_results {
+                    PartialResults::Deny => {
+                        error!("Failed to decode result for {}: {}", context, e);
+                        return Err(e);
+                    },
+                    PartialResults::AllowAndLog => {
+                        warn!("Skipping result for {} which could not be decoded: {}", context, e);
+                        skipped += 1;
+                    },
+                }
+            },
+        }
+    }
+
+    if skipped > 0 {
+        warn!("Returning partial results for {}, {} document(s) skipped", context, skipped);
+    }
+
+    Ok(models)
+}
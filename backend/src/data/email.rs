@@ -0,0 +1,122 @@
+// Evelyn: Your personal assistant, project manager and calendar
+// Copyright (C) 2017 Gregory Jensen
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use bson;
+use bson::{Bson, Document};
+use core::error_messages::EvelynDatabaseError;
+use data::cursor;
+use data::cursor::PartialResults;
+use model::email as email_model;
+use mongodb::{Client, ThreadedClient};
+use mongodb::coll::options::FindOptions;
+use mongodb::db::ThreadedDatabase;
+
+pub fn insert_email(
+    client: &Client,
+    email_model: &email_model::EmailModel,
+) -> Option<EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("email_outbox");
+
+    insert_model!(
+        collection,
+        email_model,
+        EvelynDatabaseError::InsertEmail
+    )
+}
+
+// Emails waiting to be sent whose next attempt is due, oldest first.
+pub fn lookup_pending(
+    client: &Client,
+    now: i64,
+) -> Result<Vec<email_model::EmailModel>, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("email_outbox");
+
+    let status = match bson::to_bson(&email_model::EmailStatusModel::Pending) {
+        Ok(status) => status,
+        Err(e) => return Err(EvelynDatabaseError::BSONEncodeFailed(e)),
+    };
+
+    let mut find_options = FindOptions::new();
+    find_options.sort = Some(doc!{"nextAttemptAt" => Bson::I32(1)});
+
+    match collection.find(Some(doc!{"status" => status, "nextAttemptAt" => doc!{"$lte" => now}}), Some(find_options)) {
+        Ok(c) => cursor::collect(c, EvelynDatabaseError::LookupEmails, PartialResults::AllowAndLog, "lookup pending emails"),
+        Err(e) => Err(EvelynDatabaseError::LookupEmails(e)),
+    }
+}
+
+// Counts an attempt against the email, but only if nobody else has made one
+// since it was looked up. Returns whether it was this caller which claimed
+// it, so that an email is only sent once per attempt.
+pub fn claim(
+    client: &Client,
+    email_id: &String,
+    expected_attempts: i32,
+) -> Result<bool, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("email_outbox");
+
+    let filter = doc!{"emailId" => email_id, "attempts" => expected_attempts};
+
+    match collection.update_one(filter, doc!{"$inc" => doc!{"attempts" => 1}}, None) {
+        Ok(result) => Ok(result.matched_count > 0),
+        Err(e) => Err(EvelynDatabaseError::UpdateEmail(e)),
+    }
+}
+
+pub fn mark_sent(
+    client: &Client,
+    email_id: &String,
+    date_sent: i64,
+) -> Option<EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("email_outbox");
+
+    let status = match bson::to_bson(&email_model::EmailStatusModel::Sent) {
+        Ok(status) => status,
+        Err(e) => return Some(EvelynDatabaseError::BSONEncodeFailed(e)),
+    };
+
+    let update = doc!{"$set" => doc!{"status" => status, "dateSent" => date_sent, "lastError" => Bson::Null}};
+
+    match collection.update_one(doc!{"emailId" => email_id}, update, None) {
+        Ok(_) => None,
+        Err(e) => Some(EvelynDatabaseError::UpdateEmail(e)),
+    }
+}
+
+pub fn record_failure(
+    client: &Client,
+    email_id: &String,
+    status: &email_model::EmailStatusModel,
+    next_attempt_at: i64,
+    last_error: &String,
+) -> Option<EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("email_outbox");
+
+    let status = match bson::to_bson(status) {
+        Ok(status) => status,
+        Err(e) => return Some(EvelynDatabaseError::BSONEncodeFailed(e)),
+    };
+
+    let mut update = Document::new();
+    update.insert("status", status);
+    update.insert("nextAttemptAt", next_attempt_at);
+    update.insert("lastError", last_error.to_owned());
+
+    match collection.update_one(doc!{"emailId" => email_id}, doc!{"$set" => update}, None) {
+        Ok(_) => None,
+        Err(e) => Some(EvelynDatabaseError::UpdateEmail(e)),
+    }
+}
@@ -17,6 +17,9 @@
 #[macro_use]
 mod data_macros;
 
+pub mod cursor;
+pub mod version;
+pub mod trash;
 pub mod conf;
 pub mod user;
 pub mod simple_task;
@@ -25,3 +28,7 @@ pub mod calendar;
 pub mod server_admin;
 pub mod user_group;
 pub mod agile;
+pub mod reminder;
+pub mod notification;
+pub mod email;
+pub mod webhook;
@@ -0,0 +1,76 @@
+// Evelyn: Your personal assistant, project manager and calendar
+// Copyright (C) 2017 Gregory Jensen
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use bson;
+use bson::Bson;
+use core::error_messages::{EvelynBaseError, EvelynDatabaseError};
+use data::cursor;
+use data::cursor::PartialResults;
+use model::notification as notification_model;
+use mongodb::{Client, ThreadedClient};
+use mongodb::coll::options::FindOptions;
+use mongodb::db::ThreadedDatabase;
+
+pub fn insert_notification(
+    client: &Client,
+    notification_model: &notification_model::NotificationModel,
+) -> Option<EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("notification");
+
+    insert_model!(
+        collection,
+        notification_model,
+        EvelynDatabaseError::InsertNotification
+    )
+}
+
+// Newest first.
+pub fn lookup_notifications(
+    client: &Client,
+    user_id: &String,
+    unread_only: bool,
+) -> Result<Vec<notification_model::NotificationModel>, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("notification");
+
+    let mut filter = doc!{"userId" => user_id};
+    if unread_only {
+        filter.insert("read", false);
+    }
+
+    let mut find_options = FindOptions::new();
+    find_options.sort = Some(doc!{"dateCreated" => Bson::I32(-1)});
+
+    match collection.find(Some(filter), Some(find_options)) {
+        Ok(c) => cursor::collect(c, EvelynDatabaseError::LookupNotifications, PartialResults::Deny, "lookup notifications"),
+        Err(e) => Err(EvelynDatabaseError::LookupNotifications(e)),
+    }
+}
+
+pub fn mark_read(
+    client: &Client,
+    user_id: &String,
+    notification_ids: &Vec<String>,
+) -> Option<EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("notification");
+
+    let ids: bson::Array = notification_ids.iter().map(|x| Bson::String(x.clone())).collect();
+    let filter = doc!{"userId" => user_id, "notificationId" => doc!{"$in" => ids}};
+
+    match collection.update_many(filter, doc!{"$set" => doc!{"read" => true}}, None) {
+        Ok(_) => None,
+        Err(e) => Some(EvelynDatabaseError::UpdateNotifications(e)),
+    }
+}
@@ -0,0 +1,146 @@
+// Evelyn: Your personal assistant, project manager and calendar
+// Copyright (C) 2017 Gregory Jensen
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use bson;
+use bson::{Bson, Document};
+use core::error_messages::{EvelynBaseError, EvelynDatabaseError};
+use data::cursor;
+use data::cursor::PartialResults;
+use model::reminder as reminder_model;
+use mongodb::{Client, ThreadedClient};
+use mongodb::coll::options::FindOptions;
+use mongodb::db::ThreadedDatabase;
+
+fn optional_timestamp(timestamp: Option<i64>) -> Bson {
+    match timestamp {
+        Some(timestamp) => Bson::I64(timestamp),
+        None => Bson::Null,
+    }
+}
+
+pub fn insert_reminder(
+    client: &Client,
+    reminder_model: &reminder_model::ReminderModel,
+) -> Option<EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("reminder");
+
+    insert_model!(
+        collection,
+        reminder_model,
+        EvelynDatabaseError::InsertReminder
+    )
+}
+
+pub fn lookup_reminders(
+    client: &Client,
+    user_id: &String,
+) -> Result<Vec<reminder_model::ReminderModel>, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("reminder");
+
+    let mut find_options = FindOptions::new();
+    find_options.sort = Some(doc!{"dateCreated" => Bson::I32(1)});
+
+    match collection.find(Some(doc!{"userId" => user_id}), Some(find_options)) {
+        Ok(c) => cursor::collect(c, EvelynDatabaseError::LookupReminders, PartialResults::Deny, "lookup reminders"),
+        Err(e) => Err(EvelynDatabaseError::LookupReminders(e)),
+    }
+}
+
+pub fn lookup_reminders_for_source(
+    client: &Client,
+    source: &reminder_model::ReminderSourceModel,
+    source_id: &String,
+) -> Result<Vec<reminder_model::ReminderModel>, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("reminder");
+
+    let source = match bson::to_bson(source) {
+        Ok(source) => source,
+        Err(e) => return Err(EvelynDatabaseError::BSONEncodeFailed(e)),
+    };
+
+    match collection.find(Some(doc!{"source" => source, "sourceId" => source_id}), None) {
+        Ok(c) => cursor::collect(c, EvelynDatabaseError::LookupReminders, PartialResults::Deny, "lookup reminders for source"),
+        Err(e) => Err(EvelynDatabaseError::LookupReminders(e)),
+    }
+}
+
+// Reminders which should have fired by the given time. Skipping a reminder
+// which can't be read lets the rest still fire.
+pub fn lookup_due(
+    client: &Client,
+    now: i64,
+) -> Result<Vec<reminder_model::ReminderModel>, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("reminder");
+
+    let mut find_options = FindOptions::new();
+    find_options.sort = Some(doc!{"nextFireAt" => Bson::I32(1)});
+
+    match collection.find(Some(doc!{"nextFireAt" => doc!{"$lte" => now}}), Some(find_options)) {
+        Ok(c) => cursor::collect(c, EvelynDatabaseError::LookupReminders, PartialResults::AllowAndLog, "lookup due reminders"),
+        Err(e) => Err(EvelynDatabaseError::LookupReminders(e)),
+    }
+}
+
+// Moves the reminder on, but only if it hasn't already been moved on since it
+// was looked up. Returns whether it was this caller which moved it, so that
+// a reminder is only delivered once.
+pub fn reschedule(
+    client: &Client,
+    reminder_id: &String,
+    expected_next_fire_at: Option<i64>,
+    source_time: Option<i64>,
+    next_fire_at: Option<i64>,
+) -> Result<bool, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("reminder");
+
+    let filter = doc!{"reminderId" => reminder_id, "nextFireAt" => optional_timestamp(expected_next_fire_at)};
+
+    let mut update = Document::new();
+    update.insert("sourceTime", optional_timestamp(source_time));
+    update.insert("nextFireAt", optional_timestamp(next_fire_at));
+
+    match collection.update_one(filter, doc!{"$set" => update}, None) {
+        Ok(result) => Ok(result.matched_count > 0),
+        Err(e) => Err(EvelynDatabaseError::UpdateReminder(e)),
+    }
+}
+
+pub fn remove_reminder(
+    client: &Client,
+    user_id: &String,
+    reminder_id: &String,
+) -> Option<EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("reminder");
+
+    match collection.delete_one(doc!{"userId" => user_id, "reminderId" => reminder_id}, None) {
+        Ok(ref result) if result.deleted_count == 0 => Some(EvelynDatabaseError::ReminderNotFound(EvelynBaseError::NothingElse)),
+        Ok(_) => None,
+        Err(e) => Some(EvelynDatabaseError::RemoveReminder(e)),
+    }
+}
+
+// Used once the item the reminder is for has gone.
+pub fn delete_reminder(
+    client: &Client,
+    reminder_id: &String,
+) -> Option<EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("reminder");
+
+    match collection.delete_one(doc!{"reminderId" => reminder_id}, None) {
+        Ok(_) => None,
+        Err(e) => Some(EvelynDatabaseError::RemoveReminder(e)),
+    }
+}
@@ -14,10 +14,223 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use core::error_messages::EvelynDatabaseError;
+use bson::{Bson, Document};
+use core::error_messages::{EvelynBaseError, EvelynDatabaseError};
+use data::trash;
+use model::trash::DeletionModel;
 use mongodb::{Client, ThreadedClient};
 use mongodb::db::ThreadedDatabase;
 
+// Collections which may be named as a purge target.
+const COLLECTIONS: [&'static str; 17] = [
+    "user",
+    "usergroup",
+    "simpletask",
+    "todolist",
+    "todolist_template",
+    "calendar",
+    "agile_project",
+    "agile_task",
+    "agile_story",
+    "agile_sprint",
+    "agile_link",
+    "agile_audit",
+    "reminder",
+    "notification",
+    "email_outbox",
+    "webhook_subscription",
+    "webhook_delivery",
+];
+
+// Collections whose documents go to the trash rather than being dropped.
+const TRASH_COLLECTIONS: [&'static str; 4] = ["simpletask", "todolist", "usergroup", "calendar"];
+
+// Stands in for a deleted user where records are kept but must no longer
+// identify them.
+pub const DELETED_USER_ID: &'static str = "deleted-user";
+
+pub enum ScopeAction {
+    // Remove the matching documents.
+    Delete,
+    // Keep the matching documents but apply this update to remove references
+    // to the data being deleted.
+    Detach(Document),
+}
+
+pub struct Scope {
+    pub collection: &'static str,
+    pub filter: Document,
+    pub action: ScopeAction,
+}
+
+// Agile records which name the user who made or changed them. Like audit
+// entries they stay with the project but stop identifying the user.
+const AGILE_USER_ID_FIELDS: [(&'static str, &'static str); 7] = [
+    ("agile_project", "createdByUserId"),
+    ("agile_task", "createdByUserId"),
+    ("agile_task", "modifiedByUserId"),
+    ("agile_task", "assignment.assignedByUserId"),
+    ("agile_story", "createdByUserId"),
+    ("agile_sprint", "createdByUserId"),
+    ("agile_link", "createdByUserId"),
+];
+
+pub fn build_user_scopes(user_id: &String) -> Vec<Scope> {
+    let mut scopes = vec![
+        Scope {
+            collection: "simpletask",
+            filter: doc!{"userId" => user_id},
+            action: ScopeAction::Delete,
+        },
+        Scope {
+            collection: "todolist",
+            filter: doc!{"userId" => user_id},
+            action: ScopeAction::Delete,
+        },
+        Scope {
+            collection: "todolist_template",
+            filter: doc!{"userId" => user_id},
+            action: ScopeAction::Delete,
+        },
+        Scope {
+            collection: "calendar",
+            filter: doc!{"userId" => user_id},
+            action: ScopeAction::Delete,
+        },
+        Scope {
+            collection: "reminder",
+            filter: doc!{"userId" => user_id},
+            action: ScopeAction::Delete,
+        },
+        Scope {
+            collection: "notification",
+            filter: doc!{"userId" => user_id},
+            action: ScopeAction::Delete,
+        },
+        Scope {
+            collection: "email_outbox",
+            filter: doc!{"userId" => user_id},
+            action: ScopeAction::Delete,
+        },
+        Scope {
+            collection: "webhook_subscription",
+            filter: doc!{"userId" => user_id},
+            action: ScopeAction::Delete,
+        },
+        Scope {
+            collection: "webhook_delivery",
+            filter: doc!{"userId" => user_id},
+            action: ScopeAction::Delete,
+        },
+        Scope {
+            collection: "usergroup",
+            filter: doc!{"createdByUserId" => user_id},
+            action: ScopeAction::Delete,
+        },
+        Scope {
+            collection: "usergroup",
+            filter: doc!{"members.userId" => user_id},
+            action: ScopeAction::Detach(doc!{"$pull" => doc!{"members" => doc!{"userId" => user_id}}}),
+        },
+        Scope {
+            collection: "todolist",
+            filter: doc!{"userShares.userId" => user_id},
+            action: ScopeAction::Detach(doc!{"$pull" => doc!{"userShares" => doc!{"userId" => user_id}}}),
+        },
+        Scope {
+            collection: "calendar",
+            filter: doc!{"attendees.userId" => user_id},
+            action: ScopeAction::Detach(doc!{"$pull" => doc!{"attendees" => doc!{"userId" => user_id}}}),
+        },
+        Scope {
+            collection: "agile_project",
+            filter: doc!{"userContributors.userId" => user_id},
+            action: ScopeAction::Detach(doc!{"$pull" => doc!{"userContributors" => doc!{"userId" => user_id}}}),
+        },
+        Scope {
+            collection: "agile_task",
+            filter: doc!{"assignment.assignedToUserId" => user_id},
+            action: ScopeAction::Detach(doc!{"$set" => doc!{"assignment" => Bson::Null}}),
+        },
+        Scope {
+            collection: "agile_audit",
+            filter: doc!{"userId" => user_id},
+            action: ScopeAction::Detach(doc!{"$set" => doc!{"userId" => DELETED_USER_ID}}),
+        },
+    ];
+
+    for &(collection, field) in AGILE_USER_ID_FIELDS.iter() {
+        scopes.push(Scope {
+            collection: collection,
+            filter: doc!{field => user_id},
+            action: ScopeAction::Detach(doc!{"$set" => doc!{field => DELETED_USER_ID}}),
+        });
+    }
+
+    // The user record goes last so that a failed deletion can be retried
+    // by the same user.
+    scopes.push(Scope {
+        collection: "user",
+        filter: doc!{"userId" => user_id},
+        action: ScopeAction::Delete,
+    });
+
+    scopes
+}
+
+pub fn build_project_scopes(project_id: &String) -> Vec<Scope> {
+    let mut scopes: Vec<Scope> = ["agile_task", "agile_story", "agile_sprint", "agile_link", "agile_audit",
+                                  "webhook_subscription", "webhook_delivery"].iter().map(|collection| {
+        Scope {
+            collection: *collection,
+            filter: doc!{"projectId" => project_id},
+            action: ScopeAction::Delete,
+        }
+    }).collect();
+
+    scopes.push(Scope {
+        collection: "agile_project",
+        filter: doc!{"projectId" => project_id},
+        action: ScopeAction::Delete,
+    });
+
+    scopes
+}
+
+pub fn count_scope(
+    client: &Client,
+    scope: &Scope,
+) -> Result<i64, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection(scope.collection);
+
+    match collection.count(Some(scope.filter.clone()), None) {
+        Ok(count) => Ok(count),
+        Err(e) => Err(EvelynDatabaseError::CountAdminScope(e)),
+    }
+}
+
+pub fn apply_scope(
+    client: &Client,
+    scope: &Scope,
+) -> Result<i64, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection(scope.collection);
+
+    match scope.action {
+        ScopeAction::Delete => {
+            match collection.delete_many(scope.filter.clone(), None) {
+                Ok(result) => Ok(result.deleted_count as i64),
+                Err(e) => Err(EvelynDatabaseError::ApplyAdminScope(e)),
+            }
+        },
+        ScopeAction::Detach(ref update_query) => {
+            match collection.update_many(scope.filter.clone(), update_query.clone(), None) {
+                Ok(result) => Ok(result.modified_count as i64),
+                Err(e) => Err(EvelynDatabaseError::ApplyAdminScope(e)),
+            }
+        },
+    }
+}
+
 pub fn purge_database(client: &Client) -> Option<EvelynDatabaseError> {
     let db = client.db("evelyn");
 
@@ -29,10 +242,30 @@ pub fn purge_database(client: &Client) -> Option<EvelynDatabaseError> {
 
 pub fn purge_collection(
     target: &String,
+    deletion: &DeletionModel,
     client: &Client,
 ) -> Option<EvelynDatabaseError> {
+    if !COLLECTIONS.iter().any(|collection| *collection == target.as_str()) {
+        return Some(EvelynDatabaseError::UnknownPurgeTarget(EvelynBaseError::NothingElse));
+    }
+
     let db = client.db("evelyn");
 
+    if TRASH_COLLECTIONS.iter().any(|collection| *collection == target.as_str()) {
+        let mut filter = Document::new();
+        trash::add_not_deleted(&mut filter);
+
+        let update_query = match trash::build_mark_deleted_update(deletion) {
+            Ok(update_query) => update_query,
+            Err(e) => return Some(e),
+        };
+
+        return match db.collection(target).update_many(filter, update_query, None) {
+            Ok(_) => None,
+            Err(e) => Some(EvelynDatabaseError::PurgeCollection(e)),
+        };
+    }
+
     match db.drop_collection(target) {
         Ok(_) => None,
         Err(e) => Some(EvelynDatabaseError::PurgeCollection(e)),
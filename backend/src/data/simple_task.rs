@@ -17,8 +17,14 @@
 use bson;
 use bson::{Bson, Document};
 use core::error_messages::{EvelynBaseError, EvelynDatabaseError};
+use data::cursor;
+use data::cursor::PartialResults;
+use data::trash;
+use data::version;
 use model;
+use model::trash::DeletionModel;
 use mongodb::{Client, ThreadedClient};
+use mongodb::coll::options::FindOptions;
 use mongodb::db::ThreadedDatabase;
 
 pub fn insert_simple_task(
@@ -39,38 +45,358 @@ pub fn insert_simple_task(
     }
 }
 
+// Sorting by due date is done in two parts, tasks with a due date and then
+// those without, because an empty due date would otherwise sort first.
+#[derive(Clone, Copy, PartialEq)]
+enum LookupPart {
+    All,
+    WithDueDate,
+    WithoutDueDate,
+}
+
+fn lookup_parts(sort_by: &model::simple_task::SimpleTaskSortModel) -> Vec<LookupPart> {
+    match *sort_by {
+        model::simple_task::SimpleTaskSortModel::DueDate => vec![LookupPart::WithDueDate, LookupPart::WithoutDueDate],
+        _ => vec![LookupPart::All],
+    }
+}
+
+fn part_of(
+    sort_by: &model::simple_task::SimpleTaskSortModel,
+    task: &model::simple_task::SimpleTaskModel,
+) -> LookupPart {
+    match *sort_by {
+        model::simple_task::SimpleTaskSortModel::DueDate if task.due_date.is_none() => LookupPart::WithoutDueDate,
+        model::simple_task::SimpleTaskSortModel::DueDate => LookupPart::WithDueDate,
+        _ => LookupPart::All,
+    }
+}
+
+// The fields tasks are ordered by, ending with the task id so that the order
+// is total and a page can carry on from any task.
+fn sort_keys(
+    part: LookupPart,
+    sort_by: &model::simple_task::SimpleTaskSortModel,
+    sort_descending: bool,
+) -> Vec<(&'static str, i32)> {
+    let direction = if sort_descending { -1 } else { 1 };
+
+    match (part, sort_by) {
+        (LookupPart::WithDueDate, _) => vec![("dueDate", direction), ("title", 1), ("taskId", 1)],
+        (LookupPart::WithoutDueDate, _) => vec![("title", 1), ("taskId", 1)],
+        (LookupPart::All, &model::simple_task::SimpleTaskSortModel::Priority) => vec![("priority", direction), ("title", 1), ("taskId", 1)],
+        (LookupPart::All, _) => vec![("title", direction), ("taskId", 1)],
+    }
+}
+
+fn sort_value(
+    task: &model::simple_task::SimpleTaskModel,
+    key: &str,
+) -> Bson {
+    match key {
+        "dueDate" => task.due_date.map_or(Bson::Null, Bson::I64),
+        "priority" => Bson::I32(task.priority),
+        "title" => Bson::String(task.title.to_owned()),
+        _ => Bson::String(task.task_id.to_owned()),
+    }
+}
+
+// Matches the tasks which come after the given task in the sort order.
+fn build_after_filter(
+    keys: &Vec<(&'static str, i32)>,
+    task: &model::simple_task::SimpleTaskModel,
+) -> Document {
+    let mut alternatives = bson::Array::new();
+
+    for i in 0..keys.len() {
+        let mut alternative = Document::new();
+        for &(key, _) in keys[..i].iter() {
+            alternative.insert(key, sort_value(task, key));
+        }
+
+        let (key, direction) = keys[i];
+        let mut comparison = Document::new();
+        comparison.insert(if direction < 0 { "$lt" } else { "$gt" }, sort_value(task, key));
+        alternative.insert(key, comparison);
+
+        alternatives.push(Bson::Document(alternative));
+    }
+
+    doc!{"$or" => Bson::Array(alternatives)}
+}
+
+fn escape_regex(value: &str) -> String {
+    let mut escaped = String::new();
+    for c in value.chars() {
+        if !c.is_alphanumeric() && !c.is_whitespace() {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+fn build_lookup_filter(simple_task_lookup_model: &model::simple_task::SimpleTaskLookupModel) -> Vec<Document> {
+    let mut filter = doc!{"userId" => &simple_task_lookup_model.user_id};
+    trash::add_not_deleted(&mut filter);
+
+    if !simple_task_lookup_model.show_completed {
+        filter.insert("completed", false);
+    }
+    if let Some(ref tag) = simple_task_lookup_model.tag {
+        filter.insert("tags", tag.to_owned());
+    }
+    if let Some(priority) = simple_task_lookup_model.priority {
+        filter.insert("priority", priority);
+    }
+    if let Some(ref series_id) = simple_task_lookup_model.series_id {
+        filter.insert("seriesId", series_id.to_owned());
+    }
+    if let Some(ref parent_task_id) = simple_task_lookup_model.parent_task_id {
+        filter.insert("parentTaskId", parent_task_id.to_owned());
+    }
+
+    let mut due_date_range = Document::new();
+    if let Some(due_after) = simple_task_lookup_model.due_after {
+        due_date_range.insert("$gte", Bson::I64(due_after));
+    }
+    if let Some(due_before) = simple_task_lookup_model.due_before {
+        due_date_range.insert("$lt", Bson::I64(due_before));
+    }
+    if !due_date_range.is_empty() {
+        due_date_range.insert("$ne", Bson::Null);
+        filter.insert("dueDate", due_date_range);
+    }
+
+    let mut clauses = vec![filter];
+
+    if let Some(ref search) = simple_task_lookup_model.search {
+        let pattern = escape_regex(search);
+        clauses.push(doc!{"$or" => Bson::Array(vec![
+            Bson::Document(doc!{"title" => Bson::RegExp(pattern.to_owned(), String::from("i"))}),
+            Bson::Document(doc!{"description" => Bson::RegExp(pattern, String::from("i"))}),
+        ])});
+    }
+
+    clauses
+}
+
+// Returns a page of tasks and whether there are more after it.
 pub fn lookup_simple_tasks(
     client: &Client,
     simple_task_lookup_model: &model::simple_task::SimpleTaskLookupModel,
-) -> Result<Vec<model::simple_task::SimpleTaskModel>, EvelynDatabaseError> {
+) -> Result<(Vec<model::simple_task::SimpleTaskModel>, bool), EvelynDatabaseError> {
     let collection = client.db("evelyn").collection("simpletask");
 
-    let ref user_id = simple_task_lookup_model.user_id;
-    let query = doc!{"userId" => user_id};
-
-    let cursor = collection.find(Some(query), None);
-
-    match cursor {
-        Ok(c) => {
-            let docs: Vec<model::simple_task::SimpleTaskModel> = 
-                c.filter_map(|x| match x {
-                    Ok(x) => {
-                        match bson::from_bson(bson::Bson::Document(x)) {
-                            Ok(obj) => obj,
-                            Err(e) => {
-                                error!("BSON Serialize error in lookup simple task {}", e);
-                                None        
-                            }
-                        }
-                    },
-                    Err(e) => {
-                        error!("Database error in lookup simple task {}", e);
-                        None
-                    },
-                    })
-                .collect();
-            Ok(docs)
+    // The cursor task is looked up even if it has since been removed so that
+    // the next page can still be found.
+    let cursor_task: Option<model::simple_task::SimpleTaskModel> = match simple_task_lookup_model.cursor {
+        Some(ref task_id) => {
+            match find_simple_task(client, &simple_task_lookup_model.user_id, task_id) {
+                Ok(Some(task)) => Some(task),
+                Ok(None) => return Err(EvelynDatabaseError::SimpleTaskCursorNotFound(EvelynBaseError::NothingElse)),
+                Err(e) => return Err(e),
+            }
+        },
+        None => None,
+    };
+
+    let ref sort_by = simple_task_lookup_model.sort_by;
+    let parts = lookup_parts(sort_by);
+    let first_part = match cursor_task {
+        Some(ref task) => parts.iter().position(|x| *x == part_of(sort_by, task)).unwrap_or(0),
+        None => 0,
+    };
+
+    // One more than the page size, to find out whether there is another page.
+    let wanted = if simple_task_lookup_model.limit > 0 { Some(simple_task_lookup_model.limit as usize + 1) } else { None };
+
+    let mut tasks = Vec::new();
+
+    for (i, part) in parts.into_iter().enumerate().skip(first_part) {
+        let mut find_options = FindOptions::new();
+
+        if let Some(wanted) = wanted {
+            if tasks.len() >= wanted {
+                break;
+            }
+            find_options.limit = Some((wanted - tasks.len()) as i64);
+        }
+
+        let keys = sort_keys(part, sort_by, simple_task_lookup_model.sort_descending);
+
+        let mut sort = Document::new();
+        for &(key, direction) in keys.iter() {
+            sort.insert(key, Bson::I32(direction));
+        }
+        find_options.sort = Some(sort);
+
+        let mut clauses = build_lookup_filter(simple_task_lookup_model);
+        match part {
+            LookupPart::WithDueDate => clauses.push(doc!{"dueDate" => doc!{"$ne" => Bson::Null}}),
+            LookupPart::WithoutDueDate => clauses.push(doc!{"dueDate" => Bson::Null}),
+            LookupPart::All => {},
+        }
+        if i == first_part {
+            if let Some(ref task) = cursor_task {
+                clauses.push(build_after_filter(&keys, task));
+            }
+        }
+
+        let query = doc!{"$and" => Bson::Array(clauses.into_iter().map(Bson::Document).collect())};
+
+        match collection.find(Some(query), Some(find_options)) {
+            Ok(c) => match cursor::collect(c, EvelynDatabaseError::LookupSimpleTask, PartialResults::Deny, "lookup simple tasks") {
+                Ok(found) => tasks.extend(found),
+                Err(e) => return Err(e),
+            },
+            Err(e) => return Err(EvelynDatabaseError::LookupSimpleTask(e)),
+        }
+    }
+
+    let more = match wanted {
+        Some(wanted) if tasks.len() >= wanted => {
+            tasks.truncate(simple_task_lookup_model.limit as usize);
+            true
         },
+        _ => false,
+    };
+
+    Ok((tasks, more))
+}
+
+pub fn lookup_all_simple_tasks(
+    client: &Client,
+    user_id: &String,
+) -> Result<Vec<model::simple_task::SimpleTaskModel>, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("simpletask");
+
+    let mut query = doc!{"userId" => user_id};
+    trash::add_not_deleted(&mut query);
+
+    match collection.find(Some(query), None) {
+        Ok(cursor) => cursor::collect(cursor, EvelynDatabaseError::LookupSimpleTask, PartialResults::Deny, "lookup all simple tasks"),
+        Err(e) => Err(EvelynDatabaseError::LookupSimpleTask(e)),
+    }
+}
+
+pub fn lookup_children(
+    client: &Client,
+    user_id: &String,
+    parent_task_ids: Vec<String>,
+) -> Result<Vec<model::simple_task::SimpleTaskModel>, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("simpletask");
+
+    let parent_task_ids: bson::Array = parent_task_ids.into_iter().map(Bson::String).collect();
+    let mut query = doc!{"userId" => user_id, "parentTaskId" => doc!{"$in" => parent_task_ids}};
+    trash::add_not_deleted(&mut query);
+
+    match collection.find(Some(query), None) {
+        Ok(cursor) => cursor::collect(cursor, EvelynDatabaseError::LookupSimpleTask, PartialResults::Deny, "lookup simple task children"),
+        Err(e) => Err(EvelynDatabaseError::LookupSimpleTask(e)),
+    }
+}
+
+// Tasks from before priorities and tags need them set so that they can be
+// filtered and sorted on.
+pub fn migrate_defaults(client: &Client) -> Result<i32, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("simpletask");
+
+    let defaults = vec![
+        ("priority", Bson::I32(model::simple_task::PRIORITY_NORMAL)),
+        ("tags", Bson::Array(bson::Array::new())),
+    ];
+
+    let mut migrated = 0;
+    for (field, value) in defaults {
+        let mut set = Document::new();
+        set.insert(field, value);
+
+        match collection.update_many(doc!{field => doc!{"$exists" => false}}, doc!{"$set" => set}, None) {
+            Ok(result) => migrated += result.modified_count,
+            Err(e) => return Err(EvelynDatabaseError::MigrateSimpleTasks(e)),
+        }
+    }
+
+    Ok(migrated)
+}
+
+// Due dates used to be stored as strings. Those which can't be read are
+// removed rather than left to fail every lookup.
+pub fn migrate_string_due_dates(
+    client: &Client,
+    parse: fn(&str) -> Option<i64>,
+) -> Result<i32, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("simpletask");
+
+    let filter = doc!{"dueDate" => doc!{"$type" => cursor::BSON_TYPE_STRING}};
+
+    let c = match collection.find(Some(filter), None) {
+        Ok(c) => c,
+        Err(e) => return Err(EvelynDatabaseError::MigrateSimpleTasks(e)),
+    };
+
+    let mut migrated = 0;
+    for item in c {
+        let document = match item {
+            Ok(document) => document,
+            Err(e) => return Err(EvelynDatabaseError::MigrateSimpleTasks(e)),
+        };
+
+        let due_date = match document.get("dueDate") {
+            Some(&Bson::String(ref due_date)) if due_date.is_empty() => Bson::Null,
+            Some(&Bson::String(ref due_date)) => match parse(due_date) {
+                Some(due_date) => Bson::I64(due_date),
+                None => {
+                    warn!("Removing the unreadable due date of simple task [{}]", cursor::document_id(&document));
+                    Bson::Null
+                },
+            },
+            _ => continue,
+        };
+
+        let identity_filter = match document.get("_id") {
+            Some(id) => doc!{"_id" => id.clone()},
+            None => continue,
+        };
+
+        match collection.update_one(identity_filter, doc!{"$set" => doc!{"dueDate" => due_date, "dueDateOnly" => false}}, None) {
+            Ok(_) => migrated += 1,
+            Err(e) => return Err(EvelynDatabaseError::MigrateSimpleTasks(e)),
+        }
+    }
+
+    Ok(migrated)
+}
+
+pub fn lookup_simple_task(
+    client: &Client,
+    user_id: &String,
+    task_id: &String,
+) -> Result<model::simple_task::SimpleTaskModel, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("simpletask");
+
+    let mut query = doc!{"userId" => user_id, "taskId" => task_id};
+    trash::add_not_deleted(&mut query);
+
+    match collection.find_one(Some(query), None) {
+        Ok(Some(document)) => cursor::decode_document(document),
+        Ok(None) => Err(EvelynDatabaseError::SimpleTaskNotFound(EvelynBaseError::NothingElse)),
+        Err(e) => Err(EvelynDatabaseError::LookupSimpleTask(e)),
+    }
+}
+
+// Finds a task whether or not it has been removed.
+pub fn find_simple_task(
+    client: &Client,
+    user_id: &String,
+    task_id: &String,
+) -> Result<Option<model::simple_task::SimpleTaskModel>, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("simpletask");
+
+    match collection.find_one(Some(doc!{"userId" => user_id, "taskId" => task_id}), None) {
+        Ok(Some(document)) => cursor::decode_document(document).map(Some),
+        Ok(None) => Ok(None),
         Err(e) => Err(EvelynDatabaseError::LookupSimpleTask(e)),
     }
 }
@@ -83,7 +409,10 @@ pub fn update_simple_task(
 
     let ref user_id = simple_task_update_model.user_id;
     let ref task_id = simple_task_update_model.task_id;
-    let filter = doc!("userId" => user_id, "taskId" => task_id);
+    let mut identity_filter = doc!("userId" => user_id, "taskId" => task_id);
+    trash::add_not_deleted(&mut identity_filter);
+    let mut filter = identity_filter.clone();
+    version::add_expected_version(&mut filter, simple_task_update_model.expected_version);
 
     let mut update_query = Document::new();
 
@@ -95,34 +424,272 @@ pub fn update_simple_task(
         update_query.insert("description",
                             Bson::String(simple_task_update_model.description.unwrap()));
     }
-    if simple_task_update_model.due_date.is_some() {
-        update_query.insert("dueDate",
-                            Bson::String(simple_task_update_model.due_date.unwrap()));
+    if let Some(due_date) = simple_task_update_model.due_date {
+        update_query.insert("dueDate", due_date.map_or(Bson::Null, Bson::I64));
+    }
+    if let Some(due_date_only) = simple_task_update_model.due_date_only {
+        update_query.insert("dueDateOnly", Bson::Boolean(due_date_only));
+    }
+    if simple_task_update_model.time_zone.is_some() {
+        update_query.insert("timeZone",
+                            Bson::String(simple_task_update_model.time_zone.unwrap()));
     }
     if simple_task_update_model.completed.is_some() {
         update_query.insert("completed",
                             Bson::Boolean(simple_task_update_model.completed.unwrap()));
     }
+    if let Some(priority) = simple_task_update_model.priority {
+        update_query.insert("priority", Bson::I32(priority));
+    }
+    if let Some(tags) = simple_task_update_model.tags {
+        update_query.insert("tags", Bson::Array(tags.into_iter().map(Bson::String).collect()));
+    }
+    if let Some(recurrence) = simple_task_update_model.recurrence {
+        update_query.insert("recurrence", recurrence.map_or(Bson::Null, Bson::String));
+    }
+    if let Some(series_id) = simple_task_update_model.series_id {
+        update_query.insert("seriesId", Bson::String(series_id));
+    }
+    if let Some(series_begin) = simple_task_update_model.series_begin {
+        update_query.insert("seriesBegin", Bson::I64(series_begin));
+    }
+    if let Some(date_completed) = simple_task_update_model.date_completed {
+        update_query.insert("dateCompleted", date_completed.map_or(Bson::Null, Bson::I64));
+    }
+    if let Some(ref checklist) = simple_task_update_model.checklist {
+        match bson::to_bson(checklist) {
+            Ok(checklist) => update_query.insert("checklist", checklist),
+            Err(e) => return Some(EvelynDatabaseError::BSONEncodeFailed(e)),
+        };
+    }
+    if let Some(parent_task_id) = simple_task_update_model.parent_task_id {
+        update_query.insert("parentTaskId", parent_task_id.map_or(Bson::Null, Bson::String));
+    }
+    if let Some(complete_with_children) = simple_task_update_model.complete_with_children {
+        update_query.insert("completeWithChildren", Bson::Boolean(complete_with_children));
+    }
 
     let mut set_update_query = Document::new();
     set_update_query.insert("$set", update_query);
+    version::add_version_increment(&mut set_update_query);
 
     match collection.update_one(filter, set_update_query, None) {
-        Ok(_) => None,
+        Ok(result) => {
+            version::check_update_result(
+                &collection,
+                result,
+                identity_filter,
+                simple_task_update_model.expected_version,
+                EvelynDatabaseError::SimpleTaskNotFound(EvelynBaseError::NothingElse),
+                EvelynDatabaseError::SimpleTaskVersionConflict(EvelynBaseError::NothingElse),
+                EvelynDatabaseError::UpdateSimpleTask,
+            )
+        },
         Err(e) => Some(EvelynDatabaseError::UpdateSimpleTask(e)),
     }
 }
 
 pub fn remove(
     client: &Client,
+    user_id: &String,
     task_id: String,
+    deletion: &DeletionModel,
 ) -> Option<EvelynDatabaseError> {
     let collection = client.db("evelyn").collection("simpletask");
 
-    let filter = doc!{"taskId" => task_id};
+    let mut filter = doc!{"userId" => user_id, "taskId" => task_id};
+    trash::add_not_deleted(&mut filter);
+
+    let update_query = match trash::build_mark_deleted_update(deletion) {
+        Ok(update_query) => update_query,
+        Err(e) => return Some(e),
+    };
 
-    match collection.delete_one(filter, None) {
+    match collection.update_one(filter, update_query, None) {
+        Ok(ref result) if result.matched_count == 0 => Some(EvelynDatabaseError::SimpleTaskNotFound(EvelynBaseError::NothingElse)),
         Ok(_) => None,
         Err(e) => Some(EvelynDatabaseError::RemoveSimpleTask(e)),
     }
 }
+
+pub fn lookup_deleted(
+    client: &Client,
+    user_id: &String,
+    deleted_since: i64,
+) -> Result<Vec<model::simple_task::SimpleTaskModel>, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("simpletask");
+
+    let mut filter = doc!{"userId" => user_id};
+    trash::add_deleted_since(&mut filter, deleted_since);
+
+    match collection.find(Some(filter), None) {
+        Ok(cursor) => cursor::collect(cursor, EvelynDatabaseError::LookupTrash, PartialResults::Deny, "lookup deleted simple tasks"),
+        Err(e) => Err(EvelynDatabaseError::LookupTrash(e)),
+    }
+}
+
+pub fn restore(
+    client: &Client,
+    user_id: &String,
+    task_id: &String,
+    deleted_since: i64,
+) -> Option<EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("simpletask");
+
+    let mut filter = doc!{"userId" => user_id, "taskId" => task_id};
+    trash::add_deleted_since(&mut filter, deleted_since);
+
+    match collection.update_one(filter, trash::build_restore_update(), None) {
+        Ok(ref result) if result.matched_count == 0 => Some(EvelynDatabaseError::TrashItemNotFound(EvelynBaseError::NothingElse)),
+        Ok(_) => None,
+        Err(e) => Some(EvelynDatabaseError::RestoreFromTrash(e)),
+    }
+}
+
+pub fn purge_deleted(
+    client: &Client,
+    deleted_before: i64,
+) -> Result<i32, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("simpletask");
+
+    trash::purge_deleted_before(&collection, deleted_before).map_err(EvelynDatabaseError::PurgeTrash)
+}
+
+// Completes a task if it is still at the version it was read at. Returns
+// whether it was completed.
+pub fn complete_simple_task(
+    client: &Client,
+    user_id: &String,
+    task_id: &String,
+    expected_version: i64,
+    date_completed: i64,
+) -> Result<bool, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("simpletask");
+
+    let mut filter = doc!{"userId" => user_id, "taskId" => task_id, "completed" => false};
+    trash::add_not_deleted(&mut filter);
+    version::add_expected_version(&mut filter, Some(expected_version));
+
+    let mut update_query = doc!{"$set" => doc!{"completed" => true, "dateCompleted" => Bson::I64(date_completed)}};
+    version::add_version_increment(&mut update_query);
+
+    match collection.update_one(filter, update_query, None) {
+        Ok(result) => Ok(result.modified_count > 0),
+        Err(e) => Err(EvelynDatabaseError::UpdateSimpleTask(e)),
+    }
+}
+
+// Left out of the version as it isn't something the user changes.
+pub fn set_next_task_id(
+    client: &Client,
+    user_id: &String,
+    task_id: &String,
+    next_task_id: &String,
+) -> Option<EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("simpletask");
+
+    let filter = doc!{"userId" => user_id, "taskId" => task_id};
+    let update_query = doc!{"$set" => doc!{"nextTaskId" => next_task_id}};
+
+    match collection.update_one(filter, update_query, None) {
+        Ok(_) => None,
+        Err(e) => Some(EvelynDatabaseError::UpdateSimpleTask(e)),
+    }
+}
+
+pub fn update_checklist_item(
+    client: &Client,
+    update_model: &model::simple_task::SimpleTaskChecklistItemUpdateModel,
+) -> Option<EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("simpletask");
+
+    let ref user_id = update_model.user_id;
+    let ref task_id = update_model.task_id;
+    let mut identity_filter = doc!{"userId" => user_id, "taskId" => task_id};
+    trash::add_not_deleted(&mut identity_filter);
+    // Without this an out of range index would pad the checklist with nulls.
+    identity_filter.insert(format!("checklist.{}", update_model.item_index), doc!{"$exists" => true});
+    let mut filter = identity_filter.clone();
+    version::add_expected_version(&mut filter, update_model.expected_version);
+
+    let mut update_query = Document::new();
+    update_query.insert(format!("checklist.{}.isDone", update_model.item_index), update_model.is_done);
+
+    let mut set_update_query = Document::new();
+    set_update_query.insert("$set", update_query);
+    version::add_version_increment(&mut set_update_query);
+
+    match collection.update_one(filter, set_update_query, None) {
+        Ok(result) => {
+            version::check_update_result(
+                &collection,
+                result,
+                identity_filter,
+                update_model.expected_version,
+                EvelynDatabaseError::SimpleTaskNotFound(EvelynBaseError::NothingElse),
+                EvelynDatabaseError::SimpleTaskVersionConflict(EvelynBaseError::NothingElse),
+                EvelynDatabaseError::UpdateSimpleTaskChecklist,
+            )
+        },
+        Err(e) => Some(EvelynDatabaseError::UpdateSimpleTaskChecklist(e)),
+    }
+}
+
+// Records that a task is being converted, completing it, if it is still at the
+// version it was read at and hasn't been converted already. Returns whether
+// the conversion was recorded.
+pub fn claim_conversion(
+    client: &Client,
+    user_id: &String,
+    task_id: &String,
+    expected_version: i64,
+    converted_to: &model::conversion::ConversionLinkModel,
+    date_completed: i64,
+) -> Result<bool, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("simpletask");
+
+    let mut filter = doc!{"userId" => user_id, "taskId" => task_id, "convertedTo" => Bson::Null};
+    trash::add_not_deleted(&mut filter);
+    version::add_expected_version(&mut filter, Some(expected_version));
+
+    let converted_to = match bson::to_bson(converted_to) {
+        Ok(converted_to) => converted_to,
+        Err(_) => return Err(EvelynDatabaseError::SerialisationFailed(EvelynBaseError::NothingElse)),
+    };
+
+    let mut update_query = doc!{"$set" => doc!{
+        "convertedTo" => converted_to,
+        "completed" => true,
+        "dateCompleted" => Bson::I64(date_completed)
+    }};
+    version::add_version_increment(&mut update_query);
+
+    match collection.update_one(filter, update_query, None) {
+        Ok(result) => Ok(result.modified_count > 0),
+        Err(e) => Err(EvelynDatabaseError::ConvertSimpleTask(e)),
+    }
+}
+
+// Undoes a claimed conversion whose new item couldn't be created.
+pub fn release_conversion(
+    client: &Client,
+    user_id: &String,
+    task_id: &String,
+    converted_to_id: &String,
+) -> Option<EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("simpletask");
+
+    let filter = doc!{"userId" => user_id, "taskId" => task_id, "convertedTo.itemId" => converted_to_id};
+
+    let mut update_query = doc!{"$set" => doc!{
+        "convertedTo" => Bson::Null,
+        "completed" => false,
+        "dateCompleted" => Bson::Null
+    }};
+    version::add_version_increment(&mut update_query);
+
+    match collection.update_one(filter, update_query, None) {
+        Ok(_) => None,
+        Err(e) => Some(EvelynDatabaseError::ConvertSimpleTask(e)),
+    }
+}
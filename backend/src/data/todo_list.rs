@@ -17,11 +17,47 @@
 use bson;
 use bson::{Bson, Document};
 use core::error_messages::{EvelynBaseError, EvelynDatabaseError};
+use data::cursor;
+use data::cursor::PartialResults;
+use data::trash;
+use data::version;
 use model;
+use model::trash::DeletionModel;
 use mongodb::{Client, ThreadedClient};
 use mongodb::coll::options::FindOptions;
 use mongodb::db::ThreadedDatabase;
 
+// Finds the lists the user owns or which have been shared with them or one of
+// their groups. Only lists shared for editing are found when they are to be
+// changed. Updates through the positional operator can't use this, as the
+// share arrays would compete with the items for the matched position, so
+// their callers check access first.
+fn build_access_filter(
+    access: &model::todo_list::TodoListAccessModel,
+    edit: bool,
+) -> Document {
+    let mut user_share_filter = doc!{"userId" => &access.user_id};
+
+    let user_group_ids: bson::Array = access.user_group_ids.iter().map(|x| Bson::String(x.clone())).collect();
+    let mut user_group_share_filter = doc!{"userGroupId" => doc!{"$in" => user_group_ids}};
+
+    if edit {
+        user_share_filter.insert("permission", "edit");
+        user_group_share_filter.insert("permission", "edit");
+    }
+
+    let mut arr = bson::Array::new();
+    arr.push(Bson::Document(doc!{"userId" => &access.user_id}));
+    arr.push(Bson::Document(doc!{"userShares" => doc!{"$elemMatch" => user_share_filter}}));
+    arr.push(Bson::Document(doc!{"userGroupShares" => doc!{"$elemMatch" => user_group_share_filter}}));
+
+    let mut filter = Document::new();
+    filter.insert("$or", Bson::Array(arr));
+    trash::add_not_deleted(&mut filter);
+
+    filter
+}
+
 pub fn insert_todo_list(
     client: &Client,
     create_todo_list_model: &model::todo_list::TodoListModel,
@@ -46,9 +82,8 @@ pub fn add_item_to_todo_list(
 ) -> Option<EvelynDatabaseError> {
     let collection = client.db("evelyn").collection("todolist");
 
-    let ref user_id = add_item_todo_list_model.user_id;
-    let ref todo_list_id = add_item_todo_list_model.todo_list_id;
-    let filter = doc!("userId" => user_id, "todoListId" => todo_list_id);
+    let mut filter = build_access_filter(&add_item_todo_list_model.access, true);
+    filter.insert("todoListId", &add_item_todo_list_model.todo_list_id);
 
     let mut update_query = Document::new();
     let bson_todo_list_item_model = bson::to_bson(&add_item_todo_list_model.todo_list_item).unwrap();
@@ -57,9 +92,16 @@ pub fn add_item_to_todo_list(
 
         let mut push_update_query = Document::new();
         push_update_query.insert("$push", update_query);
+        version::add_version_increment(&mut push_update_query);
 
         match collection.update_one(filter, push_update_query, None) {
-            Ok(_) => None,
+            Ok(result) => {
+                if result.matched_count == 0 {
+                    Some(EvelynDatabaseError::TodoListNotFound(EvelynBaseError::NothingElse))
+                } else {
+                    None
+                }
+            },
             Err(e) => Some(EvelynDatabaseError::AddItemToTodoList(e)),
         }
     } else {
@@ -73,40 +115,27 @@ pub fn lookup_todo_lists(
 ) -> Result<Vec<model::todo_list::TodoListsModel>, EvelynDatabaseError> {
     let collection = client.db("evelyn").collection("todolist");
 
-    let ref user_id = lookup_todo_lists_model.user_id;
-    let query = doc!{"userId" => user_id};
+    let mut query = build_access_filter(&lookup_todo_lists_model.access, false);
+    if !lookup_todo_lists_model.show_archived {
+        query.insert("archived", doc!{"$ne" => true});
+    }
+    if lookup_todo_lists_model.incomplete_only {
+        query.insert("todoListItems", doc!{"$elemMatch" => doc!{"isDone" => false}});
+    }
 
     let mut find_options = FindOptions::new();
 
     let mut projection = Document::new();
     projection.insert("title", Bson::I32(1));
     projection.insert("todoListId", Bson::I32(1));
-    projection.insert("_id", Bson::I32(0));
+    projection.insert("archived", Bson::I32(1));
+    projection.insert("userId", Bson::I32(1));
+    projection.insert("userShares", Bson::I32(1));
+    projection.insert("userGroupShares", Bson::I32(1));
     find_options.projection = Some(projection);
 
-    let cursor = collection.find(Some(query), Some(find_options));
-
-    match cursor {
-        Ok(cursor) => {
-            Ok(cursor.filter_map(|x| {
-                match x {
-                    Ok(x) => {
-                        match bson::from_bson(bson::Bson::Document(x)) {
-                            Ok(obj) => obj,
-                            Err(e) => {
-                                error!("BSON Serialize error in lookup todo lists {}", e);
-                                None        
-                            }
-                        }
-                    },
-                    Err(e) => {
-                        error!("Database error in lookup todo lists {}", e);
-                        None
-                    },
-                }
-            })
-                   .collect())
-        },
+    match collection.find(Some(query), Some(find_options)) {
+        Ok(c) => cursor::collect(c, EvelynDatabaseError::LookupTodoLists, PartialResults::Deny, "lookup todo lists"),
         Err(e) => Err(EvelynDatabaseError::LookupTodoLists(e)),
     }
 }
@@ -117,17 +146,13 @@ pub fn lookup_todo_list(
 ) -> Result<model::todo_list::TodoListModel, EvelynDatabaseError> {
     let collection = client.db("evelyn").collection("todolist");
 
-    let ref user_id = lookup_todo_list_model.user_id;
-    let ref todo_list_id = lookup_todo_list_model.todo_list_id;
-    let query = doc!{"userId" => user_id, "todoListId" => todo_list_id};
+    let mut query = build_access_filter(&lookup_todo_list_model.access, false);
+    query.insert("todoListId", &lookup_todo_list_model.todo_list_id);
 
     match collection.find_one(Some(query), None) {
         Ok(result) => {
             if let Some(result) = result {
-                match bson::from_bson(bson::Bson::Document(result)) {
-                    Ok(obj) => Ok(obj),
-                    Err(e) => Err(EvelynDatabaseError::BSONDecodeFailed(e))
-                }
+                cursor::decode_document(result)
             } else {
                 Err(EvelynDatabaseError::TodoListNotFound(EvelynBaseError::NothingElse))
             }
@@ -142,19 +167,418 @@ pub fn update_todo_list_item(
 ) -> Option<EvelynDatabaseError> {
     let collection = client.db("evelyn").collection("todolist");
 
-    let ref user_id = update_todo_list_item.user_id;
     let ref todo_list_id = update_todo_list_item.todo_list_id;
-    let match_query = doc!{"userId" => user_id, "todoListId" => todo_list_id};
+    let ref item_id = update_todo_list_item.item_id;
+    let mut identity_filter = doc!{"todoListId" => todo_list_id, "todoListItems.itemId" => item_id};
+    trash::add_not_deleted(&mut identity_filter);
+    let mut match_query = identity_filter.clone();
+    version::add_expected_version(&mut match_query, update_todo_list_item.expected_version);
 
+    // The positional operator picks out the item the filter matched.
     let mut update_query = Document::new();
-    update_query.insert(format!("todoListItems.{}.isDone", update_todo_list_item.item_index),
-                        update_todo_list_item.is_done);
+    match update_todo_list_item.is_done {
+        Some(true) => {
+            update_query.insert("todoListItems.$.isDone", true);
+            update_query.insert("todoListItems.$.doneByUserId", &update_todo_list_item.user_id);
+            update_query.insert("todoListItems.$.dateDone", Bson::I64(update_todo_list_item.date_done));
+        },
+        Some(false) => {
+            update_query.insert("todoListItems.$.isDone", false);
+            update_query.insert("todoListItems.$.doneByUserId", Bson::Null);
+            update_query.insert("todoListItems.$.dateDone", Bson::Null);
+        },
+        None => {},
+    }
+    if let Some(ref text) = update_todo_list_item.text {
+        update_query.insert("todoListItems.$.text", text);
+    }
 
     let mut set_update_query = Document::new();
     set_update_query.insert("$set", update_query);
+    version::add_version_increment(&mut set_update_query);
 
     match collection.update_one(match_query, set_update_query, None) {
-        Ok(_) => None,
+        Ok(result) => {
+            version::check_update_result(
+                &collection,
+                result,
+                identity_filter,
+                update_todo_list_item.expected_version,
+                EvelynDatabaseError::TodoListNotFound(EvelynBaseError::NothingElse),
+                EvelynDatabaseError::TodoListVersionConflict(EvelynBaseError::NothingElse),
+                EvelynDatabaseError::UpdateTodoListItem,
+            )
+        },
         Err(e) => Some(EvelynDatabaseError::UpdateTodoListItem(e)),
     }
 }
+
+// Records that an item is being converted if it hasn't been converted
+// already. Returns whether the conversion was recorded.
+pub fn claim_item_conversion(
+    client: &Client,
+    todo_list_id: &String,
+    item_id: &String,
+    converted_to: &model::conversion::ConversionLinkModel,
+) -> Result<bool, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("todolist");
+
+    let mut filter = doc!{
+        "todoListId" => todo_list_id,
+        "todoListItems" => doc!{"$elemMatch" => doc!{"itemId" => item_id, "convertedTo" => Bson::Null}}
+    };
+    trash::add_not_deleted(&mut filter);
+
+    let converted_to = match bson::to_bson(converted_to) {
+        Ok(converted_to) => converted_to,
+        Err(_) => return Err(EvelynDatabaseError::SerialisationFailed(EvelynBaseError::NothingElse)),
+    };
+
+    let mut update_query = Document::new();
+    update_query.insert("todoListItems.$.convertedTo", converted_to);
+
+    let mut set_update_query = Document::new();
+    set_update_query.insert("$set", update_query);
+    version::add_version_increment(&mut set_update_query);
+
+    match collection.update_one(filter, set_update_query, None) {
+        Ok(result) => Ok(result.modified_count > 0),
+        Err(e) => Err(EvelynDatabaseError::ConvertTodoListItem(e)),
+    }
+}
+
+// Undoes a claimed conversion whose simple task couldn't be created.
+pub fn release_item_conversion(
+    client: &Client,
+    todo_list_id: &String,
+    converted_to_id: &String,
+) -> Option<EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("todolist");
+
+    let filter = doc!{"todoListId" => todo_list_id, "todoListItems.convertedTo.itemId" => converted_to_id};
+
+    let mut update_query = Document::new();
+    update_query.insert("todoListItems.$.convertedTo", Bson::Null);
+
+    let mut set_update_query = Document::new();
+    set_update_query.insert("$set", update_query);
+    version::add_version_increment(&mut set_update_query);
+
+    match collection.update_one(filter, set_update_query, None) {
+        Ok(_) => None,
+        Err(e) => Some(EvelynDatabaseError::ConvertTodoListItem(e)),
+    }
+}
+
+pub fn update_todo_list(
+    client: &Client,
+    update_todo_list_model: &model::todo_list::UpdateTodoListModel,
+) -> Option<EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("todolist");
+
+    let ref user_id = update_todo_list_model.user_id;
+    let ref todo_list_id = update_todo_list_model.todo_list_id;
+    let mut identity_filter = doc!{"userId" => user_id, "todoListId" => todo_list_id};
+    trash::add_not_deleted(&mut identity_filter);
+    let mut match_query = identity_filter.clone();
+    version::add_expected_version(&mut match_query, update_todo_list_model.expected_version);
+
+    let mut update_query = Document::new();
+    if let Some(ref title) = update_todo_list_model.title {
+        update_query.insert("title", title);
+    }
+    if let Some(archived) = update_todo_list_model.archived {
+        update_query.insert("archived", archived);
+    }
+
+    let mut set_update_query = Document::new();
+    set_update_query.insert("$set", update_query);
+    version::add_version_increment(&mut set_update_query);
+
+    match collection.update_one(match_query, set_update_query, None) {
+        Ok(result) => {
+            version::check_update_result(
+                &collection,
+                result,
+                identity_filter,
+                update_todo_list_model.expected_version,
+                EvelynDatabaseError::TodoListNotFound(EvelynBaseError::NothingElse),
+                EvelynDatabaseError::TodoListVersionConflict(EvelynBaseError::NothingElse),
+                EvelynDatabaseError::UpdateTodoList,
+            )
+        },
+        Err(e) => Some(EvelynDatabaseError::UpdateTodoList(e)),
+    }
+}
+
+// Replaces the items of a list if it is still at the version it was read at.
+// Whoever read it must already have been allowed to change it.
+pub fn replace_todo_list_items(
+    client: &Client,
+    todo_list_id: &String,
+    todo_list_items: &Vec<model::todo_list::item::TodoListItemModel>,
+    expected_version: i64,
+) -> Option<EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("todolist");
+
+    let identity_filter = doc!{"todoListId" => todo_list_id};
+    let mut match_query = identity_filter.clone();
+    version::add_expected_version(&mut match_query, Some(expected_version));
+
+    let todo_list_items = match bson::to_bson(todo_list_items) {
+        Ok(todo_list_items) => todo_list_items,
+        Err(e) => return Some(EvelynDatabaseError::BSONEncodeFailed(e)),
+    };
+
+    let mut set_update_query = doc!{"$set" => doc!{"todoListItems" => todo_list_items}};
+    version::add_version_increment(&mut set_update_query);
+
+    match collection.update_one(match_query, set_update_query, None) {
+        Ok(result) => {
+            version::check_update_result(
+                &collection,
+                result,
+                identity_filter,
+                Some(expected_version),
+                EvelynDatabaseError::TodoListNotFound(EvelynBaseError::NothingElse),
+                EvelynDatabaseError::TodoListVersionConflict(EvelynBaseError::NothingElse),
+                EvelynDatabaseError::UpdateTodoList,
+            )
+        },
+        Err(e) => Some(EvelynDatabaseError::UpdateTodoList(e)),
+    }
+}
+
+pub fn remove_todo_list_item(
+    client: &Client,
+    access: &model::todo_list::TodoListAccessModel,
+    todo_list_id: &String,
+    item_id: &String,
+    expected_version: Option<i64>,
+) -> Option<EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("todolist");
+
+    let mut identity_filter = build_access_filter(access, true);
+    identity_filter.insert("todoListId", todo_list_id);
+    identity_filter.insert("todoListItems.itemId", item_id);
+    let mut match_query = identity_filter.clone();
+    version::add_expected_version(&mut match_query, expected_version);
+
+    let mut pull_update_query = doc!{"$pull" => doc!{"todoListItems" => doc!{"itemId" => item_id}}};
+    version::add_version_increment(&mut pull_update_query);
+
+    match collection.update_one(match_query, pull_update_query, None) {
+        Ok(result) => {
+            version::check_update_result(
+                &collection,
+                result,
+                identity_filter,
+                expected_version,
+                EvelynDatabaseError::TodoListNotFound(EvelynBaseError::NothingElse),
+                EvelynDatabaseError::TodoListVersionConflict(EvelynBaseError::NothingElse),
+                EvelynDatabaseError::RemoveTodoListItem,
+            )
+        },
+        Err(e) => Some(EvelynDatabaseError::RemoveTodoListItem(e)),
+    }
+}
+
+// Replaces who a list is shared with, if it is still at the version it was
+// read at.
+pub fn update_todo_list_shares(
+    client: &Client,
+    todo_list_model: &model::todo_list::TodoListModel,
+) -> Option<EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("todolist");
+
+    let mut identity_filter = doc!{"userId" => &todo_list_model.user_id, "todoListId" => &todo_list_model.todo_list_id};
+    trash::add_not_deleted(&mut identity_filter);
+    let mut match_query = identity_filter.clone();
+    version::add_expected_version(&mut match_query, Some(todo_list_model.version));
+
+    let user_shares = match bson::to_bson(&todo_list_model.user_shares) {
+        Ok(user_shares) => user_shares,
+        Err(e) => return Some(EvelynDatabaseError::BSONEncodeFailed(e)),
+    };
+    let user_group_shares = match bson::to_bson(&todo_list_model.user_group_shares) {
+        Ok(user_group_shares) => user_group_shares,
+        Err(e) => return Some(EvelynDatabaseError::BSONEncodeFailed(e)),
+    };
+
+    let mut set_update_query = doc!{"$set" => doc!{"userShares" => user_shares, "userGroupShares" => user_group_shares}};
+    version::add_version_increment(&mut set_update_query);
+
+    match collection.update_one(match_query, set_update_query, None) {
+        Ok(result) => {
+            version::check_update_result(
+                &collection,
+                result,
+                identity_filter,
+                Some(todo_list_model.version),
+                EvelynDatabaseError::TodoListNotFound(EvelynBaseError::NothingElse),
+                EvelynDatabaseError::TodoListVersionConflict(EvelynBaseError::NothingElse),
+                EvelynDatabaseError::UpdateTodoListShares,
+            )
+        },
+        Err(e) => Some(EvelynDatabaseError::UpdateTodoListShares(e)),
+    }
+}
+
+pub fn remove(
+    client: &Client,
+    user_id: &String,
+    todo_list_id: &String,
+    deletion: &DeletionModel,
+) -> Option<EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("todolist");
+
+    let mut filter = doc!{"userId" => user_id, "todoListId" => todo_list_id};
+    trash::add_not_deleted(&mut filter);
+
+    let update_query = match trash::build_mark_deleted_update(deletion) {
+        Ok(update_query) => update_query,
+        Err(e) => return Some(e),
+    };
+
+    match collection.update_one(filter, update_query, None) {
+        Ok(ref result) if result.matched_count == 0 => Some(EvelynDatabaseError::TodoListNotFound(EvelynBaseError::NothingElse)),
+        Ok(_) => None,
+        Err(e) => Some(EvelynDatabaseError::RemoveTodoList(e)),
+    }
+}
+
+pub fn lookup_deleted(
+    client: &Client,
+    user_id: &String,
+    deleted_since: i64,
+) -> Result<Vec<model::todo_list::TodoListModel>, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("todolist");
+
+    let mut filter = doc!{"userId" => user_id};
+    trash::add_deleted_since(&mut filter, deleted_since);
+
+    match collection.find(Some(filter), None) {
+        Ok(cursor) => cursor::collect(cursor, EvelynDatabaseError::LookupTrash, PartialResults::Deny, "lookup deleted todo lists"),
+        Err(e) => Err(EvelynDatabaseError::LookupTrash(e)),
+    }
+}
+
+pub fn restore(
+    client: &Client,
+    user_id: &String,
+    todo_list_id: &String,
+    deleted_since: i64,
+) -> Option<EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("todolist");
+
+    let mut filter = doc!{"userId" => user_id, "todoListId" => todo_list_id};
+    trash::add_deleted_since(&mut filter, deleted_since);
+
+    match collection.update_one(filter, trash::build_restore_update(), None) {
+        Ok(ref result) if result.matched_count == 0 => Some(EvelynDatabaseError::TrashItemNotFound(EvelynBaseError::NothingElse)),
+        Ok(_) => None,
+        Err(e) => Some(EvelynDatabaseError::RestoreFromTrash(e)),
+    }
+}
+
+pub fn purge_deleted(
+    client: &Client,
+    deleted_before: i64,
+) -> Result<i32, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("todolist");
+
+    trash::purge_deleted_before(&collection, deleted_before).map_err(EvelynDatabaseError::PurgeTrash)
+}
+
+// Items used to be found by their position in the list and had no id.
+pub fn lookup_lists_without_item_ids(client: &Client) -> Result<Vec<model::todo_list::TodoListModel>, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("todolist");
+
+    let filter = doc!{"todoListItems" => doc!{"$elemMatch" => doc!{"itemId" => doc!{"$exists" => false}}}};
+
+    match collection.find(Some(filter), None) {
+        Ok(c) => cursor::collect(c, EvelynDatabaseError::MigrateTodoLists, PartialResults::Deny, "lookup todo lists without item ids"),
+        Err(e) => Err(EvelynDatabaseError::MigrateTodoLists(e)),
+    }
+}
+
+// Templates the user made or which were shared with one of their groups.
+fn build_template_access_filter(access: &model::todo_list::TodoListAccessModel) -> Document {
+    let user_group_ids: bson::Array = access.user_group_ids.iter().map(|x| Bson::String(x.clone())).collect();
+
+    let mut arr = bson::Array::new();
+    arr.push(Bson::Document(doc!{"userId" => &access.user_id}));
+    arr.push(Bson::Document(doc!{"userGroupId" => doc!{"$in" => user_group_ids}}));
+
+    let mut filter = Document::new();
+    filter.insert("$or", Bson::Array(arr));
+
+    filter
+}
+
+pub fn insert_template(
+    client: &Client,
+    template_model: &model::todo_list::template::TodoListTemplateModel,
+) -> Option<EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("todolist_template");
+
+    match bson::to_bson(template_model) {
+        Ok(Bson::Document(document)) => {
+            match collection.insert_one(document, None) {
+                Ok(_) => None,
+                Err(e) => Some(EvelynDatabaseError::InsertTodoListTemplate(e)),
+            }
+        },
+        Ok(_) => Some(EvelynDatabaseError::SerialisationFailed(EvelynBaseError::NothingElse)),
+        Err(e) => Some(EvelynDatabaseError::BSONEncodeFailed(e)),
+    }
+}
+
+pub fn lookup_templates(
+    client: &Client,
+    access: &model::todo_list::TodoListAccessModel,
+) -> Result<Vec<model::todo_list::template::TodoListTemplateModel>, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("todolist_template");
+
+    let filter = build_template_access_filter(access);
+
+    match collection.find(Some(filter), None) {
+        Ok(c) => cursor::collect(c, EvelynDatabaseError::LookupTodoListTemplates, PartialResults::Deny, "lookup todo list templates"),
+        Err(e) => Err(EvelynDatabaseError::LookupTodoListTemplates(e)),
+    }
+}
+
+pub fn lookup_template(
+    client: &Client,
+    access: &model::todo_list::TodoListAccessModel,
+    template_id: &String,
+) -> Result<model::todo_list::template::TodoListTemplateModel, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("todolist_template");
+
+    let mut filter = build_template_access_filter(access);
+    filter.insert("templateId", template_id);
+
+    match collection.find_one(Some(filter), None) {
+        Ok(Some(document)) => cursor::decode_document(document),
+        Ok(None) => Err(EvelynDatabaseError::TodoListTemplateNotFound(EvelynBaseError::NothingElse)),
+        Err(e) => Err(EvelynDatabaseError::LookupTodoListTemplates(e)),
+    }
+}
+
+pub fn remove_template(
+    client: &Client,
+    user_id: &String,
+    template_id: &String,
+) -> Option<EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("todolist_template");
+
+    let filter = doc!{"userId" => user_id, "templateId" => template_id};
+
+    match collection.delete_one(filter, None) {
+        Ok(ref result) if result.deleted_count == 0 => {
+            Some(EvelynDatabaseError::TodoListTemplateNotFound(EvelynBaseError::NothingElse))
+        },
+        Ok(_) => None,
+        Err(e) => Some(EvelynDatabaseError::RemoveTodoListTemplate(e)),
+    }
+}
@@ -0,0 +1,63 @@
+// Evelyn: Your personal assistant, project manager and calendar
+// Copyright (C) 2017 Gregory Jensen
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use bson;
+use bson::{Bson, Document};
+use core::error_messages::{EvelynBaseError, EvelynDatabaseError};
+use model::trash::DeletionModel;
+use mongodb::coll::Collection;
+use mongodb::error::Error as MongoDbError;
+
+// Documents which have never been deleted have no deleted field, a null
+// filter matches those as well as restored documents.
+pub fn add_not_deleted(filter: &mut Document) {
+    filter.insert("deleted", Bson::Null);
+}
+
+pub fn add_deleted_since(
+    filter: &mut Document,
+    deleted_since: i64,
+) {
+    filter.insert("deleted.dateDeleted", doc!{"$gte" => deleted_since});
+}
+
+pub fn build_mark_deleted_update(deletion: &DeletionModel) -> Result<Document, EvelynDatabaseError> {
+    match bson::to_bson(deletion) {
+        Ok(Bson::Document(document)) => {
+            let mut set_update_query = Document::new();
+            set_update_query.insert("deleted", document);
+
+            let mut update_query = Document::new();
+            update_query.insert("$set", set_update_query);
+            Ok(update_query)
+        },
+        Ok(_) => Err(EvelynDatabaseError::SerialisationFailed(EvelynBaseError::NothingElse)),
+        Err(e) => Err(EvelynDatabaseError::BSONEncodeFailed(e)),
+    }
+}
+
+pub fn build_restore_update() -> Document {
+    doc!{"$unset" => doc!{"deleted" => ""}}
+}
+
+pub fn purge_deleted_before(
+    collection: &Collection,
+    deleted_before: i64,
+) -> Result<i32, MongoDbError> {
+    let filter = doc!{"deleted.dateDeleted" => doc!{"$lt" => deleted_before}};
+
+    collection.delete_many(filter, None).map(|result| result.deleted_count)
+}
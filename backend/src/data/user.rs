@@ -17,6 +17,9 @@
 use bson;
 use bson::{Bson, Document};
 use core::error_messages::{EvelynBaseError, EvelynDatabaseError};
+use data::cursor;
+use data::cursor::PartialResults;
+use model::notification::NotificationKindModel;
 use model::user::{UserModel, SearchResult};
 use mongodb::{Client, ThreadedClient};
 use mongodb::coll::options::FindOptions;
@@ -58,14 +61,8 @@ pub fn find_user(
     match result {
         Ok(result_unwrap) => {
             match result_unwrap {
-                Some(item) => {
-                    match bson::from_bson(bson::Bson::Document(item)) {
-                        Ok(bson_obj) => Ok(Some(bson_obj)),
-                        Err(e) => Err(EvelynDatabaseError::BSONDecodeFailed(e))
-                    }
-                },
-                // Todo: fix again
-                None => Ok(None)
+                Some(item) => cursor::decode_document(item).map(Some),
+                None => Ok(None),
             }
         },
         Err(e) => Err(EvelynDatabaseError::LookupUser(e)),
@@ -84,14 +81,8 @@ pub fn find_user_by_id(
     match result {
         Ok(result_unwrap) => {
             match result_unwrap {
-                Some(item) => {
-                    match bson::from_bson(bson::Bson::Document(item)) {
-                        Ok(bson_obj) => Ok(Some(bson_obj)),
-                        Err(e) => Err(EvelynDatabaseError::BSONDecodeFailed(e))
-                    }
-                },
-                // Todo: fix again
-                None => Ok(None)
+                Some(item) => cursor::decode_document(item).map(Some),
+                None => Ok(None),
             }
         },
         Err(e) => Err(EvelynDatabaseError::LookupUser(e)),
@@ -112,31 +103,53 @@ pub fn search_for_users(
     let mut projection = Document::new();
     projection.insert("userId", Bson::I32(1));
     projection.insert("userName", Bson::I32(1));
-    projection.insert("_id", Bson::I32(0));
     find_options.projection = Some(projection);
 
-    let cursor = collection.find(Some(filter), Some(find_options));
-
-    match cursor {
-        Ok(c) => {
-            let results: Vec<SearchResult> = c.filter_map(|x| match x {
-               Ok(x) => {
-                   match bson::from_bson(bson::Bson::Document(x)) {
-                        Ok(obj) => obj,
-                        Err(e) => {
-                            error!("BSON Serialize error in lookup users {}", e);
-                            None        
-                        }
-                    }
-               },
-               Err(e) => {
-                    error!("Database error in search for users {}", e);
-                    None
-               },
-           }).collect();
-
-           Ok(results)
-        },
+    // Search is best effort, one bad user record shouldn't hide everyone else.
+    match collection.find(Some(filter), Some(find_options)) {
+        Ok(c) => cursor::collect(c, EvelynDatabaseError::SearchForUsers, PartialResults::AllowAndLog, "search for users"),
         Err(e) => Err(EvelynDatabaseError::SearchForUsers(e)),
     }
 }
+
+pub fn update_time_zone(
+    client: &Client,
+    user_id: &String,
+    time_zone: &Option<String>,
+) -> Option<EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("user");
+
+    let filter = doc!{"userId" => user_id};
+
+    let mut set = Document::new();
+    match *time_zone {
+        Some(ref time_zone) => set.insert("timeZone", time_zone.to_owned()),
+        None => set.insert("timeZone", Bson::Null),
+    };
+    let update = doc!{"$set" => set};
+
+    match collection.update_one(filter, update, None) {
+        Ok(ref result) if result.matched_count == 0 => Some(EvelynDatabaseError::UserNotFound(EvelynBaseError::NothingElse)),
+        Ok(_) => None,
+        Err(e) => Some(EvelynDatabaseError::UpdateUser(e)),
+    }
+}
+
+pub fn update_email_notifications(
+    client: &Client,
+    user_id: &String,
+    kinds: &Vec<NotificationKindModel>,
+) -> Option<EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("user");
+
+    let kinds = match bson::to_bson(kinds) {
+        Ok(kinds) => kinds,
+        Err(e) => return Some(EvelynDatabaseError::BSONEncodeFailed(e)),
+    };
+
+    match collection.update_one(doc!{"userId" => user_id}, doc!{"$set" => doc!{"emailNotifications" => kinds}}, None) {
+        Ok(ref result) if result.matched_count == 0 => Some(EvelynDatabaseError::UserNotFound(EvelynBaseError::NothingElse)),
+        Ok(_) => None,
+        Err(e) => Some(EvelynDatabaseError::UpdateUser(e)),
+    }
+}
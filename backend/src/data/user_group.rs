@@ -17,6 +17,10 @@
 use bson;
 use bson::{Bson, Document};
 use core::error_messages::{EvelynBaseError, EvelynDatabaseError};
+use data::cursor;
+use data::cursor::PartialResults;
+use data::trash;
+use model::trash::DeletionModel;
 use model::user_group as user_group_model;
 use mongodb::{Client, ThreadedClient};
 use mongodb::coll::options::FindOptions;
@@ -35,6 +39,7 @@ fn build_user_group_lookup_filter(user_id: &String) -> Document {
 
     let mut filter = Document::new();
     filter.insert("$or", Bson::Array(arr));
+    trash::add_not_deleted(&mut filter);
 
     filter
 }
@@ -52,18 +57,70 @@ pub fn insert_user_group(
 
 pub fn remove_user_group(
     client: &Client,
+    user_id: &String,
     user_group_id: String,
+    deletion: &DeletionModel,
 ) -> Option<EvelynDatabaseError> {
     let collection = client.db("evelyn").collection("usergroup");
 
-    let filter = doc!{"userGroupId" => user_group_id};
+    let mut filter = doc!{"createdByUserId" => user_id, "userGroupId" => user_group_id};
+    trash::add_not_deleted(&mut filter);
+
+    let update_query = match trash::build_mark_deleted_update(deletion) {
+        Ok(update_query) => update_query,
+        Err(e) => return Some(e),
+    };
 
-    match collection.delete_one(filter, None) { 
+    match collection.update_one(filter, update_query, None) {
+        Ok(ref result) if result.matched_count == 0 => Some(EvelynDatabaseError::UserGroupNotFound(EvelynBaseError::NothingElse)),
         Ok(_) => None,
         Err(e) => Some(EvelynDatabaseError::RemoveUserGroup(e)),
     }
 }
 
+pub fn lookup_deleted(
+    client: &Client,
+    user_id: &String,
+    deleted_since: i64,
+) -> Result<Vec<user_group_model::UserGroupModel>, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("usergroup");
+
+    let mut filter = doc!{"createdByUserId" => user_id};
+    trash::add_deleted_since(&mut filter, deleted_since);
+
+    match collection.find(Some(filter), None) {
+        Ok(c) => cursor::collect(c, EvelynDatabaseError::LookupTrash, PartialResults::Deny, "lookup deleted user groups"),
+        Err(e) => Err(EvelynDatabaseError::LookupTrash(e)),
+    }
+}
+
+pub fn restore(
+    client: &Client,
+    user_id: &String,
+    user_group_id: &String,
+    deleted_since: i64,
+) -> Option<EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("usergroup");
+
+    let mut filter = doc!{"createdByUserId" => user_id, "userGroupId" => user_group_id};
+    trash::add_deleted_since(&mut filter, deleted_since);
+
+    match collection.update_one(filter, trash::build_restore_update(), None) {
+        Ok(ref result) if result.matched_count == 0 => Some(EvelynDatabaseError::TrashItemNotFound(EvelynBaseError::NothingElse)),
+        Ok(_) => None,
+        Err(e) => Some(EvelynDatabaseError::RestoreFromTrash(e)),
+    }
+}
+
+pub fn purge_deleted(
+    client: &Client,
+    deleted_before: i64,
+) -> Result<i32, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("usergroup");
+
+    trash::purge_deleted_before(&collection, deleted_before).map_err(EvelynDatabaseError::PurgeTrash)
+}
+
 pub fn lookup_user_groups(
     user_id: &String,
     client: &Client,
@@ -78,31 +135,10 @@ pub fn lookup_user_groups(
     projection.insert("userGroupId", Bson::I32(1));
     projection.insert("name", Bson::I32(1));
     projection.insert("description", Bson::I32(1));
-    projection.insert("_id", Bson::I32(0));
     find_options.projection = Some(projection);
 
-    let cursor = collection.find(Some(filter), Some(find_options));
-
-    match cursor {
-        Ok(cursor) => {
-            Ok(cursor.filter_map(|x| {
-                match x {
-                    Ok(x) => {
-                        match bson::from_bson(bson::Bson::Document(x)) {
-                            Ok(obj) => obj,
-                            Err(e) => {
-                                error!("BSON Serialize error in lookup user groups {}", e);
-                                None        
-                            }
-                        }
-                    },
-                    Err(e) => {
-                        error!("Database error in lookup user groups {}", e);
-                        None
-                    },
-                }
-            }).collect())
-        },
+    match collection.find(Some(filter), Some(find_options)) {
+        Ok(c) => cursor::collect(c, EvelynDatabaseError::LookupUserGroups, PartialResults::Deny, "lookup user groups"),
         Err(e) => Err(EvelynDatabaseError::LookupUserGroups(e)),
     }
 }
@@ -120,10 +156,7 @@ pub fn lookup_user_group(
     match collection.find_one(Some(filter), None) {
         Ok(result) => {
             if let Some(result) = result {
-                match bson::from_bson(bson::Bson::Document(result)) {
-                    Ok(obj) => Ok(obj),
-                    Err(e) => Err(EvelynDatabaseError::BSONDecodeFailed(e))
-                }
+                cursor::decode_document(result)
             } else {
                 Err(EvelynDatabaseError::UserGroupNotFound(EvelynBaseError::NothingElse))
             }
@@ -139,7 +172,8 @@ pub fn add_member(
     let collection = client.db("evelyn").collection("usergroup");
 
     let ref user_group_id = add_member_model.user_group_id;
-    let filter = doc!("userGroupId" => user_group_id);
+    let mut filter = doc!("userGroupId" => user_group_id);
+    trash::add_not_deleted(&mut filter);
 
     let mut update_query = Document::new();
     let bson_member_model = bson::to_bson(&add_member_model.user_group_member_model).unwrap();
@@ -165,7 +199,8 @@ pub fn remove_member(
     let collection = client.db("evelyn").collection("usergroup");
 
     let ref user_group_id = remove_member_model.user_group_id;
-    let filter = doc!("userGroupId" => user_group_id);
+    let mut filter = doc!("userGroupId" => user_group_id);
+    trash::add_not_deleted(&mut filter);
 
     let mut update_query = Document::new();
     let bson_member_model = bson::to_bson(&remove_member_model.user_group_member_model).unwrap();
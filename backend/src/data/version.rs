@@ -0,0 +1,85 @@
+// Evelyn: Your personal assistant, project manager and calendar
+// Copyright (C) 2017 Gregory Jensen
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use bson;
+use bson::{Bson, Document};
+use core::error_messages::EvelynDatabaseError;
+use mongodb::coll::Collection;
+use mongodb::coll::results::UpdateResult;
+use mongodb::error::Error as MongoDbError;
+
+pub const INITIAL_VERSION: i64 = 1;
+
+// Documents written before versioning was added have no version field, those
+// are treated as version 0.
+pub fn add_expected_version(
+    filter: &mut Document,
+    expected_version: Option<i64>,
+) {
+    if let Some(expected_version) = expected_version {
+        if expected_version == 0 {
+            let mut versions = bson::Array::new();
+            versions.push(Bson::I64(0));
+            versions.push(Bson::Null);
+
+            filter.insert("version", doc!{"$in" => versions});
+        } else {
+            filter.insert("version", Bson::I64(expected_version));
+        }
+    }
+}
+
+pub fn add_version_increment(update_query: &mut Document) {
+    update_query.insert("$inc", doc!{"version" => Bson::I64(1)});
+}
+
+// An update which matched nothing either targeted a document which doesn't
+// exist or was made against an out of date version. Look the document up
+// without the version to find out which.
+pub fn check_update_result(
+    collection: &Collection,
+    result: UpdateResult,
+    identity_filter: Document,
+    expected_version: Option<i64>,
+    not_found: EvelynDatabaseError,
+    version_conflict: EvelynDatabaseError,
+    lookup_error: fn(MongoDbError) -> EvelynDatabaseError,
+) -> Option<EvelynDatabaseError> {
+    if result.matched_count > 0 {
+        return None;
+    }
+
+    Some(explain_no_match(collection, identity_filter, expected_version, not_found, version_conflict, lookup_error))
+}
+
+pub fn explain_no_match(
+    collection: &Collection,
+    identity_filter: Document,
+    expected_version: Option<i64>,
+    not_found: EvelynDatabaseError,
+    version_conflict: EvelynDatabaseError,
+    lookup_error: fn(MongoDbError) -> EvelynDatabaseError,
+) -> EvelynDatabaseError {
+    if expected_version.is_none() {
+        return not_found;
+    }
+
+    match collection.count(Some(identity_filter), None) {
+        Ok(0) => not_found,
+        Ok(_) => version_conflict,
+        Err(e) => lookup_error(e),
+    }
+}
@@ -0,0 +1,231 @@
+// Evelyn: Your personal assistant, project manager and calendar
+// Copyright (C) 2017 Gregory Jensen
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use bson;
+use bson::{Bson, Document};
+use core::error_messages::{EvelynBaseError, EvelynDatabaseError};
+use data::cursor;
+use data::cursor::PartialResults;
+use model::webhook as webhook_model;
+use mongodb::{Client, ThreadedClient};
+use mongodb::coll::options::FindOptions;
+use mongodb::db::ThreadedDatabase;
+
+pub fn insert_subscription(
+    client: &Client,
+    subscription_model: &webhook_model::WebhookSubscriptionModel,
+) -> Option<EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("webhook_subscription");
+
+    insert_model!(
+        collection,
+        subscription_model,
+        EvelynDatabaseError::InsertWebhook
+    )
+}
+
+pub fn lookup_subscriptions(
+    client: &Client,
+    user_id: &String,
+) -> Result<Vec<webhook_model::WebhookSubscriptionModel>, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("webhook_subscription");
+
+    let mut find_options = FindOptions::new();
+    find_options.sort = Some(doc!{"dateCreated" => Bson::I32(1)});
+
+    match collection.find(Some(doc!{"userId" => user_id}), Some(find_options)) {
+        Ok(c) => cursor::collect(c, EvelynDatabaseError::LookupWebhooks, PartialResults::Deny, "lookup webhooks"),
+        Err(e) => Err(EvelynDatabaseError::LookupWebhooks(e)),
+    }
+}
+
+pub fn lookup_subscription(
+    client: &Client,
+    user_id: &String,
+    subscription_id: &String,
+) -> Result<webhook_model::WebhookSubscriptionModel, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("webhook_subscription");
+
+    match collection.find_one(Some(doc!{"userId" => user_id, "subscriptionId" => subscription_id}), None) {
+        Ok(Some(document)) => cursor::decode_document(document),
+        Ok(None) => Err(EvelynDatabaseError::WebhookNotFound(EvelynBaseError::NothingElse)),
+        Err(e) => Err(EvelynDatabaseError::LookupWebhooks(e)),
+    }
+}
+
+// Used when sending, where the subscription may have been removed since the
+// delivery was queued.
+pub fn find_subscription(
+    client: &Client,
+    subscription_id: &String,
+) -> Result<Option<webhook_model::WebhookSubscriptionModel>, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("webhook_subscription");
+
+    match collection.find_one(Some(doc!{"subscriptionId" => subscription_id}), None) {
+        Ok(Some(document)) => cursor::decode_document(document).map(Some),
+        Ok(None) => Ok(None),
+        Err(e) => Err(EvelynDatabaseError::LookupWebhooks(e)),
+    }
+}
+
+// Subscriptions to a project which want to hear about the given event.
+pub fn lookup_subscriptions_for_event(
+    client: &Client,
+    project_id: &String,
+    event_type: &webhook_model::WebhookEventTypeModel,
+) -> Result<Vec<webhook_model::WebhookSubscriptionModel>, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("webhook_subscription");
+
+    let event_type = match bson::to_bson(event_type) {
+        Ok(event_type) => event_type,
+        Err(e) => return Err(EvelynDatabaseError::BSONEncodeFailed(e)),
+    };
+
+    match collection.find(Some(doc!{"projectId" => project_id, "eventTypes" => event_type}), None) {
+        Ok(c) => cursor::collect(c, EvelynDatabaseError::LookupWebhooks, PartialResults::AllowAndLog, "lookup webhooks for event"),
+        Err(e) => Err(EvelynDatabaseError::LookupWebhooks(e)),
+    }
+}
+
+// The delivery log goes with the subscription.
+pub fn remove_subscription(
+    client: &Client,
+    user_id: &String,
+    subscription_id: &String,
+) -> Option<EvelynDatabaseError> {
+    let db = client.db("evelyn");
+
+    match db.collection("webhook_subscription").delete_one(doc!{"userId" => user_id, "subscriptionId" => subscription_id}, None) {
+        Ok(ref result) if result.deleted_count == 0 => return Some(EvelynDatabaseError::WebhookNotFound(EvelynBaseError::NothingElse)),
+        Ok(_) => {},
+        Err(e) => return Some(EvelynDatabaseError::RemoveWebhook(e)),
+    }
+
+    match db.collection("webhook_delivery").delete_many(doc!{"subscriptionId" => subscription_id}, None) {
+        Ok(_) => None,
+        Err(e) => Some(EvelynDatabaseError::RemoveWebhook(e)),
+    }
+}
+
+pub fn insert_delivery(
+    client: &Client,
+    delivery_model: &webhook_model::WebhookDeliveryModel,
+) -> Option<EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("webhook_delivery");
+
+    insert_model!(
+        collection,
+        delivery_model,
+        EvelynDatabaseError::InsertWebhookDelivery
+    )
+}
+
+// Newest first.
+pub fn lookup_deliveries(
+    client: &Client,
+    subscription_id: &String,
+    limit: i64,
+) -> Result<Vec<webhook_model::WebhookDeliveryModel>, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("webhook_delivery");
+
+    let mut find_options = FindOptions::new();
+    find_options.sort = Some(doc!{"dateCreated" => Bson::I32(-1)});
+    if limit > 0 {
+        find_options.limit = Some(limit);
+    }
+
+    match collection.find(Some(doc!{"subscriptionId" => subscription_id}), Some(find_options)) {
+        Ok(c) => cursor::collect(c, EvelynDatabaseError::LookupWebhookDeliveries, PartialResults::Deny, "lookup webhook deliveries"),
+        Err(e) => Err(EvelynDatabaseError::LookupWebhookDeliveries(e)),
+    }
+}
+
+// Deliveries waiting to be sent whose next attempt is due, oldest first.
+pub fn lookup_pending_deliveries(
+    client: &Client,
+    now: i64,
+) -> Result<Vec<webhook_model::WebhookDeliveryModel>, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("webhook_delivery");
+
+    let status = match bson::to_bson(&webhook_model::WebhookDeliveryStatusModel::Pending) {
+        Ok(status) => status,
+        Err(e) => return Err(EvelynDatabaseError::BSONEncodeFailed(e)),
+    };
+
+    let mut find_options = FindOptions::new();
+    find_options.sort = Some(doc!{"nextAttemptAt" => Bson::I32(1)});
+
+    match collection.find(Some(doc!{"status" => status, "nextAttemptAt" => doc!{"$lte" => now}}), Some(find_options)) {
+        Ok(c) => cursor::collect(c, EvelynDatabaseError::LookupWebhookDeliveries, PartialResults::AllowAndLog, "lookup pending webhook deliveries"),
+        Err(e) => Err(EvelynDatabaseError::LookupWebhookDeliveries(e)),
+    }
+}
+
+// Counts an attempt against the delivery, but only if nobody else has made
+// one since it was looked up. Returns whether it was this caller which
+// claimed it, so that each attempt is only sent once.
+pub fn claim_delivery(
+    client: &Client,
+    delivery_id: &String,
+    expected_attempts: i32,
+) -> Result<bool, EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("webhook_delivery");
+
+    let filter = doc!{"deliveryId" => delivery_id, "attempts" => expected_attempts};
+
+    match collection.update_one(filter, doc!{"$inc" => doc!{"attempts" => 1}}, None) {
+        Ok(result) => Ok(result.matched_count > 0),
+        Err(e) => Err(EvelynDatabaseError::UpdateWebhookDelivery(e)),
+    }
+}
+
+pub fn record_attempt(
+    client: &Client,
+    delivery_id: &String,
+    status: &webhook_model::WebhookDeliveryStatusModel,
+    next_attempt_at: i64,
+    response_status: Option<i32>,
+    last_error: Option<String>,
+    date_delivered: Option<i64>,
+) -> Option<EvelynDatabaseError> {
+    let collection = client.db("evelyn").collection("webhook_delivery");
+
+    let status = match bson::to_bson(status) {
+        Ok(status) => status,
+        Err(e) => return Some(EvelynDatabaseError::BSONEncodeFailed(e)),
+    };
+
+    let mut update = Document::new();
+    update.insert("status", status);
+    update.insert("nextAttemptAt", next_attempt_at);
+    update.insert("responseStatus", match response_status {
+        Some(response_status) => Bson::I32(response_status),
+        None => Bson::Null,
+    });
+    update.insert("lastError", match last_error {
+        Some(last_error) => Bson::String(last_error),
+        None => Bson::Null,
+    });
+    update.insert("dateDelivered", match date_delivered {
+        Some(date_delivered) => Bson::I64(date_delivered),
+        None => Bson::Null,
+    });
+
+    match collection.update_one(doc!{"deliveryId" => delivery_id}, doc!{"$set" => update}, None) {
+        Ok(_) => None,
+        Err(e) => Some(EvelynDatabaseError::UpdateWebhookDelivery(e)),
+    }
+}
@@ -16,12 +16,14 @@
 
 #[macro_use(bson, doc)]
 extern crate bson;
+extern crate serde;
 #[macro_use]
 extern crate serde_derive;
 extern crate mongodb;
 extern crate serde_json;
 extern crate jsonwebtoken as jwt;
 extern crate chrono;
+extern crate chrono_tz;
 extern crate config;
 extern crate uuid;
 #[macro_use]
@@ -29,6 +31,7 @@ extern crate log;
 extern crate log4rs;
 extern crate hyper;
 extern crate hyper_openssl;
+extern crate openssl;
 extern crate unicase;
 extern crate time;
 
@@ -39,6 +42,11 @@ pub mod model;
 pub mod core;
 
 use std::env;
+use std::sync::Arc;
+use core::email::EmailChannel;
+use core::notification::{InboxChannel, NotificationChannel, Notifier};
+use core::smtp::SmtpConfig;
+use core::scheduler::Scheduler;
 use mongodb::{Client, ThreadedClient};
 use processing::ProcessorData;
 use server::http::HttpServer;
@@ -71,6 +79,76 @@ pub fn hello_evelyn() {
         Err(e) => panic!("Connection to the database failed {}", e),
     };
 
+    if let Some(e) = core::calendar::migrate_event_times(&client) {
+        error!("{}", e);
+    }
+
+    if let Some(e) = core::simple_task::migrate_simple_tasks(&client) {
+        error!("{}", e);
+    }
+
+    if let Some(e) = core::todo_list::migrate_todo_lists(&client) {
+        error!("{}", e);
+    }
+
+    let mut scheduler = Scheduler::new(client.clone());
+    let mut email_scheduler = Scheduler::new(client.clone());
+    let mut webhook_scheduler = Scheduler::new(client.clone());
+
+    let mut channels: Vec<Box<NotificationChannel>> = vec![Box::new(InboxChannel)];
+
+    let smtp_host = conf.get_smtp_host();
+    if smtp_host.is_empty() {
+        info!("No SMTP relay is configured so notifications won't be emailed");
+    } else {
+        channels.push(Box::new(EmailChannel));
+
+        let smtp_config = SmtpConfig {
+            host: smtp_host,
+            port: conf.get_smtp_port() as u16,
+            user_name: conf.get_smtp_user_name(),
+            password: conf.get_smtp_password(),
+        };
+        let from = conf.get_email_from_address();
+        let max_attempts = conf.get_email_max_attempts() as i32;
+        email_scheduler.add_job("email outbox", conf.get_email_outbox_interval_seconds(), Box::new(move |client: &Client| {
+            if let Some(e) = core::email::send_outbox(client, &smtp_config, &from, max_attempts) {
+                error!("{}", e);
+            }
+        }));
+    }
+
+    let notifier = Arc::new(Notifier::new(channels));
+
+    let retention_days = conf.get_trash_retention_days();
+    scheduler.add_job("trash purge", conf.get_trash_purge_interval_seconds(), Box::new(move |client: &Client| {
+        if let Some(e) = core::trash::purge_expired(client, retention_days) {
+            error!("Trash purge failed: {}", e);
+        }
+    }));
+
+    let reminder_notifier = notifier.clone();
+    scheduler.add_job("reminders", conf.get_reminder_interval_seconds(), Box::new(move |client: &Client| {
+        if let Some(e) = core::reminder::fire_due(client, &reminder_notifier) {
+            error!("{}", e);
+        }
+    }));
+
+    let webhook_max_attempts = conf.get_webhook_max_attempts() as i32;
+    let webhook_allowed_hosts = conf.get_webhook_allowed_hosts();
+    webhook_scheduler.add_job("webhooks", conf.get_webhook_interval_seconds(), Box::new(move |client: &Client| {
+        if let Some(e) = core::webhook::announce_started_sprints(client) {
+            error!("{}", e);
+        }
+        if let Some(e) = core::webhook::send_deliveries(client, webhook_max_attempts, &webhook_allowed_hosts) {
+            error!("{}", e);
+        }
+    }));
+
+    scheduler.start();
+    email_scheduler.start();
+    webhook_scheduler.start();
+
     let token_service = core::token_service::TokenService::new(String::from("a_very_important_secret"));
     let server_session_token = token_service.create_server_session_token();
 
@@ -79,6 +157,7 @@ pub fn hello_evelyn() {
         token_service: token_service,
         conf: conf,
         server_session_token: server_session_token,
+        notifier: notifier,
     };
 
     let mut router = Router::new();
@@ -0,0 +1,62 @@
+// Evelyn: Your personal assistant, project manager and calendar
+// Copyright (C) 2017 Gregory Jensen
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+
+// A day at a glance, gathered from the calendar, simple tasks, agile sprints
+// and todo lists.
+
+use model::ErrorModel;
+use model::agile::task::TaskExternalModel;
+use model::calendar::CalendarEventExternalModel;
+use model::simple_task::SimpleTaskExternalModel;
+use model::todo_list::TodoListsExternalModel;
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct LookupAgendaRequestModel {
+    pub token: String,
+    // A YYYY-MM-DD date in the user's time zone, today when left out.
+    #[serde(default)]
+    pub date: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct AgendaAgileTaskExternalModel {
+    pub sprint_id: String,
+    pub sprint_title: String,
+    pub task: TaskExternalModel,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct AgendaExternalModel {
+    pub date: String,
+    pub events: Vec<CalendarEventExternalModel>,
+    // Incomplete tasks due on the day or before it.
+    pub simple_tasks: Vec<SimpleTaskExternalModel>,
+    // Tasks assigned to the user in the sprints running now.
+    pub agile_tasks: Vec<AgendaAgileTaskExternalModel>,
+    // Unarchived lists with items left to do.
+    pub todo_lists: Vec<TodoListsExternalModel>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct LookupAgendaResponseModel {
+    pub agenda: Option<AgendaExternalModel>,
+    pub error: Option<ErrorModel>,
+}
@@ -0,0 +1,99 @@
+// Evelyn: Your personal assistant, project manager and calendar
+// Copyright (C) 2017 Gregory Jensen
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use model::ErrorModel;
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum AuditEntityTypeModel {
+    Project,
+    Task,
+    Story,
+    Sprint,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub enum AuditActionModel {
+    Create,
+    Update,
+    Link,
+    Assign,
+    AddContributor,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub enum AuditActionExternalModel {
+    Create,
+    Update,
+    Link,
+    Assign,
+    AddContributor,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldChangeModel {
+    pub field: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEntryModel {
+    pub audit_entry_id: String,
+    pub project_id: String,
+    pub entity_type: AuditEntityTypeModel,
+    pub entity_id: String,
+    pub action: AuditActionModel,
+    pub user_id: String,
+    pub date: i64,
+    pub changes: Vec<FieldChangeModel>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct LookupHistoryRequestModel {
+    pub token: String,
+    pub project_id: String,
+    pub entity_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct FieldChangeExternalModel {
+    pub field: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct AuditEntryExternalModel {
+    pub action: AuditActionExternalModel,
+    pub user_id: String,
+    pub date: String,
+    pub changes: Vec<FieldChangeExternalModel>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct LookupHistoryResponseModel {
+    pub history: Vec<AuditEntryExternalModel>,
+    pub error: Option<ErrorModel>,
+}
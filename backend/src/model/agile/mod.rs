@@ -19,3 +19,4 @@ pub mod task;
 pub mod sprint;
 pub mod heirarchy;
 pub mod story;
+pub mod audit;
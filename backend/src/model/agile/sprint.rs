@@ -43,6 +43,9 @@ pub struct SprintModel {
     pub title: String,
     pub start_date: i64,
     pub end_date: i64,
+    // Set once webhooks have been told the sprint has started.
+    #[serde(default)]
+    pub start_announced: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -15,6 +15,7 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use model::ErrorModel;
+use model::conversion::{ConversionLinkExternalModel, ConversionLinkModel};
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "PascalCase")]
@@ -46,6 +47,11 @@ pub struct TaskModel {
     pub description: String,
     pub original_estimate: String,
     pub assignment: Option<AssignmentModel>,
+    // The simple task this was promoted from.
+    #[serde(default)]
+    pub origin: Option<ConversionLinkModel>,
+    #[serde(default)]
+    pub version: i64,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -74,6 +80,8 @@ pub struct TaskExternalModel {
     pub modified_by_user: Option<UserExternalModel>,
     pub date_modified: String,
     pub assignment: Option<AssignmentExternalModel>,
+    pub origin: Option<ConversionLinkExternalModel>,
+    pub version: i64,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -115,6 +123,7 @@ pub struct UpdateTaskRequestModel {
     pub description: Option<String>,
     pub original_estimate: Option<String>,
     pub assign_to_user_id: Option<String>,
+    pub expected_version: Option<i64>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -133,6 +142,7 @@ pub struct UpdateTaskModel {
     pub description: Option<String>,
     pub original_estimate: Option<String>,
     pub assignment: Option<AssignmentModel>,
+    pub expected_version: Option<i64>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -148,3 +158,60 @@ pub struct LookupBacklogResponseModel {
     pub tasks: Vec<TaskPreviewExternalModel>,
     pub error: Option<ErrorModel>,
 }
+
+// Where an assigned task sits in its project. Tasks which aren't linked to a
+// story or sprint are on the backlog, linked tasks are planned until a sprint
+// they are in is running.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub enum TaskStatusExternalModel {
+    Backlog,
+    Planned,
+    InSprint,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct LookupAssignedTasksRequestModel {
+    pub token: String,
+    // The page size, zero for every task.
+    #[serde(default)]
+    pub limit: u32,
+    // Only tasks in this project, otherwise every project contributed to.
+    #[serde(default)]
+    pub project_id: Option<String>,
+    #[serde(default)]
+    pub status: Option<TaskStatusExternalModel>,
+    // The NextCursor of the previous page.
+    #[serde(default)]
+    pub cursor: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct AssignedTaskExternalModel {
+    pub status: TaskStatusExternalModel,
+    pub task: TaskExternalModel,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct LookupAssignedTasksResponseModel {
+    pub tasks: Vec<AssignedTaskExternalModel>,
+    // None when there are no more tasks.
+    pub next_cursor: Option<String>,
+    pub error: Option<ErrorModel>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AssignedTaskLookupModel {
+    pub user_id: String,
+    pub project_ids: Vec<String>,
+    // Narrows the tasks down by id, to pick out a status.
+    pub task_ids_in: Option<Vec<String>>,
+    pub task_ids_not_in: Vec<String>,
+    pub limit: u32,
+    // The id of the last task on the previous page.
+    pub cursor: Option<String>,
+}
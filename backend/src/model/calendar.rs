@@ -15,6 +15,7 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use model::ErrorModel;
+use model::trash::DeletionModel;
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "PascalCase")]
@@ -23,6 +24,11 @@ pub struct CalendarAddEventRequestModel {
     pub event_begin: String,
     pub event_end: String,
     pub title: String,
+    #[serde(default)]
+    pub recurrence: Option<String>,
+    // The zone the event happens in, defaults to the user's time zone.
+    #[serde(default)]
+    pub time_zone: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -36,9 +42,92 @@ pub struct CalendarAddEventResponseModel {
 pub struct CalendarEventModel {
     pub user_id: String,
     pub event_id: String,
+    pub event_begin: i64,
+    pub event_end: i64,
+    pub title: String,
+    // Set on events which were imported from another calendar.
+    #[serde(default)]
+    pub uid: Option<String>,
+    #[serde(default)]
+    pub recurrence: Option<String>,
+    // Recurring events are expanded in this zone, None is UTC.
+    #[serde(default)]
+    pub time_zone: Option<String>,
+    // The end of the last occurrence of a recurring event, None if the event
+    // doesn't recur or recurs forever.
+    #[serde(default)]
+    pub series_end: Option<i64>,
+    #[serde(default)]
+    pub exception_dates: Vec<i64>,
+    #[serde(default)]
+    pub overrides: Vec<OccurrenceOverrideModel>,
+    #[serde(default)]
+    pub attendees: Vec<AttendeeModel>,
+    #[serde(default)]
+    pub version: i64,
+    #[serde(default)]
+    pub deleted: Option<DeletionModel>,
+}
+
+// Changes to a single occurrence of a recurring event, the occurrence is
+// identified by the time it would have begun.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct OccurrenceOverrideModel {
+    pub occurrence_begin: i64,
+    pub title: Option<String>,
+    pub event_begin: i64,
+    pub event_end: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum AttendeeResponseModel {
+    NeedsAction,
+    Accepted,
+    Declined,
+    Tentative,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AttendeeModel {
+    pub user_id: String,
+    // Set when the user was invited as a member of a user group.
+    pub user_group_id: Option<String>,
+    pub response: AttendeeResponseModel,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub enum AttendeeResponseExternalModel {
+    NeedsAction,
+    Accepted,
+    Declined,
+    Tentative,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct AttendeeExternalModel {
+    pub user_id: String,
+    pub user_group_id: Option<String>,
+    pub response: AttendeeResponseExternalModel,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct CalendarEventExternalModel {
+    pub event_id: String,
+    pub organizer_user_id: String,
     pub event_begin: String,
     pub event_end: String,
     pub title: String,
+    pub recurrence: Option<String>,
+    pub time_zone: Option<String>,
+    pub occurrence_begin: Option<String>,
+    pub attendees: Vec<AttendeeExternalModel>,
+    pub version: i64,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -52,5 +141,213 @@ pub struct CalendarLookupRequestModel {
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "PascalCase")]
 pub struct CalendarLookupResponseModel {
-    pub error: ErrorModel,
+    pub events: Vec<CalendarEventExternalModel>,
+    pub error: Option<ErrorModel>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct CalendarUpdateEventRequestModel {
+    pub token: String,
+    pub event_id: String,
+    pub new_title: Option<String>,
+    pub new_event_begin: Option<String>,
+    pub new_event_end: Option<String>,
+    #[serde(default)]
+    pub new_recurrence: Option<String>,
+    #[serde(default)]
+    pub clear_recurrence: bool,
+    #[serde(default)]
+    pub new_time_zone: Option<String>,
+    pub expected_version: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct CalendarUpdateEventResponseModel {
+    pub error: Option<ErrorModel>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CalendarEventUpdateModel {
+    pub user_id: String,
+    pub event_id: String,
+    pub title: Option<String>,
+    pub schedule: Option<CalendarEventScheduleModel>,
+    pub expected_version: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CalendarEventScheduleModel {
+    pub event_begin: i64,
+    pub event_end: i64,
+    pub recurrence: Option<String>,
+    pub time_zone: Option<String>,
+    pub series_end: Option<i64>,
+    // Exceptions are tied to the times of the occurrences, so they are
+    // dropped when those times move.
+    pub clear_exceptions: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct CalendarRemoveEventRequestModel {
+    pub token: String,
+    pub event_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct CalendarRemoveEventResponseModel {
+    pub error: Option<ErrorModel>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CalendarEventOccurrencesUpdateModel {
+    pub user_id: String,
+    pub event_id: String,
+    pub exception_dates: Vec<i64>,
+    pub overrides: Vec<OccurrenceOverrideModel>,
+    pub series_end: Option<i64>,
+    pub expected_version: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct CalendarUpdateOccurrenceRequestModel {
+    pub token: String,
+    pub event_id: String,
+    pub occurrence_begin: String,
+    pub new_title: Option<String>,
+    pub new_event_begin: Option<String>,
+    pub new_event_end: Option<String>,
+    pub expected_version: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct CalendarUpdateOccurrenceResponseModel {
+    pub error: Option<ErrorModel>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct CalendarCancelOccurrenceRequestModel {
+    pub token: String,
+    pub event_id: String,
+    pub occurrence_begin: String,
+    pub expected_version: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct CalendarCancelOccurrenceResponseModel {
+    pub error: Option<ErrorModel>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct CalendarExportRequestModel {
+    pub token: String,
+    #[serde(default)]
+    pub include_simple_tasks: bool,
+    #[serde(default)]
+    pub include_sprints: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct CalendarExportResponseModel {
+    pub calendar: Option<String>,
+    pub error: Option<ErrorModel>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct CalendarImportRequestModel {
+    pub token: String,
+    pub calendar: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct CalendarImportResponseModel {
+    pub created: i32,
+    pub updated: i32,
+    pub skipped: i32,
+    pub error: Option<ErrorModel>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct CalendarInviteRequestModel {
+    pub token: String,
+    pub event_id: String,
+    #[serde(default)]
+    pub user_ids: Vec<String>,
+    #[serde(default)]
+    pub user_group_ids: Vec<String>,
+    pub expected_version: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct CalendarInviteResponseModel {
+    pub error: Option<ErrorModel>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct CalendarEventAttendeesUpdateModel {
+    pub user_id: String,
+    pub event_id: String,
+    pub attendees: Vec<AttendeeModel>,
+    pub expected_version: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct CalendarRespondRequestModel {
+    pub token: String,
+    pub event_id: String,
+    pub response: AttendeeResponseExternalModel,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct CalendarRespondResponseModel {
+    pub error: Option<ErrorModel>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct CalendarFreeBusyRequestModel {
+    pub token: String,
+    pub user_ids: Vec<String>,
+    pub time_range_begin: String,
+    pub time_range_end: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct BusyPeriodExternalModel {
+    pub begin: String,
+    pub end: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct FreeBusyExternalModel {
+    pub user_id: String,
+    pub busy: Vec<BusyPeriodExternalModel>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct CalendarFreeBusyResponseModel {
+    pub users: Vec<FreeBusyExternalModel>,
+    pub error: Option<ErrorModel>,
 }
@@ -0,0 +1,95 @@
+// Evelyn: Your personal assistant, project manager and calendar
+// Copyright (C) 2017 Gregory Jensen
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+
+// Work can move from a todo list to a simple task and on to an agile task.
+// The item it came from and the item it became are recorded on each side so
+// that it can be followed in either direction.
+
+use model::ErrorModel;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum ConvertedItemTypeModel {
+    TodoListItem,
+    SimpleTask,
+    AgileTask,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub enum ConvertedItemTypeExternalModel {
+    TodoListItem,
+    SimpleTask,
+    AgileTask,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversionLinkModel {
+    pub item_type: ConvertedItemTypeModel,
+    // The id of the task or todo list item.
+    pub item_id: String,
+    // The todo list or project the item is in.
+    pub container_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct ConversionLinkExternalModel {
+    pub item_type: ConvertedItemTypeExternalModel,
+    pub item_id: String,
+    pub container_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct PromoteSimpleTaskRequestModel {
+    pub token: String,
+    pub task_id: String,
+    pub project_id: String,
+    #[serde(default)]
+    pub original_estimate: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct PromoteSimpleTaskResponseModel {
+    pub agile_task_id: Option<String>,
+    pub error: Option<ErrorModel>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct ConvertTodoListItemRequestModel {
+    pub token: String,
+    pub todo_list_id: String,
+    #[serde(default)]
+    pub item_id: Option<String>,
+    #[serde(default)]
+    pub item_index: Option<i32>,
+    #[serde(default)]
+    pub due_date: Option<String>,
+    #[serde(default)]
+    pub time_zone: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct ConvertTodoListItemResponseModel {
+    pub task_id: Option<String>,
+    pub error: Option<ErrorModel>,
+}
@@ -21,6 +21,13 @@ pub mod todo_list;
 pub mod calendar;
 pub mod server_admin;
 pub mod agile;
+pub mod trash;
+pub mod reminder;
+pub mod notification;
+pub mod email;
+pub mod webhook;
+pub mod conversion;
+pub mod agenda;
 
 use core::error_messages;
 use std::error::Error;
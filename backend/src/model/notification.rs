@@ -0,0 +1,86 @@
+// Evelyn: Your personal assistant, project manager and calendar
+// Copyright (C) 2017 Gregory Jensen
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use model::ErrorModel;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum NotificationKindModel {
+    Reminder,
+    TaskAssigned,
+    AddedToUserGroup,
+    RemovedFromUserGroup,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub enum NotificationKindExternalModel {
+    Reminder,
+    TaskAssigned,
+    AddedToUserGroup,
+    RemovedFromUserGroup,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationModel {
+    pub notification_id: String,
+    pub user_id: String,
+    pub kind: NotificationKindModel,
+    pub title: String,
+    pub body: String,
+    pub date_created: i64,
+    pub read: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct LookupNotificationsRequestModel {
+    pub token: String,
+    #[serde(default)]
+    pub unread_only: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct NotificationExternalModel {
+    pub notification_id: String,
+    pub kind: NotificationKindExternalModel,
+    pub title: String,
+    pub body: String,
+    pub date_created: String,
+    pub read: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct LookupNotificationsResponseModel {
+    pub notifications: Vec<NotificationExternalModel>,
+    pub error: Option<ErrorModel>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct MarkNotificationsReadRequestModel {
+    pub token: String,
+    pub notification_ids: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct MarkNotificationsReadResponseModel {
+    pub error: Option<ErrorModel>,
+}
@@ -0,0 +1,103 @@
+// Evelyn: Your personal assistant, project manager and calendar
+// Copyright (C) 2017 Gregory Jensen
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use model::ErrorModel;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum ReminderSourceModel {
+    CalendarEvent,
+    SimpleTask,
+    SprintStart,
+    SprintEnd,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub enum ReminderSourceExternalModel {
+    CalendarEvent,
+    SimpleTask,
+    SprintStart,
+    SprintEnd,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ReminderModel {
+    pub reminder_id: String,
+    pub user_id: String,
+    pub source: ReminderSourceModel,
+    pub source_id: String,
+    pub minutes_before: i64,
+    // The time the source begins, or is due, for the next time the reminder
+    // fires. For a recurring event this is a single occurrence.
+    pub source_time: Option<i64>,
+    // None once there is nothing left to remind the user about.
+    pub next_fire_at: Option<i64>,
+    pub date_created: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct AddReminderRequestModel {
+    pub token: String,
+    pub source: ReminderSourceExternalModel,
+    pub source_id: String,
+    pub minutes_before: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct AddReminderResponseModel {
+    pub reminder_id: Option<String>,
+    pub error: Option<ErrorModel>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct LookupRemindersRequestModel {
+    pub token: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct ReminderExternalModel {
+    pub reminder_id: String,
+    pub source: ReminderSourceExternalModel,
+    pub source_id: String,
+    pub minutes_before: i64,
+    pub next_fire_at: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct LookupRemindersResponseModel {
+    pub reminders: Vec<ReminderExternalModel>,
+    pub error: Option<ErrorModel>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct RemoveReminderRequestModel {
+    pub token: String,
+    pub reminder_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct RemoveReminderResponseModel {
+    pub error: Option<ErrorModel>,
+}
@@ -29,3 +29,47 @@ pub struct PurgeRequestModel {
 pub struct PurgeResponseModel {
     pub error: Option<ErrorModel>,
 }
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub enum ScopeActionExternalModel {
+    Delete,
+    Detach,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct AffectedDataExternalModel {
+    pub collection: String,
+    pub action: ScopeActionExternalModel,
+    pub count: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct DeleteUserRequestModel {
+    pub token: String,
+    pub dry_run: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct DeleteUserResponseModel {
+    pub affected: Vec<AffectedDataExternalModel>,
+    pub error: Option<ErrorModel>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct DeleteProjectRequestModel {
+    pub token: String,
+    pub project_id: String,
+    pub dry_run: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct DeleteProjectResponseModel {
+    pub affected: Vec<AffectedDataExternalModel>,
+    pub error: Option<ErrorModel>,
+}
@@ -15,6 +15,62 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use model::ErrorModel;
+use model::conversion::{ConversionLinkExternalModel, ConversionLinkModel};
+use model::todo_list::item::{TodoListItemExternalModel, TodoListItemModel};
+use model::trash::DeletionModel;
+
+// Stored as one of the PRIORITY_ numbers below so that tasks can be sorted by
+// it.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub enum SimpleTaskPriorityExternalModel {
+    Low,
+    Normal,
+    High,
+    Urgent,
+}
+
+pub const PRIORITY_LOW: i32 = 0;
+pub const PRIORITY_NORMAL: i32 = 1;
+pub const PRIORITY_HIGH: i32 = 2;
+pub const PRIORITY_URGENT: i32 = 3;
+
+fn default_priority() -> i32 {
+    PRIORITY_NORMAL
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum SimpleTaskSortModel {
+    // Tasks without a due date come after those with one.
+    DueDate,
+    Priority,
+    Title,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub enum SimpleTaskSortExternalModel {
+    DueDate,
+    Priority,
+    Title,
+}
+
+impl Default for SimpleTaskSortExternalModel {
+    fn default() -> Self {
+        SimpleTaskSortExternalModel::DueDate
+    }
+}
+
+// Ranges of due dates worked out from the current day in the user's time
+// zone. Weeks start on Monday.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub enum SimpleTaskViewExternalModel {
+    Overdue,
+    DueToday,
+    DueThisWeek,
+}
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "PascalCase")]
@@ -22,7 +78,29 @@ pub struct CreateSimpleTaskRequestModel {
     pub token: String,
     pub title: String,
     pub description: String,
-    pub due_date: String,
+    // A date and time, or a date on its own for a task due some time that
+    // day. Missing or empty for a task without a due date.
+    #[serde(default)]
+    pub due_date: Option<String>,
+    // The zone a due date without an offset is read in, defaults to the
+    // user's time zone.
+    #[serde(default)]
+    pub time_zone: Option<String>,
+    #[serde(default)]
+    pub priority: Option<SimpleTaskPriorityExternalModel>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    // An RRULE, completing the task adds the next occurrence as a new task.
+    // Recurring tasks need a due date.
+    #[serde(default)]
+    pub recurrence: Option<String>,
+    #[serde(default)]
+    pub checklist: Vec<TodoListItemExternalModel>,
+    #[serde(default)]
+    pub parent_task_id: Option<String>,
+    // Completes this task once all of its children are completed.
+    #[serde(default)]
+    pub complete_with_children: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -39,8 +117,49 @@ pub struct SimpleTaskModel {
     pub task_id: String,
     pub title: String,
     pub description: String,
-    pub due_date: String,
+    // Due dates given as a date on its own are stored as the last second of
+    // that day so that the task isn't overdue until the day is over.
+    #[serde(default)]
+    pub due_date: Option<i64>,
+    #[serde(default)]
+    pub due_date_only: bool,
+    #[serde(default)]
+    pub time_zone: Option<String>,
     pub completed: bool,
+    #[serde(default = "default_priority")]
+    pub priority: i32,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub recurrence: Option<String>,
+    // Shared by every occurrence of a recurring task, the id of the first.
+    #[serde(default)]
+    pub series_id: Option<String>,
+    // The due date the recurrence is counted from.
+    #[serde(default)]
+    pub series_begin: Option<i64>,
+    #[serde(default)]
+    pub date_completed: Option<i64>,
+    #[serde(default)]
+    pub checklist: Vec<TodoListItemModel>,
+    #[serde(default)]
+    pub parent_task_id: Option<String>,
+    #[serde(default)]
+    pub complete_with_children: bool,
+    // Where the task was converted from.
+    #[serde(default)]
+    pub origin: Option<ConversionLinkModel>,
+    // Set when the task has been promoted to an agile task, which completes it.
+    #[serde(default)]
+    pub converted_to: Option<ConversionLinkModel>,
+    // The occurrence of a recurring task which was added when this one was
+    // completed.
+    #[serde(default)]
+    pub next_task_id: Option<String>,
+    #[serde(default)]
+    pub version: i64,
+    #[serde(default)]
+    pub deleted: Option<DeletionModel>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -49,16 +168,72 @@ pub struct SimpleTaskExternalModel {
     pub task_id: String,
     pub title: String,
     pub description: String,
-    pub due_date: String,
+    // A date on its own when DueDateOnly is set, otherwise a date time in the
+    // user's time zone.
+    pub due_date: Option<String>,
+    pub due_date_only: bool,
+    pub time_zone: Option<String>,
     pub completed: bool,
+    pub priority: SimpleTaskPriorityExternalModel,
+    pub tags: Vec<String>,
+    pub recurrence: Option<String>,
+    pub series_id: Option<String>,
+    pub date_completed: Option<String>,
+    pub checklist: Vec<TodoListItemExternalModel>,
+    pub parent_task_id: Option<String>,
+    pub complete_with_children: bool,
+    pub progress: SimpleTaskProgressExternalModel,
+    pub origin: Option<ConversionLinkExternalModel>,
+    pub converted_to: Option<ConversionLinkExternalModel>,
+    pub version: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct SimpleTaskProgressExternalModel {
+    pub checklist_items_done: u32,
+    pub checklist_items: u32,
+    pub children_done: u32,
+    pub children: u32,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "PascalCase")]
 pub struct LookupSimpleTaskRequestModel {
     pub token: String,
+    // The page size, zero for every task.
     pub limit: u32,
     pub show_completed: bool,
+    #[serde(default)]
+    pub tag: Option<String>,
+    #[serde(default)]
+    pub priority: Option<SimpleTaskPriorityExternalModel>,
+    // Only tasks due in this range, read in the user's time zone. Tasks
+    // without a due date are left out when either is given.
+    #[serde(default)]
+    pub due_after: Option<String>,
+    #[serde(default)]
+    pub due_before: Option<String>,
+    // Narrows the due date range further. Overdue leaves out completed tasks.
+    #[serde(default)]
+    pub view: Option<SimpleTaskViewExternalModel>,
+    // Every occurrence of a recurring task, set ShowCompleted to include the
+    // completed ones.
+    #[serde(default)]
+    pub series_id: Option<String>,
+    // The children of a task.
+    #[serde(default)]
+    pub parent_task_id: Option<String>,
+    // Matched against the title and description, ignoring case.
+    #[serde(default)]
+    pub search: Option<String>,
+    #[serde(default)]
+    pub sort_by: SimpleTaskSortExternalModel,
+    #[serde(default)]
+    pub sort_descending: bool,
+    // The NextCursor of the previous page.
+    #[serde(default)]
+    pub cursor: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -66,6 +241,8 @@ pub struct LookupSimpleTaskRequestModel {
 pub struct LookupSimpleTaskResponseModel {
     pub error: Option<ErrorModel>,
     pub simple_tasks: Vec<SimpleTaskExternalModel>,
+    // None when there are no more tasks.
+    pub next_cursor: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -74,6 +251,17 @@ pub struct SimpleTaskLookupModel {
     pub user_id: String,
     pub limit: u32,
     pub show_completed: bool,
+    pub tag: Option<String>,
+    pub priority: Option<i32>,
+    pub due_after: Option<i64>,
+    pub due_before: Option<i64>,
+    pub series_id: Option<String>,
+    pub parent_task_id: Option<String>,
+    pub search: Option<String>,
+    pub sort_by: SimpleTaskSortModel,
+    pub sort_descending: bool,
+    // The id of the last task on the previous page.
+    pub cursor: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -83,14 +271,36 @@ pub struct UpdateSimpleTaskRequestModel {
     pub task_id: String,
     pub new_title: Option<String>,
     pub new_description: Option<String>,
+    // Empty to remove the due date.
     pub new_due_date: Option<String>,
+    #[serde(default)]
+    pub new_time_zone: Option<String>,
     pub new_completed: Option<bool>,
+    #[serde(default)]
+    pub new_priority: Option<SimpleTaskPriorityExternalModel>,
+    #[serde(default)]
+    pub new_tags: Option<Vec<String>>,
+    #[serde(default)]
+    pub new_recurrence: Option<String>,
+    #[serde(default)]
+    pub clear_recurrence: bool,
+    // Replaces the whole checklist, for adding, removing and reordering items.
+    #[serde(default)]
+    pub new_checklist: Option<Vec<TodoListItemExternalModel>>,
+    // Empty to make the task a top level task.
+    #[serde(default)]
+    pub new_parent_task_id: Option<String>,
+    #[serde(default)]
+    pub new_complete_with_children: Option<bool>,
+    pub expected_version: Option<i64>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "PascalCase")]
 pub struct UpdateSimpleTaskResponseModel {
     pub error: Option<ErrorModel>,
+    // The next occurrence, when a recurring task was completed.
+    pub next_task_id: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -100,8 +310,48 @@ pub struct SimpleTaskUpdateModel {
     pub task_id: String,
     pub title: Option<String>,
     pub description: Option<String>,
-    pub due_date: Option<String>,
+    // The outer option is whether the due date changes.
+    pub due_date: Option<Option<i64>>,
+    pub due_date_only: Option<bool>,
+    pub time_zone: Option<String>,
     pub completed: Option<bool>,
+    pub priority: Option<i32>,
+    pub tags: Option<Vec<String>>,
+    // The outer option is whether the recurrence changes.
+    pub recurrence: Option<Option<String>>,
+    pub series_id: Option<String>,
+    pub series_begin: Option<i64>,
+    pub date_completed: Option<Option<i64>>,
+    pub checklist: Option<Vec<TodoListItemModel>>,
+    pub parent_task_id: Option<Option<String>>,
+    pub complete_with_children: Option<bool>,
+    pub expected_version: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct UpdateSimpleTaskChecklistItemRequestModel {
+    pub token: String,
+    pub task_id: String,
+    pub item_index: i32,
+    pub is_done: bool,
+    pub expected_version: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct UpdateSimpleTaskChecklistItemResponseModel {
+    pub error: Option<ErrorModel>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SimpleTaskChecklistItemUpdateModel {
+    pub user_id: String,
+    pub task_id: String,
+    pub item_index: i32,
+    pub is_done: bool,
+    pub expected_version: Option<i64>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
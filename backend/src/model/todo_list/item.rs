@@ -15,19 +15,41 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use model::ErrorModel;
+use model::conversion::{ConversionLinkExternalModel, ConversionLinkModel};
+use model::todo_list::TodoListAccessModel;
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct TodoListItemModel {
+    #[serde(default)]
+    pub item_id: String,
     pub text: String,
     pub is_done: bool,
+    // Who checked the item and when, while it is done.
+    #[serde(default)]
+    pub done_by_user_id: Option<String>,
+    #[serde(default)]
+    pub date_done: Option<i64>,
+    // The simple task the item was converted to.
+    #[serde(default)]
+    pub converted_to: Option<ConversionLinkModel>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "PascalCase")]
 pub struct TodoListItemExternalModel {
+    // Set by the server, ignored when items are added.
+    #[serde(default)]
+    pub item_id: String,
     pub text: String,
     pub is_done: bool,
+    // Ignored when items are added.
+    #[serde(default)]
+    pub done_by_user_id: Option<String>,
+    #[serde(default)]
+    pub date_done: Option<String>,
+    #[serde(default)]
+    pub converted_to: Option<ConversionLinkExternalModel>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -41,13 +63,14 @@ pub struct AddItemTodoListRequestModel {
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "PascalCase")]
 pub struct AddItemTodoListResponseModel {
+    pub item_id: Option<String>,
     pub error: Option<ErrorModel>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct AddItemTodoListModel {
-    pub user_id: String,
+    pub access: TodoListAccessModel,
     pub todo_list_id: String,
     pub todo_list_item: TodoListItemModel,
 }
@@ -57,8 +80,17 @@ pub struct AddItemTodoListModel {
 pub struct UpdateItemTodoListRequestModel {
     pub token: String,
     pub todo_list_id: String,
-    pub item_index: i32,
-    pub is_done: bool,
+    // Items are found by id. The index is still accepted from older clients
+    // but may find a different item if the list changes.
+    #[serde(default)]
+    pub item_id: Option<String>,
+    #[serde(default)]
+    pub item_index: Option<i32>,
+    #[serde(default)]
+    pub is_done: Option<bool>,
+    #[serde(default)]
+    pub new_text: Option<String>,
+    pub expected_version: Option<i64>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -70,8 +102,45 @@ pub struct UpdateItemTodoListResponseModel {
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct UpdateTodoListItemModel {
+    // Who made the change, recorded when the item is checked.
     pub user_id: String,
     pub todo_list_id: String,
-    pub item_index: i32,
-    pub is_done: bool,
+    pub item_id: String,
+    pub is_done: Option<bool>,
+    pub date_done: i64,
+    pub text: Option<String>,
+    pub expected_version: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct RemoveItemTodoListRequestModel {
+    pub token: String,
+    pub todo_list_id: String,
+    pub item_id: String,
+    pub expected_version: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct RemoveItemTodoListResponseModel {
+    pub error: Option<ErrorModel>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct MoveItemTodoListRequestModel {
+    pub token: String,
+    pub todo_list_id: String,
+    pub item_id: String,
+    // Where the item should end up, counting from zero. Positions past the
+    // end move it to the end.
+    pub position: i32,
+    pub expected_version: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct MoveItemTodoListResponseModel {
+    pub error: Option<ErrorModel>,
 }
@@ -15,8 +15,70 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 pub mod item;
+pub mod template;
 
 use model::ErrorModel;
+use model::trash::DeletionModel;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum TodoListPermissionModel {
+    Read,
+    Edit,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub enum TodoListPermissionExternalModel {
+    Read,
+    Edit,
+}
+
+// What the user looking at a list can do with it.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub enum TodoListAccessExternalModel {
+    Owner,
+    Edit,
+    Read,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TodoListUserShareModel {
+    pub user_id: String,
+    pub permission: TodoListPermissionModel,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TodoListUserGroupShareModel {
+    pub user_group_id: String,
+    pub permission: TodoListPermissionModel,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct TodoListUserShareExternalModel {
+    pub user_id: String,
+    pub permission: TodoListPermissionExternalModel,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct TodoListUserGroupShareExternalModel {
+    pub user_group_id: String,
+    pub permission: TodoListPermissionExternalModel,
+}
+
+// The user asking for lists and the groups they belong to, which together
+// decide the lists they can see.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TodoListAccessModel {
+    pub user_id: String,
+    pub user_group_ids: Vec<String>,
+}
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -25,20 +87,45 @@ pub struct TodoListModel {
     pub todo_list_id: String,
     pub title: String,
     pub todo_list_items: Vec<item::TodoListItemModel>,
+    // Archived lists are left out of the list lookup unless asked for.
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub user_shares: Vec<TodoListUserShareModel>,
+    #[serde(default)]
+    pub user_group_shares: Vec<TodoListUserGroupShareModel>,
+    #[serde(default)]
+    pub version: i64,
+    #[serde(default)]
+    pub deleted: Option<DeletionModel>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "PascalCase")]
 pub struct TodoListExternalModel {
+    pub todo_list_id: String,
+    pub owner_user_id: String,
+    pub access: TodoListAccessExternalModel,
     pub title: String,
     pub todo_list_items: Vec<item::TodoListItemExternalModel>,
+    pub archived: bool,
+    pub user_shares: Vec<TodoListUserShareExternalModel>,
+    pub user_group_shares: Vec<TodoListUserGroupShareExternalModel>,
+    pub version: i64,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct TodoListsModel {
+    pub user_id: String,
     pub title: String,
     pub todo_list_id: String,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub user_shares: Vec<TodoListUserShareModel>,
+    #[serde(default)]
+    pub user_group_shares: Vec<TodoListUserGroupShareModel>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -46,6 +133,9 @@ pub struct TodoListsModel {
 pub struct TodoListsExternalModel {
     pub title: String,
     pub todo_list_id: String,
+    pub owner_user_id: String,
+    pub access: TodoListAccessExternalModel,
+    pub archived: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -67,6 +157,8 @@ pub struct CreateTodoListResponseModel {
 #[serde(rename_all = "PascalCase")]
 pub struct LookupTodoListsRequestModel {
     pub token: String,
+    #[serde(default)]
+    pub show_archived: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -79,7 +171,10 @@ pub struct LookupTodoListsResponseModel {
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "PascalCase")]
 pub struct LookupTodoListsModel {
-    pub user_id: String,
+    pub access: TodoListAccessModel,
+    pub show_archived: bool,
+    // Only lists with items left to do.
+    pub incomplete_only: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -92,7 +187,7 @@ pub struct LookupTodoListRequestModel {
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct LookupTodoListModel {
-    pub user_id: String,
+    pub access: TodoListAccessModel,
     pub todo_list_id: String,
 }
 
@@ -102,3 +197,83 @@ pub struct LookupTodoListResponseModel {
     pub todo_list: Option<TodoListExternalModel>,
     pub error: Option<ErrorModel>,
 }
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct UpdateTodoListRequestModel {
+    pub token: String,
+    pub todo_list_id: String,
+    #[serde(default)]
+    pub new_title: Option<String>,
+    #[serde(default)]
+    pub archived: Option<bool>,
+    pub expected_version: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct UpdateTodoListResponseModel {
+    pub error: Option<ErrorModel>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateTodoListModel {
+    pub user_id: String,
+    pub todo_list_id: String,
+    pub title: Option<String>,
+    pub archived: Option<bool>,
+    pub expected_version: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct RemoveTodoListRequestModel {
+    pub token: String,
+    pub todo_list_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct RemoveTodoListResponseModel {
+    pub error: Option<ErrorModel>,
+}
+
+// Shares a list with one user or one user group, replacing the permission
+// they had before.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct ShareTodoListRequestModel {
+    pub token: String,
+    pub todo_list_id: String,
+    #[serde(default)]
+    pub user_id: Option<String>,
+    #[serde(default)]
+    pub user_group_id: Option<String>,
+    pub permission: TodoListPermissionExternalModel,
+    pub expected_version: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct ShareTodoListResponseModel {
+    pub error: Option<ErrorModel>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct UnshareTodoListRequestModel {
+    pub token: String,
+    pub todo_list_id: String,
+    #[serde(default)]
+    pub user_id: Option<String>,
+    #[serde(default)]
+    pub user_group_id: Option<String>,
+    pub expected_version: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct UnshareTodoListResponseModel {
+    pub error: Option<ErrorModel>,
+}
@@ -0,0 +1,119 @@
+// Evelyn: Your personal assistant, project manager and calendar
+// Copyright (C) 2017 Gregory Jensen
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+
+use model::ErrorModel;
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TodoListTemplateItemModel {
+    pub text: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct TodoListTemplateModel {
+    pub user_id: String,
+    pub template_id: String,
+    pub title: String,
+    pub items: Vec<TodoListTemplateItemModel>,
+    // Members of the group can use the template as well as its owner.
+    pub user_group_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct TodoListTemplateItemExternalModel {
+    pub text: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct TodoListTemplateExternalModel {
+    pub template_id: String,
+    pub owner_user_id: String,
+    pub title: String,
+    pub items: Vec<TodoListTemplateItemExternalModel>,
+    pub user_group_id: Option<String>,
+    // The names used in `{{name}}` placeholders in the title and items.
+    pub placeholders: Vec<String>,
+}
+
+// Saves a copy of a list's items as a template, titled after the list unless
+// a title is given.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct CreateTodoListTemplateRequestModel {
+    pub token: String,
+    pub todo_list_id: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub user_group_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct CreateTodoListTemplateResponseModel {
+    pub template_id: Option<String>,
+    pub error: Option<ErrorModel>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct LookupTodoListTemplatesRequestModel {
+    pub token: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct LookupTodoListTemplatesResponseModel {
+    pub templates: Option<Vec<TodoListTemplateExternalModel>>,
+    pub error: Option<ErrorModel>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct InstantiateTodoListTemplateRequestModel {
+    pub token: String,
+    pub template_id: String,
+    #[serde(default)]
+    pub title: Option<String>,
+    // Values for the `{{name}}` placeholders, by name.
+    #[serde(default)]
+    pub placeholders: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct InstantiateTodoListTemplateResponseModel {
+    pub todo_list_id: Option<String>,
+    pub error: Option<ErrorModel>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct RemoveTodoListTemplateRequestModel {
+    pub token: String,
+    pub template_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct RemoveTodoListTemplateResponseModel {
+    pub error: Option<ErrorModel>,
+}
@@ -15,6 +15,7 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use model::ErrorModel;
+use model::notification::{NotificationKindExternalModel, NotificationKindModel};
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "PascalCase")]
@@ -22,6 +23,8 @@ pub struct CreateUserRequestModel {
     pub user_name: String,
     pub email_address: String,
     pub password: String,
+    #[serde(default)]
+    pub time_zone: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -51,6 +54,44 @@ pub struct UserModel {
     pub user_name: String,
     pub email_address: String,
     pub password: String,
+    // IANA time zone name, dates in responses are rendered in this zone and
+    // dates without an offset in requests are read in it.
+    #[serde(default)]
+    pub time_zone: Option<String>,
+    // The kinds of notification which are also sent by email, None for all
+    // of them.
+    #[serde(default)]
+    pub email_notifications: Option<Vec<NotificationKindModel>>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct LookupProfileRequestModel {
+    pub token: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct LookupProfileResponseModel {
+    pub user_name: String,
+    pub email_address: String,
+    pub time_zone: Option<String>,
+    pub email_notifications: Vec<NotificationKindExternalModel>,
+    pub error: Option<ErrorModel>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct UpdateProfileRequestModel {
+    pub token: String,
+    pub new_time_zone: Option<String>,
+    pub new_email_notifications: Option<Vec<NotificationKindExternalModel>>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct UpdateProfileResponseModel {
+    pub error: Option<ErrorModel>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -15,6 +15,7 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use model::ErrorModel;
+use model::trash::DeletionModel;
 
 pub mod member;
 
@@ -55,6 +56,8 @@ pub struct UserGroupModel {
     pub name: String,
     pub description: String,
     pub members: Vec<member::UserGroupMemberModel>,
+    #[serde(default)]
+    pub deleted: Option<DeletionModel>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
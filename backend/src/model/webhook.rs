@@ -0,0 +1,194 @@
+// Evelyn: Your personal assistant, project manager and calendar
+// Copyright (C) 2017 Gregory Jensen
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use model::ErrorModel;
+use std::collections::BTreeMap;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum WebhookEventTypeModel {
+    TaskCreated,
+    TaskUpdated,
+    TaskAssigned,
+    StoryCreated,
+    SprintStarted,
+    LinkMade,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub enum WebhookEventTypeExternalModel {
+    TaskCreated,
+    TaskUpdated,
+    TaskAssigned,
+    StoryCreated,
+    SprintStarted,
+    LinkMade,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookSubscriptionModel {
+    pub subscription_id: String,
+    pub user_id: String,
+    pub project_id: String,
+    pub url: String,
+    // Used to sign the payloads so the receiver can check they came from us.
+    pub secret: String,
+    pub event_types: Vec<WebhookEventTypeModel>,
+    pub date_created: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum WebhookDeliveryStatusModel {
+    Pending,
+    Delivered,
+    // Given up on after too many attempts.
+    Failed,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub enum WebhookDeliveryStatusExternalModel {
+    Pending,
+    Delivered,
+    Failed,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WebhookDeliveryModel {
+    pub delivery_id: String,
+    pub subscription_id: String,
+    pub user_id: String,
+    // So that deliveries can be removed with their project.
+    #[serde(default)]
+    pub project_id: String,
+    pub event_type: WebhookEventTypeModel,
+    // The JSON body, kept exactly as it is signed and sent.
+    pub payload: String,
+    pub status: WebhookDeliveryStatusModel,
+    pub attempts: i32,
+    pub next_attempt_at: i64,
+    pub last_error: Option<String>,
+    pub response_status: Option<i32>,
+    pub date_created: i64,
+    pub date_delivered: Option<i64>,
+}
+
+// The body which is posted to the subscriber.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct WebhookPayloadExternalModel {
+    pub delivery_id: String,
+    pub event_type: WebhookEventTypeExternalModel,
+    pub project_id: String,
+    pub entity_id: String,
+    pub title: String,
+    // The user who made the change, None for events such as a sprint starting.
+    pub user_id: Option<String>,
+    pub date: String,
+    // Anything else which describes the event, such as who a task was
+    // assigned to.
+    pub details: BTreeMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct AddWebhookRequestModel {
+    pub token: String,
+    pub project_id: String,
+    pub url: String,
+    pub secret: String,
+    pub event_types: Vec<WebhookEventTypeExternalModel>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct AddWebhookResponseModel {
+    pub subscription_id: Option<String>,
+    pub error: Option<ErrorModel>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct LookupWebhooksRequestModel {
+    pub token: String,
+}
+
+// The secret isn't given back once it has been set.
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct WebhookSubscriptionExternalModel {
+    pub subscription_id: String,
+    pub project_id: String,
+    pub url: String,
+    pub event_types: Vec<WebhookEventTypeExternalModel>,
+    pub date_created: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct LookupWebhooksResponseModel {
+    pub subscriptions: Vec<WebhookSubscriptionExternalModel>,
+    pub error: Option<ErrorModel>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct RemoveWebhookRequestModel {
+    pub token: String,
+    pub subscription_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct RemoveWebhookResponseModel {
+    pub error: Option<ErrorModel>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct LookupWebhookDeliveriesRequestModel {
+    pub token: String,
+    pub subscription_id: String,
+    // Zero for no limit.
+    #[serde(default)]
+    pub limit: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct WebhookDeliveryExternalModel {
+    pub delivery_id: String,
+    pub event_type: WebhookEventTypeExternalModel,
+    pub status: WebhookDeliveryStatusExternalModel,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub response_status: Option<i32>,
+    pub date_created: String,
+    // None once the delivery has succeeded or been given up on.
+    pub next_attempt_at: Option<String>,
+    pub date_delivered: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "PascalCase")]
+pub struct LookupWebhookDeliveriesResponseModel {
+    pub deliveries: Vec<WebhookDeliveryExternalModel>,
+    pub error: Option<ErrorModel>,
+}
@@ -0,0 +1,56 @@
+// Evelyn: Your personal assistant, project manager and calendar
+// Copyright (C) 2017 Gregory Jensen
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+
+use core::agenda;
+use core::error_messages::{EvelynBaseError, EvelynCoreError, EvelynServiceError};
+use model;
+use processing;
+use serde_json;
+use server::routing::{RouterInput, RouterOutput};
+use std::sync::Arc;
+
+pub fn lookup_my_day_processor(
+    router_input: RouterInput,
+    processor_data: Arc<processing::ProcessorData>,
+) -> RouterOutput {
+    match decode_router_input_to_model!(model::agenda::LookupAgendaRequestModel, router_input) {
+        Ok(request_model) => {
+            let session_token_model = validate_session!(processor_data, request_model);
+
+            match agenda::lookup_my_day(request_model, session_token_model, processor_data) {
+                Ok(response) => {
+                    model_to_router_output!(response)
+                },
+                Err(e) => {
+                    model_to_router_output!(model::agenda::LookupAgendaResponseModel {
+                        agenda: None,
+                        error: service_error_to_model!(match e {
+                            EvelynCoreError::InvalidAgendaDate(_) => EvelynServiceError::InvalidAgendaDate(e),
+                            _ => EvelynServiceError::LookupAgenda(e),
+                        }),
+                    })
+                },
+            }
+        },
+        Err(e) => {
+            model_to_router_output!(model::agenda::LookupAgendaResponseModel {
+                agenda: None,
+                error: service_error_to_model!(EvelynServiceError::CouldNotDecodeTheRequestPayload(e)),
+            })
+        },
+    }
+}
@@ -58,9 +58,9 @@ pub fn add_user_contributor_processor(
 ) -> RouterOutput {
     match decode_router_input_to_model!(project_model::AddUserContributorRequestModel, router_input) {
         Ok(request_model) => {
-            validate_session!(processor_data, request_model);
+            let session_token_model = validate_session!(processor_data, request_model);
 
-            match project::add_user_contributor(request_model, processor_data) {
+            match project::add_user_contributor(request_model, session_token_model, processor_data) {
                 None => {
                     model_to_router_output!(project_model::AddUserContributorResponseModel {
                         error: None,
@@ -87,9 +87,9 @@ pub fn add_user_group_contributor_processor(
 ) -> RouterOutput {
     match decode_router_input_to_model!(project_model::AddUserGroupContributorRequestModel, router_input) {
         Ok(request_model) => {
-            validate_session!(processor_data, request_model);
+            let session_token_model = validate_session!(processor_data, request_model);
 
-            match project::add_user_group_contributor(request_model, processor_data) {
+            match project::add_user_group_contributor(request_model, session_token_model, processor_data) {
                 None => {
                     model_to_router_output!(project_model::AddUserGroupContributorResponseModel {
                         error: None,
@@ -14,9 +14,10 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use core::error_messages::{EvelynServiceError, EvelynBaseError};
+use core::error_messages::{EvelynServiceError, EvelynBaseError, EvelynCoreError};
 use model;
 use model::agile::sprint as sprint_model;
+use model::agile::audit as audit_model;
 use core::agile::sprint;
 use processing;
 use serde_json;
@@ -38,7 +39,7 @@ pub fn create_processor(
                 Err(e) => {
                     model_to_router_output!(sprint_model::CreateSprintResponseModel {
                         sprint_id: None,
-                        error: service_error_to_model!(EvelynServiceError::CreateAgileSprint(e)),
+                        error: service_error_to_model!(create_error_to_service_error(e)),
                     })
                 },
             }
@@ -87,9 +88,9 @@ pub fn lookup_backlog_processor(
 ) -> RouterOutput {
     match decode_router_input_to_model!(sprint_model::LookupBacklogRequestModel, router_input) {
         Ok(request_model) => {
-            validate_session!(processor_data, request_model);
+            let session_token_model = validate_session!(processor_data, request_model);
 
-            match sprint::lookup_backlog(request_model, processor_data) {
+            match sprint::lookup_backlog(request_model, session_token_model, processor_data) {
                 Ok(response) => {
                     model_to_router_output!(response)
                 },
@@ -109,3 +110,39 @@ pub fn lookup_backlog_processor(
         },
     }
 }
+
+pub fn history_processor(
+    router_input: RouterInput,
+    processor_data: Arc<processing::ProcessorData>,
+) -> RouterOutput {
+    match decode_router_input_to_model!(audit_model::LookupHistoryRequestModel, router_input) {
+        Ok(request_model) => {
+            let session_token_model = validate_session!(processor_data, request_model);
+
+            match sprint::history(request_model, session_token_model, processor_data) {
+                Ok(response) => {
+                    model_to_router_output!(response)
+                },
+                Err(e) => {
+                    model_to_router_output!(audit_model::LookupHistoryResponseModel {
+                        history: Vec::new(),
+                        error: service_error_to_model!(EvelynServiceError::LookupAgileHistory(e)),
+                    })
+                },
+            }
+        },
+        Err(e) => {
+            model_to_router_output!(audit_model::LookupHistoryResponseModel {
+                history: Vec::new(),
+                error: service_error_to_model!(EvelynServiceError::CouldNotDecodeTheRequestPayload(e)),
+            })
+        },
+    }
+}
+
+fn create_error_to_service_error(e: EvelynCoreError) -> EvelynServiceError {
+    match e {
+        EvelynCoreError::InvalidDateTime(_) => EvelynServiceError::InvalidDateTime(e),
+        _ => EvelynServiceError::CreateAgileSprint(e),
+    }
+}
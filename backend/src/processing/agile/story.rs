@@ -17,6 +17,7 @@
 use core::error_messages::{EvelynServiceError, EvelynBaseError};
 use model;
 use model::agile::story as story_model;
+use model::agile::audit as audit_model;
 use core::agile::story;
 use processing;
 use serde_json;
@@ -109,3 +110,32 @@ pub fn lookup_backlog_processor(
         },
     }
 }
+
+pub fn history_processor(
+    router_input: RouterInput,
+    processor_data: Arc<processing::ProcessorData>,
+) -> RouterOutput {
+    match decode_router_input_to_model!(audit_model::LookupHistoryRequestModel, router_input) {
+        Ok(request_model) => {
+            let session_token_model = validate_session!(processor_data, request_model);
+
+            match story::history(request_model, session_token_model, processor_data) {
+                Ok(response) => {
+                    model_to_router_output!(response)
+                },
+                Err(e) => {
+                    model_to_router_output!(audit_model::LookupHistoryResponseModel {
+                        history: Vec::new(),
+                        error: service_error_to_model!(EvelynServiceError::LookupAgileHistory(e)),
+                    })
+                },
+            }
+        },
+        Err(e) => {
+            model_to_router_output!(audit_model::LookupHistoryResponseModel {
+                history: Vec::new(),
+                error: service_error_to_model!(EvelynServiceError::CouldNotDecodeTheRequestPayload(e)),
+            })
+        },
+    }
+}
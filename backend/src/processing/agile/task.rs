@@ -14,9 +14,10 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use core::error_messages::{EvelynServiceError, EvelynBaseError};
+use core::error_messages::{EvelynServiceError, EvelynBaseError, EvelynCoreError};
 use model;
 use model::agile::task as task_model;
+use model::agile::audit as audit_model;
 use core::agile::task;
 use processing;
 use serde_json;
@@ -58,9 +59,9 @@ pub fn lookup_processor(
 ) -> RouterOutput {
     match decode_router_input_to_model!(task_model::LookupTaskRequestModel, router_input) {
         Ok(request_model) => {
-            validate_session!(processor_data, request_model);
+            let session_token_model = validate_session!(processor_data, request_model);
 
-            match task::lookup(request_model, processor_data) {
+            match task::lookup(request_model, session_token_model, processor_data) {
                 Ok(response) => {
                     model_to_router_output!(response)
                 },
@@ -110,6 +111,37 @@ pub fn lookup_backlog_processor(
     }
 }
 
+pub fn lookup_assigned_processor(
+    router_input: RouterInput,
+    processor_data: Arc<processing::ProcessorData>,
+) -> RouterOutput {
+    match decode_router_input_to_model!(task_model::LookupAssignedTasksRequestModel, router_input) {
+        Ok(request_model) => {
+            let session_token_model = validate_session!(processor_data, request_model);
+
+            match task::lookup_assigned(request_model, session_token_model, processor_data) {
+                Ok(response) => {
+                    model_to_router_output!(response)
+                },
+                Err(e) => {
+                    model_to_router_output!(task_model::LookupAssignedTasksResponseModel {
+                        tasks: Vec::new(),
+                        next_cursor: None,
+                        error: service_error_to_model!(EvelynServiceError::LookupAssignedAgileTasks(e)),
+                    })
+                },
+            }
+        },
+        Err(e) => {
+            model_to_router_output!(task_model::LookupAssignedTasksResponseModel {
+                tasks: Vec::new(),
+                next_cursor: None,
+                error: service_error_to_model!(EvelynServiceError::CouldNotDecodeTheRequestPayload(e)),
+            })
+        },
+    }
+}
+
 pub fn update_processor(
     router_input: RouterInput,
     processor_data: Arc<processing::ProcessorData>,
@@ -126,7 +158,7 @@ pub fn update_processor(
                 },
                 Some(e) => {
                     model_to_router_output!(task_model::UpdateTaskResponseModel {
-                        error: service_error_to_model!(EvelynServiceError::UpdateAgileTask(e)),
+                        error: service_error_to_model!(update_error_to_service_error(e)),
                     })
                 },
             }
@@ -138,3 +170,40 @@ pub fn update_processor(
         },
     }
 }
+
+pub fn history_processor(
+    router_input: RouterInput,
+    processor_data: Arc<processing::ProcessorData>,
+) -> RouterOutput {
+    match decode_router_input_to_model!(audit_model::LookupHistoryRequestModel, router_input) {
+        Ok(request_model) => {
+            let session_token_model = validate_session!(processor_data, request_model);
+
+            match task::history(request_model, session_token_model, processor_data) {
+                Ok(response) => {
+                    model_to_router_output!(response)
+                },
+                Err(e) => {
+                    model_to_router_output!(audit_model::LookupHistoryResponseModel {
+                        history: Vec::new(),
+                        error: service_error_to_model!(EvelynServiceError::LookupAgileHistory(e)),
+                    })
+                },
+            }
+        },
+        Err(e) => {
+            model_to_router_output!(audit_model::LookupHistoryResponseModel {
+                history: Vec::new(),
+                error: service_error_to_model!(EvelynServiceError::CouldNotDecodeTheRequestPayload(e)),
+            })
+        },
+    }
+}
+
+fn update_error_to_service_error(e: EvelynCoreError) -> EvelynServiceError {
+    match e {
+        EvelynCoreError::AgileTaskVersionConflict(_) => EvelynServiceError::AgileTaskVersionConflict(e),
+        EvelynCoreError::AgileTaskNotFound(_) => EvelynServiceError::AgileTaskNotFound(e),
+        _ => EvelynServiceError::UpdateAgileTask(e),
+    }
+}
@@ -15,7 +15,7 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use core::calendar;
-use core::error_messages::{EvelynBaseError, EvelynServiceError};
+use core::error_messages::{EvelynBaseError, EvelynCoreError, EvelynServiceError};
 use model;
 use model::calendar as calendar_model;
 use processing;
@@ -45,7 +45,12 @@ pub fn calendar_add_event_processor(
                 Some(e) => {
                     RouterOutput {
                         response_body: serde_json::to_string(&calendar_model::CalendarAddEventResponseModel {
-                                                                 error: Some(From::from(EvelynServiceError::AddCalendarEvent(e))),
+                                                                 error: Some(From::from(match e {
+                                                                     EvelynCoreError::InvalidCalendarEventTime(_) => EvelynServiceError::InvalidCalendarEventTime(e),
+                                                                     EvelynCoreError::InvalidRecurrenceRule(_) => EvelynServiceError::InvalidRecurrenceRule(e),
+                                                                     EvelynCoreError::InvalidTimeZone(_) => EvelynServiceError::InvalidTimeZone(e),
+                                                                     _ => EvelynServiceError::AddCalendarEvent(e),
+                                                                 })),
                                                              })
                                 .unwrap(),
                     }
@@ -65,3 +70,339 @@ pub fn calendar_add_event_processor(
         },
     }
 }
+
+pub fn calendar_lookup_processor(
+    router_input: RouterInput,
+    processor_data: Arc<processing::ProcessorData>,
+) -> RouterOutput {
+    match decode_router_input_to_model!(calendar_model::CalendarLookupRequestModel, router_input) {
+        Ok(request_model) => {
+            let session_token_model = validate_session!(processor_data, request_model);
+
+            match calendar::calendar_lookup(request_model, session_token_model, processor_data) {
+                Ok(response) => {
+                    model_to_router_output!(response)
+                },
+                Err(e) => {
+                    model_to_router_output!(calendar_model::CalendarLookupResponseModel {
+                        events: Vec::new(),
+                        error: service_error_to_model!(match e {
+                            EvelynCoreError::InvalidCalendarEventTime(_) => EvelynServiceError::InvalidCalendarEventTime(e),
+                            _ => EvelynServiceError::LookupCalendarEvents(e),
+                        }),
+                    })
+                },
+            }
+        },
+        Err(e) => {
+            model_to_router_output!(calendar_model::CalendarLookupResponseModel {
+                events: Vec::new(),
+                error: service_error_to_model!(EvelynServiceError::CouldNotDecodeTheRequestPayload(e)),
+            })
+        },
+    }
+}
+
+pub fn calendar_update_event_processor(
+    router_input: RouterInput,
+    processor_data: Arc<processing::ProcessorData>,
+) -> RouterOutput {
+    match decode_router_input_to_model!(calendar_model::CalendarUpdateEventRequestModel, router_input) {
+        Ok(request_model) => {
+            let session_token_model = validate_session!(processor_data, request_model);
+
+            match calendar::calendar_update_event(request_model, session_token_model, processor_data) {
+                None => {
+                    model_to_router_output!(calendar_model::CalendarUpdateEventResponseModel {
+                        error: None,
+                    })
+                },
+                Some(e) => {
+                    model_to_router_output!(calendar_model::CalendarUpdateEventResponseModel {
+                        error: service_error_to_model!(match e {
+                            EvelynCoreError::InvalidCalendarEventTime(_) => EvelynServiceError::InvalidCalendarEventTime(e),
+                            EvelynCoreError::CalendarEventNotFound(_) => EvelynServiceError::CalendarEventNotFound(e),
+                            EvelynCoreError::CalendarEventVersionConflict(_) => EvelynServiceError::CalendarEventVersionConflict(e),
+                            EvelynCoreError::InvalidRecurrenceRule(_) => EvelynServiceError::InvalidRecurrenceRule(e),
+                            EvelynCoreError::InvalidTimeZone(_) => EvelynServiceError::InvalidTimeZone(e),
+                            _ => EvelynServiceError::UpdateCalendarEvent(e),
+                        }),
+                    })
+                },
+            }
+        },
+        Err(e) => {
+            model_to_router_output!(calendar_model::CalendarUpdateEventResponseModel {
+                error: service_error_to_model!(EvelynServiceError::CouldNotDecodeTheRequestPayload(e)),
+            })
+        },
+    }
+}
+
+pub fn calendar_remove_event_processor(
+    router_input: RouterInput,
+    processor_data: Arc<processing::ProcessorData>,
+) -> RouterOutput {
+    match decode_router_input_to_model!(calendar_model::CalendarRemoveEventRequestModel, router_input) {
+        Ok(request_model) => {
+            let session_token_model = validate_session!(processor_data, request_model);
+
+            match calendar::calendar_remove_event(request_model, session_token_model, processor_data) {
+                None => {
+                    model_to_router_output!(calendar_model::CalendarRemoveEventResponseModel {
+                        error: None,
+                    })
+                },
+                Some(e) => {
+                    model_to_router_output!(calendar_model::CalendarRemoveEventResponseModel {
+                        error: service_error_to_model!(match e {
+                            EvelynCoreError::CalendarEventNotFound(_) => EvelynServiceError::CalendarEventNotFound(e),
+                            _ => EvelynServiceError::RemoveCalendarEvent(e),
+                        }),
+                    })
+                },
+            }
+        },
+        Err(e) => {
+            model_to_router_output!(calendar_model::CalendarRemoveEventResponseModel {
+                error: service_error_to_model!(EvelynServiceError::CouldNotDecodeTheRequestPayload(e)),
+            })
+        },
+    }
+}
+
+pub fn calendar_update_occurrence_processor(
+    router_input: RouterInput,
+    processor_data: Arc<processing::ProcessorData>,
+) -> RouterOutput {
+    match decode_router_input_to_model!(calendar_model::CalendarUpdateOccurrenceRequestModel, router_input) {
+        Ok(request_model) => {
+            let session_token_model = validate_session!(processor_data, request_model);
+
+            match calendar::calendar_update_occurrence(request_model, session_token_model, processor_data) {
+                None => {
+                    model_to_router_output!(calendar_model::CalendarUpdateOccurrenceResponseModel {
+                        error: None,
+                    })
+                },
+                Some(e) => {
+                    model_to_router_output!(calendar_model::CalendarUpdateOccurrenceResponseModel {
+                        error: service_error_to_model!(occurrence_error_to_service_error(e, EvelynServiceError::UpdateCalendarEventOccurrence)),
+                    })
+                },
+            }
+        },
+        Err(e) => {
+            model_to_router_output!(calendar_model::CalendarUpdateOccurrenceResponseModel {
+                error: service_error_to_model!(EvelynServiceError::CouldNotDecodeTheRequestPayload(e)),
+            })
+        },
+    }
+}
+
+pub fn calendar_cancel_occurrence_processor(
+    router_input: RouterInput,
+    processor_data: Arc<processing::ProcessorData>,
+) -> RouterOutput {
+    match decode_router_input_to_model!(calendar_model::CalendarCancelOccurrenceRequestModel, router_input) {
+        Ok(request_model) => {
+            let session_token_model = validate_session!(processor_data, request_model);
+
+            match calendar::calendar_cancel_occurrence(request_model, session_token_model, processor_data) {
+                None => {
+                    model_to_router_output!(calendar_model::CalendarCancelOccurrenceResponseModel {
+                        error: None,
+                    })
+                },
+                Some(e) => {
+                    model_to_router_output!(calendar_model::CalendarCancelOccurrenceResponseModel {
+                        error: service_error_to_model!(occurrence_error_to_service_error(e, EvelynServiceError::CancelCalendarEventOccurrence)),
+                    })
+                },
+            }
+        },
+        Err(e) => {
+            model_to_router_output!(calendar_model::CalendarCancelOccurrenceResponseModel {
+                error: service_error_to_model!(EvelynServiceError::CouldNotDecodeTheRequestPayload(e)),
+            })
+        },
+    }
+}
+
+fn occurrence_error_to_service_error(
+    e: EvelynCoreError,
+    otherwise: fn(EvelynCoreError) -> EvelynServiceError,
+) -> EvelynServiceError {
+    match e {
+        EvelynCoreError::InvalidCalendarEventTime(_) => EvelynServiceError::InvalidCalendarEventTime(e),
+        EvelynCoreError::InvalidRecurrenceRule(_) => EvelynServiceError::InvalidRecurrenceRule(e),
+        EvelynCoreError::CalendarEventNotFound(_) => EvelynServiceError::CalendarEventNotFound(e),
+        EvelynCoreError::CalendarEventOccurrenceNotFound(_) => EvelynServiceError::CalendarEventOccurrenceNotFound(e),
+        EvelynCoreError::CalendarEventVersionConflict(_) => EvelynServiceError::CalendarEventVersionConflict(e),
+        _ => otherwise(e),
+    }
+}
+
+pub fn calendar_export_processor(
+    router_input: RouterInput,
+    processor_data: Arc<processing::ProcessorData>,
+) -> RouterOutput {
+    match decode_router_input_to_model!(calendar_model::CalendarExportRequestModel, router_input) {
+        Ok(request_model) => {
+            let session_token_model = validate_session!(processor_data, request_model);
+
+            match calendar::calendar_export(request_model, session_token_model, processor_data) {
+                Ok(response) => {
+                    model_to_router_output!(response)
+                },
+                Err(e) => {
+                    model_to_router_output!(calendar_model::CalendarExportResponseModel {
+                        calendar: None,
+                        error: service_error_to_model!(EvelynServiceError::ExportCalendar(e)),
+                    })
+                },
+            }
+        },
+        Err(e) => {
+            model_to_router_output!(calendar_model::CalendarExportResponseModel {
+                calendar: None,
+                error: service_error_to_model!(EvelynServiceError::CouldNotDecodeTheRequestPayload(e)),
+            })
+        },
+    }
+}
+
+pub fn calendar_import_processor(
+    router_input: RouterInput,
+    processor_data: Arc<processing::ProcessorData>,
+) -> RouterOutput {
+    match decode_router_input_to_model!(calendar_model::CalendarImportRequestModel, router_input) {
+        Ok(request_model) => {
+            let session_token_model = validate_session!(processor_data, request_model);
+
+            match calendar::calendar_import(request_model, session_token_model, processor_data) {
+                Ok(response) => {
+                    model_to_router_output!(response)
+                },
+                Err(e) => {
+                    model_to_router_output!(calendar_model::CalendarImportResponseModel {
+                        created: 0,
+                        updated: 0,
+                        skipped: 0,
+                        error: service_error_to_model!(match e {
+                            EvelynCoreError::InvalidICalendar(_) => EvelynServiceError::InvalidICalendar(e),
+                            _ => EvelynServiceError::ImportCalendar(e),
+                        }),
+                    })
+                },
+            }
+        },
+        Err(e) => {
+            model_to_router_output!(calendar_model::CalendarImportResponseModel {
+                created: 0,
+                updated: 0,
+                skipped: 0,
+                error: service_error_to_model!(EvelynServiceError::CouldNotDecodeTheRequestPayload(e)),
+            })
+        },
+    }
+}
+
+pub fn calendar_invite_processor(
+    router_input: RouterInput,
+    processor_data: Arc<processing::ProcessorData>,
+) -> RouterOutput {
+    match decode_router_input_to_model!(calendar_model::CalendarInviteRequestModel, router_input) {
+        Ok(request_model) => {
+            let session_token_model = validate_session!(processor_data, request_model);
+
+            match calendar::calendar_invite(request_model, session_token_model, processor_data) {
+                None => {
+                    model_to_router_output!(calendar_model::CalendarInviteResponseModel {
+                        error: None,
+                    })
+                },
+                Some(e) => {
+                    model_to_router_output!(calendar_model::CalendarInviteResponseModel {
+                        error: service_error_to_model!(match e {
+                            EvelynCoreError::CalendarEventNotFound(_) => EvelynServiceError::CalendarEventNotFound(e),
+                            EvelynCoreError::CalendarEventVersionConflict(_) => EvelynServiceError::CalendarEventVersionConflict(e),
+                            EvelynCoreError::CalendarInviteeNotFound(_) => EvelynServiceError::CalendarInviteeNotFound(e),
+                            _ => EvelynServiceError::InviteToCalendarEvent(e),
+                        }),
+                    })
+                },
+            }
+        },
+        Err(e) => {
+            model_to_router_output!(calendar_model::CalendarInviteResponseModel {
+                error: service_error_to_model!(EvelynServiceError::CouldNotDecodeTheRequestPayload(e)),
+            })
+        },
+    }
+}
+
+pub fn calendar_respond_processor(
+    router_input: RouterInput,
+    processor_data: Arc<processing::ProcessorData>,
+) -> RouterOutput {
+    match decode_router_input_to_model!(calendar_model::CalendarRespondRequestModel, router_input) {
+        Ok(request_model) => {
+            let session_token_model = validate_session!(processor_data, request_model);
+
+            match calendar::calendar_respond(request_model, session_token_model, processor_data) {
+                None => {
+                    model_to_router_output!(calendar_model::CalendarRespondResponseModel {
+                        error: None,
+                    })
+                },
+                Some(e) => {
+                    model_to_router_output!(calendar_model::CalendarRespondResponseModel {
+                        error: service_error_to_model!(match e {
+                            EvelynCoreError::NotInvitedToCalendarEvent(_) => EvelynServiceError::NotInvitedToCalendarEvent(e),
+                            _ => EvelynServiceError::RespondToCalendarEvent(e),
+                        }),
+                    })
+                },
+            }
+        },
+        Err(e) => {
+            model_to_router_output!(calendar_model::CalendarRespondResponseModel {
+                error: service_error_to_model!(EvelynServiceError::CouldNotDecodeTheRequestPayload(e)),
+            })
+        },
+    }
+}
+
+pub fn calendar_free_busy_processor(
+    router_input: RouterInput,
+    processor_data: Arc<processing::ProcessorData>,
+) -> RouterOutput {
+    match decode_router_input_to_model!(calendar_model::CalendarFreeBusyRequestModel, router_input) {
+        Ok(request_model) => {
+            let session_token_model = validate_session!(processor_data, request_model);
+
+            match calendar::calendar_free_busy(request_model, session_token_model, processor_data) {
+                Ok(response) => {
+                    model_to_router_output!(response)
+                },
+                Err(e) => {
+                    model_to_router_output!(calendar_model::CalendarFreeBusyResponseModel {
+                        users: Vec::new(),
+                        error: service_error_to_model!(match e {
+                            EvelynCoreError::InvalidCalendarEventTime(_) => EvelynServiceError::InvalidCalendarEventTime(e),
+                            EvelynCoreError::CalendarInviteeNotFound(_) => EvelynServiceError::CalendarInviteeNotFound(e),
+                            _ => EvelynServiceError::LookupFreeBusy(e),
+                        }),
+                    })
+                },
+            }
+        },
+        Err(e) => {
+            model_to_router_output!(calendar_model::CalendarFreeBusyResponseModel {
+                users: Vec::new(),
+                error: service_error_to_model!(EvelynServiceError::CouldNotDecodeTheRequestPayload(e)),
+            })
+        },
+    }
+}
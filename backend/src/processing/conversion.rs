@@ -0,0 +1,92 @@
+// Evelyn: Your personal assistant, project manager and calendar
+// Copyright (C) 2017 Gregory Jensen
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use core::conversion;
+use core::error_messages::{EvelynBaseError, EvelynCoreError, EvelynServiceError};
+use model;
+use processing;
+use serde_json;
+use server::routing::{RouterInput, RouterOutput};
+use std::sync::Arc;
+
+pub fn promote_simple_task_processor(
+    router_input: RouterInput,
+    processor_data: Arc<processing::ProcessorData>,
+) -> RouterOutput {
+    match decode_router_input_to_model!(model::conversion::PromoteSimpleTaskRequestModel, router_input) {
+        Ok(request_model) => {
+            let session_token_model = validate_session!(processor_data, request_model);
+
+            match conversion::promote_simple_task(request_model, session_token_model, processor_data) {
+                Ok(response) => {
+                    model_to_router_output!(response)
+                },
+                Err(e) => {
+                    model_to_router_output!(model::conversion::PromoteSimpleTaskResponseModel {
+                        agile_task_id: None,
+                        error: service_error_to_model!(match e {
+                            EvelynCoreError::SimpleTaskNotFound(_) => EvelynServiceError::SimpleTaskNotFound(e),
+                            EvelynCoreError::SimpleTaskVersionConflict(_) => EvelynServiceError::SimpleTaskVersionConflict(e),
+                            EvelynCoreError::CannotPromoteToAgileProject(_) => EvelynServiceError::CannotPromoteToAgileProject(e),
+                            _ => EvelynServiceError::FailedToPromoteSimpleTask(e),
+                        }),
+                    })
+                },
+            }
+        },
+        Err(e) => {
+            model_to_router_output!(model::conversion::PromoteSimpleTaskResponseModel {
+                agile_task_id: None,
+                error: service_error_to_model!(EvelynServiceError::CouldNotDecodeTheRequestPayload(e)),
+            })
+        },
+    }
+}
+
+pub fn convert_todo_list_item_processor(
+    router_input: RouterInput,
+    processor_data: Arc<processing::ProcessorData>,
+) -> RouterOutput {
+    match decode_router_input_to_model!(model::conversion::ConvertTodoListItemRequestModel, router_input) {
+        Ok(request_model) => {
+            let session_token_model = validate_session!(processor_data, request_model);
+
+            match conversion::convert_todo_list_item(request_model, session_token_model, processor_data) {
+                Ok(response) => {
+                    model_to_router_output!(response)
+                },
+                Err(e) => {
+                    model_to_router_output!(model::conversion::ConvertTodoListItemResponseModel {
+                        task_id: None,
+                        error: service_error_to_model!(match e {
+                            EvelynCoreError::TodoListNotFound(_) => EvelynServiceError::TodoListNotFound(e),
+                            EvelynCoreError::TodoListVersionConflict(_) => EvelynServiceError::TodoListVersionConflict(e),
+                            EvelynCoreError::InvalidDateTime(_) => EvelynServiceError::InvalidDateTime(e),
+                            EvelynCoreError::InvalidTimeZone(_) => EvelynServiceError::InvalidTimeZone(e),
+                            _ => EvelynServiceError::ConvertTodoListItem(e),
+                        }),
+                    })
+                },
+            }
+        },
+        Err(e) => {
+            model_to_router_output!(model::conversion::ConvertTodoListItemResponseModel {
+                task_id: None,
+                error: service_error_to_model!(EvelynServiceError::CouldNotDecodeTheRequestPayload(e)),
+            })
+        },
+    }
+}
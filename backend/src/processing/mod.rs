@@ -14,10 +14,12 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+use core::notification::Notifier;
 use core::token_service::TokenService;
 use data::conf;
 use mongodb::Client;
 use server::routing::Router;
+use std::sync::Arc;
 
 #[macro_use]
 mod macros;
@@ -29,22 +31,33 @@ pub mod todo_list;
 pub mod calendar;
 pub mod server_admin;
 pub mod agile;
+pub mod trash;
+pub mod reminder;
+pub mod notification;
+pub mod webhook;
+pub mod conversion;
+pub mod agenda;
 
 pub struct ProcessorData {
     pub data_store: Client,
     pub token_service: TokenService,
     pub conf: conf::Conf,
     pub server_session_token: String,
+    pub notifier: Arc<Notifier>,
 }
 
 pub fn load_processors(router: &mut Router) {
     // TODO only loaded if a flag is passed to evelyn, or the request is
     // authenticated?
     router.add_rule("/purge", server_admin::purge_processor);
+    router.add_rule("/admin/user/delete", server_admin::delete_user_processor);
+    router.add_rule("/admin/project/delete", server_admin::delete_project_processor);
 
     router.add_rule("/user/create", user::create_user_processor);
     router.add_rule("/user/logon", user::logon_user_processor);
     router.add_rule("/user/search", user::search_processor);
+    router.add_rule("/user/profile/lookup", user::lookup_profile_processor);
+    router.add_rule("/user/profile/update", user::update_profile_processor);
 
     router.add_rule("/usergroup/create", user_group::create_user_group_processor);
     router.add_rule("/usergroup/remove", user_group::remove_user_group_processor);
@@ -56,13 +69,26 @@ pub fn load_processors(router: &mut Router) {
     router.add_rule("/simpletask/create", simple_task::create_simple_task_processor);
     router.add_rule("/simpletask/lookup", simple_task::lookup_simple_task_processor);
     router.add_rule("/simpletask/update", simple_task::update_simple_task_processor);
+    router.add_rule("/simpletask/checklist/update", simple_task::update_checklist_item_processor);
     router.add_rule("/simpletask/remove", simple_task::remove_processor);
+    router.add_rule("/simpletask/promote", conversion::promote_simple_task_processor);
 
     router.add_rule("/todolist/create", todo_list::create_todo_list_processor);
     router.add_rule("/todolist/lookuplists", todo_list::lookup_todo_lists_processor);
     router.add_rule("/todolist/lookup", todo_list::lookup_todo_list_processor);
+    router.add_rule("/todolist/update", todo_list::update_todo_list_processor);
+    router.add_rule("/todolist/remove", todo_list::remove_processor);
+    router.add_rule("/todolist/share/add", todo_list::share_todo_list_processor);
+    router.add_rule("/todolist/share/remove", todo_list::unshare_todo_list_processor);
+    router.add_rule("/todolist/template/create", todo_list::create_template_processor);
+    router.add_rule("/todolist/template/lookup", todo_list::lookup_templates_processor);
+    router.add_rule("/todolist/template/instantiate", todo_list::instantiate_template_processor);
+    router.add_rule("/todolist/template/remove", todo_list::remove_template_processor);
     router.add_rule("/todolist/item/add", todo_list::add_item_todo_list_processor);
     router.add_rule("/todolist/item/update", todo_list::update_item_todo_list_processor);
+    router.add_rule("/todolist/item/remove", todo_list::remove_item_todo_list_processor);
+    router.add_rule("/todolist/item/move", todo_list::move_item_todo_list_processor);
+    router.add_rule("/todolist/item/convert", conversion::convert_todo_list_item_processor);
 
     router.add_rule("/agile/project/create", agile::project::create_processor);
     router.add_rule("/agile/project/lookup/contributingto", agile::project::lookup_contributing_to_processor);
@@ -74,17 +100,48 @@ pub fn load_processors(router: &mut Router) {
     router.add_rule("/agile/task/create", agile::task::create_processor);
     router.add_rule("/agile/task/lookup", agile::task::lookup_processor);
     router.add_rule("/agile/task/lookup/backlog", agile::task::lookup_backlog_processor);
+    router.add_rule("/agile/task/lookup/assigned", agile::task::lookup_assigned_processor);
     router.add_rule("/agile/task/update", agile::task::update_processor);
+    router.add_rule("/agile/task/history", agile::task::history_processor);
 
     router.add_rule("/agile/story/create", agile::story::create_processor);
     router.add_rule("/agile/story/lookup", agile::story::lookup_processor);
     router.add_rule("/agile/story/lookup/backlog", agile::story::lookup_backlog_processor);
+    router.add_rule("/agile/story/history", agile::story::history_processor);
 
     router.add_rule("/agile/sprint/create", agile::sprint::create_processor);
     router.add_rule("/agile/sprint/lookup/active", agile::sprint::lookup_active_processor);
     router.add_rule("/agile/sprint/lookup/backlog", agile::sprint::lookup_backlog_processor);
+    router.add_rule("/agile/sprint/history", agile::sprint::history_processor);
     
     router.add_rule("/agile/heirarchy/link", agile::heirarchy::link_processor);
 
     router.add_rule("/calendar/addevent", calendar::calendar_add_event_processor);
+    router.add_rule("/calendar/lookup", calendar::calendar_lookup_processor);
+    router.add_rule("/calendar/event/update", calendar::calendar_update_event_processor);
+    router.add_rule("/calendar/event/remove", calendar::calendar_remove_event_processor);
+    router.add_rule("/calendar/event/occurrence/update", calendar::calendar_update_occurrence_processor);
+    router.add_rule("/calendar/event/occurrence/cancel", calendar::calendar_cancel_occurrence_processor);
+    router.add_rule("/calendar/export", calendar::calendar_export_processor);
+    router.add_rule("/calendar/import", calendar::calendar_import_processor);
+    router.add_rule("/calendar/event/invite", calendar::calendar_invite_processor);
+    router.add_rule("/calendar/event/respond", calendar::calendar_respond_processor);
+    router.add_rule("/calendar/freebusy", calendar::calendar_free_busy_processor);
+
+    router.add_rule("/trash/lookup", trash::lookup_processor);
+    router.add_rule("/trash/restore", trash::restore_processor);
+
+    router.add_rule("/reminder/add", reminder::add_processor);
+    router.add_rule("/reminder/lookup", reminder::lookup_processor);
+    router.add_rule("/reminder/remove", reminder::remove_processor);
+
+    router.add_rule("/notification/lookup", notification::lookup_processor);
+    router.add_rule("/notification/markread", notification::mark_read_processor);
+
+    router.add_rule("/webhook/add", webhook::add_processor);
+    router.add_rule("/webhook/lookup", webhook::lookup_processor);
+    router.add_rule("/webhook/remove", webhook::remove_processor);
+    router.add_rule("/webhook/deliveries", webhook::lookup_deliveries_processor);
+
+    router.add_rule("/agenda/myday", agenda::lookup_my_day_processor);
 }
@@ -0,0 +1,82 @@
+// Evelyn: Your personal assistant, project manager and calendar
+// Copyright (C) 2017 Gregory Jensen
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use core::error_messages::{EvelynBaseError, EvelynServiceError};
+use core::notification;
+use model;
+use model::notification as notification_model;
+use processing;
+use serde_json;
+use server::routing::{RouterInput, RouterOutput};
+use std::sync::Arc;
+
+pub fn lookup_processor(
+    router_input: RouterInput,
+    processor_data: Arc<processing::ProcessorData>,
+) -> RouterOutput {
+    match decode_router_input_to_model!(notification_model::LookupNotificationsRequestModel, router_input) {
+        Ok(request_model) => {
+            let session_token_model = validate_session!(processor_data, request_model);
+
+            match notification::lookup_notifications(request_model, session_token_model, processor_data) {
+                Ok(response) => {
+                    model_to_router_output!(response)
+                },
+                Err(e) => {
+                    model_to_router_output!(notification_model::LookupNotificationsResponseModel {
+                        notifications: Vec::new(),
+                        error: service_error_to_model!(EvelynServiceError::LookupNotifications(e)),
+                    })
+                },
+            }
+        },
+        Err(e) => {
+            model_to_router_output!(notification_model::LookupNotificationsResponseModel {
+                notifications: Vec::new(),
+                error: service_error_to_model!(EvelynServiceError::CouldNotDecodeTheRequestPayload(e)),
+            })
+        },
+    }
+}
+
+pub fn mark_read_processor(
+    router_input: RouterInput,
+    processor_data: Arc<processing::ProcessorData>,
+) -> RouterOutput {
+    match decode_router_input_to_model!(notification_model::MarkNotificationsReadRequestModel, router_input) {
+        Ok(request_model) => {
+            let session_token_model = validate_session!(processor_data, request_model);
+
+            match notification::mark_read(request_model, session_token_model, processor_data) {
+                None => {
+                    model_to_router_output!(notification_model::MarkNotificationsReadResponseModel {
+                        error: None,
+                    })
+                },
+                Some(e) => {
+                    model_to_router_output!(notification_model::MarkNotificationsReadResponseModel {
+                        error: service_error_to_model!(EvelynServiceError::MarkNotificationsRead(e)),
+                    })
+                },
+            }
+        },
+        Err(e) => {
+            model_to_router_output!(notification_model::MarkNotificationsReadResponseModel {
+                error: service_error_to_model!(EvelynServiceError::CouldNotDecodeTheRequestPayload(e)),
+            })
+        },
+    }
+}
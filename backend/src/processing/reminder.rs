@@ -0,0 +1,118 @@
+// Evelyn: Your personal assistant, project manager and calendar
+// Copyright (C) 2017 Gregory Jensen
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use core::error_messages::{EvelynBaseError, EvelynCoreError, EvelynServiceError};
+use core::reminder;
+use model;
+use model::reminder as reminder_model;
+use processing;
+use serde_json;
+use server::routing::{RouterInput, RouterOutput};
+use std::sync::Arc;
+
+pub fn add_processor(
+    router_input: RouterInput,
+    processor_data: Arc<processing::ProcessorData>,
+) -> RouterOutput {
+    match decode_router_input_to_model!(reminder_model::AddReminderRequestModel, router_input) {
+        Ok(request_model) => {
+            let session_token_model = validate_session!(processor_data, request_model);
+
+            match reminder::add_reminder(request_model, session_token_model, processor_data) {
+                Ok(response) => {
+                    model_to_router_output!(response)
+                },
+                Err(e) => {
+                    model_to_router_output!(reminder_model::AddReminderResponseModel {
+                        reminder_id: None,
+                        error: service_error_to_model!(match e {
+                            EvelynCoreError::InvalidReminder(_) => EvelynServiceError::InvalidReminder(e),
+                            EvelynCoreError::ReminderSourceNotFound(_) => EvelynServiceError::ReminderSourceNotFound(e),
+                            _ => EvelynServiceError::AddReminder(e),
+                        }),
+                    })
+                },
+            }
+        },
+        Err(e) => {
+            model_to_router_output!(reminder_model::AddReminderResponseModel {
+                reminder_id: None,
+                error: service_error_to_model!(EvelynServiceError::CouldNotDecodeTheRequestPayload(e)),
+            })
+        },
+    }
+}
+
+pub fn lookup_processor(
+    router_input: RouterInput,
+    processor_data: Arc<processing::ProcessorData>,
+) -> RouterOutput {
+    match decode_router_input_to_model!(reminder_model::LookupRemindersRequestModel, router_input) {
+        Ok(request_model) => {
+            let session_token_model = validate_session!(processor_data, request_model);
+
+            match reminder::lookup_reminders(session_token_model, processor_data) {
+                Ok(response) => {
+                    model_to_router_output!(response)
+                },
+                Err(e) => {
+                    model_to_router_output!(reminder_model::LookupRemindersResponseModel {
+                        reminders: Vec::new(),
+                        error: service_error_to_model!(EvelynServiceError::LookupReminders(e)),
+                    })
+                },
+            }
+        },
+        Err(e) => {
+            model_to_router_output!(reminder_model::LookupRemindersResponseModel {
+                reminders: Vec::new(),
+                error: service_error_to_model!(EvelynServiceError::CouldNotDecodeTheRequestPayload(e)),
+            })
+        },
+    }
+}
+
+pub fn remove_processor(
+    router_input: RouterInput,
+    processor_data: Arc<processing::ProcessorData>,
+) -> RouterOutput {
+    match decode_router_input_to_model!(reminder_model::RemoveReminderRequestModel, router_input) {
+        Ok(request_model) => {
+            let session_token_model = validate_session!(processor_data, request_model);
+
+            match reminder::remove_reminder(request_model, session_token_model, processor_data) {
+                None => {
+                    model_to_router_output!(reminder_model::RemoveReminderResponseModel {
+                        error: None,
+                    })
+                },
+                Some(e) => {
+                    model_to_router_output!(reminder_model::RemoveReminderResponseModel {
+                        error: service_error_to_model!(match e {
+                            EvelynCoreError::ReminderNotFound(_) => EvelynServiceError::ReminderNotFound(e),
+                            _ => EvelynServiceError::RemoveReminder(e),
+                        }),
+                    })
+                },
+            }
+        },
+        Err(e) => {
+            model_to_router_output!(reminder_model::RemoveReminderResponseModel {
+                error: service_error_to_model!(EvelynServiceError::CouldNotDecodeTheRequestPayload(e)),
+            })
+        },
+    }
+}
@@ -14,9 +14,10 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use core::error_messages::{EvelynBaseError, EvelynServiceError};
+use core::error_messages::{EvelynBaseError, EvelynCoreError, EvelynServiceError};
 use core::server_admin;
 use model;
+use model::server_admin as server_admin_model;
 use processing;
 use serde_json;
 use server::routing::{RouterInput, RouterOutput};
@@ -32,20 +33,24 @@ pub fn purge_processor(
 
     match request_model_de {
         Ok(request_model) => {
-            let error = match request_model.target_type.as_str() {
-                "database" => {
-                    match server_admin::purge_database(processor_data) {
-                        None => None,
-                        Some(e) => Some(EvelynServiceError::FailedToPurge(e)),
-                    }
-                },
-                "database_area" => {
-                    match server_admin::purge_database_area(&request_model.target, processor_data) {
-                        None => None,
-                        Some(e) => Some(EvelynServiceError::FailedToPurge(e)),
-                    }
-                },
-                _ => Some(EvelynServiceError::InvalidPurgeTargetType(EvelynBaseError::NothingElse)),
+            let error = if !processor_data.conf.is_allow_destructive_purge() {
+                Some(EvelynServiceError::DestructivePurgeDisabled(EvelynBaseError::NothingElse))
+            } else {
+                match request_model.target_type.as_str() {
+                    "database" => {
+                        match server_admin::purge_database(processor_data) {
+                            None => None,
+                            Some(e) => Some(EvelynServiceError::FailedToPurge(e)),
+                        }
+                    },
+                    "database_area" => {
+                        match server_admin::purge_database_area(&request_model.target, processor_data) {
+                            None => None,
+                            Some(e) => Some(EvelynServiceError::FailedToPurge(e)),
+                        }
+                    },
+                    _ => Some(EvelynServiceError::InvalidPurgeTargetType(EvelynBaseError::NothingElse)),
+                }
             };
 
             match error {
@@ -79,3 +84,64 @@ pub fn purge_processor(
         },
     }
 }
+
+pub fn delete_user_processor(
+    router_input: RouterInput,
+    processor_data: Arc<processing::ProcessorData>,
+) -> RouterOutput {
+    match decode_router_input_to_model!(server_admin_model::DeleteUserRequestModel, router_input) {
+        Ok(request_model) => {
+            let session_token_model = validate_session!(processor_data, request_model);
+
+            match server_admin::delete_user(request_model, session_token_model, processor_data) {
+                Ok(response) => {
+                    model_to_router_output!(response)
+                },
+                Err(e) => {
+                    model_to_router_output!(server_admin_model::DeleteUserResponseModel {
+                        affected: Vec::new(),
+                        error: service_error_to_model!(EvelynServiceError::DeleteUserData(e)),
+                    })
+                },
+            }
+        },
+        Err(e) => {
+            model_to_router_output!(server_admin_model::DeleteUserResponseModel {
+                affected: Vec::new(),
+                error: service_error_to_model!(EvelynServiceError::CouldNotDecodeTheRequestPayload(e)),
+            })
+        },
+    }
+}
+
+pub fn delete_project_processor(
+    router_input: RouterInput,
+    processor_data: Arc<processing::ProcessorData>,
+) -> RouterOutput {
+    match decode_router_input_to_model!(server_admin_model::DeleteProjectRequestModel, router_input) {
+        Ok(request_model) => {
+            let session_token_model = validate_session!(processor_data, request_model);
+
+            match server_admin::delete_project(request_model, session_token_model, processor_data) {
+                Ok(response) => {
+                    model_to_router_output!(response)
+                },
+                Err(e) => {
+                    model_to_router_output!(server_admin_model::DeleteProjectResponseModel {
+                        affected: Vec::new(),
+                        error: service_error_to_model!(match e {
+                            EvelynCoreError::NotPermittedToDeleteAgileProject(_) => EvelynServiceError::NotPermittedToDeleteAgileProject(e),
+                            _ => EvelynServiceError::DeleteAgileProject(e),
+                        }),
+                    })
+                },
+            }
+        },
+        Err(e) => {
+            model_to_router_output!(server_admin_model::DeleteProjectResponseModel {
+                affected: Vec::new(),
+                error: service_error_to_model!(EvelynServiceError::CouldNotDecodeTheRequestPayload(e)),
+            })
+        },
+    }
+}
@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use core::error_messages::{EvelynBaseError, EvelynServiceError};
+use core::error_messages::{EvelynBaseError, EvelynCoreError, EvelynServiceError};
 use core::simple_task;
 use model;
 use processing;
@@ -42,7 +42,7 @@ pub fn create_simple_task_processor(
                     RouterOutput {
                         response_body: serde_json::to_string(&model::simple_task::CreateSimpleTaskResponseModel {
                                                                  task_id: None,
-                                                                 error: Some(From::from(EvelynServiceError::FailedToCreateSimpleTask(e))),
+                                                                 error: Some(From::from(create_error_to_service_error(e))),
                                                              })
                                 .unwrap(),
                     }
@@ -82,7 +82,8 @@ pub fn lookup_simple_task_processor(
                     RouterOutput {
                         response_body: serde_json::to_string(&model::simple_task::LookupSimpleTaskResponseModel {
                                                                  simple_tasks: Vec::new(),
-                                                                 error: Some(From::from(EvelynServiceError::FailedToLookupSimpleTask(e))),
+                                                                 next_cursor: None,
+                                                                 error: Some(From::from(lookup_error_to_service_error(e))),
                                                              })
                                 .unwrap(),
                     }
@@ -92,6 +93,7 @@ pub fn lookup_simple_task_processor(
         Err(e) => {
             let response = model::simple_task::LookupSimpleTaskResponseModel {
                 simple_tasks: Vec::new(),
+                next_cursor: None,
                 error: Some(From::from(EvelynServiceError::CouldNotDecodeTheRequestPayload(e))),
             };
 
@@ -113,19 +115,17 @@ pub fn update_simple_task_processor(
             let session_token_model = validate_session!(processor_data, request_model);
 
             match simple_task::update_simple_task(request_model, session_token_model, processor_data) {
-                None => {
+                Ok(response) => {
                     RouterOutput {
-                        response_body: serde_json::to_string(&model::simple_task::UpdateSimpleTaskResponseModel {
-                                                                 error: None,
-                                                             })
-                                .unwrap(),
+                        response_body: serde_json::to_string(&response).unwrap(),
                     }
                 },
-                Some(e) => {
-                    let model: model::ErrorModel = From::from(EvelynServiceError::FailedToUpdateSimpleTask(e));
+                Err(e) => {
+                    let model: model::ErrorModel = From::from(update_error_to_service_error(e));
                     RouterOutput {
                         response_body: serde_json::to_string(&model::simple_task::UpdateSimpleTaskResponseModel {
                                                                  error: Some(model),
+                                                                 next_task_id: None,
                                                              })
                                 .unwrap(),
                     }
@@ -137,12 +137,46 @@ pub fn update_simple_task_processor(
             RouterOutput {
                 response_body: serde_json::to_string(&model::simple_task::UpdateSimpleTaskResponseModel {
                      error: Some(model),
+                     next_task_id: None,
                  }).unwrap(),
             }
         },
     }
 }
 
+pub fn update_checklist_item_processor(
+    router_input: RouterInput,
+    processor_data: Arc<processing::ProcessorData>,
+) -> RouterOutput {
+    match decode_router_input_to_model!(model::simple_task::UpdateSimpleTaskChecklistItemRequestModel, router_input) {
+        Ok(request_model) => {
+            let session_token_model = validate_session!(processor_data, request_model);
+
+            match simple_task::update_checklist_item(request_model, session_token_model, processor_data) {
+                None => {
+                    model_to_router_output!(model::simple_task::UpdateSimpleTaskChecklistItemResponseModel {
+                        error: None,
+                    })
+                },
+                Some(e) => {
+                    model_to_router_output!(model::simple_task::UpdateSimpleTaskChecklistItemResponseModel {
+                        error: service_error_to_model!(match e {
+                            EvelynCoreError::SimpleTaskNotFound(_) => EvelynServiceError::SimpleTaskNotFound(e),
+                            EvelynCoreError::SimpleTaskVersionConflict(_) => EvelynServiceError::SimpleTaskVersionConflict(e),
+                            _ => EvelynServiceError::FailedToUpdateSimpleTaskChecklist(e),
+                        }),
+                    })
+                },
+            }
+        },
+        Err(e) => {
+            model_to_router_output!(model::simple_task::UpdateSimpleTaskChecklistItemResponseModel {
+                error: service_error_to_model!(EvelynServiceError::CouldNotDecodeTheRequestPayload(e)),
+            })
+        },
+    }
+}
+
 pub fn remove_processor(
     router_input: RouterInput,
     processor_data: Arc<processing::ProcessorData>,
@@ -151,9 +185,9 @@ pub fn remove_processor(
 
     match request_model_de {
         Ok(request_model) => {
-            validate_session!(processor_data, request_model);
+            let session_token_model = validate_session!(processor_data, request_model);
 
-            match simple_task::remove(request_model, processor_data) {
+            match simple_task::remove(request_model, session_token_model, processor_data) {
                 None => {
                     RouterOutput {
                         response_body: serde_json::to_string(&model::simple_task::RemoveSimpleTaskResponseModel {
@@ -164,7 +198,10 @@ pub fn remove_processor(
                 Some(e) => {
                     RouterOutput {
                         response_body: serde_json::to_string(&model::simple_task::RemoveSimpleTaskResponseModel {
-                             error: service_error_to_model!(EvelynServiceError::FailedToRemoveSimpleTask(e)),
+                             error: service_error_to_model!(match e {
+                                 EvelynCoreError::SimpleTaskNotFound(_) => EvelynServiceError::SimpleTaskNotFound(e),
+                                 _ => EvelynServiceError::FailedToRemoveSimpleTask(e),
+                             }),
                          }).unwrap(),
                     }
                 },
@@ -179,3 +216,35 @@ pub fn remove_processor(
         },
     }
 }
+
+fn update_error_to_service_error(e: EvelynCoreError) -> EvelynServiceError {
+    match e {
+        EvelynCoreError::SimpleTaskVersionConflict(_) => EvelynServiceError::SimpleTaskVersionConflict(e),
+        EvelynCoreError::SimpleTaskNotFound(_) => EvelynServiceError::SimpleTaskNotFound(e),
+        EvelynCoreError::InvalidDateTime(_) => EvelynServiceError::InvalidDateTime(e),
+        EvelynCoreError::InvalidTimeZone(_) => EvelynServiceError::InvalidTimeZone(e),
+        EvelynCoreError::InvalidRecurrenceRule(_) => EvelynServiceError::InvalidRecurrenceRule(e),
+        EvelynCoreError::RecurringSimpleTaskWithoutDueDate(_) => EvelynServiceError::RecurringSimpleTaskWithoutDueDate(e),
+        EvelynCoreError::InvalidParentSimpleTask(_) => EvelynServiceError::InvalidParentSimpleTask(e),
+        _ => EvelynServiceError::FailedToUpdateSimpleTask(e),
+    }
+}
+
+fn create_error_to_service_error(e: EvelynCoreError) -> EvelynServiceError {
+    match e {
+        EvelynCoreError::InvalidDateTime(_) => EvelynServiceError::InvalidDateTime(e),
+        EvelynCoreError::InvalidTimeZone(_) => EvelynServiceError::InvalidTimeZone(e),
+        EvelynCoreError::InvalidRecurrenceRule(_) => EvelynServiceError::InvalidRecurrenceRule(e),
+        EvelynCoreError::RecurringSimpleTaskWithoutDueDate(_) => EvelynServiceError::RecurringSimpleTaskWithoutDueDate(e),
+        EvelynCoreError::InvalidParentSimpleTask(_) => EvelynServiceError::InvalidParentSimpleTask(e),
+        _ => EvelynServiceError::FailedToCreateSimpleTask(e),
+    }
+}
+
+fn lookup_error_to_service_error(e: EvelynCoreError) -> EvelynServiceError {
+    match e {
+        EvelynCoreError::InvalidDateTime(_) => EvelynServiceError::InvalidDateTime(e),
+        EvelynCoreError::InvalidSimpleTaskCursor(_) => EvelynServiceError::InvalidSimpleTaskCursor(e),
+        _ => EvelynServiceError::FailedToLookupSimpleTask(e),
+    }
+}
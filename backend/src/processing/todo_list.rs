@@ -14,7 +14,7 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
-use core::error_messages::{EvelynBaseError, EvelynServiceError};
+use core::error_messages::{EvelynBaseError, EvelynCoreError, EvelynServiceError};
 use core::todo_list;
 use model;
 use processing;
@@ -73,20 +73,21 @@ pub fn add_item_todo_list_processor(
             let session_token_model = validate_session!(processor_data, request_model);
 
             match todo_list::add_item_to_todo_list(request_model, session_token_model, processor_data) {
-                Some(e) => {
+                Err(e) => {
                     RouterOutput {
                         response_body: serde_json::to_string(&model::todo_list::item::AddItemTodoListResponseModel {
-                                                                 error: Some(From::from(EvelynServiceError::AddItemToTodoList(e))),
+                                                                 item_id: None,
+                                                                 error: Some(From::from(match e {
+                                                                     EvelynCoreError::TodoListNotFound(_) => EvelynServiceError::TodoListNotFound(e),
+                                                                     _ => EvelynServiceError::AddItemToTodoList(e),
+                                                                 })),
                                                              })
                                 .unwrap(),
                     }
                 },
-                None => {
+                Ok(result) => {
                     RouterOutput {
-                        response_body: serde_json::to_string(&model::todo_list::item::AddItemTodoListResponseModel {
-                                                                 error: None,
-                                                             })
-                                .unwrap(),
+                        response_body: serde_json::to_string(&result).unwrap(),
                     }
                 },
             }
@@ -95,6 +96,7 @@ pub fn add_item_todo_list_processor(
             let model: model::ErrorModel = From::from(EvelynServiceError::CouldNotDecodeTheRequestPayload(e));
             RouterOutput {
                 response_body: serde_json::to_string(&model::todo_list::item::AddItemTodoListResponseModel {
+                                                         item_id: None,
                                                          error: Some(model),
                                                      })
                         .unwrap(),
@@ -113,7 +115,7 @@ pub fn lookup_todo_lists_processor(
         Ok(request_model) => {
             let session_token_model = validate_session!(processor_data, request_model);
 
-            match todo_list::lookup_todo_lists(session_token_model, processor_data) {
+            match todo_list::lookup_todo_lists(request_model, session_token_model, processor_data) {
                 Ok(result) => {
                     RouterOutput {
                         response_body: serde_json::to_string(&result).unwrap(),
@@ -133,7 +135,8 @@ pub fn lookup_todo_lists_processor(
         Err(e) => {
             let model: model::ErrorModel = From::from(EvelynServiceError::CouldNotDecodeTheRequestPayload(e));
             RouterOutput {
-                response_body: serde_json::to_string(&model::todo_list::item::AddItemTodoListResponseModel {
+                response_body: serde_json::to_string(&model::todo_list::LookupTodoListsResponseModel {
+                                                         todo_lists: None,
                                                          error: Some(model),
                                                      })
                         .unwrap(),
@@ -204,7 +207,7 @@ pub fn update_item_todo_list_processor(
                 Some(e) => {
                     RouterOutput {
                         response_body: serde_json::to_string(&model::todo_list::item::UpdateItemTodoListResponseModel {
-                                                                 error: Some(From::from(EvelynServiceError::UpdateTodoListItem(e))),
+                                                                 error: Some(From::from(update_error_to_service_error(e))),
                                                              })
                                 .unwrap(),
                     }
@@ -222,3 +225,336 @@ pub fn update_item_todo_list_processor(
         },
     }
 }
+
+pub fn remove_item_todo_list_processor(
+    router_input: RouterInput,
+    processor_data: Arc<processing::ProcessorData>,
+) -> RouterOutput {
+    match decode_router_input_to_model!(model::todo_list::item::RemoveItemTodoListRequestModel, router_input) {
+        Ok(request_model) => {
+            let session_token_model = validate_session!(processor_data, request_model);
+
+            match todo_list::remove_todo_list_item(request_model, session_token_model, processor_data) {
+                None => {
+                    model_to_router_output!(model::todo_list::item::RemoveItemTodoListResponseModel {
+                        error: None,
+                    })
+                },
+                Some(e) => {
+                    model_to_router_output!(model::todo_list::item::RemoveItemTodoListResponseModel {
+                        error: service_error_to_model!(match e {
+                            EvelynCoreError::TodoListVersionConflict(_) => EvelynServiceError::TodoListVersionConflict(e),
+                            EvelynCoreError::TodoListNotFound(_) => EvelynServiceError::TodoListNotFound(e),
+                            _ => EvelynServiceError::RemoveTodoListItem(e),
+                        }),
+                    })
+                },
+            }
+        },
+        Err(e) => {
+            model_to_router_output!(model::todo_list::item::RemoveItemTodoListResponseModel {
+                error: service_error_to_model!(EvelynServiceError::CouldNotDecodeTheRequestPayload(e)),
+            })
+        },
+    }
+}
+
+pub fn move_item_todo_list_processor(
+    router_input: RouterInput,
+    processor_data: Arc<processing::ProcessorData>,
+) -> RouterOutput {
+    match decode_router_input_to_model!(model::todo_list::item::MoveItemTodoListRequestModel, router_input) {
+        Ok(request_model) => {
+            let session_token_model = validate_session!(processor_data, request_model);
+
+            match todo_list::move_todo_list_item(request_model, session_token_model, processor_data) {
+                None => {
+                    model_to_router_output!(model::todo_list::item::MoveItemTodoListResponseModel {
+                        error: None,
+                    })
+                },
+                Some(e) => {
+                    model_to_router_output!(model::todo_list::item::MoveItemTodoListResponseModel {
+                        error: service_error_to_model!(match e {
+                            EvelynCoreError::TodoListVersionConflict(_) => EvelynServiceError::TodoListVersionConflict(e),
+                            EvelynCoreError::TodoListNotFound(_) => EvelynServiceError::TodoListNotFound(e),
+                            _ => EvelynServiceError::MoveTodoListItem(e),
+                        }),
+                    })
+                },
+            }
+        },
+        Err(e) => {
+            model_to_router_output!(model::todo_list::item::MoveItemTodoListResponseModel {
+                error: service_error_to_model!(EvelynServiceError::CouldNotDecodeTheRequestPayload(e)),
+            })
+        },
+    }
+}
+
+pub fn update_todo_list_processor(
+    router_input: RouterInput,
+    processor_data: Arc<processing::ProcessorData>,
+) -> RouterOutput {
+    match decode_router_input_to_model!(model::todo_list::UpdateTodoListRequestModel, router_input) {
+        Ok(request_model) => {
+            let session_token_model = validate_session!(processor_data, request_model);
+
+            match todo_list::update_todo_list(request_model, session_token_model, processor_data) {
+                None => {
+                    model_to_router_output!(model::todo_list::UpdateTodoListResponseModel {
+                        error: None,
+                    })
+                },
+                Some(e) => {
+                    model_to_router_output!(model::todo_list::UpdateTodoListResponseModel {
+                        error: service_error_to_model!(match e {
+                            EvelynCoreError::TodoListVersionConflict(_) => EvelynServiceError::TodoListVersionConflict(e),
+                            EvelynCoreError::TodoListNotFound(_) => EvelynServiceError::TodoListNotFound(e),
+                            _ => EvelynServiceError::UpdateTodoList(e),
+                        }),
+                    })
+                },
+            }
+        },
+        Err(e) => {
+            model_to_router_output!(model::todo_list::UpdateTodoListResponseModel {
+                error: service_error_to_model!(EvelynServiceError::CouldNotDecodeTheRequestPayload(e)),
+            })
+        },
+    }
+}
+
+pub fn remove_processor(
+    router_input: RouterInput,
+    processor_data: Arc<processing::ProcessorData>,
+) -> RouterOutput {
+    match decode_router_input_to_model!(model::todo_list::RemoveTodoListRequestModel, router_input) {
+        Ok(request_model) => {
+            let session_token_model = validate_session!(processor_data, request_model);
+
+            match todo_list::remove(request_model, session_token_model, processor_data) {
+                None => {
+                    model_to_router_output!(model::todo_list::RemoveTodoListResponseModel {
+                        error: None,
+                    })
+                },
+                Some(e) => {
+                    model_to_router_output!(model::todo_list::RemoveTodoListResponseModel {
+                        error: service_error_to_model!(match e {
+                            EvelynCoreError::TodoListNotFound(_) => EvelynServiceError::TodoListNotFound(e),
+                            _ => EvelynServiceError::RemoveTodoList(e),
+                        }),
+                    })
+                },
+            }
+        },
+        Err(e) => {
+            model_to_router_output!(model::todo_list::RemoveTodoListResponseModel {
+                error: service_error_to_model!(EvelynServiceError::CouldNotDecodeTheRequestPayload(e)),
+            })
+        },
+    }
+}
+
+fn update_error_to_service_error(e: EvelynCoreError) -> EvelynServiceError {
+    match e {
+        EvelynCoreError::TodoListVersionConflict(_) => EvelynServiceError::TodoListVersionConflict(e),
+        EvelynCoreError::TodoListNotFound(_) => EvelynServiceError::TodoListNotFound(e),
+        _ => EvelynServiceError::UpdateTodoListItem(e),
+    }
+}
+
+pub fn share_todo_list_processor(
+    router_input: RouterInput,
+    processor_data: Arc<processing::ProcessorData>,
+) -> RouterOutput {
+    match decode_router_input_to_model!(model::todo_list::ShareTodoListRequestModel, router_input) {
+        Ok(request_model) => {
+            let session_token_model = validate_session!(processor_data, request_model);
+
+            match todo_list::share_todo_list(request_model, session_token_model, processor_data) {
+                None => {
+                    model_to_router_output!(model::todo_list::ShareTodoListResponseModel {
+                        error: None,
+                    })
+                },
+                Some(e) => {
+                    model_to_router_output!(model::todo_list::ShareTodoListResponseModel {
+                        error: service_error_to_model!(match e {
+                            EvelynCoreError::TodoListVersionConflict(_) => EvelynServiceError::TodoListVersionConflict(e),
+                            EvelynCoreError::TodoListNotFound(_) => EvelynServiceError::TodoListNotFound(e),
+                            EvelynCoreError::InvalidTodoListShare(_) => EvelynServiceError::InvalidTodoListShare(e),
+                            _ => EvelynServiceError::UpdateTodoListShares(e),
+                        }),
+                    })
+                },
+            }
+        },
+        Err(e) => {
+            model_to_router_output!(model::todo_list::ShareTodoListResponseModel {
+                error: service_error_to_model!(EvelynServiceError::CouldNotDecodeTheRequestPayload(e)),
+            })
+        },
+    }
+}
+
+pub fn unshare_todo_list_processor(
+    router_input: RouterInput,
+    processor_data: Arc<processing::ProcessorData>,
+) -> RouterOutput {
+    match decode_router_input_to_model!(model::todo_list::UnshareTodoListRequestModel, router_input) {
+        Ok(request_model) => {
+            let session_token_model = validate_session!(processor_data, request_model);
+
+            match todo_list::unshare_todo_list(request_model, session_token_model, processor_data) {
+                None => {
+                    model_to_router_output!(model::todo_list::UnshareTodoListResponseModel {
+                        error: None,
+                    })
+                },
+                Some(e) => {
+                    model_to_router_output!(model::todo_list::UnshareTodoListResponseModel {
+                        error: service_error_to_model!(match e {
+                            EvelynCoreError::TodoListVersionConflict(_) => EvelynServiceError::TodoListVersionConflict(e),
+                            EvelynCoreError::TodoListNotFound(_) => EvelynServiceError::TodoListNotFound(e),
+                            EvelynCoreError::InvalidTodoListShare(_) => EvelynServiceError::InvalidTodoListShare(e),
+                            _ => EvelynServiceError::UpdateTodoListShares(e),
+                        }),
+                    })
+                },
+            }
+        },
+        Err(e) => {
+            model_to_router_output!(model::todo_list::UnshareTodoListResponseModel {
+                error: service_error_to_model!(EvelynServiceError::CouldNotDecodeTheRequestPayload(e)),
+            })
+        },
+    }
+}
+
+pub fn create_template_processor(
+    router_input: RouterInput,
+    processor_data: Arc<processing::ProcessorData>,
+) -> RouterOutput {
+    match decode_router_input_to_model!(model::todo_list::template::CreateTodoListTemplateRequestModel, router_input) {
+        Ok(request_model) => {
+            let session_token_model = validate_session!(processor_data, request_model);
+
+            match todo_list::create_template(request_model, session_token_model, processor_data) {
+                Ok(response) => {
+                    model_to_router_output!(response)
+                },
+                Err(e) => {
+                    model_to_router_output!(model::todo_list::template::CreateTodoListTemplateResponseModel {
+                        template_id: None,
+                        error: service_error_to_model!(match e {
+                            EvelynCoreError::TodoListNotFound(_) => EvelynServiceError::TodoListNotFound(e),
+                            EvelynCoreError::InvalidTodoListTemplateUserGroup(_) => EvelynServiceError::InvalidTodoListTemplateUserGroup(e),
+                            _ => EvelynServiceError::CreateTodoListTemplate(e),
+                        }),
+                    })
+                },
+            }
+        },
+        Err(e) => {
+            model_to_router_output!(model::todo_list::template::CreateTodoListTemplateResponseModel {
+                template_id: None,
+                error: service_error_to_model!(EvelynServiceError::CouldNotDecodeTheRequestPayload(e)),
+            })
+        },
+    }
+}
+
+pub fn lookup_templates_processor(
+    router_input: RouterInput,
+    processor_data: Arc<processing::ProcessorData>,
+) -> RouterOutput {
+    match decode_router_input_to_model!(model::todo_list::template::LookupTodoListTemplatesRequestModel, router_input) {
+        Ok(request_model) => {
+            let session_token_model = validate_session!(processor_data, request_model);
+
+            match todo_list::lookup_templates(session_token_model, processor_data) {
+                Ok(response) => {
+                    model_to_router_output!(response)
+                },
+                Err(e) => {
+                    model_to_router_output!(model::todo_list::template::LookupTodoListTemplatesResponseModel {
+                        templates: None,
+                        error: service_error_to_model!(EvelynServiceError::LookupTodoListTemplates(e)),
+                    })
+                },
+            }
+        },
+        Err(e) => {
+            model_to_router_output!(model::todo_list::template::LookupTodoListTemplatesResponseModel {
+                templates: None,
+                error: service_error_to_model!(EvelynServiceError::CouldNotDecodeTheRequestPayload(e)),
+            })
+        },
+    }
+}
+
+pub fn instantiate_template_processor(
+    router_input: RouterInput,
+    processor_data: Arc<processing::ProcessorData>,
+) -> RouterOutput {
+    match decode_router_input_to_model!(model::todo_list::template::InstantiateTodoListTemplateRequestModel, router_input) {
+        Ok(request_model) => {
+            let session_token_model = validate_session!(processor_data, request_model);
+
+            match todo_list::instantiate_template(request_model, session_token_model, processor_data) {
+                Ok(response) => {
+                    model_to_router_output!(response)
+                },
+                Err(e) => {
+                    model_to_router_output!(model::todo_list::template::InstantiateTodoListTemplateResponseModel {
+                        todo_list_id: None,
+                        error: service_error_to_model!(match e {
+                            EvelynCoreError::TodoListTemplateNotFound(_) => EvelynServiceError::TodoListTemplateNotFound(e),
+                            _ => EvelynServiceError::InstantiateTodoListTemplate(e),
+                        }),
+                    })
+                },
+            }
+        },
+        Err(e) => {
+            model_to_router_output!(model::todo_list::template::InstantiateTodoListTemplateResponseModel {
+                todo_list_id: None,
+                error: service_error_to_model!(EvelynServiceError::CouldNotDecodeTheRequestPayload(e)),
+            })
+        },
+    }
+}
+
+pub fn remove_template_processor(
+    router_input: RouterInput,
+    processor_data: Arc<processing::ProcessorData>,
+) -> RouterOutput {
+    match decode_router_input_to_model!(model::todo_list::template::RemoveTodoListTemplateRequestModel, router_input) {
+        Ok(request_model) => {
+            let session_token_model = validate_session!(processor_data, request_model);
+
+            match todo_list::remove_template(request_model, session_token_model, processor_data) {
+                None => {
+                    model_to_router_output!(model::todo_list::template::RemoveTodoListTemplateResponseModel {
+                        error: None,
+                    })
+                },
+                Some(e) => {
+                    model_to_router_output!(model::todo_list::template::RemoveTodoListTemplateResponseModel {
+                        error: service_error_to_model!(match e {
+                            EvelynCoreError::TodoListTemplateNotFound(_) => EvelynServiceError::TodoListTemplateNotFound(e),
+                            _ => EvelynServiceError::RemoveTodoListTemplate(e),
+                        }),
+                    })
+                },
+            }
+        },
+        Err(e) => {
+            model_to_router_output!(model::todo_list::template::RemoveTodoListTemplateResponseModel {
+                error: service_error_to_model!(EvelynServiceError::CouldNotDecodeTheRequestPayload(e)),
+            })
+        },
+    }
+}
@@ -49,6 +49,22 @@ pub fn create_user_processor(
                                         .unwrap(),
                             }
                         },
+                        EvelynCoreError::InvalidTimeZone(_) => {
+                            RouterOutput {
+                                response_body: serde_json::to_string(&model::user::CreateUserResponseModel {
+                                                                         error: Some(From::from(EvelynServiceError::InvalidTimeZone(e))),
+                                                                     })
+                                        .unwrap(),
+                            }
+                        },
+                        EvelynCoreError::InvalidEmailAddress(_) => {
+                            RouterOutput {
+                                response_body: serde_json::to_string(&model::user::CreateUserResponseModel {
+                                                                         error: Some(From::from(EvelynServiceError::InvalidEmailAddress(e))),
+                                                                     })
+                                        .unwrap(),
+                            }
+                        },
                         _ => {
                             RouterOutput {
                                 response_body: serde_json::to_string(&model::user::CreateUserResponseModel {
@@ -151,3 +167,70 @@ pub fn search_processor(
         },
     }
 }
+
+pub fn lookup_profile_processor(
+    router_input: RouterInput,
+    processor_data: Arc<processing::ProcessorData>,
+) -> RouterOutput {
+    match decode_router_input_to_model!(model::user::LookupProfileRequestModel, router_input) {
+        Ok(request_model) => {
+            let session_token_model = validate_session!(processor_data, request_model);
+
+            match user::lookup_profile(&session_token_model.user_id, processor_data) {
+                Ok(response) => {
+                    model_to_router_output!(response)
+                },
+                Err(e) => {
+                    model_to_router_output!(model::user::LookupProfileResponseModel {
+                        user_name: String::new(),
+                        email_address: String::new(),
+                        time_zone: None,
+                        error: service_error_to_model!(EvelynServiceError::LookupUserProfile(e)),
+                    })
+                },
+            }
+        },
+        Err(e) => {
+            model_to_router_output!(model::user::LookupProfileResponseModel {
+                user_name: String::new(),
+                email_address: String::new(),
+                time_zone: None,
+                error: service_error_to_model!(EvelynServiceError::CouldNotDecodeTheRequestPayload(e)),
+            })
+        },
+    }
+}
+
+pub fn update_profile_processor(
+    router_input: RouterInput,
+    processor_data: Arc<processing::ProcessorData>,
+) -> RouterOutput {
+    match decode_router_input_to_model!(model::user::UpdateProfileRequestModel, router_input) {
+        Ok(request_model) => {
+            let session_token_model = validate_session!(processor_data, request_model);
+
+            match user::update_profile(request_model, &session_token_model.user_id, processor_data) {
+                None => {
+                    model_to_router_output!(model::user::UpdateProfileResponseModel {
+                        error: None,
+                    })
+                },
+                Some(e) => {
+                    let service_error = match e {
+                        EvelynCoreError::InvalidTimeZone(_) => EvelynServiceError::InvalidTimeZone(e),
+                        _ => EvelynServiceError::UpdateUserProfile(e),
+                    };
+
+                    model_to_router_output!(model::user::UpdateProfileResponseModel {
+                        error: service_error_to_model!(service_error),
+                    })
+                },
+            }
+        },
+        Err(e) => {
+            model_to_router_output!(model::user::UpdateProfileResponseModel {
+                error: service_error_to_model!(EvelynServiceError::CouldNotDecodeTheRequestPayload(e)),
+            })
+        },
+    }
+}
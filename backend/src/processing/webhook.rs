@@ -0,0 +1,149 @@
+// Evelyn: Your personal assistant, project manager and calendar
+// Copyright (C) 2017 Gregory Jensen
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use core::error_messages::{EvelynBaseError, EvelynCoreError, EvelynServiceError};
+use core::webhook;
+use model;
+use model::webhook as webhook_model;
+use processing;
+use serde_json;
+use server::routing::{RouterInput, RouterOutput};
+use std::sync::Arc;
+
+pub fn add_processor(
+    router_input: RouterInput,
+    processor_data: Arc<processing::ProcessorData>,
+) -> RouterOutput {
+    match decode_router_input_to_model!(webhook_model::AddWebhookRequestModel, router_input) {
+        Ok(request_model) => {
+            let session_token_model = validate_session!(processor_data, request_model);
+
+            match webhook::add_webhook(request_model, session_token_model, processor_data) {
+                Ok(response) => {
+                    model_to_router_output!(response)
+                },
+                Err(e) => {
+                    model_to_router_output!(webhook_model::AddWebhookResponseModel {
+                        subscription_id: None,
+                        error: service_error_to_model!(match e {
+                            EvelynCoreError::InvalidWebhook(_) => EvelynServiceError::InvalidWebhook(e),
+                            _ => EvelynServiceError::AddWebhook(e),
+                        }),
+                    })
+                },
+            }
+        },
+        Err(e) => {
+            model_to_router_output!(webhook_model::AddWebhookResponseModel {
+                subscription_id: None,
+                error: service_error_to_model!(EvelynServiceError::CouldNotDecodeTheRequestPayload(e)),
+            })
+        },
+    }
+}
+
+pub fn lookup_processor(
+    router_input: RouterInput,
+    processor_data: Arc<processing::ProcessorData>,
+) -> RouterOutput {
+    match decode_router_input_to_model!(webhook_model::LookupWebhooksRequestModel, router_input) {
+        Ok(request_model) => {
+            let session_token_model = validate_session!(processor_data, request_model);
+
+            match webhook::lookup_webhooks(session_token_model, processor_data) {
+                Ok(response) => {
+                    model_to_router_output!(response)
+                },
+                Err(e) => {
+                    model_to_router_output!(webhook_model::LookupWebhooksResponseModel {
+                        subscriptions: Vec::new(),
+                        error: service_error_to_model!(EvelynServiceError::LookupWebhooks(e)),
+                    })
+                },
+            }
+        },
+        Err(e) => {
+            model_to_router_output!(webhook_model::LookupWebhooksResponseModel {
+                subscriptions: Vec::new(),
+                error: service_error_to_model!(EvelynServiceError::CouldNotDecodeTheRequestPayload(e)),
+            })
+        },
+    }
+}
+
+pub fn remove_processor(
+    router_input: RouterInput,
+    processor_data: Arc<processing::ProcessorData>,
+) -> RouterOutput {
+    match decode_router_input_to_model!(webhook_model::RemoveWebhookRequestModel, router_input) {
+        Ok(request_model) => {
+            let session_token_model = validate_session!(processor_data, request_model);
+
+            match webhook::remove_webhook(request_model, session_token_model, processor_data) {
+                None => {
+                    model_to_router_output!(webhook_model::RemoveWebhookResponseModel {
+                        error: None,
+                    })
+                },
+                Some(e) => {
+                    model_to_router_output!(webhook_model::RemoveWebhookResponseModel {
+                        error: service_error_to_model!(match e {
+                            EvelynCoreError::WebhookNotFound(_) => EvelynServiceError::WebhookNotFound(e),
+                            _ => EvelynServiceError::RemoveWebhook(e),
+                        }),
+                    })
+                },
+            }
+        },
+        Err(e) => {
+            model_to_router_output!(webhook_model::RemoveWebhookResponseModel {
+                error: service_error_to_model!(EvelynServiceError::CouldNotDecodeTheRequestPayload(e)),
+            })
+        },
+    }
+}
+
+pub fn lookup_deliveries_processor(
+    router_input: RouterInput,
+    processor_data: Arc<processing::ProcessorData>,
+) -> RouterOutput {
+    match decode_router_input_to_model!(webhook_model::LookupWebhookDeliveriesRequestModel, router_input) {
+        Ok(request_model) => {
+            let session_token_model = validate_session!(processor_data, request_model);
+
+            match webhook::lookup_deliveries(request_model, session_token_model, processor_data) {
+                Ok(response) => {
+                    model_to_router_output!(response)
+                },
+                Err(e) => {
+                    model_to_router_output!(webhook_model::LookupWebhookDeliveriesResponseModel {
+                        deliveries: Vec::new(),
+                        error: service_error_to_model!(match e {
+                            EvelynCoreError::WebhookNotFound(_) => EvelynServiceError::WebhookNotFound(e),
+                            _ => EvelynServiceError::LookupWebhookDeliveries(e),
+                        }),
+                    })
+                },
+            }
+        },
+        Err(e) => {
+            model_to_router_output!(webhook_model::LookupWebhookDeliveriesResponseModel {
+                deliveries: Vec::new(),
+                error: service_error_to_model!(EvelynServiceError::CouldNotDecodeTheRequestPayload(e)),
+            })
+        },
+    }
+}
@@ -0,0 +1,570 @@
+// Evelyn: Your personal assistant, project manager and calendar
+// Copyright (C) 2017 Gregory Jensen
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+
+// A CalDAV (RFC 4791) view of each user's calendar, so that desktop and phone
+// clients can subscribe to it. Users are authenticated with HTTP basic auth
+// using their Evelyn email address and password.
+//
+// /caldav/                              Service root
+// /caldav/{user id}/                    Principal and calendar home
+// /caldav/{user id}/calendar/           The user's calendar
+// /caldav/{user id}/calendar/{id}.ics   An event
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::io::Read;
+use std::sync::Arc;
+
+use hyper::header::{Authorization, Basic};
+use hyper::server::{Request, Response};
+use hyper::status::StatusCode;
+
+use core::calendar;
+use core::calendar::StoredVersionCondition;
+use core::date_time_service;
+use core::error_messages::{EvelynCoreError, EvelynDatabaseError};
+use core::icalendar;
+use core::user;
+use data::calendar as calendar_data;
+use model;
+use model::calendar::CalendarEventModel;
+use processing::ProcessorData;
+
+pub const CALDAV_PATH: &'static str = "/caldav";
+
+const CALENDAR_NAME: &'static str = "calendar";
+const EVENT_EXTENSION: &'static str = ".ics";
+
+const XML_CONTENT_TYPE: &'static str = "application/xml; charset=utf-8";
+const CALENDAR_CONTENT_TYPE: &'static str = "text/calendar; charset=utf-8";
+
+enum Resource {
+    Root,
+    Principal(String),
+    Calendar(String),
+    Event(String, String),
+}
+
+impl Resource {
+    fn user_id(&self) -> Option<&String> {
+        match *self {
+            Resource::Root => None,
+            Resource::Principal(ref user_id) |
+            Resource::Calendar(ref user_id) |
+            Resource::Event(ref user_id, _) => Some(user_id),
+        }
+    }
+}
+
+struct CalDavResponse {
+    status: StatusCode,
+    body: String,
+    content_type: Option<&'static str>,
+    etag: Option<String>,
+}
+
+impl CalDavResponse {
+    fn status(status: StatusCode) -> Self {
+        CalDavResponse {
+            status: status,
+            body: String::new(),
+            content_type: None,
+            etag: None,
+        }
+    }
+
+    fn multistatus(responses: Vec<String>) -> Self {
+        CalDavResponse {
+            status: StatusCode::MultiStatus,
+            body: format!(
+                "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+                 <d:multistatus xmlns:d=\"DAV:\" xmlns:c=\"urn:ietf:params:xml:ns:caldav\" xmlns:cs=\"http://calendarserver.org/ns/\">{}</d:multistatus>",
+                responses.join("")
+            ),
+            content_type: Some(XML_CONTENT_TYPE),
+            etag: None,
+        }
+    }
+}
+
+pub fn is_caldav_path(path: &str) -> bool {
+    path == CALDAV_PATH || path.starts_with(&format!("{}/", CALDAV_PATH))
+}
+
+fn parse_path(path: &str) -> Option<Resource> {
+    let path = path.split('?').next().unwrap_or("");
+    let segments: Vec<&str> = path[CALDAV_PATH.len()..].split('/').filter(|x| !x.is_empty()).collect();
+
+    match segments.len() {
+        0 => Some(Resource::Root),
+        1 => Some(Resource::Principal(String::from(segments[0]))),
+        2 if segments[1] == CALENDAR_NAME => Some(Resource::Calendar(String::from(segments[0]))),
+        3 if segments[1] == CALENDAR_NAME && segments[2].ends_with(EVENT_EXTENSION) => {
+            let event_id = segments[2].trim_right_matches(EVENT_EXTENSION);
+            Some(Resource::Event(String::from(segments[0]), String::from(event_id)))
+        },
+        _ => None,
+    }
+}
+
+fn principal_href(user_id: &String) -> String {
+    format!("{}/{}/", CALDAV_PATH, user_id)
+}
+
+fn calendar_href(user_id: &String) -> String {
+    format!("{}/{}/{}/", CALDAV_PATH, user_id, CALENDAR_NAME)
+}
+
+fn event_href(event: &CalendarEventModel) -> String {
+    format!("{}{}{}", calendar_href(&event.user_id), event.event_id, EVENT_EXTENSION)
+}
+
+fn event_etag(event: &CalendarEventModel) -> String {
+    format!("\"{}\"", event.version)
+}
+
+// Changes whenever an event is added, changed or removed so that clients
+// know when to sync.
+fn calendar_ctag(events: &Vec<CalendarEventModel>) -> String {
+    let mut versions: Vec<(&String, i64)> = events.iter().map(|x| (&x.event_id, x.version)).collect();
+    versions.sort();
+
+    let mut hasher = DefaultHasher::new();
+    versions.hash(&mut hasher);
+    format!("{:x}", hasher.finish())
+}
+
+fn escape_xml(value: &str) -> String {
+    value.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn prop_response(
+    href: &str,
+    props: &str,
+) -> String {
+    format!(
+        "<d:response><d:href>{}</d:href><d:propstat><d:prop>{}</d:prop><d:status>HTTP/1.1 200 OK</d:status></d:propstat></d:response>",
+        escape_xml(href),
+        props
+    )
+}
+
+fn not_found_response(href: &str) -> String {
+    format!("<d:response><d:href>{}</d:href><d:status>HTTP/1.1 404 Not Found</d:status></d:response>", escape_xml(href))
+}
+
+fn event_props(
+    event: &CalendarEventModel,
+    include_calendar_data: bool,
+) -> String {
+    let mut props = format!(
+        "<d:resourcetype/><d:getetag>{}</d:getetag><d:getcontenttype>{}</d:getcontenttype>",
+        escape_xml(&event_etag(event)),
+        CALENDAR_CONTENT_TYPE
+    );
+
+    if include_calendar_data {
+        props.push_str(&format!("<c:calendar-data>{}</c:calendar-data>", escape_xml(&calendar::event_to_icalendar(event))));
+    }
+
+    props
+}
+
+// The text of each element with the given name, whatever namespace prefix
+// the client used for it.
+fn element_texts(
+    body: &str,
+    name: &str,
+) -> Vec<String> {
+    let mut texts = Vec::new();
+    let mut rest = body;
+
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+
+        let tag_end = match rest.find('>') {
+            Some(tag_end) => tag_end,
+            None => break,
+        };
+
+        let tag = &rest[..tag_end];
+        let local_name = tag.rsplit(':').next().unwrap_or("");
+        rest = &rest[tag_end + 1..];
+
+        if local_name == name {
+            let text_end = rest.find('<').unwrap_or(rest.len());
+            texts.push(rest[..text_end].trim().to_owned());
+        }
+    }
+
+    texts
+}
+
+// The value of an attribute of the first element with the given name.
+fn element_attribute(
+    body: &str,
+    name: &str,
+    attribute: &str,
+) -> Option<String> {
+    let mut rest = body;
+
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+
+        let tag_end = match rest.find('>') {
+            Some(tag_end) => tag_end,
+            None => return None,
+        };
+
+        let tag = &rest[..tag_end];
+        let tag_name = tag.split_whitespace().next().unwrap_or("");
+
+        if tag_name.rsplit(':').next() == Some(name) {
+            let pattern = format!("{}=\"", attribute);
+            return tag.find(&pattern).and_then(|position| {
+                let value = &tag[position + pattern.len()..];
+                value.find('"').map(|value_end| value[..value_end].to_owned())
+            });
+        }
+
+        rest = &rest[tag_end + 1..];
+    }
+
+    None
+}
+
+fn authenticate(
+    req: &Request,
+    processor_data: Arc<ProcessorData>,
+) -> Option<model::user::UserModel> {
+    let credentials = match req.headers.get::<Authorization<Basic>>() {
+        Some(&Authorization(ref credentials)) => credentials,
+        None => return None,
+    };
+
+    let password = credentials.password.clone().unwrap_or(String::new());
+
+    match user::authenticate(&credentials.username, &password, processor_data) {
+        Ok(user) => Some(user),
+        Err(EvelynCoreError::InvalidLogon(_)) => None,
+        Err(e) => {
+            error!("CalDAV authentication failed: {}", e);
+            None
+        },
+    }
+}
+
+fn lookup_events(
+    processor_data: &Arc<ProcessorData>,
+    user_id: &String,
+) -> Result<Vec<CalendarEventModel>, CalDavResponse> {
+    calendar_data::lookup_all_events(&processor_data.data_store, user_id).map_err(|e| {
+        error!("CalDAV failed to lookup events: {}", e);
+        CalDavResponse::status(StatusCode::InternalServerError)
+    })
+}
+
+fn lookup_event(
+    processor_data: &Arc<ProcessorData>,
+    user_id: &String,
+    event_id: &String,
+) -> Result<Option<CalendarEventModel>, CalDavResponse> {
+    match calendar_data::lookup_event(&processor_data.data_store, user_id, event_id) {
+        Ok(event) => Ok(Some(event)),
+        Err(EvelynDatabaseError::CalendarEventNotFound(_)) => Ok(None),
+        Err(e) => {
+            error!("CalDAV failed to lookup event: {}", e);
+            Err(CalDavResponse::status(StatusCode::InternalServerError))
+        },
+    }
+}
+
+fn propfind(
+    resource: &Resource,
+    user: &model::user::UserModel,
+    depth: &str,
+    processor_data: &Arc<ProcessorData>,
+) -> CalDavResponse {
+    let principal = format!("<d:current-user-principal><d:href>{}</d:href></d:current-user-principal>", principal_href(&user.user_id));
+
+    match *resource {
+        Resource::Root => {
+            CalDavResponse::multistatus(vec![prop_response(&format!("{}/", CALDAV_PATH), &format!("<d:resourcetype><d:collection/></d:resourcetype>{}", principal))])
+        },
+        Resource::Principal(ref user_id) => {
+            let mut responses = vec![prop_response(&principal_href(user_id), &format!(
+                "<d:resourcetype><d:collection/><d:principal/></d:resourcetype><d:displayname>{}</d:displayname>{}\
+                 <c:calendar-home-set><d:href>{}</d:href></c:calendar-home-set>",
+                escape_xml(&user.user_name),
+                principal,
+                principal_href(user_id)
+            ))];
+
+            if depth != "0" {
+                responses.push(prop_response(&calendar_href(user_id), "<d:resourcetype><d:collection/><c:calendar/></d:resourcetype>"));
+            }
+
+            CalDavResponse::multistatus(responses)
+        },
+        Resource::Calendar(ref user_id) => {
+            let events = match lookup_events(processor_data, user_id) {
+                Ok(events) => events,
+                Err(response) => return response,
+            };
+
+            let mut responses = vec![prop_response(&calendar_href(user_id), &format!(
+                "<d:resourcetype><d:collection/><c:calendar/></d:resourcetype><d:displayname>Evelyn</d:displayname>{}\
+                 <c:supported-calendar-component-set><c:comp name=\"VEVENT\"/></c:supported-calendar-component-set>\
+                 <cs:getctag>{}</cs:getctag>",
+                principal,
+                calendar_ctag(&events)
+            ))];
+
+            if depth != "0" {
+                for event in &events {
+                    responses.push(prop_response(&event_href(event), &event_props(event, false)));
+                }
+            }
+
+            CalDavResponse::multistatus(responses)
+        },
+        Resource::Event(ref user_id, ref event_id) => {
+            match lookup_event(processor_data, user_id, event_id) {
+                Ok(Some(event)) => CalDavResponse::multistatus(vec![prop_response(&event_href(&event), &event_props(&event, false))]),
+                Ok(None) => CalDavResponse::status(StatusCode::NotFound),
+                Err(response) => response,
+            }
+        },
+    }
+}
+
+fn report(
+    user_id: &String,
+    body: &str,
+    processor_data: &Arc<ProcessorData>,
+) -> CalDavResponse {
+    let mut responses = Vec::new();
+
+    if body.contains("calendar-multiget") {
+        for href in element_texts(body, "href") {
+            let event_id = href.trim_right_matches('/')
+                .rsplit('/')
+                .next()
+                .unwrap_or("")
+                .trim_right_matches(EVENT_EXTENSION)
+                .to_owned();
+
+            match lookup_event(processor_data, user_id, &event_id) {
+                Ok(Some(event)) => responses.push(prop_response(&href, &event_props(&event, true))),
+                Ok(None) => responses.push(not_found_response(&href)),
+                Err(response) => return response,
+            }
+        }
+    } else {
+        // A calendar query, only filtering by time range is supported.
+        let range_begin = element_attribute(body, "time-range", "start").and_then(|x| icalendar::parse_date_time(&x));
+        let range_end = element_attribute(body, "time-range", "end").and_then(|x| icalendar::parse_date_time(&x));
+
+        let events = match (range_begin, range_end) {
+            (None, None) => lookup_events(processor_data, user_id),
+            (range_begin, range_end) => {
+                calendar_data::lookup_events(&processor_data.data_store, user_id, range_begin.unwrap_or(0), range_end.unwrap_or(i64::max_value()))
+                    .map_err(|e| {
+                        error!("CalDAV failed to lookup events: {}", e);
+                        CalDavResponse::status(StatusCode::InternalServerError)
+                    })
+            },
+        };
+
+        match events {
+            Ok(events) => {
+                for event in &events {
+                    responses.push(prop_response(&event_href(event), &event_props(event, true)));
+                }
+            },
+            Err(response) => return response,
+        }
+    }
+
+    CalDavResponse::multistatus(responses)
+}
+
+fn get(
+    user_id: &String,
+    event_id: &String,
+    processor_data: &Arc<ProcessorData>,
+) -> CalDavResponse {
+    match lookup_event(processor_data, user_id, event_id) {
+        Ok(Some(event)) => CalDavResponse {
+            status: StatusCode::Ok,
+            body: calendar::event_to_icalendar(&event),
+            content_type: Some(CALENDAR_CONTENT_TYPE),
+            etag: Some(event_etag(&event)),
+        },
+        Ok(None) => CalDavResponse::status(StatusCode::NotFound),
+        Err(response) => response,
+    }
+}
+
+// Clients use If-Match to only overwrite the version they have and
+// If-None-Match: * to only create new events.
+fn stored_version_condition(req: &Request) -> StoredVersionCondition {
+    let raw_header = |name: &str| {
+        req.headers.get_raw(name)
+            .and_then(|x| x.first())
+            .and_then(|x| String::from_utf8(x.clone()).ok())
+    };
+
+    if let Some(if_match) = raw_header("If-Match") {
+        match if_match.trim().trim_matches('"').parse::<i64>() {
+            Ok(version) => return StoredVersionCondition::Exists(version),
+            Err(_) if if_match.trim() == "*" => return StoredVersionCondition::Any,
+            // No version can match an etag Evelyn didn't hand out.
+            Err(_) => return StoredVersionCondition::Exists(-1),
+        }
+    }
+
+    match raw_header("If-None-Match") {
+        Some(ref if_none_match) if if_none_match.trim() == "*" => StoredVersionCondition::DoesNotExist,
+        _ => StoredVersionCondition::Any,
+    }
+}
+
+fn put(
+    user_id: &String,
+    event_id: &String,
+    body: &str,
+    condition: StoredVersionCondition,
+    processor_data: &Arc<ProcessorData>,
+) -> CalDavResponse {
+    match calendar::store_icalendar_event(&processor_data.data_store, user_id, event_id, body, condition) {
+        Ok((created, version)) => CalDavResponse {
+            status: if created { StatusCode::Created } else { StatusCode::NoContent },
+            body: String::new(),
+            content_type: None,
+            etag: Some(format!("\"{}\"", version)),
+        },
+        Err(EvelynCoreError::CalendarEventVersionConflict(_)) => CalDavResponse::status(StatusCode::PreconditionFailed),
+        Err(EvelynCoreError::CalendarEventIdTaken(_)) => CalDavResponse::status(StatusCode::Conflict),
+        Err(EvelynCoreError::InvalidICalendar(_)) |
+        Err(EvelynCoreError::InvalidRecurrenceRule(_)) => CalDavResponse::status(StatusCode::UnsupportedMediaType),
+        Err(e) => {
+            error!("CalDAV failed to store event: {}", e);
+            CalDavResponse::status(StatusCode::InternalServerError)
+        },
+    }
+}
+
+fn delete(
+    user_id: &String,
+    event_id: &String,
+    processor_data: &Arc<ProcessorData>,
+) -> CalDavResponse {
+    let deletion = model::trash::DeletionModel {
+        deleted_by_user_id: user_id.clone(),
+        date_deleted: date_time_service::get_timestamp(),
+    };
+
+    match calendar_data::remove_event(&processor_data.data_store, user_id, event_id, &deletion) {
+        None => CalDavResponse::status(StatusCode::NoContent),
+        Some(EvelynDatabaseError::CalendarEventNotFound(_)) => CalDavResponse::status(StatusCode::NotFound),
+        Some(e) => {
+            error!("CalDAV failed to remove event: {}", e);
+            CalDavResponse::status(StatusCode::InternalServerError)
+        },
+    }
+}
+
+fn send(
+    mut res: Response,
+    response: CalDavResponse,
+) {
+    *res.status_mut() = response.status;
+    res.headers_mut().set_raw("DAV", vec![b"1, 3, calendar-access".to_vec()]);
+
+    match response.content_type {
+        Some(content_type) => res.headers_mut().set_raw("Content-Type", vec![content_type.as_bytes().to_vec()]),
+        None => {
+            res.headers_mut().remove_raw("Content-Type");
+        },
+    }
+
+    if let Some(etag) = response.etag {
+        res.headers_mut().set_raw("ETag", vec![etag.into_bytes()]);
+    }
+
+    if let Err(e) = res.send(response.body.as_bytes()) {
+        error!("Failed to send CalDAV response: {}", e);
+    }
+}
+
+pub fn handle(
+    mut req: Request,
+    mut res: Response,
+    processor_data: Arc<ProcessorData>,
+) {
+    let method = req.method.to_string();
+
+    if method == "OPTIONS" {
+        res.headers_mut().set_raw("Allow", vec![b"OPTIONS, PROPFIND, REPORT, GET, PUT, DELETE".to_vec()]);
+        return send(res, CalDavResponse::status(StatusCode::Ok));
+    }
+
+    let resource = match parse_path(&format!("{}", req.uri)) {
+        Some(resource) => resource,
+        None => return send(res, CalDavResponse::status(StatusCode::NotFound)),
+    };
+
+    let user = match authenticate(&req, processor_data.clone()) {
+        Some(user) => user,
+        None => {
+            res.headers_mut().set_raw("WWW-Authenticate", vec![b"Basic realm=\"Evelyn\"".to_vec()]);
+            return send(res, CalDavResponse::status(StatusCode::Unauthorized));
+        },
+    };
+
+    // Users can only see their own calendar.
+    if resource.user_id().map_or(false, |x| *x != user.user_id) {
+        return send(res, CalDavResponse::status(StatusCode::Forbidden));
+    }
+
+    let depth = req.headers.get_raw("Depth")
+        .and_then(|x| x.first())
+        .and_then(|x| String::from_utf8(x.clone()).ok())
+        .unwrap_or(String::from("0"));
+    let condition = stored_version_condition(&req);
+
+    let mut body = String::new();
+    if let Err(e) = req.read_to_string(&mut body) {
+        debug!("Failed to read CalDAV request body: {}", e);
+        return send(res, CalDavResponse::status(StatusCode::BadRequest));
+    }
+
+    let response = match (method.as_str(), &resource) {
+        ("PROPFIND", resource) => propfind(resource, &user, depth.trim(), &processor_data),
+        ("REPORT", &Resource::Calendar(ref user_id)) => report(user_id, &body, &processor_data),
+        ("GET", &Resource::Event(ref user_id, ref event_id)) => get(user_id, event_id, &processor_data),
+        ("PUT", &Resource::Event(ref user_id, ref event_id)) => put(user_id, event_id, &body, condition, &processor_data),
+        ("DELETE", &Resource::Event(ref user_id, ref event_id)) => delete(user_id, event_id, &processor_data),
+        _ => CalDavResponse::status(StatusCode::MethodNotAllowed),
+    };
+
+    send(res, response)
+}
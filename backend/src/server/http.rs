@@ -29,6 +29,7 @@ use hyper_openssl::OpensslServer;
 use serde_json;
 
 use processing::ProcessorData;
+use server::caldav;
 use server::routing::{Router, RouterInput};
 use model;
 use core::error_messages;
@@ -68,6 +69,10 @@ impl Handler for HttpHandler {
 
         debug!("Process request {}", req.method);
 
+        if caldav::is_caldav_path(&format!("{}", req.uri)) {
+            return caldav::handle(req, res, self.processor_data.clone());
+        }
+
         match req.method {
             Method::Options => {
                 res.headers_mut().set(AccessControlAllowOrigin::Any);
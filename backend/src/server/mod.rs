@@ -14,5 +14,6 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+pub mod caldav;
 pub mod http;
 pub mod routing;
@@ -14,9 +14,20 @@
 // You should have received a copy of the GNU General Public License
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
+#[macro_use(bson, doc)]
+extern crate bson;
 extern crate evelyn;
 
+use evelyn::core::date_time_service as dts;
+use evelyn::core::date_time_service::UTC;
+use evelyn::core::error_messages::EvelynDatabaseError;
+use evelyn::core::icalendar;
+use evelyn::core::recurrence;
+use evelyn::core::smtp;
+use evelyn::core::todo_list;
 use evelyn::core::token_service::TokenService;
+use evelyn::core::webhook;
+use std::collections::BTreeMap;
 
 #[test]
 pub fn create_and_decode_session_token_using_the_token_service() {
@@ -27,6 +38,8 @@ pub fn create_and_decode_session_token_using_the_token_service() {
         user_name: String::from("the username"),
         email_address: String::from("the email address"),
         password: String::from("the password"),
+        time_zone: None,
+        email_notifications: None,
     };
 
     let server_session_token = token_service.create_server_session_token();
@@ -38,3 +51,376 @@ pub fn create_and_decode_session_token_using_the_token_service() {
     assert_eq!(decoded.user_id, "the user id");
     assert_eq!(decoded.server_session_token, server_session_token);
 }
+
+#[test]
+pub fn decode_failure_reports_the_offending_document_id() {
+    let document = doc!{"userId" => "the user id", "userName" => 42};
+
+    match evelyn::data::cursor::decode_document::<evelyn::model::user::UserModel>(document) {
+        Err(EvelynDatabaseError::BSONDecodeFailed(e)) => assert_eq!(e.document_id, "userId=the user id"),
+        _ => panic!("Expected the document to fail to decode"),
+    }
+}
+
+fn timestamp(date_time: &str) -> i64 {
+    dts::parse_timestamp(date_time, &UTC).unwrap()
+}
+
+#[test]
+pub fn dates_without_an_offset_are_read_in_the_given_zone() {
+    let new_york = dts::parse_time_zone("America/New_York").unwrap();
+
+    assert_eq!(dts::parse_timestamp("2017-06-05T09:00:00+02:00", &new_york).unwrap(), timestamp("2017-06-05T07:00:00Z"));
+    assert_eq!(dts::parse_timestamp("2017-06-05T09:00:00", &new_york).unwrap(), timestamp("2017-06-05T13:00:00Z"));
+    assert_eq!(dts::parse_timestamp("2017-12-05 09:00", &new_york).unwrap(), timestamp("2017-12-05T14:00:00Z"));
+    assert_eq!(dts::parse_timestamp("2017-06-05", &new_york).unwrap(), timestamp("2017-06-05T04:00:00Z"));
+    assert_eq!(dts::timestamp_to_string(timestamp("2017-06-05T13:00:00Z"), &new_york), "2017-06-05T09:00:00-04:00");
+}
+
+#[test]
+pub fn invalid_dates_and_zones_are_errors() {
+    let london = dts::parse_time_zone("Europe/London").unwrap();
+
+    assert!(dts::parse_timestamp("next tuesday", &UTC).is_err());
+    assert!(dts::parse_timestamp("2017-02-30T09:00:00Z", &UTC).is_err());
+    // The clocks went forward at 1am so half past one never happened.
+    assert!(dts::parse_timestamp("2017-03-26T01:30:00", &london).is_err());
+    assert!(dts::parse_time_zone("Mars/Olympus_Mons").is_err());
+}
+
+#[test]
+pub fn days_start_after_a_skipped_midnight() {
+    let sao_paulo = dts::parse_time_zone("America/Sao_Paulo").unwrap();
+    let day = dts::parse_date("2017-10-15").unwrap();
+
+    assert_eq!(dts::date_to_string(&day), "2017-10-15");
+    assert!(dts::parse_date("2017-10-15T09:00:00Z").is_none());
+    // The clocks went forward at midnight so the day began at one.
+    assert_eq!(dts::start_of_day(&day, &sao_paulo), timestamp("2017-10-15T03:00:00Z"));
+}
+
+#[test]
+pub fn weekly_recurrence_expands_each_listed_weekday() {
+    let rule = recurrence::parse("FREQ=WEEKLY;BYDAY=MO,WE;COUNT=4").unwrap();
+    let series_begin = timestamp("2017-06-05T09:00:00Z");
+
+    let occurrences = recurrence::occurrences_between(&rule, series_begin, 3600, series_begin, timestamp("2017-12-31T00:00:00Z"), &UTC);
+
+    assert_eq!(occurrences, vec![
+        timestamp("2017-06-05T09:00:00Z"),
+        timestamp("2017-06-07T09:00:00Z"),
+        timestamp("2017-06-12T09:00:00Z"),
+        timestamp("2017-06-14T09:00:00Z"),
+    ]);
+    assert_eq!(recurrence::series_end(&rule, series_begin, 3600, &UTC), Some(timestamp("2017-06-14T10:00:00Z")));
+}
+
+#[test]
+pub fn next_occurrence_is_found_until_the_series_ends() {
+    let rule = recurrence::parse("FREQ=DAILY;INTERVAL=2;COUNT=3").unwrap();
+    let series_begin = timestamp("2017-06-05T23:59:59Z");
+
+    assert_eq!(recurrence::next_occurrence_after(&rule, series_begin, series_begin, &UTC), Some(timestamp("2017-06-07T23:59:59Z")));
+    assert_eq!(recurrence::next_occurrence_after(&rule, series_begin, timestamp("2017-06-08T00:00:00Z"), &UTC), Some(timestamp("2017-06-09T23:59:59Z")));
+    assert_eq!(recurrence::next_occurrence_after(&rule, series_begin, timestamp("2017-06-09T23:59:59Z"), &UTC), None);
+}
+
+#[test]
+pub fn monthly_recurrence_skips_months_without_the_day() {
+    let rule = recurrence::parse("RRULE:FREQ=MONTHLY;UNTIL=20170531").unwrap();
+    let series_begin = timestamp("2017-01-31T12:00:00Z");
+
+    let occurrences = recurrence::occurrences_between(&rule, series_begin, 0, series_begin, timestamp("2018-01-01T00:00:00Z"), &UTC);
+
+    assert_eq!(occurrences, vec![
+        timestamp("2017-01-31T12:00:00Z"),
+        timestamp("2017-03-31T12:00:00Z"),
+        timestamp("2017-05-31T12:00:00Z"),
+    ]);
+}
+
+#[test]
+pub fn monthly_recurrence_finds_the_last_weekday_of_the_month() {
+    let rule = recurrence::parse("FREQ=MONTHLY;INTERVAL=2;BYDAY=-1FR").unwrap();
+    let series_begin = timestamp("2017-06-30T16:00:00Z");
+
+    let occurrences = recurrence::occurrences_between(&rule, series_begin, 0, timestamp("2017-07-01T00:00:00Z"), timestamp("2017-11-01T00:00:00Z"), &UTC);
+
+    assert_eq!(occurrences, vec![
+        timestamp("2017-08-25T16:00:00Z"),
+        timestamp("2017-10-27T16:00:00Z"),
+    ]);
+    assert_eq!(recurrence::series_end(&rule, series_begin, 0, &UTC), None);
+    assert!(recurrence::is_occurrence(&rule, series_begin, timestamp("2017-10-27T16:00:00Z"), &UTC));
+    assert!(!recurrence::is_occurrence(&rule, series_begin, timestamp("2017-09-29T16:00:00Z"), &UTC));
+}
+
+#[test]
+pub fn recurrence_expansion_includes_occurrences_overlapping_the_range() {
+    let rule = recurrence::parse("FREQ=DAILY").unwrap();
+    let series_begin = timestamp("2017-06-01T22:00:00Z");
+
+    let occurrences = recurrence::occurrences_between(&rule, series_begin, 4 * 3600, timestamp("2017-06-03T00:00:00Z"), timestamp("2017-06-03T23:59:59Z"), &UTC);
+
+    assert_eq!(occurrences, vec![
+        timestamp("2017-06-02T22:00:00Z"),
+        timestamp("2017-06-03T22:00:00Z"),
+    ]);
+}
+
+#[test]
+pub fn recurrence_keeps_the_local_time_across_daylight_saving_changes() {
+    let london = dts::parse_time_zone("Europe/London").unwrap();
+    let rule = recurrence::parse("FREQ=WEEKLY;COUNT=3").unwrap();
+    let series_begin = timestamp("2017-03-19T09:00:00Z");
+
+    let occurrences = recurrence::occurrences_between(&rule, series_begin, 3600, series_begin, timestamp("2017-04-30T00:00:00Z"), &london);
+
+    assert_eq!(occurrences, vec![
+        timestamp("2017-03-19T09:00:00Z"),
+        timestamp("2017-03-26T08:00:00Z"),
+        timestamp("2017-04-02T08:00:00Z"),
+    ]);
+}
+
+#[test]
+pub fn unsupported_recurrence_rules_are_rejected() {
+    assert!(recurrence::parse("INTERVAL=2").is_err());
+    assert!(recurrence::parse("FREQ=HOURLY").is_err());
+    assert!(recurrence::parse("FREQ=DAILY;COUNT=2;UNTIL=20170101").is_err());
+    assert!(recurrence::parse("FREQ=DAILY;BYMONTHDAY=3").is_err());
+    assert!(recurrence::parse("FREQ=WEEKLY;BYDAY=2MO").is_err());
+    assert!(recurrence::parse("FREQ=DAILY;INTERVAL=0").is_err());
+}
+
+#[test]
+pub fn icalendar_events_are_read_from_folded_lines() {
+    let document = "BEGIN:VCALENDAR\r\n\
+                    VERSION:2.0\r\n\
+                    BEGIN:VEVENT\r\n\
+                    UID:first@example.com\r\n\
+                    DTSTART;TZID=Europe/London:20170605T090000\r\n\
+                    DTEND:20170605T100000Z\r\n\
+                    SUMMARY:Planning\\, with a very long title which has been folded over\r\n  \
+                    two lines\r\n\
+                    RRULE:FREQ=WEEKLY;COUNT=3\r\n\
+                    EXDATE;TZID=Europe/London:20170612T090000\r\n\
+                    BEGIN:VALARM\r\n\
+                    SUMMARY:Not the event summary\r\n\
+                    END:VALARM\r\n\
+                    END:VEVENT\r\n\
+                    BEGIN:VEVENT\r\n\
+                    UID:second@example.com\r\n\
+                    DTSTART;VALUE=DATE:20170607\r\n\
+                    END:VEVENT\r\n\
+                    BEGIN:VEVENT\r\n\
+                    SUMMARY:No uid\r\n\
+                    END:VEVENT\r\n\
+                    END:VCALENDAR\r\n";
+
+    let (events, skipped) = icalendar::parse_events(document).unwrap();
+
+    assert_eq!(skipped, 1);
+    assert_eq!(events.len(), 2);
+
+    assert_eq!(events[0].uid, "first@example.com");
+    assert_eq!(events[0].summary, "Planning, with a very long title which has been folded over two lines");
+    assert_eq!(events[0].begin, timestamp("2017-06-05T08:00:00Z"));
+    assert_eq!(events[0].end, timestamp("2017-06-05T10:00:00Z"));
+    assert_eq!(events[0].time_zone, Some(String::from("Europe/London")));
+    assert_eq!(events[0].recurrence, Some(String::from("FREQ=WEEKLY;COUNT=3")));
+    assert_eq!(events[0].exception_dates, vec![timestamp("2017-06-12T08:00:00Z")]);
+
+    assert_eq!(events[1].begin, timestamp("2017-06-07T00:00:00Z"));
+    assert_eq!(events[1].end, timestamp("2017-06-08T00:00:00Z"));
+}
+
+#[test]
+pub fn icalendar_durations_give_the_end() {
+    let document = "BEGIN:VCALENDAR\r\n\
+                    BEGIN:VEVENT\r\n\
+                    UID:timed@example.com\r\n\
+                    DTSTART:20170605T090000Z\r\n\
+                    DURATION:PT1H30M\r\n\
+                    END:VEVENT\r\n\
+                    BEGIN:VEVENT\r\n\
+                    UID:zoned@example.com\r\n\
+                    DTSTART;TZID=Europe/London:20170325T120000\r\n\
+                    DURATION:P1D\r\n\
+                    END:VEVENT\r\n\
+                    BEGIN:VEVENT\r\n\
+                    UID:all-day@example.com\r\n\
+                    DTSTART;VALUE=DATE:20170607\r\n\
+                    DURATION:P1W\r\n\
+                    END:VEVENT\r\n\
+                    BEGIN:VEVENT\r\n\
+                    UID:negative@example.com\r\n\
+                    DTSTART:20170605T090000Z\r\n\
+                    DURATION:-PT1H\r\n\
+                    END:VEVENT\r\n\
+                    END:VCALENDAR\r\n";
+
+    let (events, skipped) = icalendar::parse_events(document).unwrap();
+
+    assert_eq!(skipped, 1);
+    assert_eq!(events.len(), 3);
+
+    assert_eq!(events[0].end, timestamp("2017-06-05T10:30:00Z"));
+    // The clocks go forward overnight so the day is only 23 hours long.
+    assert_eq!(events[1].end, timestamp("2017-03-26T11:00:00Z"));
+    assert_eq!(events[2].end, timestamp("2017-06-14T00:00:00Z"));
+
+    assert_eq!(icalendar::parse_duration("P1DT2H3M4S"), Some((1, 2 * 60 * 60 + 3 * 60 + 4)));
+    assert_eq!(icalendar::parse_duration("PT"), None);
+    assert_eq!(icalendar::parse_duration("P1H"), None);
+}
+
+#[test]
+pub fn icalendar_documents_are_rejected_without_a_calendar() {
+    assert!(icalendar::parse_events("BEGIN:VEVENT\r\nEND:VEVENT\r\n").is_err());
+}
+
+#[test]
+pub fn icalendar_writer_escapes_and_folds_text() {
+    let mut writer = icalendar::ICalendarWriter::new();
+    writer.begin("VEVENT");
+    writer.text_property("SUMMARY", &format!("a;b,c\n{}", "x".repeat(80)));
+    writer.end("VEVENT");
+
+    let document = writer.finish();
+
+    assert!(document.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+    assert!(document.contains("SUMMARY:a\\;b\\,c\\n"));
+    assert!(document.lines().all(|x| x.trim_right_matches('\r').len() <= 75));
+    assert!(document.ends_with("END:VCALENDAR\r\n"));
+
+    let (events, _) = icalendar::parse_events(&document.replace("BEGIN:VEVENT\r\n", "BEGIN:VEVENT\r\nUID:x\r\nDTSTART:20170101T000000Z\r\n")).unwrap();
+    assert_eq!(events[0].summary, format!("a;b,c\n{}", "x".repeat(80)));
+}
+
+#[test]
+pub fn icalendar_writer_defines_the_zones_it_uses() {
+    let mut writer = icalendar::ICalendarWriter::new();
+    writer.begin("VEVENT");
+    writer.property("UID", "x");
+    // 2017-06-05 09:00 in London.
+    writer.zoned_date_time_property("DTSTART", &[1496649600], "Europe/London");
+    writer.end("VEVENT");
+
+    let document = writer.finish();
+
+    let time_zone = document.find("BEGIN:VTIMEZONE\r\nTZID:Europe/London\r\n").unwrap();
+    assert!(time_zone < document.find("BEGIN:VEVENT").unwrap());
+    assert!(document.contains("BEGIN:DAYLIGHT\r\nDTSTART:20170326T010000\r\n"));
+    assert!(document.contains("BEGIN:STANDARD\r\nDTSTART:20171029T020000\r\n"));
+    assert!(document.contains("TZOFFSETFROM:+0000\r\nTZOFFSETTO:+0100\r\n"));
+    assert!(document.contains("TZOFFSETFROM:+0100\r\nTZOFFSETTO:+0000\r\n"));
+    assert!(document.contains("DTSTART;TZID=Europe/London:20170605T090000\r\n"));
+
+    let (events, _) = icalendar::parse_events(&document).unwrap();
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].begin, 1496649600);
+}
+
+#[test]
+pub fn smtp_base64_pads_partial_groups() {
+    assert_eq!(smtp::base64_encode(b"Evelyn"), "RXZlbHlu");
+    assert_eq!(smtp::base64_encode(b"Eve"), "RXZl");
+    assert_eq!(smtp::base64_encode(b"Ev"), "RXY=");
+    assert_eq!(smtp::base64_encode(b"E"), "RQ==");
+}
+
+#[test]
+pub fn smtp_messages_have_both_alternatives_and_encoded_headers() {
+    let message = smtp::EmailMessage {
+        from: String::from("evelyn@localhost"),
+        to: String::from("rupert@evelyn.com"),
+        subject: String::from("Caf\u{e9}"),
+        text_body: String::from("plain"),
+        html_body: String::from("<p>html</p>"),
+        message_id: String::from("the-id@evelyn"),
+        date: dts::timestamp_to_rfc2822(0),
+    };
+
+    let formatted = smtp::format_message(&message);
+
+    assert!(formatted.contains("Subject: =?UTF-8?B?Q2Fmw6k=?=\r\n"));
+    assert!(formatted.contains("Message-ID: <the-id@evelyn>\r\n"));
+    assert!(formatted.contains("Date: Thu, 01 Jan 1970 00:00:00 +0000\r\n"));
+    assert!(formatted.contains("Content-Type: text/plain; charset=utf-8"));
+    assert!(formatted.contains("Content-Type: text/html; charset=utf-8"));
+    assert!(formatted.contains(&smtp::base64_encode(b"<p>html</p>")));
+}
+
+#[test]
+pub fn smtp_addresses_cannot_inject_commands_or_headers() {
+    assert!(smtp::is_safe_address("rupert@evelyn.com"));
+    assert!(!smtp::is_safe_address("rupert@evelyn.com>\r\nRCPT TO:<other@evelyn.com"));
+    assert!(!smtp::is_safe_address("rupert@evelyn.com\nBcc: other@evelyn.com"));
+    assert!(!smtp::is_safe_address(""));
+
+    let config = smtp::SmtpConfig {
+        host: String::from("127.0.0.1"),
+        port: 1,
+        user_name: String::new(),
+        password: String::new(),
+    };
+    let message = smtp::EmailMessage {
+        from: String::from("evelyn@localhost"),
+        to: String::from("rupert@evelyn.com\r\nBcc: other@evelyn.com"),
+        subject: String::from("Hi"),
+        text_body: String::from("plain"),
+        html_body: String::from("<p>html</p>"),
+        message_id: String::from("the-id@evelyn"),
+        date: dts::timestamp_to_rfc2822(0),
+    };
+
+    // Refused before a connection is made.
+    match smtp::send(&config, &message) {
+        Err(smtp::SmtpError::InvalidAddress(_)) => {},
+        other => panic!("expected an invalid address, got {:?}", other),
+    }
+}
+
+#[test]
+pub fn webhook_signatures_are_hex_hmac_sha256() {
+    // RFC 4231 test case 2.
+    assert_eq!(webhook::sign("Jefe", "what do ya want for nothing?").unwrap(),
+               "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843");
+}
+
+#[test]
+pub fn webhooks_are_only_sent_to_public_addresses() {
+    let no_hosts: Vec<String> = Vec::new();
+
+    assert!(webhook::is_allowed_url("https://93.184.216.34/hook", &no_hosts));
+    assert!(!webhook::is_allowed_url("http://127.0.0.1:27017", &no_hosts));
+    assert!(!webhook::is_allowed_url("http://169.254.169.254/", &no_hosts));
+    assert!(!webhook::is_allowed_url("http://10.1.2.3/hook", &no_hosts));
+    assert!(!webhook::is_allowed_url("http://[::1]/hook", &no_hosts));
+    assert!(!webhook::is_allowed_url("http://[fd00::1]/hook", &no_hosts));
+    assert!(!webhook::is_allowed_url("http://[::ffff:192.168.0.1]/hook", &no_hosts));
+    assert!(!webhook::is_allowed_url("ftp://93.184.216.34/hook", &no_hosts));
+
+    let ci_hosts = vec![String::from("127.0.0.1")];
+    assert!(webhook::is_allowed_url("http://127.0.0.1:8000/hook", &ci_hosts));
+}
+
+#[test]
+pub fn retries_back_off_up_to_an_hour() {
+    assert_eq!(evelyn::core::scheduler::retry_delay(1), 60);
+    assert_eq!(evelyn::core::scheduler::retry_delay(2), 120);
+    assert_eq!(evelyn::core::scheduler::retry_delay(4), 480);
+    assert_eq!(evelyn::core::scheduler::retry_delay(20), 3600);
+}
+
+#[test]
+pub fn todo_list_template_placeholders_are_filled_by_name() {
+    let mut values = BTreeMap::new();
+    values.insert(String::from("version"), String::from("1.2"));
+
+    assert_eq!(todo_list::fill_placeholders("Tag {{version}} and {{ version }}", &values), "Tag 1.2 and 1.2");
+    assert_eq!(todo_list::fill_placeholders("Email {{team}} about {{version}}", &values), "Email {{team}} about 1.2");
+    assert_eq!(todo_list::fill_placeholders("Unclosed {{version", &values), "Unclosed {{version");
+    assert_eq!(todo_list::find_placeholders("{{team}} {{version}} {{team}}"), vec!["team", "version"]);
+}
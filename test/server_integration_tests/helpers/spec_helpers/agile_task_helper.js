@@ -22,7 +22,9 @@ module.exports = {
     createTask: createTask,
     lookupTask: lookupTask,
     lookupBacklog: lookupBacklog,
-    updateTask: updateTask
+    lookupAssigned: lookupAssigned,
+    updateTask: updateTask,
+    lookupHistory: lookupHistory
 };
 
 function createTask(token, projectId, taskRef, otherProperties) {
@@ -53,6 +55,13 @@ function lookupBacklog(token, projectId) {
     .then(serverErrorHelper.newResponseHandler());
 }
 
+function lookupAssigned(token, filters) {
+    return httpHelper.post('/agile/task/lookup/assigned', _.assign({
+        Token: token
+    }, filters))
+    .then(serverErrorHelper.newResponseHandler());
+}
+
 function updateTask(token, projectId, taskId, updateProperties) {
     var payload = {
         Token: token,
@@ -76,3 +85,12 @@ function updateTask(token, projectId, taskId, updateProperties) {
     return httpHelper.post('/agile/task/update', payload)
     .then(serverErrorHelper.newResponseHandler());
 }
+
+function lookupHistory(token, projectId, taskId) {
+    return httpHelper.post('/agile/task/history', {
+        Token: token,
+        ProjectId: projectId,
+        EntityId: taskId
+    })
+    .then(serverErrorHelper.newResponseHandler());
+}
@@ -22,8 +22,17 @@ module.exports = {
     createTodoList: createTodoList,
     addItem: addItem,
     updateItem: updateItem,
+    removeItem: removeItem,
+    moveItem: moveItem,
+    updateList: updateList,
+    removeList: removeList,
+    shareList: shareList,
+    unshareList: unshareList,
     lookupPreviews: lookupPreviews,
-    lookupList: lookupList
+    lookupList: lookupList,
+    createTemplate: createTemplate,
+    lookupTemplates: lookupTemplates,
+    instantiateTemplate: instantiateTemplate
 };
 
 function createTodoList(token, title) {
@@ -48,6 +57,39 @@ function updateItem(request) {
     .then(serverErrorHelper.newResponseHandler());
 }
 
+function removeItem(request) {
+    return httpHelper.post('/todolist/item/remove', request)
+    .then(serverErrorHelper.newResponseHandler());
+}
+
+function moveItem(request) {
+    return httpHelper.post('/todolist/item/move', request)
+    .then(serverErrorHelper.newResponseHandler());
+}
+
+function updateList(request) {
+    return httpHelper.post('/todolist/update', request)
+    .then(serverErrorHelper.newResponseHandler());
+}
+
+function removeList(token, todo_list_id) {
+    return httpHelper.post('/todolist/remove', {
+        Token: token,
+        TodoListId: todo_list_id
+    })
+    .then(serverErrorHelper.newResponseHandler());
+}
+
+function shareList(request) {
+    return httpHelper.post('/todolist/share/add', request)
+    .then(serverErrorHelper.newResponseHandler());
+}
+
+function unshareList(request) {
+    return httpHelper.post('/todolist/share/remove', request)
+    .then(serverErrorHelper.newResponseHandler());
+}
+
 function lookupPreviews(token) {
     return httpHelper.post('/todolist/lookuplists', {
         Token: token
@@ -62,3 +104,20 @@ function lookupList(token, todo_list_id) {
     })
     .then(serverErrorHelper.newResponseHandler());
 }
+
+function createTemplate(request) {
+    return httpHelper.post('/todolist/template/create', request)
+    .then(serverErrorHelper.newResponseHandler());
+}
+
+function lookupTemplates(token) {
+    return httpHelper.post('/todolist/template/lookup', {
+        Token: token
+    })
+    .then(serverErrorHelper.newResponseHandler());
+}
+
+function instantiateTemplate(request) {
+    return httpHelper.post('/todolist/template/instantiate', request)
+    .then(serverErrorHelper.newResponseHandler());
+}
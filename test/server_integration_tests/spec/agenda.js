@@ -0,0 +1,158 @@
+// Evelyn: Your personal assistant, project manager and calendar
+// Copyright (C) 2017 Gregory Jensen
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+
+if (!global.Promise) {
+    global.Promise = require('bluebird');
+}
+
+var expect = require('chai').expect;
+var _ = require('lodash');
+
+var httpHelper = require('../helpers/chai_http_request_helper.js');
+var commonRequestsHelper = require('../helpers/common_requests_helper.js');
+
+var projectHelper = require('../helpers/spec_helpers/agile_project_helper.js');
+var taskHelper = require('../helpers/spec_helpers/agile_task_helper.js');
+var sprintHelper = require('../helpers/spec_helpers/agile_sprint_helper.js');
+var heirarchyHelper = require('../helpers/spec_helpers/agile_heirarchy_helper.js');
+var todoListHelper = require('../helpers/spec_helpers/todo_list_helper.js');
+
+describe('Agenda', function() {
+    var token = null;
+    var userId = null;
+
+    function lookupMyDay(date) {
+        return httpHelper.post('/agenda/myday', {
+            Token: token,
+            Date: date
+        });
+    }
+
+    before(function () {
+        return commonRequestsHelper.chaiHttpPostPurgeDatabase()
+        .then(function () {
+            return commonRequestsHelper.createUserAndLogon();
+        })
+        .then(function (_token) {
+            token = _token;
+            return commonRequestsHelper.searchForUsers(token, 'rupert');
+        })
+        .then(function (response) {
+            userId = response.SearchResults[0].UserId;
+        });
+    });
+
+    it('Gathers the day from each area', function () {
+        var projectId = null;
+        var sprintId = null;
+        var taskId = null;
+        var doneListId = null;
+
+        return httpHelper.post('/calendar/addevent', {
+            Token: token,
+            Title: "Standup",
+            EventBegin: "2017-09-01T10:00:00Z",
+            EventEnd: "2017-09-01T10:15:00Z"
+        })
+        .then(function () {
+            return httpHelper.post('/calendar/addevent', {
+                Token: token,
+                Title: "Retro",
+                EventBegin: "2017-09-02T10:00:00Z",
+                EventEnd: "2017-09-02T11:00:00Z"
+            });
+        })
+        .then(function () {
+            return httpHelper.post('/simpletask/create', {
+                Token: token,
+                Title: "Overdue",
+                Description: "",
+                DueDate: "2017-08-30"
+            });
+        })
+        .then(function () {
+            return httpHelper.post('/simpletask/create', {
+                Token: token,
+                Title: "Next week",
+                Description: "",
+                DueDate: "2017-09-05"
+            });
+        })
+        .then(function () {
+            return todoListHelper.createTodoList(token, "Shopping");
+        })
+        .then(function (response) {
+            return todoListHelper.addItem(token, response.TodoListId, {
+                Text: "Eggs",
+                IsDone: false
+            });
+        })
+        .then(function () {
+            return todoListHelper.createTodoList(token, "Done");
+        })
+        .then(function (response) {
+            doneListId = response.TodoListId;
+            return todoListHelper.addItem(token, doneListId, {
+                Text: "Milk",
+                IsDone: true
+            });
+        })
+        .then(function () {
+            return projectHelper.createProject(token, "agenda");
+        })
+        .then(function (response) {
+            projectId = response.ProjectId;
+            return sprintHelper.createSprint(token, projectId, "Sprint 1");
+        })
+        .then(function (response) {
+            sprintId = response.SprintId;
+            return taskHelper.createTask(token, projectId, "agenda");
+        })
+        .then(function (response) {
+            taskId = response.TaskId;
+            return taskHelper.updateTask(token, projectId, taskId, {assignToUserId: userId});
+        })
+        .then(function () {
+            return heirarchyHelper.createLink(token, projectId, "Sprint", sprintId, "Task", taskId);
+        })
+        .then(function () {
+            return taskHelper.createTask(token, projectId, "unplanned");
+        })
+        .then(function () {
+            return lookupMyDay("2017-09-01");
+        })
+        .then(function (response) {
+            expect(response.Error).to.be.null;
+
+            var agenda = response.Agenda;
+            expect(agenda.Date).to.equal("2017-09-01");
+            expect(_.map(agenda.Events, 'Title')).to.deep.equal(["Standup"]);
+            expect(_.map(agenda.SimpleTasks, 'Title')).to.deep.equal(["Overdue"]);
+            expect(_.map(agenda.TodoLists, 'Title')).to.deep.equal(["Shopping"]);
+            expect(agenda.AgileTasks).to.have.lengthOf(1);
+            expect(agenda.AgileTasks[0].SprintId).to.equal(sprintId);
+            expect(agenda.AgileTasks[0].Task.TaskId).to.equal(taskId);
+        });
+    });
+
+    it('Rejects dates which are not YYYY-MM-DD', function () {
+        return lookupMyDay("1st September")
+        .then(function (response) {
+            expect(response.Error.ErrorCode).to.equal("101102");
+        });
+    });
+});
@@ -19,6 +19,7 @@ if (!global.Promise) {
 }
 
 var expect = require('chai').expect;
+var moment = require('moment');
 var _ = require('lodash');
 
 var httpHelper = require('../helpers/chai_http_request_helper.js');
@@ -301,4 +302,163 @@ describe('Agile: Task', function() {
             });
         });
     });
+
+    describe('Assigned', function () {
+        var taskIds = {};
+
+        beforeEach(function () {
+            var userId = null;
+            var activeSprintId = null;
+            var laterSprintId = null;
+
+            return commonRequestsHelper.searchForUsers(token, 'user')
+            .then(function (response) {
+                userId = _.find(response.SearchResults, {UserName: 'user'}).UserId;
+
+                return agileSprintHelper.createSprint(token, projectId, 'active');
+            })
+            .then(function (response) {
+                activeSprintId = response.SprintId;
+
+                return agileSprintHelper.createSprint(token, projectId, 'later', {
+                    startDate: moment().utc().add(5, 'days'),
+                    endDate: moment().utc().add(10, 'days')
+                });
+            })
+            .then(function (response) {
+                laterSprintId = response.SprintId;
+
+                return Promise.all(_.map(['backlog', 'planned', 'inSprint', 'unassigned'], function (ref) {
+                    return agileTaskHelper.createTask(token, projectId, ref)
+                    .then(function (response) {
+                        taskIds[ref] = response.TaskId;
+                    });
+                }));
+            })
+            .then(function () {
+                return Promise.all(_.map(['backlog', 'planned', 'inSprint'], function (ref) {
+                    return agileTaskHelper.updateTask(token, projectId, taskIds[ref], {assignToUserId: userId});
+                }));
+            })
+            .then(function () {
+                return agileHeirarchyHelper.createLink(token, projectId, 'Sprint', laterSprintId, 'Task', taskIds.planned);
+            })
+            .then(function () {
+                return agileHeirarchyHelper.createLink(token, projectId, 'Sprint', activeSprintId, 'Task', taskIds.inSprint);
+            });
+        });
+
+        it('Looks up the tasks assigned to the user with their status', function () {
+            return agileTaskHelper.lookupAssigned(token, {})
+            .then(function (response) {
+                expect(response.Tasks).to.have.lengthOf(3);
+                expect(response.NextCursor).to.be.null;
+
+                var statuses = _.fromPairs(_.map(response.Tasks, function (x) {
+                    return [x.Task.TaskId, x.Status];
+                }));
+                var expected = {};
+                expected[taskIds.backlog] = 'Backlog';
+                expected[taskIds.planned] = 'Planned';
+                expected[taskIds.inSprint] = 'InSprint';
+                expect(statuses).to.deep.equal(expected);
+            });
+        });
+
+        it('Filters by status', function () {
+            return agileTaskHelper.lookupAssigned(token, {Status: 'Planned'})
+            .then(function (response) {
+                expect(_.map(response.Tasks, 'Task.TaskId')).to.deep.equal([taskIds.planned]);
+            });
+        });
+
+        it('Filters by project', function () {
+            return agileProjectHelper.createProject(token, 'other_project')
+            .then(function (response) {
+                return agileTaskHelper.lookupAssigned(token, {ProjectId: response.ProjectId});
+            })
+            .then(function (response) {
+                expect(response.Tasks).to.be.empty;
+            });
+        });
+
+        it('Pages through the tasks', function () {
+            var firstPage = null;
+
+            return agileTaskHelper.lookupAssigned(token, {Limit: 2})
+            .then(function (response) {
+                firstPage = _.map(response.Tasks, 'Task.TaskId');
+                expect(firstPage).to.have.lengthOf(2);
+                expect(response.NextCursor).to.equal(_.last(firstPage));
+
+                return agileTaskHelper.lookupAssigned(token, {Limit: 2, Cursor: response.NextCursor});
+            })
+            .then(function (response) {
+                expect(response.Tasks).to.have.lengthOf(1);
+                expect(response.NextCursor).to.be.null;
+                expect(firstPage).to.not.include(response.Tasks[0].Task.TaskId);
+            });
+        });
+
+        it('Leaves out tasks assigned to others', function () {
+            return agileTaskHelper.lookupAssigned(altToken, {})
+            .then(function (response) {
+                expect(response.Tasks).to.be.empty;
+            });
+        });
+    });
+
+    describe('History', function() {
+        it('Records changes to a task', function() {
+            var taskId = null;
+
+            return agileTaskHelper.createTask(token, projectId, 'history_ref')
+            .then(function (response) {
+                taskId = response.TaskId;
+
+                return agileTaskHelper.updateTask(token, projectId, taskId, {
+                    title: 'new title',
+                    description: 'description_history_ref'
+                });
+            })
+            .then(function () {
+                return agileTaskHelper.lookupHistory(token, projectId, taskId);
+            })
+            .then(function (response) {
+                expect(response.History).to.be.an.array;
+                expect(response.History).to.have.lengthOf(2);
+                expect(response.History[0].Action).to.equal('Create');
+
+                var update = response.History[1];
+                expect(update.Action).to.equal('Update');
+                expect(update.Changes).to.have.lengthOf(1);
+                expect(update.Changes[0].Field).to.equal('title');
+                expect(update.Changes[0].Before).to.equal('title_history_ref');
+                expect(update.Changes[0].After).to.equal('new title');
+            });
+        });
+
+        it('Is only shown to contributors to the project', function() {
+            var taskId = null;
+
+            return agileTaskHelper.createTask(token, projectId, 'history_ref')
+            .then(function (response) {
+                taskId = response.TaskId;
+
+                return commonRequestsHelper.createUserAndLogon('outsider');
+            })
+            .then(function (outsiderToken) {
+                return httpHelper.post('/agile/task/history', {
+                    Token: outsiderToken,
+                    ProjectId: projectId,
+                    EntityId: taskId
+                });
+            })
+            .then(function (response) {
+                expect(response.Error).to.not.be.null;
+                expect(response.Error.ErrorCode).to.equal('1006501');
+                expect(response.History).to.be.empty;
+            });
+        });
+    });
 });
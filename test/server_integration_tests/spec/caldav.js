@@ -0,0 +1,159 @@
+// Evelyn: Your personal assistant, project manager and calendar
+// Copyright (C) 2017 Gregory Jensen
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+
+if (!global.Promise) {
+    global.Promise = require('bluebird');
+}
+
+var chai = require('chai');
+var chaiHttp = require('chai-http');
+var expect = chai.expect;
+
+var commonRequestsHelper = require('../helpers/common_requests_helper.js');
+
+chai.use(chaiHttp);
+
+describe('CalDAV', function() {
+    var calendarPath = null;
+
+    var event = [
+        'BEGIN:VCALENDAR',
+        'VERSION:2.0',
+        'PRODID:-//Test//Test//EN',
+        'BEGIN:VEVENT',
+        'UID:caldav-event@example.com',
+        'DTSTAMP:20170901T000000Z',
+        'DTSTART:20170904T090000Z',
+        'DTEND:20170904T100000Z',
+        'SUMMARY:From a CalDAV client',
+        'END:VEVENT',
+        'END:VCALENDAR',
+        ''
+    ].join('\r\n');
+
+    function request() {
+        return chai.request('localhost:8080');
+    }
+
+    before(function () {
+        return commonRequestsHelper.chaiHttpPostPurgeDatabase()
+        .then(function () {
+            return commonRequestsHelper.createUserAndLogon();
+        })
+        .then(function (token) {
+            return commonRequestsHelper.searchForUsers(token);
+        })
+        .then(function (response) {
+            calendarPath = '/caldav/' + response.SearchResults[0].UserId + '/calendar/';
+        });
+    });
+
+    it('Requires credentials', function() {
+        return request()
+        .get(calendarPath + 'missing.ics')
+        .then(function () {
+            throw new Error('Expected the request to be rejected');
+        }, function (err) {
+            expect(err.response).to.have.status(401);
+        });
+    });
+
+    it('Stores, reads and removes an event', function() {
+        var eventPath = calendarPath + 'caldav-event.ics';
+
+        return request()
+        .put(eventPath)
+        .auth('rupert@evelyn.com', 'asdf')
+        .set('Content-Type', 'text/calendar')
+        .set('If-None-Match', '*')
+        .send(event)
+        .then(function (res) {
+            expect(res).to.have.status(201);
+            expect(res).to.have.header('etag', '"1"');
+
+            return request()
+            .get(eventPath)
+            .auth('rupert@evelyn.com', 'asdf')
+            .buffer(true);
+        })
+        .then(function (res) {
+            expect(res).to.have.status(200);
+            expect(res.text).to.contain('SUMMARY:From a CalDAV client');
+
+            return request()
+            .del(eventPath)
+            .auth('rupert@evelyn.com', 'asdf');
+        })
+        .then(function (res) {
+            expect(res).to.have.status(204);
+        });
+    });
+
+    it('Rejects a write against an old version', function() {
+        return request()
+        .put(calendarPath + 'versioned.ics')
+        .auth('rupert@evelyn.com', 'asdf')
+        .set('Content-Type', 'text/calendar')
+        .send(event)
+        .then(function (res) {
+            expect(res).to.have.status(201);
+
+            return request()
+            .put(calendarPath + 'versioned.ics')
+            .auth('rupert@evelyn.com', 'asdf')
+            .set('Content-Type', 'text/calendar')
+            .set('If-Match', '"7"')
+            .send(event);
+        })
+        .then(function () {
+            throw new Error('Expected the request to be rejected');
+        }, function (err) {
+            expect(err.response).to.have.status(412);
+        });
+    });
+
+    it('Will not reuse the id of an event in the trash', function() {
+        var eventPath = calendarPath + 'trashed.ics';
+
+        return request()
+        .put(eventPath)
+        .auth('rupert@evelyn.com', 'asdf')
+        .set('Content-Type', 'text/calendar')
+        .send(event)
+        .then(function (res) {
+            expect(res).to.have.status(201);
+
+            return request()
+            .del(eventPath)
+            .auth('rupert@evelyn.com', 'asdf');
+        })
+        .then(function (res) {
+            expect(res).to.have.status(204);
+
+            return request()
+            .put(eventPath)
+            .auth('rupert@evelyn.com', 'asdf')
+            .set('Content-Type', 'text/calendar')
+            .send(event);
+        })
+        .then(function () {
+            throw new Error('Expected the request to be rejected');
+        }, function (err) {
+            expect(err.response).to.have.status(409);
+        });
+    });
+});
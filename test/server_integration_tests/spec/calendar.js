@@ -19,6 +19,7 @@ if (!global.Promise) {
 }
 
 var expect = require('chai').expect;
+var moment = require('moment');
 
 var httpHelper = require('../helpers/chai_http_request_helper.js');
 var commonRequestsHelper = require('../helpers/common_requests_helper.js');
@@ -52,4 +53,548 @@ describe('Calendar', function() {
         });
     });
 
+    describe('Lookup', function() {
+        var base = moment.utc('2017-06-01T00:00:00Z');
+
+        function addEvent(title, beginHours, endHours) {
+            return httpHelper.post('/calendar/addevent', {
+                Token: token,
+                Title: title,
+                EventBegin: base.clone().add(beginHours, 'hours').toISOString(),
+                EventEnd: base.clone().add(endHours, 'hours').toISOString()
+            })
+            .then(function (response) {
+                expect(response.Error).to.be.null;
+            });
+        }
+
+        before(function () {
+            return commonRequestsHelper.chaiHttpPostPurgeDatabaseArea('calendar')
+            .then(function () {
+                return addEvent('later', 5, 6);
+            })
+            .then(function () {
+                return addEvent('overlapping start', 1, 3);
+            })
+            .then(function () {
+                return addEvent('outside', 20, 21);
+            });
+        });
+
+        it('Finds events overlapping the range sorted by start', function() {
+            return httpHelper.post('/calendar/lookup', {
+                Token: token,
+                TimeRangeBegin: base.clone().add(2, 'hours').toISOString(),
+                TimeRangeEnd: base.clone().add(10, 'hours').toISOString()
+            })
+            .then(function (response) {
+                expect(response.Error).to.be.null;
+                expect(response.Events).to.have.lengthOf(2);
+                expect(response.Events[0].Title).to.equal('overlapping start');
+                expect(response.Events[1].Title).to.equal('later');
+            });
+        });
+
+        it('Rejects a range which ends before it begins', function() {
+            return httpHelper.post('/calendar/lookup', {
+                Token: token,
+                TimeRangeBegin: base.clone().add(10, 'hours').toISOString(),
+                TimeRangeEnd: base.clone().add(2, 'hours').toISOString()
+            })
+            .then(function (response) {
+                expect(response.Error).to.not.be.null;
+                expect(response.Error.ErrorCode).to.equal('100503');
+            });
+        });
+    });
+
+    describe('Update and remove', function() {
+        var base = moment.utc('2017-07-01T00:00:00Z');
+        var eventId = null;
+
+        function lookupEvents() {
+            return httpHelper.post('/calendar/lookup', {
+                Token: token,
+                TimeRangeBegin: base.toISOString(),
+                TimeRangeEnd: base.clone().add(1, 'days').toISOString()
+            })
+            .then(function (response) {
+                expect(response.Error).to.be.null;
+                return response.Events;
+            });
+        }
+
+        before(function () {
+            return commonRequestsHelper.chaiHttpPostPurgeDatabaseArea('calendar')
+            .then(function () {
+                return httpHelper.post('/calendar/addevent', {
+                    Token: token,
+                    Title: 'to change',
+                    EventBegin: base.clone().add(2, 'hours').toISOString(),
+                    EventEnd: base.clone().add(3, 'hours').toISOString()
+                });
+            })
+            .then(lookupEvents)
+            .then(function (events) {
+                expect(events).to.have.lengthOf(1);
+                eventId = events[0].EventId;
+            });
+        });
+
+        it('Updates only the given fields', function() {
+            return httpHelper.post('/calendar/event/update', {
+                Token: token,
+                EventId: eventId,
+                NewTitle: 'changed',
+                NewEventEnd: base.clone().add(5, 'hours').toISOString()
+            })
+            .then(function (response) {
+                expect(response.Error).to.be.null;
+                return lookupEvents();
+            })
+            .then(function (events) {
+                expect(events[0].Title).to.equal('changed');
+                expect(moment.utc(events[0].EventBegin).isSame(base.clone().add(2, 'hours'))).to.be.true;
+                expect(moment.utc(events[0].EventEnd).isSame(base.clone().add(5, 'hours'))).to.be.true;
+            });
+        });
+
+        it('Rejects an end before the stored beginning', function() {
+            return httpHelper.post('/calendar/event/update', {
+                Token: token,
+                EventId: eventId,
+                NewEventEnd: base.clone().add(1, 'hours').toISOString()
+            })
+            .then(function (response) {
+                expect(response.Error).to.not.be.null;
+                expect(response.Error.ErrorCode).to.equal('100503');
+            });
+        });
+
+        it('Removes the event', function() {
+            return httpHelper.post('/calendar/event/remove', {
+                Token: token,
+                EventId: eventId
+            })
+            .then(function (response) {
+                expect(response.Error).to.be.null;
+                return lookupEvents();
+            })
+            .then(function (events) {
+                expect(events).to.have.lengthOf(0);
+            });
+        });
+
+        it('Reports a missing event', function() {
+            return httpHelper.post('/calendar/event/remove', {
+                Token: token,
+                EventId: eventId
+            })
+            .then(function (response) {
+                expect(response.Error).to.not.be.null;
+                expect(response.Error.ErrorCode).to.equal('100506');
+            });
+        });
+    });
+
+    describe('Recurring events', function() {
+        var base = moment.utc('2017-08-07T09:00:00Z');
+
+        function lookupOccurrences() {
+            return httpHelper.post('/calendar/lookup', {
+                Token: token,
+                TimeRangeBegin: base.toISOString(),
+                TimeRangeEnd: base.clone().add(30, 'days').toISOString()
+            })
+            .then(function (response) {
+                expect(response.Error).to.be.null;
+                return response.Events;
+            });
+        }
+
+        before(function () {
+            return commonRequestsHelper.chaiHttpPostPurgeDatabaseArea('calendar')
+            .then(function () {
+                return httpHelper.post('/calendar/addevent', {
+                    Token: token,
+                    Title: 'stand up',
+                    EventBegin: base.toISOString(),
+                    EventEnd: base.clone().add(15, 'minutes').toISOString(),
+                    Recurrence: 'FREQ=WEEKLY;BYDAY=MO,TH;COUNT=4'
+                });
+            })
+            .then(function (response) {
+                expect(response.Error).to.be.null;
+            });
+        });
+
+        it('Expands each occurrence', function() {
+            return lookupOccurrences()
+            .then(function (events) {
+                expect(events).to.have.lengthOf(4);
+                expect(moment.utc(events[1].EventBegin).isSame(base.clone().add(3, 'days'))).to.be.true;
+                expect(events[1].OccurrenceBegin).to.equal(events[1].EventBegin);
+            });
+        });
+
+        it('Cancels a single occurrence', function() {
+            return lookupOccurrences()
+            .then(function (events) {
+                return httpHelper.post('/calendar/event/occurrence/cancel', {
+                    Token: token,
+                    EventId: events[1].EventId,
+                    OccurrenceBegin: events[1].OccurrenceBegin
+                });
+            })
+            .then(function (response) {
+                expect(response.Error).to.be.null;
+                return lookupOccurrences();
+            })
+            .then(function (events) {
+                expect(events).to.have.lengthOf(3);
+            });
+        });
+
+        it('Moves a single occurrence', function() {
+            return lookupOccurrences()
+            .then(function (events) {
+                return httpHelper.post('/calendar/event/occurrence/update', {
+                    Token: token,
+                    EventId: events[0].EventId,
+                    OccurrenceBegin: events[0].OccurrenceBegin,
+                    NewTitle: 'long stand up',
+                    NewEventEnd: base.clone().add(1, 'hours').toISOString()
+                });
+            })
+            .then(function (response) {
+                expect(response.Error).to.be.null;
+                return lookupOccurrences();
+            })
+            .then(function (events) {
+                expect(events).to.have.lengthOf(3);
+                expect(events[0].Title).to.equal('long stand up');
+                expect(events[1].Title).to.equal('stand up');
+            });
+        });
+
+        it('Rejects a time which is not an occurrence', function() {
+            return lookupOccurrences()
+            .then(function (events) {
+                return httpHelper.post('/calendar/event/occurrence/cancel', {
+                    Token: token,
+                    EventId: events[0].EventId,
+                    OccurrenceBegin: base.clone().add(1, 'days').toISOString()
+                });
+            })
+            .then(function (response) {
+                expect(response.Error).to.not.be.null;
+                expect(response.Error.ErrorCode).to.equal('100509');
+            });
+        });
+
+        it('Rejects an unsupported rule', function() {
+            return httpHelper.post('/calendar/addevent', {
+                Token: token,
+                Title: 'hourly',
+                EventBegin: base.toISOString(),
+                EventEnd: base.toISOString(),
+                Recurrence: 'FREQ=HOURLY'
+            })
+            .then(function (response) {
+                expect(response.Error).to.not.be.null;
+                expect(response.Error.ErrorCode).to.equal('100508');
+            });
+        });
+    });
+
+    describe('iCalendar', function() {
+        var calendar = [
+            'BEGIN:VCALENDAR',
+            'VERSION:2.0',
+            'PRODID:-//Test//Test//EN',
+            'BEGIN:VEVENT',
+            'UID:imported@example.com',
+            'DTSTAMP:20170901T000000Z',
+            'DTSTART:20170904T090000Z',
+            'DTEND:20170904T100000Z',
+            'SUMMARY:Imported',
+            'END:VEVENT',
+            'END:VCALENDAR',
+            ''
+        ].join('\r\n');
+
+        before(function () {
+            return commonRequestsHelper.chaiHttpPostPurgeDatabaseArea('calendar');
+        });
+
+        it('Imports events once per uid', function() {
+            return httpHelper.post('/calendar/import', {
+                Token: token,
+                Calendar: calendar
+            })
+            .then(function (response) {
+                expect(response.Error).to.be.null;
+                expect(response.Created).to.equal(1);
+
+                return httpHelper.post('/calendar/import', {
+                    Token: token,
+                    Calendar: calendar.replace('SUMMARY:Imported', 'SUMMARY:Imported again')
+                });
+            })
+            .then(function (response) {
+                expect(response.Error).to.be.null;
+                expect(response.Created).to.equal(0);
+                expect(response.Updated).to.equal(1);
+            });
+        });
+
+        it('Exports events', function() {
+            return httpHelper.post('/calendar/export', {
+                Token: token
+            })
+            .then(function (response) {
+                expect(response.Error).to.be.null;
+                expect(response.Calendar).to.contain('UID:imported@example.com');
+                expect(response.Calendar).to.contain('SUMMARY:Imported again');
+            });
+        });
+
+        it('Rejects a document which is not a calendar', function() {
+            return httpHelper.post('/calendar/import', {
+                Token: token,
+                Calendar: 'not a calendar'
+            })
+            .then(function (response) {
+                expect(response.Error).to.not.be.null;
+                expect(response.Error.ErrorCode).to.equal('100514');
+            });
+        });
+    });
+
+    describe('Time zones', function() {
+        before(function () {
+            return commonRequestsHelper.chaiHttpPostPurgeDatabaseArea('calendar')
+            .then(function () {
+                return httpHelper.post('/user/profile/update', {
+                    Token: token,
+                    NewTimeZone: 'America/New_York'
+                });
+            })
+            .then(function (response) {
+                expect(response.Error).to.be.null;
+            });
+        });
+
+        after(function () {
+            return httpHelper.post('/user/profile/update', {
+                Token: token,
+                NewTimeZone: ''
+            });
+        });
+
+        it('Reads times without an offset in the user\'s zone and renders them in it', function() {
+            return httpHelper.post('/calendar/addevent', {
+                Token: token,
+                Title: 'standup',
+                EventBegin: '2017-06-05T09:00:00',
+                EventEnd: '2017-06-05T09:15:00'
+            })
+            .then(function (response) {
+                expect(response.Error).to.be.null;
+
+                return httpHelper.post('/calendar/lookup', {
+                    Token: token,
+                    TimeRangeBegin: '2017-06-05T12:00:00Z',
+                    TimeRangeEnd: '2017-06-05T14:00:00Z'
+                });
+            })
+            .then(function (response) {
+                expect(response.Error).to.be.null;
+                expect(response.Events).to.have.lengthOf(1);
+                expect(response.Events[0].EventBegin).to.equal('2017-06-05T09:00:00-04:00');
+                expect(response.Events[0].TimeZone).to.equal('America/New_York');
+            });
+        });
+
+        it('Rejects an unknown event time zone', function() {
+            return httpHelper.post('/calendar/addevent', {
+                Token: token,
+                Title: 'nowhere',
+                EventBegin: '2017-06-05T09:00:00',
+                EventEnd: '2017-06-05T09:15:00',
+                TimeZone: 'Nowhere/At_All'
+            })
+            .then(function (response) {
+                expect(response.Error).to.not.be.null;
+                expect(response.Error.ErrorCode).to.equal('100110');
+            });
+        });
+    });
+
+    describe('Invitations', function() {
+        var inviteeToken = null;
+        var organizerUserId = null;
+        var inviteeUserId = null;
+        var eventId = null;
+
+        function lookupInviteeEvents() {
+            return httpHelper.post('/calendar/lookup', {
+                Token: inviteeToken,
+                TimeRangeBegin: '2017-08-01T00:00:00Z',
+                TimeRangeEnd: '2017-08-02T00:00:00Z'
+            })
+            .then(function (response) {
+                expect(response.Error).to.be.null;
+                return response.Events;
+            });
+        }
+
+        before(function () {
+            return commonRequestsHelper.chaiHttpPostPurgeDatabaseArea('calendar')
+            .then(function () {
+                return commonRequestsHelper.createUserAndLogon('invitee');
+            })
+            .then(function (_token) {
+                inviteeToken = _token;
+                return commonRequestsHelper.searchForUsers(token, 'invitee');
+            })
+            .then(function (response) {
+                inviteeUserId = response.SearchResults[0].UserId;
+                return commonRequestsHelper.searchForUsers(inviteeToken, 'rupert');
+            })
+            .then(function (response) {
+                organizerUserId = response.SearchResults[0].UserId;
+                return httpHelper.post('/calendar/addevent', {
+                    Token: token,
+                    Title: 'planning',
+                    EventBegin: '2017-08-01T10:00:00Z',
+                    EventEnd: '2017-08-01T11:00:00Z'
+                });
+            })
+            .then(function (response) {
+                expect(response.Error).to.be.null;
+                return httpHelper.post('/calendar/addevent', {
+                    Token: token,
+                    Title: 'review',
+                    EventBegin: '2017-08-01T10:30:00Z',
+                    EventEnd: '2017-08-01T12:00:00Z'
+                });
+            })
+            .then(function (response) {
+                expect(response.Error).to.be.null;
+                return httpHelper.post('/calendar/lookup', {
+                    Token: token,
+                    TimeRangeBegin: '2017-08-01T00:00:00Z',
+                    TimeRangeEnd: '2017-08-02T00:00:00Z'
+                });
+            })
+            .then(function (response) {
+                eventId = response.Events[0].EventId;
+            });
+        });
+
+        it('Shows the event to the invitee', function() {
+            return httpHelper.post('/calendar/event/invite', {
+                Token: token,
+                EventId: eventId,
+                UserIds: [inviteeUserId]
+            })
+            .then(function (response) {
+                expect(response.Error).to.be.null;
+                return lookupInviteeEvents();
+            })
+            .then(function (events) {
+                expect(events).to.have.lengthOf(1);
+                expect(events[0].Title).to.equal('planning');
+                expect(events[0].OrganizerUserId).to.equal(organizerUserId);
+                expect(events[0].Attendees).to.have.lengthOf(1);
+                expect(events[0].Attendees[0].Response).to.equal('NeedsAction');
+            });
+        });
+
+        it('Rejects an unknown invitee', function() {
+            return httpHelper.post('/calendar/event/invite', {
+                Token: token,
+                EventId: eventId,
+                UserIds: ['not a user']
+            })
+            .then(function (response) {
+                expect(response.Error).to.not.be.null;
+                expect(response.Error.ErrorCode).to.equal('100519');
+            });
+        });
+
+        it('Records the invitee\'s response', function() {
+            return httpHelper.post('/calendar/event/respond', {
+                Token: inviteeToken,
+                EventId: eventId,
+                Response: 'Accepted'
+            })
+            .then(function (response) {
+                expect(response.Error).to.be.null;
+                return lookupInviteeEvents();
+            })
+            .then(function (events) {
+                expect(events[0].Attendees[0].Response).to.equal('Accepted');
+            });
+        });
+
+        it('Rejects a response from someone who was not invited', function() {
+            return httpHelper.post('/calendar/lookup', {
+                Token: token,
+                TimeRangeBegin: '2017-08-01T00:00:00Z',
+                TimeRangeEnd: '2017-08-02T00:00:00Z'
+            })
+            .then(function (response) {
+                return httpHelper.post('/calendar/event/respond', {
+                    Token: inviteeToken,
+                    EventId: response.Events[1].EventId,
+                    Response: 'Declined'
+                });
+            })
+            .then(function (response) {
+                expect(response.Error).to.not.be.null;
+                expect(response.Error.ErrorCode).to.equal('100518');
+            });
+        });
+
+        it('Merges busy times for each user', function() {
+            return httpHelper.post('/calendar/freebusy', {
+                Token: token,
+                UserIds: [organizerUserId, inviteeUserId],
+                TimeRangeBegin: '2017-08-01T00:00:00Z',
+                TimeRangeEnd: '2017-08-02T00:00:00Z'
+            })
+            .then(function (response) {
+                expect(response.Error).to.be.null;
+                expect(response.Users).to.have.lengthOf(2);
+                expect(response.Users[0].Busy).to.deep.equal([
+                    {Begin: '2017-08-01T10:00:00+00:00', End: '2017-08-01T12:00:00+00:00'}
+                ]);
+                expect(response.Users[1].Busy).to.deep.equal([
+                    {Begin: '2017-08-01T10:00:00+00:00', End: '2017-08-01T11:00:00+00:00'}
+                ]);
+            });
+        });
+
+        it('Hides the busy times of users who share nothing with the caller', function() {
+            return commonRequestsHelper.createUserAndLogon('stranger')
+            .then(function () {
+                return commonRequestsHelper.searchForUsers(token, 'stranger');
+            })
+            .then(function (response) {
+                return httpHelper.post('/calendar/freebusy', {
+                    Token: token,
+                    UserIds: [response.SearchResults[0].UserId],
+                    TimeRangeBegin: '2017-08-01T00:00:00Z',
+                    TimeRangeEnd: '2017-08-02T00:00:00Z'
+                });
+            })
+            .then(function (response) {
+                expect(response.Error).to.not.be.null;
+                expect(response.Error.ErrorCode).to.equal('100519');
+                expect(response.Users).to.be.empty;
+            });
+        });
+    });
 });
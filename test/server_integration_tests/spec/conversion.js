@@ -0,0 +1,201 @@
+// Evelyn: Your personal assistant, project manager and calendar
+// Copyright (C) 2017 Gregory Jensen
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+if (!global.Promise) {
+    global.Promise = require('bluebird');
+}
+
+var expect = require('chai').expect;
+var _ = require('lodash');
+
+var httpHelper = require('../helpers/chai_http_request_helper.js');
+var commonRequestsHelper = require('../helpers/common_requests_helper.js');
+var agileProjectHelper = require('../helpers/spec_helpers/agile_project_helper.js');
+var agileTaskHelper = require('../helpers/spec_helpers/agile_task_helper.js');
+var todoListHelper = require('../helpers/spec_helpers/todo_list_helper.js');
+
+describe('Conversion', function() {
+    var token = null;
+    var projectId = null;
+
+    function lookupSimpleTask(taskId) {
+        return httpHelper.post('/simpletask/lookup', {
+            Token: token,
+            ShowCompleted: true,
+            Limit: 0
+        })
+        .then(function(response) {
+            expect(response.Error).to.be.null;
+            return _.find(response.SimpleTasks, {TaskId: taskId});
+        });
+    }
+
+    function promote(taskId, targetProjectId) {
+        return httpHelper.post('/simpletask/promote', {
+            Token: token,
+            TaskId: taskId,
+            ProjectId: targetProjectId,
+            OriginalEstimate: "2h"
+        });
+    }
+
+    before(function () {
+        return commonRequestsHelper.chaiHttpPostPurgeDatabase()
+        .then(function () {
+            return commonRequestsHelper.createUserAndLogon();
+        })
+        .then(function (_token) {
+            token = _token;
+            return agileProjectHelper.createProject(token, 'conversion');
+        })
+        .then(function (response) {
+            projectId = response.ProjectId;
+        });
+    });
+
+    describe('Todo list item to simple task', function() {
+        var todoListId = null;
+
+        before(function() {
+            return todoListHelper.createTodoList(token, "Weekend")
+            .then(function(response) {
+                todoListId = response.TodoListId;
+                return todoListHelper.addItem(token, todoListId, {Text: "Fix the bike", IsDone: false});
+            });
+        });
+
+        it('Converts an item and links both ways', function() {
+            var itemId = null;
+            var taskId = null;
+
+            return todoListHelper.lookupList(token, todoListId)
+            .then(function(response) {
+                itemId = response.TodoList.TodoListItems[0].ItemId;
+                return httpHelper.post('/todolist/item/convert', {
+                    Token: token,
+                    TodoListId: todoListId,
+                    ItemId: itemId,
+                    DueDate: "2017-06-03"
+                });
+            })
+            .then(function(response) {
+                expect(response.Error).to.be.null;
+                taskId = response.TaskId;
+                return lookupSimpleTask(taskId);
+            })
+            .then(function(task) {
+                expect(task.Title).to.equal("Fix the bike");
+                expect(task.DueDate).to.equal("2017-06-03");
+                expect(task.Origin).to.deep.equal({ItemType: "TodoListItem", ItemId: itemId, ContainerId: todoListId});
+                return todoListHelper.lookupList(token, todoListId);
+            })
+            .then(function(response) {
+                expect(response.TodoList.TodoListItems[0].ConvertedTo).to.deep.equal({ItemType: "SimpleTask", ItemId: taskId, ContainerId: null});
+            });
+        });
+
+        it('Converting again does not duplicate the task', function() {
+            return httpHelper.post('/todolist/item/convert', {
+                Token: token,
+                TodoListId: todoListId,
+                ItemIndex: 0
+            })
+            .then(function(response) {
+                expect(response.Error).to.be.null;
+                return httpHelper.post('/simpletask/lookup', {Token: token, ShowCompleted: true, Limit: 0});
+            })
+            .then(function(response) {
+                expect(_.filter(response.SimpleTasks, {Title: "Fix the bike"})).to.have.lengthOf(1);
+            });
+        });
+
+        it('Rejects an item which does not exist', function() {
+            return httpHelper.post('/todolist/item/convert', {
+                Token: token,
+                TodoListId: todoListId,
+                ItemIndex: 5
+            })
+            .then(function(response) {
+                expect(response.Error.ErrorCode).to.equal("100407");
+            });
+        });
+    });
+
+    describe('Simple task to agile task', function() {
+        var taskId = null;
+
+        beforeEach(function() {
+            return httpHelper.post('/simpletask/create', {
+                Token: token,
+                Title: "Write the release notes",
+                Description: "For the summer release"
+            })
+            .then(function(response) {
+                expect(response.Error).to.be.null;
+                taskId = response.TaskId;
+            });
+        });
+
+        it('Promotes a task and completes it', function() {
+            var agileTaskId = null;
+
+            return promote(taskId, projectId)
+            .then(function(response) {
+                expect(response.Error).to.be.null;
+                agileTaskId = response.AgileTaskId;
+                return agileTaskHelper.lookupTask(token, projectId, agileTaskId);
+            })
+            .then(function(response) {
+                expect(response.Task.Title).to.equal("Write the release notes");
+                expect(response.Task.Description).to.equal("For the summer release");
+                expect(response.Task.OriginalEstimate).to.equal("2h");
+                expect(response.Task.Origin).to.deep.equal({ItemType: "SimpleTask", ItemId: taskId, ContainerId: null});
+                return lookupSimpleTask(taskId);
+            })
+            .then(function(task) {
+                expect(task.Completed).to.be.true;
+                expect(task.ConvertedTo).to.deep.equal({ItemType: "AgileTask", ItemId: agileTaskId, ContainerId: projectId});
+            });
+        });
+
+        it('Promoting again returns the same agile task', function() {
+            var agileTaskId = null;
+
+            return promote(taskId, projectId)
+            .then(function(response) {
+                expect(response.Error).to.be.null;
+                agileTaskId = response.AgileTaskId;
+                return promote(taskId, projectId);
+            })
+            .then(function(response) {
+                expect(response.Error).to.be.null;
+                expect(response.AgileTaskId).to.equal(agileTaskId);
+            });
+        });
+
+        it('Rejects a project the user does not contribute to', function() {
+            return promote(taskId, 'not a project')
+            .then(function(response) {
+                expect(response.Error.ErrorCode).to.equal("100312");
+                return lookupSimpleTask(taskId);
+            })
+            .then(function(task) {
+                expect(task.Completed).to.be.false;
+                expect(task.ConvertedTo).to.be.null;
+            });
+        });
+    });
+});
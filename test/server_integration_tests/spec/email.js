@@ -0,0 +1,190 @@
+// Evelyn: Your personal assistant, project manager and calendar
+// Copyright (C) 2017 Gregory Jensen
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+
+if (!global.Promise) {
+    global.Promise = require('bluebird');
+}
+
+var net = require('net');
+var expect = require('chai').expect;
+
+var httpHelper = require('../helpers/chai_http_request_helper.js');
+var commonRequestsHelper = require('../helpers/common_requests_helper.js');
+var userGroupHelper = require('../helpers/spec_helpers/user_group_helper.js');
+
+// The test configuration points the server's SMTP relay at this port.
+var SMTP_SINK_PORT = 2525;
+
+// Accepts any mail and keeps the recipient and subject of each.
+function startSmtpSink(received) {
+    var server = net.createServer(function (socket) {
+        var buffer = '';
+        var inData = false;
+        var mail = {};
+
+        socket.write('220 sink ready\r\n');
+
+        socket.on('data', function (chunk) {
+            buffer += chunk.toString();
+
+            var end;
+            while ((end = buffer.indexOf('\r\n')) !== -1) {
+                var line = buffer.substring(0, end);
+                buffer = buffer.substring(end + 2);
+
+                if (inData) {
+                    if (line === '.') {
+                        inData = false;
+                        received.push(mail);
+                        mail = {};
+                        socket.write('250 queued\r\n');
+                    } else if (line.indexOf('Subject: ') === 0 && !mail.subject) {
+                        mail.subject = line.substring('Subject: '.length);
+                    }
+                } else if (line.indexOf('RCPT TO:') === 0) {
+                    mail.to = line.substring('RCPT TO:'.length).replace(/[<>]/g, '');
+                    socket.write('250 ok\r\n');
+                } else if (line === 'DATA') {
+                    inData = true;
+                    socket.write('354 go ahead\r\n');
+                } else if (line === 'QUIT') {
+                    socket.end('221 bye\r\n');
+                } else {
+                    socket.write('250 ok\r\n');
+                }
+            }
+        });
+    });
+
+    return new Promise(function (resolve) {
+        server.listen(SMTP_SINK_PORT, '127.0.0.1', function () {
+            resolve(server);
+        });
+    });
+}
+
+function wait(milliseconds) {
+    return new Promise(function (resolve) {
+        setTimeout(resolve, milliseconds);
+    });
+}
+
+describe('Email', function() {
+    var sink = null;
+    var received = [];
+    var ownerToken = null;
+    var memberToken = null;
+    var memberUserId = null;
+
+    // Mail is sent by the server in the background, so wait for it to arrive.
+    function waitForMail(count, attempts) {
+        if (received.length >= count || attempts <= 0) {
+            return Promise.resolve(received);
+        }
+
+        return wait(500).then(function () {
+            return waitForMail(count, attempts - 1);
+        });
+    }
+
+    before(function () {
+        return startSmtpSink(received)
+        .then(function (server) {
+            sink = server;
+            return commonRequestsHelper.chaiHttpPostPurgeDatabase();
+        })
+        .then(function () {
+            return commonRequestsHelper.createUserAndLogon('owner');
+        })
+        .then(function (_token) {
+            ownerToken = _token;
+            return commonRequestsHelper.createUserAndLogon('member');
+        })
+        .then(function (_token) {
+            memberToken = _token;
+            return commonRequestsHelper.searchForUsers(ownerToken, 'member');
+        })
+        .then(function (response) {
+            memberUserId = response.SearchResults[0].UserId;
+        });
+    });
+
+    after(function () {
+        sink.close();
+    });
+
+    beforeEach(function () {
+        received.length = 0;
+    });
+
+    it('Emails a user who is added to a group', function() {
+        this.timeout(10000);
+
+        return userGroupHelper.createUserGroup(ownerToken, 'release team', 'ships things')
+        .then(function (response) {
+            return userGroupHelper.addMember(ownerToken, response.UserGroupId, memberUserId);
+        })
+        .then(function () {
+            return waitForMail(1, 10);
+        })
+        .then(function (mail) {
+            expect(mail).to.have.lengthOf(1);
+            expect(mail[0].to).to.equal('member@evelyn.com');
+            expect(mail[0].subject).to.equal('[Evelyn] Added to release team');
+        });
+    });
+
+    it('Only emails the kinds of notification the user asked for', function() {
+        this.timeout(10000);
+
+        return httpHelper.post('/user/profile/update', {
+            Token: memberToken,
+            NewEmailNotifications: []
+        })
+        .then(function (response) {
+            expect(response.Error).to.be.null;
+
+            return httpHelper.post('/user/profile/lookup', {
+                Token: memberToken
+            });
+        })
+        .then(function (response) {
+            expect(response.Error).to.be.null;
+            expect(response.EmailNotifications).to.have.lengthOf(0);
+
+            return userGroupHelper.createUserGroup(ownerToken, 'quiet team', 'does not email');
+        })
+        .then(function (response) {
+            return userGroupHelper.addMember(ownerToken, response.UserGroupId, memberUserId);
+        })
+        .then(function () {
+            return wait(3000);
+        })
+        .then(function () {
+            expect(received).to.have.lengthOf(0);
+
+            return httpHelper.post('/notification/lookup', {
+                Token: memberToken
+            });
+        })
+        .then(function (response) {
+            expect(response.Error).to.be.null;
+            expect(response.Notifications[0].Kind).to.equal('AddedToUserGroup');
+            expect(response.Notifications[0].Title).to.equal('Added to quiet team');
+        });
+    });
+});
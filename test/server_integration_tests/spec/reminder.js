@@ -0,0 +1,187 @@
+// Evelyn: Your personal assistant, project manager and calendar
+// Copyright (C) 2017 Gregory Jensen
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+
+if (!global.Promise) {
+    global.Promise = require('bluebird');
+}
+
+var expect = require('chai').expect;
+var moment = require('moment');
+
+var httpHelper = require('../helpers/chai_http_request_helper.js');
+var commonRequestsHelper = require('../helpers/common_requests_helper.js');
+
+describe('Reminders', function() {
+    var token = null;
+    var eventId = null;
+
+    // Reminders are fired by the server in the background, so wait for the
+    // notification to arrive.
+    function waitForNotifications(count, attempts) {
+        return httpHelper.post('/notification/lookup', {
+            Token: token
+        })
+        .then(function (response) {
+            expect(response.Error).to.be.null;
+
+            if (response.Notifications.length >= count || attempts <= 0) {
+                return response.Notifications;
+            }
+
+            return new Promise(function (resolve) {
+                setTimeout(resolve, 500);
+            })
+            .then(function () {
+                return waitForNotifications(count, attempts - 1);
+            });
+        });
+    }
+
+    before(function () {
+        return commonRequestsHelper.chaiHttpPostPurgeDatabase()
+        .then(function () {
+            return commonRequestsHelper.createUserAndLogon();
+        })
+        .then(function (_token) {
+            token = _token;
+
+            return httpHelper.post('/calendar/addevent', {
+                Token: token,
+                Title: 'standup',
+                EventBegin: moment.utc().add(2, 'minutes').toISOString(),
+                EventEnd: moment.utc().add(17, 'minutes').toISOString()
+            });
+        })
+        .then(function (response) {
+            expect(response.Error).to.be.null;
+
+            return httpHelper.post('/calendar/lookup', {
+                Token: token,
+                TimeRangeBegin: moment.utc().toISOString(),
+                TimeRangeEnd: moment.utc().add(1, 'hours').toISOString()
+            });
+        })
+        .then(function (response) {
+            eventId = response.Events[0].EventId;
+        });
+    });
+
+    it('Rejects a negative offset', function() {
+        return httpHelper.post('/reminder/add', {
+            Token: token,
+            Source: 'CalendarEvent',
+            SourceId: eventId,
+            MinutesBefore: -5
+        })
+        .then(function (response) {
+            expect(response.Error).to.not.be.null;
+            expect(response.Error.ErrorCode).to.equal('100806');
+        });
+    });
+
+    it('Rejects an item which does not exist', function() {
+        return httpHelper.post('/reminder/add', {
+            Token: token,
+            Source: 'SimpleTask',
+            SourceId: 'not a task',
+            MinutesBefore: 5
+        })
+        .then(function (response) {
+            expect(response.Error).to.not.be.null;
+            expect(response.Error.ErrorCode).to.equal('100805');
+        });
+    });
+
+    it('Delivers a due reminder to the inbox', function() {
+        this.timeout(10000);
+
+        return httpHelper.post('/reminder/add', {
+            Token: token,
+            Source: 'CalendarEvent',
+            SourceId: eventId,
+            MinutesBefore: 5
+        })
+        .then(function (response) {
+            expect(response.Error).to.be.null;
+            expect(response.ReminderId).to.not.be.null;
+
+            return waitForNotifications(1, 10);
+        })
+        .then(function (notifications) {
+            expect(notifications).to.have.lengthOf(1);
+            expect(notifications[0].Kind).to.equal('Reminder');
+            expect(notifications[0].Title).to.equal('Reminder: standup');
+            expect(notifications[0].Read).to.be.false;
+
+            return httpHelper.post('/notification/markread', {
+                Token: token,
+                NotificationIds: [notifications[0].NotificationId]
+            });
+        })
+        .then(function (response) {
+            expect(response.Error).to.be.null;
+
+            return httpHelper.post('/notification/lookup', {
+                Token: token,
+                UnreadOnly: true
+            });
+        })
+        .then(function (response) {
+            expect(response.Error).to.be.null;
+            expect(response.Notifications).to.have.lengthOf(0);
+        });
+    });
+
+    it('Does not fire again for an event which does not recur', function() {
+        return httpHelper.post('/reminder/lookup', {
+            Token: token
+        })
+        .then(function (response) {
+            expect(response.Error).to.be.null;
+            expect(response.Reminders).to.have.lengthOf(1);
+            expect(response.Reminders[0].NextFireAt).to.be.null;
+        });
+    });
+
+    it('Removes a reminder', function() {
+        var reminderId = null;
+
+        return httpHelper.post('/reminder/lookup', {
+            Token: token
+        })
+        .then(function (response) {
+            reminderId = response.Reminders[0].ReminderId;
+
+            return httpHelper.post('/reminder/remove', {
+                Token: token,
+                ReminderId: reminderId
+            });
+        })
+        .then(function (response) {
+            expect(response.Error).to.be.null;
+
+            return httpHelper.post('/reminder/remove', {
+                Token: token,
+                ReminderId: reminderId
+            });
+        })
+        .then(function (response) {
+            expect(response.Error).to.not.be.null;
+            expect(response.Error.ErrorCode).to.equal('100804');
+        });
+    });
+});
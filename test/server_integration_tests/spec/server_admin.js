@@ -0,0 +1,199 @@
+// Evelyn: Your personal assistant, project manager and calendar
+// Copyright (C) 2017 Gregory Jensen
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+if (!global.Promise) {
+    global.Promise = require('bluebird');
+}
+
+var chai = require('chai');
+var chaiSubset = require('chai-subset');
+var _ = require('lodash');
+
+var httpHelper = require('../helpers/chai_http_request_helper.js');
+var commonRequestsHelper = require('../helpers/common_requests_helper.js');
+
+var simpleTaskHelper = require('../helpers/spec_helpers/simple_task_helper.js');
+var agileProjectHelper = require('../helpers/spec_helpers/agile_project_helper.js');
+var agileTaskHelper = require('../helpers/spec_helpers/agile_task_helper.js');
+
+chai.use(chaiSubset);
+var expect = chai.expect;
+
+describe('Server Admin', function() {
+    var token = null;
+    var otherToken = null;
+
+    before(function () {
+        return commonRequestsHelper.chaiHttpPostPurgeDatabase()
+        .then(function () {
+            return commonRequestsHelper.createUserAndLogon('admin_user');
+        })
+        .then(function (_token) {
+            token = _token;
+            return commonRequestsHelper.createUserAndLogon('admin_other_user');
+        })
+        .then(function (_token) {
+            otherToken = _token;
+        });
+    });
+
+    describe('Delete project', function() {
+        var projectId = null;
+
+        before(function () {
+            return agileProjectHelper.createProject(token, 'admin_project')
+            .then(function (response) {
+                projectId = response.ProjectId;
+                return httpHelper.post('/webhook/add', {
+                    Token: token,
+                    ProjectId: projectId,
+                    Url: 'http://localhost:1/webhook',
+                    Secret: 'secret',
+                    EventTypes: ['TaskCreated']
+                });
+            })
+            .then(function (response) {
+                expect(response.Error).to.be.null;
+                return agileTaskHelper.createTask(token, projectId, 'admin_task');
+            });
+        });
+
+        it('Only the creator can delete a project', function() {
+            return httpHelper.post('/admin/project/delete', {
+                Token: otherToken,
+                ProjectId: projectId,
+                DryRun: true
+            })
+            .then(function (response) {
+                expect(response.Error).to.not.be.null;
+                expect(response.Error.ErrorCode).to.equal('100108');
+            });
+        });
+
+        it('Reports what would be deleted without deleting it', function() {
+            return httpHelper.post('/admin/project/delete', {
+                Token: token,
+                ProjectId: projectId,
+                DryRun: true
+            })
+            .then(function (response) {
+                expect(response.Error).to.be.null;
+                expect(response.Affected).to.containSubset([
+                    {Collection: 'agile_project', Action: 'Delete', Count: 1},
+                    {Collection: 'agile_task', Action: 'Delete', Count: 1},
+                    {Collection: 'webhook_subscription', Action: 'Delete', Count: 1},
+                    {Collection: 'webhook_delivery', Action: 'Delete', Count: 1}
+                ]);
+
+                return agileTaskHelper.lookupBacklog(token, projectId);
+            })
+            .then(function (response) {
+                expect(response.Tasks).to.have.lengthOf(1);
+            });
+        });
+
+        it('Deletes the project and its tasks', function() {
+            return httpHelper.post('/admin/project/delete', {
+                Token: token,
+                ProjectId: projectId,
+                DryRun: false
+            })
+            .then(function (response) {
+                expect(response.Error).to.be.null;
+                expect(response.Affected).to.containSubset([{Collection: 'agile_task', Count: 1}]);
+
+                return agileTaskHelper.lookupBacklog(token, projectId);
+            })
+            .then(function (response) {
+                expect(response.Tasks).to.have.lengthOf(0);
+            });
+        });
+    });
+
+    describe('Delete user', function() {
+        it('Deletes the user and their data', function() {
+            var leavingToken = null;
+
+            return commonRequestsHelper.createUserAndLogon('leaving_user')
+            .then(function (_token) {
+                leavingToken = _token;
+
+                return simpleTaskHelper.createTasks({
+                    Token: leavingToken,
+                    Title: "Task",
+                    Description: "Descriptive",
+                    DueDate: new Date().toISOString()
+                }, 1);
+            })
+            .then(function () {
+                return httpHelper.post('/admin/user/delete', {
+                    Token: leavingToken,
+                    DryRun: true
+                });
+            })
+            .then(function (response) {
+                expect(response.Error).to.be.null;
+                expect(response.Affected).to.containSubset([
+                    {Collection: 'simpletask', Action: 'Delete', Count: 1},
+                    {Collection: 'user', Action: 'Delete', Count: 1}
+                ]);
+
+                return httpHelper.post('/admin/user/delete', {
+                    Token: leavingToken,
+                    DryRun: false
+                });
+            })
+            .then(function (response) {
+                expect(response.Error).to.be.null;
+
+                return httpHelper.post('/user/logon', {
+                    EmailAddress: 'leaving_user@evelyn.com',
+                    Password: 'asdf'
+                });
+            })
+            .then(function (response) {
+                expect(response.Error).to.not.be.null;
+                expect(response.Error.ErrorCode).to.equal('100203');
+            });
+        });
+
+        it('Stops agile records identifying the user', function() {
+            var leavingToken = null;
+
+            return commonRequestsHelper.createUserAndLogon('leaving_agile_user')
+            .then(function (_token) {
+                leavingToken = _token;
+                return agileProjectHelper.createProject(leavingToken, 'leaving_project');
+            })
+            .then(function (response) {
+                return agileTaskHelper.createTask(leavingToken, response.ProjectId, 'leaving_task');
+            })
+            .then(function () {
+                return httpHelper.post('/admin/user/delete', {
+                    Token: leavingToken,
+                    DryRun: true
+                });
+            })
+            .then(function (response) {
+                expect(response.Error).to.be.null;
+                expect(response.Affected).to.containSubset([
+                    {Collection: 'agile_project', Action: 'Detach', Count: 1},
+                    {Collection: 'agile_task', Action: 'Detach', Count: 1}
+                ]);
+            });
+        });
+    });
+});
@@ -21,6 +21,7 @@ if (!global.Promise) {
 var chai = require('chai');
 var chaiSubset = require('chai-subset');
 var moment = require('moment');
+var _ = require('lodash');
 
 var httpHelper = require('../helpers/chai_http_request_helper.js');
 var commonRequestsHelper = require('../helpers/common_requests_helper.js');
@@ -289,7 +290,9 @@ describe('Simple Task', function() {
                 expect(response.Error).to.be.null;
                 expect(response.SimpleTasks).to.be.an.array;
                 expect(response.SimpleTasks).to.have.length.of.at.least(1);
-                expect(response.SimpleTasks).to.containSubset([{TaskId: simpletask.taskId, DueDate: newDate}]);
+                var task = _.find(response.SimpleTasks, {TaskId: simpletask.taskId});
+                expect(moment(task.DueDate).unix()).to.equal(moment(newDate).unix());
+                expect(task.DueDateOnly).to.be.false;
             });
         });
 
@@ -323,6 +326,543 @@ describe('Simple Task', function() {
                 expect(response.SimpleTasks).to.containSubset([{TaskId: simpletask.taskId, Completed: true}]);
             });
         });
+
+        it('Rejects an update made against an old version', function() {
+            var version = null;
+
+            return httpHelper.post(
+                '/simpletask/lookup',
+                {
+                    Token : token,
+                    Limit : 0,
+                    ShowCompleted : true
+                }
+            ).then(function (response) {
+                expect(response.Error).to.be.null;
+                version = _.find(response.SimpleTasks, {TaskId: simpletask.taskId}).Version;
+
+                return httpHelper.post(
+                    '/simpletask/update',
+                    {
+                        Token: token,
+                        TaskId: simpletask.taskId,
+                        NewTitle: simpletask.title,
+                        NewDescription: simpletask.description,
+                        NewDueDate: simpletask.dueDate,
+                        NewCompleted: simpletask.completed,
+                        ExpectedVersion: version
+                    }
+                );
+            }).then(function (response) {
+                expect(response.Error).to.be.null;
+
+                return httpHelper.post(
+                    '/simpletask/update',
+                    {
+                        Token: token,
+                        TaskId: simpletask.taskId,
+                        NewTitle: "A stale title",
+                        NewDescription: simpletask.description,
+                        NewDueDate: simpletask.dueDate,
+                        NewCompleted: simpletask.completed,
+                        ExpectedVersion: version
+                    }
+                );
+            }).then(function (response) {
+                expect(response.Error).to.not.be.null;
+                expect(response.Error.ErrorCode).to.equal("100305");
+            });
+        });
+
+        it('Reports a missing task', function() {
+            return httpHelper.post(
+                '/simpletask/update',
+                {
+                    Token: token,
+                    TaskId: "not a task id",
+                    NewTitle: simpletask.title,
+                    NewDescription: simpletask.description,
+                    NewDueDate: simpletask.dueDate,
+                    NewCompleted: simpletask.completed
+                }
+            ).then(function (response) {
+                expect(response.Error).to.not.be.null;
+                expect(response.Error.ErrorCode).to.equal("100306");
+            });
+        });
+    });
+
+    describe('Filtered lookup', function() {
+        var tasks = [
+            {Title: "Pay rent", Description: "Bank transfer", DueDate: "2030-01-10T09:00:00Z", Priority: "Urgent", Tags: ["home", "money"]},
+            {Title: "Buy milk", Description: "Semi-skimmed", DueDate: "2030-01-05T09:00:00Z", Priority: "Low", Tags: ["shopping"]},
+            {Title: "Book flights", Description: "For the (summer) trip", DueDate: "", Priority: "High", Tags: [" travel ", "money", "money"]},
+            {Title: "Clean desk", Description: "", DueDate: "2030-02-01T09:00:00Z", Tags: []}
+        ];
+
+        function lookup(options) {
+            return httpHelper.post('/simpletask/lookup', _.assign({
+                Token: token,
+                ShowCompleted: false,
+                Limit: 0
+            }, options));
+        }
+
+        before(function() {
+            return commonRequestsHelper.chaiHttpPostPurgeDatabaseArea('simpletask')
+            .then(function() {
+                return Promise.all(tasks.map(function(task) {
+                    return httpHelper.post('/simpletask/create', _.assign({Token: token}, task));
+                }));
+            })
+            .then(function(responses) {
+                responses.forEach(function(response) {
+                    expect(response.Error).to.be.null;
+                });
+            });
+        });
+
+        it('Stores priorities and tidied tags', function() {
+            return lookup({}).then(function(response) {
+                expect(response.Error).to.be.null;
+                expect(response.SimpleTasks).to.containSubset([
+                    {Title: "Book flights", Priority: "High", Tags: ["travel", "money"]},
+                    {Title: "Clean desk", Priority: "Normal", Tags: []}
+                ]);
+            });
+        });
+
+        it('Sorts by due date with undated tasks last', function() {
+            return lookup({}).then(function(response) {
+                expect(_.map(response.SimpleTasks, 'Title')).to.deep.equal(["Buy milk", "Pay rent", "Clean desk", "Book flights"]);
+            });
+        });
+
+        it('Sorts by priority', function() {
+            return lookup({SortBy: "Priority", SortDescending: true}).then(function(response) {
+                expect(_.map(response.SimpleTasks, 'Title')).to.deep.equal(["Pay rent", "Book flights", "Clean desk", "Buy milk"]);
+            });
+        });
+
+        it('Filters by tag and priority', function() {
+            return lookup({Tag: "money"}).then(function(response) {
+                expect(_.map(response.SimpleTasks, 'Title')).to.deep.equal(["Pay rent", "Book flights"]);
+
+                return lookup({Priority: "Low"});
+            })
+            .then(function(response) {
+                expect(_.map(response.SimpleTasks, 'Title')).to.deep.equal(["Buy milk"]);
+            });
+        });
+
+        it('Filters by due date range', function() {
+            return lookup({DueAfter: "2030-01-06T00:00:00Z", DueBefore: "2030-01-31T00:00:00Z"}).then(function(response) {
+                expect(_.map(response.SimpleTasks, 'Title')).to.deep.equal(["Pay rent"]);
+            });
+        });
+
+        it('Searches titles and descriptions', function() {
+            return lookup({Search: "(SUMMER)"}).then(function(response) {
+                expect(_.map(response.SimpleTasks, 'Title')).to.deep.equal(["Book flights"]);
+
+                return lookup({Search: "milk"});
+            })
+            .then(function(response) {
+                expect(_.map(response.SimpleTasks, 'Title')).to.deep.equal(["Buy milk"]);
+            });
+        });
+
+        it('Pages through tasks with a cursor', function() {
+            var titles = [];
+
+            return lookup({Limit: 3}).then(function(response) {
+                expect(response.SimpleTasks).to.have.lengthOf(3);
+                expect(response.NextCursor).to.be.a('string');
+                titles = titles.concat(_.map(response.SimpleTasks, 'Title'));
+
+                return lookup({Limit: 3, Cursor: response.NextCursor});
+            })
+            .then(function(response) {
+                expect(response.NextCursor).to.be.null;
+                titles = titles.concat(_.map(response.SimpleTasks, 'Title'));
+
+                expect(titles).to.deep.equal(["Buy milk", "Pay rent", "Clean desk", "Book flights"]);
+            });
+        });
+
+        it('Rejects an unknown cursor', function() {
+            return lookup({Limit: 3, Cursor: "not a task id"}).then(function(response) {
+                expect(response.Error).to.not.be.null;
+                expect(response.Error.ErrorCode).to.equal("100307");
+            });
+        });
+    });
+
+    describe('Due dates', function() {
+        function create(title, dueDate) {
+            return httpHelper.post('/simpletask/create', {
+                Token: token,
+                Title: title,
+                Description: "",
+                DueDate: dueDate,
+                TimeZone: "UTC"
+            })
+            .then(function(response) {
+                expect(response.Error).to.be.null;
+            });
+        }
+
+        function lookupView(view) {
+            return httpHelper.post('/simpletask/lookup', {
+                Token: token,
+                ShowCompleted: false,
+                Limit: 0,
+                View: view
+            })
+            .then(function(response) {
+                expect(response.Error).to.be.null;
+                return _.map(response.SimpleTasks, 'Title');
+            });
+        }
+
+        before(function() {
+            var today = moment.utc().format('YYYY-MM-DD');
+
+            return commonRequestsHelper.chaiHttpPostPurgeDatabaseArea('simpletask')
+            .then(function() {
+                return create("Due earlier", moment.utc().subtract(1, 'minutes').toISOString());
+            })
+            .then(function() {
+                return create("Due today", today);
+            })
+            .then(function() {
+                return create("Due next month", moment.utc().add(1, 'months').toISOString());
+            })
+            .then(function() {
+                return create("Whenever", null);
+            });
+        });
+
+        it('Keeps dates given without a time', function() {
+            return simpleTaskHelper.lookupTasks(token).then(function(response) {
+                expect(response.SimpleTasks).to.containSubset([
+                    {Title: "Due today", DueDate: moment.utc().format('YYYY-MM-DD'), DueDateOnly: true},
+                    {Title: "Whenever", DueDate: null, DueDateOnly: false}
+                ]);
+            });
+        });
+
+        it('Looks up overdue tasks', function() {
+            return lookupView("Overdue").then(function(titles) {
+                expect(titles).to.deep.equal(["Due earlier"]);
+            });
+        });
+
+        it('Looks up tasks due today', function() {
+            return lookupView("DueToday").then(function(titles) {
+                expect(titles).to.include("Due today");
+                expect(titles).to.not.include("Due next month");
+                expect(titles).to.not.include("Whenever");
+            });
+        });
+
+        it('Looks up tasks due this week', function() {
+            return lookupView("DueThisWeek").then(function(titles) {
+                expect(titles).to.include("Due today");
+                expect(titles).to.not.include("Due next month");
+            });
+        });
+
+        it('Rejects a due date which cannot be read', function() {
+            return httpHelper.post('/simpletask/create', {
+                Token: token,
+                Title: "Bad date",
+                Description: "",
+                DueDate: "next tuesday-ish"
+            })
+            .then(function(response) {
+                expect(response.Error).to.not.be.null;
+                expect(response.Error.ErrorCode).to.equal("100109");
+            });
+        });
+    });
+
+    describe('Recurring', function() {
+        var taskId = null;
+
+        function complete(id) {
+            return httpHelper.post('/simpletask/update', {
+                Token: token,
+                TaskId: id,
+                NewCompleted: true
+            });
+        }
+
+        before(function() {
+            return commonRequestsHelper.chaiHttpPostPurgeDatabaseArea('simpletask')
+            .then(function() {
+                return httpHelper.post('/simpletask/create', {
+                    Token: token,
+                    Title: "Water the plants",
+                    Description: "",
+                    DueDate: "2030-03-04",
+                    TimeZone: "UTC",
+                    Recurrence: "FREQ=WEEKLY;COUNT=2"
+                });
+            })
+            .then(function(response) {
+                expect(response.Error).to.be.null;
+                taskId = response.TaskId;
+            });
+        });
+
+        it('Adds the next occurrence when a task is completed', function() {
+            var nextTaskId = null;
+
+            return complete(taskId).then(function(response) {
+                expect(response.Error).to.be.null;
+                expect(response.NextTaskId).to.be.a('string');
+                nextTaskId = response.NextTaskId;
+
+                return httpHelper.post('/simpletask/lookup', {
+                    Token: token,
+                    ShowCompleted: true,
+                    Limit: 0,
+                    SeriesId: taskId
+                });
+            })
+            .then(function(response) {
+                expect(response.Error).to.be.null;
+                expect(response.SimpleTasks).to.have.lengthOf(2);
+                expect(response.SimpleTasks).to.containSubset([
+                    {TaskId: taskId, DueDate: "2030-03-04", Completed: true},
+                    {TaskId: nextTaskId, DueDate: "2030-03-11", Completed: false, Recurrence: "FREQ=WEEKLY;COUNT=2"}
+                ]);
+                expect(_.find(response.SimpleTasks, {TaskId: taskId}).DateCompleted).to.be.a('string');
+
+                return complete(taskId);
+            })
+            .then(function(response) {
+                // Completing it again doesn't add another occurrence.
+                expect(response.Error).to.be.null;
+                expect(response.NextTaskId).to.be.null;
+
+                return complete(nextTaskId);
+            })
+            .then(function(response) {
+                // That was the last occurrence.
+                expect(response.Error).to.be.null;
+                expect(response.NextTaskId).to.be.null;
+            });
+        });
+
+        it('Keeps the same next occurrence when a task is reopened and completed again', function() {
+            var reopenedTaskId = null;
+            var nextTaskId = null;
+
+            return httpHelper.post('/simpletask/create', {
+                Token: token,
+                Title: "Feed the cat",
+                Description: "",
+                DueDate: "2030-03-04",
+                TimeZone: "UTC",
+                Recurrence: "FREQ=DAILY"
+            })
+            .then(function(response) {
+                expect(response.Error).to.be.null;
+                reopenedTaskId = response.TaskId;
+
+                return complete(reopenedTaskId);
+            })
+            .then(function(response) {
+                nextTaskId = response.NextTaskId;
+                expect(nextTaskId).to.be.a('string');
+
+                return httpHelper.post('/simpletask/update', {
+                    Token: token,
+                    TaskId: reopenedTaskId,
+                    NewCompleted: false
+                });
+            })
+            .then(function(response) {
+                expect(response.Error).to.be.null;
+
+                return complete(reopenedTaskId);
+            })
+            .then(function(response) {
+                expect(response.Error).to.be.null;
+                expect(response.NextTaskId).to.equal(nextTaskId);
+
+                return httpHelper.post('/simpletask/lookup', {
+                    Token: token,
+                    ShowCompleted: true,
+                    Limit: 0,
+                    SeriesId: reopenedTaskId
+                });
+            })
+            .then(function(response) {
+                expect(response.SimpleTasks).to.have.lengthOf(2);
+            });
+        });
+
+        it('Needs a due date', function() {
+            return httpHelper.post('/simpletask/create', {
+                Token: token,
+                Title: "Someday",
+                Description: "",
+                Recurrence: "FREQ=DAILY"
+            })
+            .then(function(response) {
+                expect(response.Error).to.not.be.null;
+                expect(response.Error.ErrorCode).to.equal("100308");
+            });
+        });
+
+        it('Rejects an invalid rule', function() {
+            return httpHelper.post('/simpletask/create', {
+                Token: token,
+                Title: "Sometimes",
+                Description: "",
+                DueDate: "2030-03-04",
+                Recurrence: "FREQ=FORTNIGHTLY"
+            })
+            .then(function(response) {
+                expect(response.Error).to.not.be.null;
+                expect(response.Error.ErrorCode).to.equal("100508");
+            });
+        });
+    });
+
+    describe('Checklists and subtasks', function() {
+        var parentId = null;
+        var childIds = [];
+
+        function create(properties) {
+            return httpHelper.post('/simpletask/create', _.assign({
+                Token: token,
+                Description: ""
+            }, properties))
+            .then(function(response) {
+                expect(response.Error).to.be.null;
+                return response.TaskId;
+            });
+        }
+
+        function lookupTask(taskId) {
+            return httpHelper.post('/simpletask/lookup', {
+                Token: token,
+                ShowCompleted: true,
+                Limit: 0
+            })
+            .then(function(response) {
+                expect(response.Error).to.be.null;
+                return _.find(response.SimpleTasks, {TaskId: taskId});
+            });
+        }
+
+        before(function() {
+            return commonRequestsHelper.chaiHttpPostPurgeDatabaseArea('simpletask')
+            .then(function() {
+                return create({
+                    Title: "Move house",
+                    CompleteWithChildren: true,
+                    Checklist: [{Text: "Book van", IsDone: false}, {Text: "Buy boxes", IsDone: true}]
+                });
+            })
+            .then(function(taskId) {
+                parentId = taskId;
+                return create({Title: "Pack kitchen", ParentTaskId: parentId});
+            })
+            .then(function(taskId) {
+                childIds.push(taskId);
+                return create({Title: "Pack bedroom", ParentTaskId: parentId});
+            })
+            .then(function(taskId) {
+                childIds.push(taskId);
+            });
+        });
+
+        it('Reports progress', function() {
+            return lookupTask(parentId).then(function(task) {
+                expect(_.map(task.Checklist, 'Text')).to.deep.equal(["Book van", "Buy boxes"]);
+                expect(_.map(task.Checklist, 'IsDone')).to.deep.equal([false, true]);
+                expect(task.Progress).to.deep.equal({ChecklistItemsDone: 1, ChecklistItems: 2, ChildrenDone: 0, Children: 2});
+            });
+        });
+
+        it('Ticks a checklist item', function() {
+            return httpHelper.post('/simpletask/checklist/update', {
+                Token: token,
+                TaskId: parentId,
+                ItemIndex: 0,
+                IsDone: true
+            })
+            .then(function(response) {
+                expect(response.Error).to.be.null;
+                return lookupTask(parentId);
+            })
+            .then(function(task) {
+                expect(task.Progress.ChecklistItemsDone).to.equal(2);
+
+                return httpHelper.post('/simpletask/checklist/update', {
+                    Token: token,
+                    TaskId: parentId,
+                    ItemIndex: 2,
+                    IsDone: true
+                });
+            })
+            .then(function(response) {
+                expect(response.Error).to.not.be.null;
+                expect(response.Error.ErrorCode).to.equal("100306");
+            });
+        });
+
+        it('Looks up the children of a task', function() {
+            return httpHelper.post('/simpletask/lookup', {
+                Token: token,
+                ShowCompleted: true,
+                Limit: 0,
+                ParentTaskId: parentId
+            })
+            .then(function(response) {
+                expect(response.Error).to.be.null;
+                expect(_.map(response.SimpleTasks, 'TaskId').sort()).to.deep.equal(childIds.slice().sort());
+            });
+        });
+
+        it('Rejects a parent which would make a loop', function() {
+            return httpHelper.post('/simpletask/update', {
+                Token: token,
+                TaskId: parentId,
+                NewParentTaskId: childIds[0]
+            })
+            .then(function(response) {
+                expect(response.Error).to.not.be.null;
+                expect(response.Error.ErrorCode).to.equal("100309");
+            });
+        });
+
+        it('Completes the parent with its children', function() {
+            return httpHelper.post('/simpletask/update', {Token: token, TaskId: childIds[0], NewCompleted: true})
+            .then(function(response) {
+                expect(response.Error).to.be.null;
+                return lookupTask(parentId);
+            })
+            .then(function(task) {
+                expect(task.Completed).to.be.false;
+                expect(task.Progress.ChildrenDone).to.equal(1);
+
+                return httpHelper.post('/simpletask/update', {Token: token, TaskId: childIds[1], NewCompleted: true});
+            })
+            .then(function(response) {
+                expect(response.Error).to.be.null;
+                return lookupTask(parentId);
+            })
+            .then(function(task) {
+                expect(task.Completed).to.be.true;
+                expect(task.Progress.ChildrenDone).to.equal(2);
+            });
+        });
     });
 
     describe('Remove', function() {
@@ -130,9 +130,368 @@ describe('Todo List', function() {
 
                 var todo_list = response.TodoList;
                 expect(todo_list.Title).to.be.a('string').that.equals('Lookup a todo list');
+                expect(todo_list.TodoListId).to.be.a('string');
+                expect(todo_list.Archived).to.be.false;
                 expect(todo_list.TodoListItems).to.be.an.array;
                 expect(todo_list.TodoListItems).to.have.lengthOf(0);
             });
         });
     });
+
+    describe("Managing lists", function () {
+        var todo_list_id = null;
+
+        function itemTexts() {
+            return todoListHelper.lookupList(token, todo_list_id)
+            .then(function (response) {
+                return _.map(response.TodoList.TodoListItems, 'Text');
+            });
+        }
+
+        function itemId(text) {
+            return todoListHelper.lookupList(token, todo_list_id)
+            .then(function (response) {
+                return _.find(response.TodoList.TodoListItems, {Text: text}).ItemId;
+            });
+        }
+
+        beforeEach(function () {
+            return todoListHelper.createTodoList(token, "Groceries")
+            .then(function (response) {
+                todo_list_id = response.TodoListId;
+                return todoListHelper.addItem(token, todo_list_id, {Text: "Eggs", IsDone: false});
+            })
+            .then(function (response) {
+                expect(response.ItemId).to.be.a('string');
+                return todoListHelper.addItem(token, todo_list_id, {Text: "Milk", IsDone: false});
+            })
+            .then(function () {
+                return todoListHelper.addItem(token, todo_list_id, {Text: "Bread", IsDone: false});
+            });
+        });
+
+        it('Edits an item by id', function () {
+            return itemId("Milk")
+            .then(function (milkId) {
+                return todoListHelper.updateItem({
+                    Token: token,
+                    TodoListId: todo_list_id,
+                    ItemId: milkId,
+                    NewText: "Oat milk",
+                    IsDone: true
+                });
+            })
+            .then(function () {
+                return todoListHelper.lookupList(token, todo_list_id);
+            })
+            .then(function (response) {
+                var item = response.TodoList.TodoListItems[1];
+                expect(item.Text).to.equal("Oat milk");
+                expect(item.IsDone).to.be.true;
+            });
+        });
+
+        it('Removes an item', function () {
+            return itemId("Eggs")
+            .then(function (eggsId) {
+                return todoListHelper.removeItem({
+                    Token: token,
+                    TodoListId: todo_list_id,
+                    ItemId: eggsId
+                });
+            })
+            .then(itemTexts)
+            .then(function (texts) {
+                expect(texts).to.deep.equal(["Milk", "Bread"]);
+            });
+        });
+
+        it('Moves an item', function () {
+            return itemId("Bread")
+            .then(function (breadId) {
+                return todoListHelper.moveItem({
+                    Token: token,
+                    TodoListId: todo_list_id,
+                    ItemId: breadId,
+                    Position: 0
+                });
+            })
+            .then(itemTexts)
+            .then(function (texts) {
+                expect(texts).to.deep.equal(["Bread", "Eggs", "Milk"]);
+            });
+        });
+
+        it('Rejects a move at an old version', function () {
+            return itemId("Bread")
+            .then(function (breadId) {
+                return httpHelper.post('/todolist/item/move', {
+                    Token: token,
+                    TodoListId: todo_list_id,
+                    ItemId: breadId,
+                    Position: 0,
+                    ExpectedVersion: 0
+                });
+            })
+            .then(function (response) {
+                expect(response.Error.ErrorCode).to.equal("100406");
+            });
+        });
+
+        it('Renames and archives a list', function () {
+            return todoListHelper.updateList({
+                Token: token,
+                TodoListId: todo_list_id,
+                NewTitle: "Weekly shop",
+                Archived: true
+            })
+            .then(function () {
+                return todoListHelper.lookupPreviews(token);
+            })
+            .then(function (response) {
+                expect(_.map(response.TodoLists, 'TodoListId')).to.not.include(todo_list_id);
+
+                return httpHelper.post('/todolist/lookuplists', {
+                    Token: token,
+                    ShowArchived: true
+                });
+            })
+            .then(function (response) {
+                var todo_list = _.find(response.TodoLists, {TodoListId: todo_list_id});
+                expect(todo_list.Title).to.equal("Weekly shop");
+                expect(todo_list.Archived).to.be.true;
+            });
+        });
+
+        it('Deletes a list into the trash', function () {
+            return todoListHelper.removeList(token, todo_list_id)
+            .then(function () {
+                return httpHelper.post('/todolist/lookup', {
+                    Token: token,
+                    TodoListId: todo_list_id
+                });
+            })
+            .then(function (response) {
+                expect(response.Error).to.not.be.null;
+
+                return httpHelper.post('/trash/restore', {
+                    Token: token,
+                    ItemType: "TodoList",
+                    ItemId: todo_list_id
+                });
+            })
+            .then(function (response) {
+                expect(response.Error).to.be.null;
+                return itemTexts();
+            })
+            .then(function (texts) {
+                expect(texts).to.deep.equal(["Eggs", "Milk", "Bread"]);
+            });
+        });
+    });
+
+    describe("Sharing", function () {
+        var otherToken = null;
+        var otherUserId = null;
+        var ownerUserId = null;
+        var todo_list_id = null;
+
+        before(function () {
+            return commonRequestsHelper.createUserAndLogon('sharee')
+            .then(function (_token) {
+                otherToken = _token;
+                return commonRequestsHelper.searchForUsers(token, 'sharee');
+            })
+            .then(function (response) {
+                otherUserId = response.SearchResults[0].UserId;
+                return commonRequestsHelper.searchForUsers(otherToken, 'rupert');
+            })
+            .then(function (response) {
+                ownerUserId = response.SearchResults[0].UserId;
+            });
+        });
+
+        beforeEach(function () {
+            return todoListHelper.createTodoList(token, "Shared shopping")
+            .then(function (response) {
+                todo_list_id = response.TodoListId;
+                return todoListHelper.addItem(token, todo_list_id, {
+                    Text: "Eggs",
+                    IsDone: false
+                });
+            });
+        });
+
+        function shareWithOther(permission) {
+            return todoListHelper.shareList({
+                Token: token,
+                TodoListId: todo_list_id,
+                UserId: otherUserId,
+                Permission: permission
+            });
+        }
+
+        it('Shows shared lists to the user they were shared with', function () {
+            return shareWithOther("Read")
+            .then(function () {
+                return todoListHelper.lookupPreviews(otherToken);
+            })
+            .then(function (response) {
+                expect(response.TodoLists).to.have.lengthOf(1);
+                expect(response.TodoLists[0].TodoListId).to.equal(todo_list_id);
+                expect(response.TodoLists[0].OwnerUserId).to.equal(ownerUserId);
+                expect(response.TodoLists[0].Access).to.equal("Read");
+
+                return todoListHelper.lookupPreviews(token);
+            })
+            .then(function (response) {
+                expect(response.TodoLists[0].Access).to.equal("Owner");
+            });
+        });
+
+        it('Rejects changes to a list shared for reading', function () {
+            return shareWithOther("Read")
+            .then(function () {
+                return httpHelper.post('/todolist/item/add', {
+                    Token: otherToken,
+                    TodoListId: todo_list_id,
+                    TodoListItem: {Text: "Milk", IsDone: false}
+                });
+            })
+            .then(function (response) {
+                expect(response.Error.ErrorCode).to.equal("100407");
+            });
+        });
+
+        it('Records who checked an item on a list shared for editing', function () {
+            return shareWithOther("Edit")
+            .then(function () {
+                return todoListHelper.updateItem({
+                    Token: otherToken,
+                    TodoListId: todo_list_id,
+                    ItemIndex: 0,
+                    IsDone: true
+                });
+            })
+            .then(function () {
+                return todoListHelper.lookupList(token, todo_list_id);
+            })
+            .then(function (response) {
+                var item = response.TodoList.TodoListItems[0];
+                expect(item.IsDone).to.be.true;
+                expect(item.DoneByUserId).to.equal(otherUserId);
+                expect(item.DateDone).to.be.a('string');
+                expect(response.TodoList.UserShares).to.deep.equal([{UserId: otherUserId, Permission: "Edit"}]);
+            });
+        });
+
+        it('Stops sharing a list', function () {
+            return shareWithOther("Edit")
+            .then(function () {
+                return todoListHelper.unshareList({
+                    Token: token,
+                    TodoListId: todo_list_id,
+                    UserId: otherUserId
+                });
+            })
+            .then(function () {
+                return todoListHelper.lookupPreviews(otherToken);
+            })
+            .then(function (response) {
+                expect(response.TodoLists).to.have.lengthOf(0);
+            });
+        });
+
+        it('Only lets the owner share a list', function () {
+            return shareWithOther("Edit")
+            .then(function () {
+                return httpHelper.post('/todolist/share/add', {
+                    Token: otherToken,
+                    TodoListId: todo_list_id,
+                    UserId: ownerUserId,
+                    Permission: "Edit"
+                });
+            })
+            .then(function (response) {
+                expect(response.Error.ErrorCode).to.equal("100407");
+            });
+        });
+    });
+
+    describe("Templates", function () {
+        var template_id = null;
+
+        beforeEach(function () {
+            var todo_list_id = null;
+
+            return commonRequestsHelper.chaiHttpPostPurgeDatabaseArea('todolist_template')
+            .then(function () {
+                return todoListHelper.createTodoList(token, "Release {{version}}");
+            })
+            .then(function (response) {
+                todo_list_id = response.TodoListId;
+                return todoListHelper.addItem(token, todo_list_id, {
+                    Text: "Tag {{version}}",
+                    IsDone: true
+                });
+            })
+            .then(function () {
+                return todoListHelper.addItem(token, todo_list_id, {
+                    Text: "Tell {{team}}",
+                    IsDone: false
+                });
+            })
+            .then(function () {
+                return todoListHelper.createTemplate({
+                    Token: token,
+                    TodoListId: todo_list_id
+                });
+            })
+            .then(function (response) {
+                template_id = response.TemplateId;
+            });
+        });
+
+        it('Lists templates with their placeholders', function () {
+            return todoListHelper.lookupTemplates(token)
+            .then(function (response) {
+                expect(response.Templates).to.have.lengthOf(1);
+                expect(response.Templates[0].TemplateId).to.equal(template_id);
+                expect(response.Templates[0].Title).to.equal("Release {{version}}");
+                expect(_.map(response.Templates[0].Items, 'Text')).to.deep.equal(["Tag {{version}}", "Tell {{team}}"]);
+                expect(response.Templates[0].Placeholders).to.deep.equal(["version", "team"]);
+            });
+        });
+
+        it('Creates a list from a template', function () {
+            return todoListHelper.instantiateTemplate({
+                Token: token,
+                TemplateId: template_id,
+                Placeholders: {version: "1.2"}
+            })
+            .then(function (response) {
+                return todoListHelper.lookupList(token, response.TodoListId);
+            })
+            .then(function (response) {
+                var todo_list = response.TodoList;
+                expect(todo_list.Title).to.equal("Release 1.2");
+                expect(_.map(todo_list.TodoListItems, 'Text')).to.deep.equal(["Tag 1.2", "Tell {{team}}"]);
+                expect(_.map(todo_list.TodoListItems, 'IsDone')).to.deep.equal([false, false]);
+            });
+        });
+
+        it('Rejects sharing a template with a group the user is not in', function () {
+            return todoListHelper.lookupPreviews(token)
+            .then(function (response) {
+                return httpHelper.post('/todolist/template/create', {
+                    Token: token,
+                    TodoListId: response.TodoLists[0].TodoListId,
+                    UserGroupId: "not a group"
+                });
+            })
+            .then(function (response) {
+                expect(response.Error.ErrorCode).to.equal("100420");
+            });
+        });
+    });
 });
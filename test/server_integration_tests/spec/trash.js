@@ -0,0 +1,151 @@
+// Evelyn: Your personal assistant, project manager and calendar
+// Copyright (C) 2017 Gregory Jensen
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+if (!global.Promise) {
+    global.Promise = require('bluebird');
+}
+
+var chai = require('chai');
+var chaiSubset = require('chai-subset');
+var _ = require('lodash');
+
+var httpHelper = require('../helpers/chai_http_request_helper.js');
+var commonRequestsHelper = require('../helpers/common_requests_helper.js');
+
+var simpleTaskHelper = require('../helpers/spec_helpers/simple_task_helper.js');
+var userGroupHelper = require('../helpers/spec_helpers/user_group_helper.js');
+
+chai.use(chaiSubset);
+var expect = chai.expect;
+
+describe('Trash', function() {
+    var token = null;
+    var otherToken = null;
+
+    before(function () {
+        return commonRequestsHelper.chaiHttpPostPurgeDatabase()
+        .then(function () {
+            return commonRequestsHelper.createUserAndLogon('trashuser');
+        })
+        .then(function (_token) {
+            token = _token;
+            return commonRequestsHelper.createUserAndLogon('othertrashuser');
+        })
+        .then(function (_token) {
+            otherToken = _token;
+        });
+    });
+
+    it('Removed simple tasks can be restored', function() {
+        var taskId = null;
+
+        return simpleTaskHelper.createTasks({
+            Token: token,
+            Title: "Task to remove",
+            Description: "Descriptive",
+            DueDate: new Date().toISOString()
+        }, 1)
+        .then(function (response) {
+            taskId = response.TaskId;
+
+            return httpHelper.post('/simpletask/remove', {
+                Token: token,
+                TaskId: taskId
+            });
+        })
+        .then(function (response) {
+            expect(response.Error).to.be.null;
+
+            return httpHelper.post('/trash/lookup', {
+                Token: token
+            });
+        })
+        .then(function (response) {
+            expect(response.Error).to.be.null;
+            expect(response.Items).to.containSubset([{ItemType: 'SimpleTask', ItemId: taskId, Title: 'Task to remove'}]);
+
+            return httpHelper.post('/trash/lookup', {
+                Token: otherToken
+            });
+        })
+        .then(function (response) {
+            expect(response.Error).to.be.null;
+            expect(response.Items).to.have.lengthOf(0);
+
+            return httpHelper.post('/trash/restore', {
+                Token: token,
+                ItemType: 'SimpleTask',
+                ItemId: taskId
+            });
+        })
+        .then(function (response) {
+            expect(response.Error).to.be.null;
+
+            return simpleTaskHelper.lookupTasks(token);
+        })
+        .then(function (response) {
+            expect(response.SimpleTasks).to.containSubset([{TaskId: taskId}]);
+
+            return httpHelper.post('/trash/restore', {
+                Token: token,
+                ItemType: 'SimpleTask',
+                ItemId: taskId
+            });
+        })
+        .then(function (response) {
+            expect(response.Error).to.not.be.null;
+            expect(response.Error.ErrorCode).to.equal('100703');
+        });
+    });
+
+    it('Removed user groups are listed in the trash', function() {
+        var userGroupId = null;
+
+        return userGroupHelper.createUserGroup(token, "Group to remove", "the description of the group")
+        .then(function (response) {
+            userGroupId = response.UserGroupId;
+            return userGroupHelper.removeUserGroup(token, userGroupId);
+        })
+        .then(function () {
+            return httpHelper.post('/trash/lookup', {
+                Token: token
+            });
+        })
+        .then(function (response) {
+            expect(response.Error).to.be.null;
+            expect(response.Items).to.containSubset([{ItemType: 'UserGroup', ItemId: userGroupId, Title: 'Group to remove'}]);
+        });
+    });
+
+    it('Only the owner can remove a simple task', function() {
+        return simpleTaskHelper.createTasks({
+            Token: token,
+            Title: "Someone else's task",
+            Description: "Descriptive",
+            DueDate: new Date().toISOString()
+        }, 1)
+        .then(function (response) {
+            return httpHelper.post('/simpletask/remove', {
+                Token: otherToken,
+                TaskId: response.TaskId
+            });
+        })
+        .then(function (response) {
+            expect(response.Error).to.not.be.null;
+            expect(response.Error.ErrorCode).to.equal('100306');
+        });
+    });
+});
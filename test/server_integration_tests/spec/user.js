@@ -238,4 +238,58 @@ describe('User', function() {
             });
         });
     });
+
+    describe('Profile', function() {
+        var token = null;
+
+        beforeEach(function () {
+            return commonRequestsHelper.chaiHttpPostPurgeDatabaseArea('user')
+            .then(function () {
+                return commonRequestsHelper.createUserAndLogon('zoned');
+            })
+            .then(function (_token) {
+                token = _token;
+            });
+        });
+
+        it('Starts without a time zone', function() {
+            return httpHelper.post('/user/profile/lookup', {
+                Token: token
+            })
+            .then(function (response) {
+                expect(response.Error).to.be.null;
+                expect(response.UserName).to.equal('zoned');
+                expect(response.TimeZone).to.be.null;
+            });
+        });
+
+        it('Updates the time zone', function() {
+            return httpHelper.post('/user/profile/update', {
+                Token: token,
+                NewTimeZone: 'Europe/Paris'
+            })
+            .then(function (response) {
+                expect(response.Error).to.be.null;
+
+                return httpHelper.post('/user/profile/lookup', {
+                    Token: token
+                });
+            })
+            .then(function (response) {
+                expect(response.Error).to.be.null;
+                expect(response.TimeZone).to.equal('Europe/Paris');
+            });
+        });
+
+        it('Rejects a time zone which is not an IANA name', function() {
+            return httpHelper.post('/user/profile/update', {
+                Token: token,
+                NewTimeZone: 'Somewhere/Else'
+            })
+            .then(function (response) {
+                expect(response.Error).to.not.be.null;
+                expect(response.Error.ErrorCode).to.equal('100110');
+            });
+        });
+    });
 });
@@ -0,0 +1,274 @@
+// Evelyn: Your personal assistant, project manager and calendar
+// Copyright (C) 2017 Gregory Jensen
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+
+if (!global.Promise) {
+    global.Promise = require('bluebird');
+}
+
+var crypto = require('crypto');
+var http = require('http');
+var expect = require('chai').expect;
+
+var httpHelper = require('../helpers/chai_http_request_helper.js');
+var commonRequestsHelper = require('../helpers/common_requests_helper.js');
+var agileProjectHelper = require('../helpers/spec_helpers/agile_project_helper.js');
+var agileTaskHelper = require('../helpers/spec_helpers/agile_task_helper.js');
+
+var RECEIVER_PORT = 2526;
+var RECEIVER_URL = 'http://127.0.0.1:' + RECEIVER_PORT + '/hook';
+var SECRET = 'a shared secret';
+
+// Records each request and answers with whatever status the test asks for.
+function startReceiver(received, state) {
+    var server = http.createServer(function (request, response) {
+        var body = '';
+
+        request.on('data', function (chunk) {
+            body += chunk.toString();
+        });
+
+        request.on('end', function () {
+            received.push({
+                headers: request.headers,
+                body: body
+            });
+
+            response.statusCode = state.status;
+            response.end();
+        });
+    });
+
+    return new Promise(function (resolve) {
+        server.listen(RECEIVER_PORT, '127.0.0.1', function () {
+            resolve(server);
+        });
+    });
+}
+
+function wait(milliseconds) {
+    return new Promise(function (resolve) {
+        setTimeout(resolve, milliseconds);
+    });
+}
+
+describe('Webhooks', function() {
+    var receiver = null;
+    var received = [];
+    var state = { status: 200 };
+    var token = null;
+    var projectId = null;
+
+    // Deliveries are sent by the server in the background, so wait for them
+    // to arrive.
+    function waitForRequests(count, attempts) {
+        if (received.length >= count || attempts <= 0) {
+            return Promise.resolve(received);
+        }
+
+        return wait(500).then(function () {
+            return waitForRequests(count, attempts - 1);
+        });
+    }
+
+    function addWebhook(eventTypes) {
+        return httpHelper.post('/webhook/add', {
+            Token: token,
+            ProjectId: projectId,
+            Url: RECEIVER_URL,
+            Secret: SECRET,
+            EventTypes: eventTypes
+        });
+    }
+
+    before(function () {
+        return startReceiver(received, state)
+        .then(function (server) {
+            receiver = server;
+            return commonRequestsHelper.chaiHttpPostPurgeDatabase();
+        })
+        .then(function () {
+            return commonRequestsHelper.createUserAndLogon('hooked');
+        })
+        .then(function (_token) {
+            token = _token;
+            return agileProjectHelper.createProject(token, 'webhook_project');
+        })
+        .then(function (response) {
+            projectId = response.ProjectId;
+        });
+    });
+
+    after(function () {
+        receiver.close();
+    });
+
+    beforeEach(function () {
+        received.length = 0;
+        state.status = 200;
+        return commonRequestsHelper.chaiHttpPostPurgeDatabaseArea('webhook_subscription');
+    });
+
+    it('Rejects a URL which is not http or https', function() {
+        return httpHelper.post('/webhook/add', {
+            Token: token,
+            ProjectId: projectId,
+            Url: 'ftp://127.0.0.1/hook',
+            Secret: SECRET,
+            EventTypes: ['TaskCreated']
+        })
+        .then(function (response) {
+            expect(response.Error).to.not.be.null;
+            expect(response.Error.ErrorCode).to.equal('101005');
+        });
+    });
+
+    it('Rejects a URL on an address which is not public', function() {
+        return httpHelper.post('/webhook/add', {
+            Token: token,
+            ProjectId: projectId,
+            Url: 'http://169.254.169.254/latest/meta-data',
+            Secret: SECRET,
+            EventTypes: ['TaskCreated']
+        })
+        .then(function (response) {
+            expect(response.Error).to.not.be.null;
+            expect(response.Error.ErrorCode).to.equal('101005');
+        });
+    });
+
+    it('Posts a signed payload when a task is created', function() {
+        this.timeout(10000);
+        var subscriptionId = null;
+
+        return addWebhook(['TaskCreated'])
+        .then(function (response) {
+            expect(response.Error).to.be.null;
+            subscriptionId = response.SubscriptionId;
+
+            return agileTaskHelper.createTask(token, projectId, 'hooked_task');
+        })
+        .then(function () {
+            return waitForRequests(1, 10);
+        })
+        .then(function (requests) {
+            expect(requests).to.have.lengthOf(1);
+
+            var expectedSignature = crypto.createHmac('sha256', SECRET).update(requests[0].body).digest('hex');
+            expect(requests[0].headers['x-evelyn-signature']).to.equal('sha256=' + expectedSignature);
+            expect(requests[0].headers['x-evelyn-event']).to.equal('TaskCreated');
+
+            var payload = JSON.parse(requests[0].body);
+            expect(payload.EventType).to.equal('TaskCreated');
+            expect(payload.ProjectId).to.equal(projectId);
+            expect(payload.Title).to.equal('title_hooked_task');
+
+            return wait(1000);
+        })
+        .then(function () {
+            return httpHelper.post('/webhook/deliveries', {
+                Token: token,
+                SubscriptionId: subscriptionId
+            });
+        })
+        .then(function (response) {
+            expect(response.Error).to.be.null;
+            expect(response.Deliveries).to.have.lengthOf(1);
+            expect(response.Deliveries[0].Status).to.equal('Delivered');
+            expect(response.Deliveries[0].ResponseStatus).to.equal(200);
+        });
+    });
+
+    it('Keeps a failed delivery for another attempt', function() {
+        this.timeout(10000);
+        var subscriptionId = null;
+        state.status = 500;
+
+        return addWebhook(['TaskCreated'])
+        .then(function (response) {
+            subscriptionId = response.SubscriptionId;
+            return agileTaskHelper.createTask(token, projectId, 'failing_task');
+        })
+        .then(function () {
+            return waitForRequests(1, 10);
+        })
+        .then(function () {
+            return wait(1000);
+        })
+        .then(function () {
+            return httpHelper.post('/webhook/deliveries', {
+                Token: token,
+                SubscriptionId: subscriptionId
+            });
+        })
+        .then(function (response) {
+            expect(response.Error).to.be.null;
+            expect(response.Deliveries[0].Status).to.equal('Pending');
+            expect(response.Deliveries[0].Attempts).to.equal(1);
+            expect(response.Deliveries[0].ResponseStatus).to.equal(500);
+            expect(response.Deliveries[0].NextAttemptAt).to.not.be.null;
+        });
+    });
+
+    it('Does not post events which were not subscribed to', function() {
+        this.timeout(10000);
+
+        return addWebhook(['StoryCreated'])
+        .then(function () {
+            return agileTaskHelper.createTask(token, projectId, 'quiet_task');
+        })
+        .then(function () {
+            return wait(2000);
+        })
+        .then(function () {
+            expect(received).to.have.lengthOf(0);
+        });
+    });
+
+    it('Removes a webhook', function() {
+        var subscriptionId = null;
+
+        return addWebhook(['TaskCreated'])
+        .then(function (response) {
+            subscriptionId = response.SubscriptionId;
+
+            return httpHelper.post('/webhook/remove', {
+                Token: token,
+                SubscriptionId: subscriptionId
+            });
+        })
+        .then(function (response) {
+            expect(response.Error).to.be.null;
+
+            return httpHelper.post('/webhook/lookup', {
+                Token: token
+            });
+        })
+        .then(function (response) {
+            expect(response.Error).to.be.null;
+            expect(response.Subscriptions).to.have.lengthOf(0);
+
+            return httpHelper.post('/webhook/deliveries', {
+                Token: token,
+                SubscriptionId: subscriptionId
+            });
+        })
+        .then(function (response) {
+            expect(response.Error).to.not.be.null;
+            expect(response.Error.ErrorCode).to.equal('101004');
+        });
+    });
+});